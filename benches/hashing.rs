@@ -0,0 +1,40 @@
+//! Compares `encode_signal` against hashing a freshly allocated packed encoding, which is what it used to do.
+
+use alloy_primitives::address;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use idkit::{
+	hashing::{encode_signal, hash_to_field},
+	Signal,
+};
+use ruint::aliases::U256;
+
+fn allocating<V: Signal + ?Sized>(signal: &V) -> U256 {
+	hash_to_field(&signal.encode_packed())
+}
+
+fn bench_signal<V: Signal + ?Sized>(c: &mut Criterion, name: &str, signal: &V) {
+	let mut group = c.benchmark_group(format!("encode_signal/{name}"));
+
+	group.bench_function(BenchmarkId::new("allocating", name), |b| {
+		b.iter(|| allocating(black_box(signal)));
+	});
+	group.bench_function(BenchmarkId::new("encode_signal", name), |b| {
+		b.iter(|| encode_signal(black_box(signal)));
+	});
+
+	group.finish();
+}
+
+fn signals(c: &mut Criterion) {
+	let wallet = address!("11e5b5e7a3e5c9f1a1f12c1d1c1b1a1918171615");
+	let message = "a".repeat(1024);
+
+	bench_signal(c, "str", "0x11e5b5e7a3e5c9f1a1f12c1d1c1b1a1918171615");
+	bench_signal(c, "str_1kb", message.as_str());
+	bench_signal(c, "bytes_1kb", message.as_bytes());
+	bench_signal(c, "address", &wallet);
+	bench_signal(c, "tuple", &(wallet, U256::from(42), "vote_1"));
+}
+
+criterion_group!(benches, signals);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+//! Compares the `keccak256` backends on small, medium and large inputs.
+//!
+//! Run with `cargo bench --bench keccak --features keccak-asm` to include the assembly backend.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tiny_keccak::{Hasher, Keccak};
+
+fn tiny_keccak(input: &[u8]) -> [u8; 32] {
+	let mut hasher = Keccak::v256();
+	hasher.update(input);
+
+	let mut output = [0; 32];
+	hasher.finalize(&mut output);
+	output
+}
+
+#[cfg(feature = "keccak-asm")]
+fn keccak_asm(input: &[u8]) -> [u8; 32] {
+	use keccak_asm::{Digest, Keccak256};
+
+	Keccak256::digest(input).into()
+}
+
+fn backends(c: &mut Criterion) {
+	for (name, size) in [("32B", 32), ("1KB", 1024), ("1MB", 1024 * 1024)] {
+		let input = vec![0xab; size];
+
+		let mut group = c.benchmark_group(format!("keccak256/{name}"));
+		group.throughput(Throughput::Bytes(size as u64));
+
+		group.bench_function(BenchmarkId::new("tiny-keccak", name), |b| {
+			b.iter(|| tiny_keccak(black_box(&input)));
+		});
+		#[cfg(feature = "keccak-asm")]
+		group.bench_function(BenchmarkId::new("keccak-asm", name), |b| {
+			b.iter(|| keccak_asm(black_box(&input)));
+		});
+		group.bench_function(BenchmarkId::new("idkit", name), |b| {
+			b.iter(|| idkit::hashing::keccak256(black_box(&input)));
+		});
+
+		group.finish();
+	}
+}
+
+criterion_group!(benches, backends);
+criterion_main!(benches);
@@ -34,29 +34,32 @@ async fn main() {
 	))
 	.unwrap();
 
-	let pb = ProgressBar::new_spinner().with_message("Waiting for connection...");
+	let pb = ProgressBar::new_spinner().with_message(Status::WaitingForConnection.to_string());
 	pb.enable_steady_tick(Duration::from_millis(100));
 
 	let proof = loop {
 		sleep(Duration::from_millis(500)).await;
 
-		match session.poll_for_status().await.unwrap() {
+		let status = session.poll_for_status().await.unwrap();
+		let message = status.to_string();
+
+		match status {
 			Status::WaitingForConnection => continue,
 			Status::AwaitingConfirmation => {
-				if pb.message() != "Waiting for confirmation..." {
+				if pb.message() != message {
 					term.clear_screen().unwrap();
-					pb.set_message("Waiting for confirmation...");
+					pb.set_message(message);
 				}
 				continue;
 			},
-			Status::Failed(error) => {
+			Status::Failed(_) => {
 				term.clear_screen().unwrap();
 				term.write_line("\n").unwrap();
-				pb.abandon_with_message(error.to_string());
+				pb.abandon_with_message(message);
 				std::process::exit(1);
 			},
 			Status::Confirmed(proof) => {
-				pb.finish_with_message("Received proof!");
+				pb.finish_with_message(message);
 				break proof;
 			},
 		}
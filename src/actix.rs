@@ -0,0 +1,288 @@
+//! An [actix-web](https://docs.rs/actix-web) extractor that verifies proofs before the handler runs.
+//!
+//! ```no_run
+//! use actix_web::{web, App, HttpServer};
+//! use idkit::{
+//!     actix::{IdKitState, VerifiedProof},
+//!     session::{Action, AppId},
+//! };
+//!
+//! async fn vote(proof: VerifiedProof) -> String {
+//!     format!("Verified {}", proof.proof.nullifier_hash)
+//! }
+//!
+//! # async fn example(app_id: AppId, action: Action) -> std::io::Result<()> {
+//! let state = web::Data::new(IdKitState::new(app_id, action));
+//!
+//! HttpServer::new(move || {
+//!     App::new()
+//!         .app_data(state.clone())
+//!         .route("/vote", web::post().to(vote))
+//! })
+//! .bind(("127.0.0.1", 8080))?
+//! .run()
+//! .await
+//! # }
+//! ```
+
+use ::actix_web::{
+	dev::Payload, http::StatusCode, web, FromRequest, HttpRequest, HttpResponse, ResponseError,
+};
+use alloy_primitives::Bytes;
+use futures_util::future::LocalBoxFuture;
+use std::sync::Arc;
+
+pub use crate::verify::VerifiedProof;
+use crate::{
+	session::{Action, AppId},
+	verify::{
+		extract::{body_signal, MAX_BODY_LEN},
+		ErrorReply, VerifyConfig,
+	},
+	SuccessResult,
+};
+
+/// Computes the signal a proof should commit to from the request, as its packed encoding (see `Signal`).
+pub type SignalExtractor = Arc<dyn Fn(&HttpRequest, &SuccessResult) -> Bytes + Send + Sync>;
+
+/// What `VerifiedProof` verifies proofs against. Register it with `App::app_data`, wrapped in `web::Data`.
+#[derive(Clone)]
+pub struct IdKitState {
+	/// The app the proofs are for.
+	pub app_id: AppId,
+	/// The action the proofs are for.
+	pub action: Action,
+	/// Computes the signal of the proof. Defaults to the `signal` field of the body, if any.
+	pub signal_extractor: SignalExtractor,
+	/// The configuration of the requests to the Developer Portal.
+	pub config: VerifyConfig,
+}
+
+impl IdKitState {
+	/// Verifies proofs for `action` of `app_id`, with the signal sent alongside the proof and the default configuration.
+	#[must_use]
+	pub fn new(app_id: AppId, action: Action) -> Self {
+		Self {
+			app_id,
+			action,
+			signal_extractor: Arc::new(|_, result| body_signal(result)),
+			config: VerifyConfig::default(),
+		}
+	}
+
+	/// Computes the signal of the proof from the request instead, for example from the authenticated user.
+	#[must_use]
+	pub fn signal_extractor(
+		mut self,
+		extractor: impl Fn(&HttpRequest, &SuccessResult) -> Bytes + Send + Sync + 'static,
+	) -> Self {
+		self.signal_extractor = Arc::new(extractor);
+		self
+	}
+
+	/// Sends requests to the Developer Portal with `config`.
+	#[must_use]
+	pub fn config(mut self, config: VerifyConfig) -> Self {
+		self.config = config;
+		self
+	}
+}
+
+impl std::fmt::Debug for IdKitState {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IdKitState")
+			.field("app_id", &self.app_id)
+			.field("action", &self.action)
+			.field("config", &self.config)
+			.finish_non_exhaustive()
+	}
+}
+
+impl FromRequest for VerifiedProof {
+	type Error = ErrorReply;
+	type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+	fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+		let request = request.clone();
+		let body = web::Bytes::from_request(&request, payload);
+
+		Box::pin(async move {
+			let state = request
+				.app_data::<web::Data<IdKitState>>()
+				.ok_or_else(ErrorReply::internal_error)?;
+			let body = body
+				.await
+				.map_err(|error| ErrorReply::invalid_request(error.to_string()))?;
+			if body.len() > MAX_BODY_LEN {
+				return Err(ErrorReply::invalid_request("request body is too large"));
+			}
+
+			Self::from_body(
+				&body,
+				state.app_id.clone(),
+				&state.action,
+				state.config.clone(),
+				|result| (state.signal_extractor)(&request, result),
+			)
+			.await
+		})
+	}
+}
+
+impl ResponseError for ErrorReply {
+	fn status_code(&self) -> StatusCode {
+		StatusCode::from_u16(self.status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+	}
+
+	fn error_response(&self) -> HttpResponse {
+		HttpResponse::build(self.status_code()).json(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use ::actix_web::{test, App};
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{body_partial_json, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	async fn vote(proof: VerifiedProof) -> String {
+		format!(
+			"{} voted {}",
+			proof.proof.nullifier_hash,
+			String::from_utf8_lossy(&proof.signal)
+		)
+	}
+
+	fn state(server: &MockServer) -> IdKitState {
+		IdKitState::new(
+			AppId::from_str("app_123").unwrap(),
+			Action::from_str("vote_1").unwrap(),
+		)
+		.config(VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		})
+	}
+
+	async fn post_vote(state: Option<IdKitState>, body: String) -> (StatusCode, String) {
+		let mut app = App::new();
+		if let Some(state) = state {
+			app = app.app_data(web::Data::new(state));
+		}
+		let app = test::init_service(app.route("/vote", web::post().to(vote))).await;
+
+		let response = test::call_service(
+			&app,
+			test::TestRequest::post()
+				.uri("/vote")
+				.insert_header(("content-type", "application/json"))
+				.set_payload(body)
+				.to_request(),
+		)
+		.await;
+
+		let status = response.status();
+		let body = test::read_body(response).await;
+
+		(status, String::from_utf8(body.to_vec()).unwrap())
+	}
+
+	fn code(body: &str) -> serde_json::Value {
+		serde_json::from_str::<serde_json::Value>(body).unwrap()["code"].clone()
+	}
+
+	#[actix_web::test]
+	async fn test_verified_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(serde_json::json!({
+				"action": "vote_1",
+				"nullifier_hash": fixtures::proof().nullifier_hash,
+				"signal_hash": crate::hashing::FieldElement::signal("yes"),
+			})))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"success": true,
+				"action": "vote_1",
+				"nullifier_hash": fixtures::proof().nullifier_hash,
+			})))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let body = serde_json::to_string(&SuccessResult {
+			proof: fixtures::proof(),
+			signal: Some("yes".to_string()),
+		})
+		.unwrap();
+
+		assert_eq!(
+			post_vote(Some(state(&server)), body).await,
+			(
+				StatusCode::OK,
+				format!("{} voted yes", fixtures::proof().nullifier_hash)
+			)
+		);
+	}
+
+	#[actix_web::test]
+	async fn test_rejected_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let (status, body) = post_vote(Some(state(&server)), fixtures::proof().to_idkit_js()).await;
+
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+			serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+			})
+		);
+	}
+
+	#[actix_web::test]
+	async fn test_portal_unavailable() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(500).set_body_string("oops"))
+			.mount(&server)
+			.await;
+
+		let (status, body) = post_vote(Some(state(&server)), fixtures::proof().to_idkit_js()).await;
+
+		assert_eq!(status, StatusCode::BAD_GATEWAY);
+		assert_eq!(code(&body), "portal_unavailable");
+	}
+
+	#[actix_web::test]
+	async fn test_invalid_request() {
+		let server = MockServer::start().await;
+
+		let (status, body) = post_vote(Some(state(&server)), "{}".to_string()).await;
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert_eq!(code(&body), "invalid_request");
+
+		let (status, body) = post_vote(None, fixtures::proof().to_idkit_js()).await;
+		assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+		assert_eq!(code(&body), "internal_error");
+
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+}
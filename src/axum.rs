@@ -0,0 +1,358 @@
+//! An [axum](https://docs.rs/axum) extractor that verifies proofs before the handler runs.
+//!
+//! ```no_run
+//! use axum::{routing::post, Router};
+//! use idkit::{
+//!     axum::{IdKitState, VerifiedProof},
+//!     session::{Action, AppId},
+//! };
+//!
+//! async fn vote(proof: VerifiedProof) -> String {
+//!     format!("Verified {}", proof.proof.nullifier_hash)
+//! }
+//!
+//! # fn example(app_id: AppId, action: Action) {
+//! let app: Router = Router::new()
+//!     .route("/vote", post(vote))
+//!     .with_state(IdKitState::new(app_id, action));
+//! # }
+//! ```
+
+#[cfg(feature = "sse")]
+use ::axum::response::sse::{Event as SseEvent, Sse};
+use ::axum::{
+	async_trait,
+	body::to_bytes,
+	extract::{FromRef, FromRequest, Request},
+	http::request::Parts,
+	response::{IntoResponse, Response},
+	Json,
+};
+use alloy_primitives::Bytes;
+#[cfg(feature = "sse")]
+use futures_util::{Stream, StreamExt};
+use std::sync::Arc;
+
+#[cfg(feature = "sse")]
+use crate::session::{
+	sse::{self, StreamConfig},
+	Session,
+};
+pub use crate::verify::VerifiedProof;
+use crate::{
+	session::{Action, AppId},
+	verify::{
+		extract::{body_signal, MAX_BODY_LEN},
+		ErrorReply, VerifyConfig,
+	},
+	SuccessResult,
+};
+
+/// Computes the signal a proof should commit to from the request, as its packed encoding (see `Signal`).
+pub type SignalExtractor = Arc<dyn Fn(&Parts, &SuccessResult) -> Bytes + Send + Sync>;
+
+/// What `VerifiedProof` verifies proofs against. Make it available through the router's state, directly or with `FromRef`.
+#[derive(Clone)]
+pub struct IdKitState {
+	/// The app the proofs are for.
+	pub app_id: AppId,
+	/// The action the proofs are for.
+	pub action: Action,
+	/// Computes the signal of the proof. Defaults to the `signal` field of the body, if any.
+	pub signal_extractor: SignalExtractor,
+	/// The configuration of the requests to the Developer Portal.
+	pub config: VerifyConfig,
+}
+
+impl IdKitState {
+	/// Verifies proofs for `action` of `app_id`, with the signal sent alongside the proof and the default configuration.
+	#[must_use]
+	pub fn new(app_id: AppId, action: Action) -> Self {
+		Self {
+			app_id,
+			action,
+			signal_extractor: Arc::new(|_, result| body_signal(result)),
+			config: VerifyConfig::default(),
+		}
+	}
+
+	/// Computes the signal of the proof from the request instead, for example from the authenticated user.
+	#[must_use]
+	pub fn signal_extractor(
+		mut self,
+		extractor: impl Fn(&Parts, &SuccessResult) -> Bytes + Send + Sync + 'static,
+	) -> Self {
+		self.signal_extractor = Arc::new(extractor);
+		self
+	}
+
+	/// Sends requests to the Developer Portal with `config`.
+	#[must_use]
+	pub fn config(mut self, config: VerifyConfig) -> Self {
+		self.config = config;
+		self
+	}
+}
+
+impl std::fmt::Debug for IdKitState {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IdKitState")
+			.field("app_id", &self.app_id)
+			.field("action", &self.action)
+			.field("config", &self.config)
+			.finish_non_exhaustive()
+	}
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for VerifiedProof
+where
+	IdKitState: FromRef<S>,
+	S: Send + Sync,
+{
+	type Rejection = ErrorReply;
+
+	async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+		let state = IdKitState::from_ref(state);
+		let (parts, body) = request.into_parts();
+
+		let body = to_bytes(body, MAX_BODY_LEN)
+			.await
+			.map_err(|error| ErrorReply::invalid_request(error.to_string()))?;
+
+		Self::from_body(&body, state.app_id, &state.action, state.config, |result| {
+			(state.signal_extractor)(&parts, result)
+		})
+		.await
+	}
+}
+
+impl IntoResponse for ErrorReply {
+	fn into_response(self) -> Response {
+		(self.status, Json(self)).into_response()
+	}
+}
+
+/// Streams the status of `session` to the browser as server-sent events, see `session::sse::status_events`.
+#[cfg(feature = "sse")]
+pub fn status_sse(
+	session: Session,
+	config: StreamConfig,
+) -> Sse<impl Stream<Item = Result<SseEvent, std::convert::Infallible>>> {
+	Sse::new(sse::status_events(session, config).map(|event| Ok(event.into())))
+}
+
+#[cfg(feature = "sse")]
+impl From<sse::Event> for SseEvent {
+	fn from(event: sse::Event) -> Self {
+		match event {
+			sse::Event::Message { event, data } => Self::default().event(event).data(data),
+			sse::Event::Heartbeat => Self::default().comment("heartbeat"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixtures, Proof};
+	use ::axum::{body::Body, http::StatusCode, routing::post, Router};
+	use std::str::FromStr;
+	use tower::ServiceExt;
+	use url::Url;
+	use wiremock::{
+		matchers::{body_partial_json, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	async fn vote(proof: VerifiedProof) -> String {
+		format!(
+			"{} voted {}",
+			proof.proof.nullifier_hash,
+			String::from_utf8_lossy(&proof.signal)
+		)
+	}
+
+	fn router(server: &MockServer) -> Router {
+		Router::new().route("/vote", post(vote)).with_state(
+			IdKitState::new(
+				AppId::from_str("app_123").unwrap(),
+				Action::from_str("vote_1").unwrap(),
+			)
+			.config(VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			}),
+		)
+	}
+
+	async fn post_vote(router: Router, body: String) -> (StatusCode, String) {
+		let response = router
+			.oneshot(
+				Request::post("/vote")
+					.header("content-type", "application/json")
+					.body(Body::from(body))
+					.unwrap(),
+			)
+			.await
+			.unwrap();
+
+		let status = response.status();
+		let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+		(status, String::from_utf8(body.to_vec()).unwrap())
+	}
+
+	#[tokio::test]
+	async fn test_verified_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(serde_json::json!({
+				"action": "vote_1",
+				"nullifier_hash": fixtures::proof().nullifier_hash,
+				"signal_hash": crate::hashing::FieldElement::signal("yes"),
+			})))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"success": true,
+				"action": "vote_1",
+				"nullifier_hash": fixtures::proof().nullifier_hash,
+			})))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let body = serde_json::to_string(&SuccessResult {
+			proof: fixtures::proof(),
+			signal: Some("yes".to_string()),
+		})
+		.unwrap();
+
+		assert_eq!(
+			post_vote(router(&server), body).await,
+			(
+				StatusCode::OK,
+				format!("{} voted yes", fixtures::proof().nullifier_hash)
+			)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_signal_extractor() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(serde_json::json!({
+				"signal_hash": crate::hashing::FieldElement::signal("user_42"),
+			})))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let router = Router::new().route("/vote", post(vote)).with_state(
+			IdKitState::new(
+				AppId::from_str("app_123").unwrap(),
+				Action::from_str("vote_1").unwrap(),
+			)
+			.signal_extractor(|parts, _| {
+				Bytes::copy_from_slice(parts.headers["x-user-id"].as_bytes())
+			})
+			.config(VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			}),
+		);
+
+		let response = router
+			.oneshot(
+				Request::post("/vote")
+					.header("x-user-id", "user_42")
+					.body(Body::from(fixtures::proof().to_idkit_js()))
+					.unwrap(),
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(response.status(), StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn test_rejected_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let (status, body) = post_vote(router(&server), fixtures::proof().to_idkit_js()).await;
+
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+			serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+			})
+		);
+	}
+
+	#[tokio::test]
+	async fn test_invalid_request() {
+		let server = MockServer::start().await;
+
+		for body in ["", "{}", r#"{"proof":"0x1"}"#] {
+			let (status, body) = post_vote(router(&server), body.to_string()).await;
+
+			assert_eq!(status, StatusCode::BAD_REQUEST);
+			assert_eq!(
+				serde_json::from_str::<serde_json::Value>(&body).unwrap()["code"],
+				"invalid_request"
+			);
+		}
+
+		let (status, body) = post_vote(
+			router(&server),
+			Proof {
+				proof: "0x1".to_string(),
+				..fixtures::proof()
+			}
+			.to_idkit_js(),
+		)
+		.await;
+
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(&body).unwrap()["code"],
+			"malformed_proof"
+		);
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+
+	#[cfg(feature = "sse")]
+	#[tokio::test]
+	async fn test_sse_events() {
+		let events = [
+			sse::Event::Heartbeat,
+			sse::Event::Message {
+				event: "awaiting_confirmation",
+				data: r#"{"status":"awaiting_confirmation"}"#.to_string(),
+			},
+		];
+		let expected = events.iter().map(ToString::to_string).collect::<String>();
+
+		let response = Sse::new(
+			futures_util::stream::iter(events)
+				.map(|event| Ok::<_, std::convert::Infallible>(SseEvent::from(event))),
+		)
+		.into_response();
+		let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+		assert_eq!(String::from_utf8(body.to_vec()).unwrap(), expected);
+	}
+}
@@ -0,0 +1,242 @@
+//! Prompt for World ID proofs in the terminal, and verify them with the Developer Portal.
+//!
+//! Results are printed on stdout, and progress on stderr. With `--json`, every line printed on stdout is a JSON object.
+
+use clap::{Parser, Subcommand};
+use idkit::{
+	session::{Action, AppError, AppId, BridgeUrl, Session, Status, VerificationLevel},
+	verify::{self, ErrorReply, Verification, VerifyConfig},
+	Proof,
+};
+use qrcode::{render::unicode, QrCode};
+use serde_json::json;
+use std::{
+	path::{Path, PathBuf},
+	process::ExitCode,
+	time::Duration,
+};
+use url::Url;
+
+#[derive(Debug, Parser)]
+#[command(name = "idkit", version, about)]
+struct Cli {
+	/// Print machine-readable JSON on stdout, one object per line.
+	#[arg(long, global = true)]
+	json: bool,
+
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+	/// Prompt for a proof with a QR code to scan with World App, and print it as JSON once confirmed.
+	VerifyFlow {
+		/// The id of the app requesting the proof.
+		#[arg(long)]
+		app_id: AppId,
+		/// The action the proof is for.
+		#[arg(long)]
+		action: Action,
+		/// The minimum verification level to accept (`orb` or `device`).
+		#[arg(long, default_value = "orb")]
+		level: VerificationLevel,
+		/// The signal the proof commits to.
+		#[arg(long, default_value = "")]
+		signal: String,
+		/// The Wallet Bridge to relay the request through.
+		#[arg(long)]
+		bridge_url: Option<Url>,
+		/// The time between polls of the bridge, in milliseconds.
+		#[arg(long, default_value_t = 500)]
+		poll_interval: u64,
+	},
+	/// Verify an existing proof with the Developer Portal, exiting with a non-zero status if it isn't valid.
+	Verify {
+		/// The JSON file holding the proof, as printed by `verify-flow` or sent by IDKit JS.
+		#[arg(long)]
+		proof_file: PathBuf,
+		/// The id of the app the proof is for.
+		#[arg(long)]
+		app_id: AppId,
+		/// The action the proof is for.
+		#[arg(long)]
+		action: Action,
+		/// The signal the proof commits to.
+		#[arg(long, default_value = "")]
+		signal: String,
+		/// The base URL of the Developer Portal.
+		#[arg(long)]
+		portal_url: Option<Url>,
+	},
+}
+
+/// Why a command failed, with a machine-readable `code`.
+struct Failure {
+	code: String,
+	message: String,
+}
+
+impl Failure {
+	fn new(code: &str, message: String) -> Self {
+		Self {
+			code: code.to_string(),
+			message,
+		}
+	}
+}
+
+impl From<idkit::session::Error> for Failure {
+	fn from(error: idkit::session::Error) -> Self {
+		Self::new("bridge_error", error.to_string())
+	}
+}
+
+impl From<AppError> for Failure {
+	fn from(error: AppError) -> Self {
+		Self::new(error.code(), error.to_string())
+	}
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+	let cli = Cli::parse();
+
+	let result = match cli.command {
+		Command::VerifyFlow {
+			app_id,
+			action,
+			level,
+			signal,
+			bridge_url,
+			poll_interval,
+		} => {
+			verify_flow(
+				cli.json,
+				&app_id,
+				action,
+				level,
+				&signal,
+				bridge_url,
+				Duration::from_millis(poll_interval),
+			)
+			.await
+		},
+		Command::Verify {
+			proof_file,
+			app_id,
+			action,
+			signal,
+			portal_url,
+		} => verify(cli.json, &proof_file, app_id, &action, &signal, portal_url).await,
+	};
+
+	match result {
+		Ok(output) => {
+			println!("{output}");
+			ExitCode::SUCCESS
+		},
+		Err(failure) if cli.json => {
+			println!(
+				"{}",
+				json!({ "success": false, "code": failure.code, "error": failure.message })
+			);
+			ExitCode::FAILURE
+		},
+		Err(failure) => {
+			eprintln!("Error: {}", failure.message);
+			ExitCode::FAILURE
+		},
+	}
+}
+
+async fn verify_flow(
+	json: bool,
+	app_id: &AppId,
+	action: Action,
+	level: VerificationLevel,
+	signal: &str,
+	bridge_url: Option<Url>,
+	poll_interval: Duration,
+) -> Result<String, Failure> {
+	let bridge_url = match bridge_url {
+		Some(url) => BridgeUrl::try_from(url)
+			.map_err(|error| Failure::new("invalid_bridge_url", error.to_string()))?,
+		None => BridgeUrl::default(),
+	};
+
+	let session = Session::new(app_id, action, level, bridge_url, signal, None).await?;
+	let connect_url = session.connect_url();
+
+	if json {
+		println!("{}", json!({ "connect_url": connect_url.as_str() }));
+	} else {
+		let qrcode = QrCode::new(connect_url.as_str())
+			.map_err(|error| Failure::new("invalid_connect_url", error.to_string()))?;
+		eprintln!(
+			"Scan the QR code with World App, or open {connect_url}\n\n{}",
+			qrcode.render::<unicode::Dense1x2>().build()
+		);
+	}
+
+	let mut last_message = String::new();
+	loop {
+		let status = session.poll_for_status().await?;
+
+		let message = status.to_string();
+		if !json && message != last_message {
+			eprintln!("{message}");
+			last_message = message;
+		}
+
+		match status {
+			Status::WaitingForConnection | Status::AwaitingConfirmation => {
+				tokio::time::sleep(poll_interval).await;
+			},
+			Status::Confirmed(proof) => return Ok(proof.to_json_string()),
+			Status::Failed(error) => return Err(error.into()),
+		}
+	}
+}
+
+async fn verify(
+	json: bool,
+	proof_file: &Path,
+	app_id: AppId,
+	action: &Action,
+	signal: &str,
+	portal_url: Option<Url>,
+) -> Result<String, Failure> {
+	let proof = std::fs::read_to_string(proof_file)
+		.map_err(|error| Failure::new("invalid_proof_file", error.to_string()))
+		.and_then(|contents| {
+			Proof::from_json_str(&contents)
+				.map_err(|error| Failure::new("invalid_proof_file", error.to_string()))
+		})?;
+
+	let failure =
+		|error: verify::Error| Failure::new(&ErrorReply::from(&error).code, error.to_string());
+	let mut config = VerifyConfig::try_default().map_err(failure)?;
+	if let Some(portal_url) = portal_url {
+		config.base_url = portal_url;
+	}
+
+	let response = Verification::with_config(app_id, action.clone(), config)
+		.map_err(failure)?
+		.proof(proof.clone())
+		.signal(signal)
+		.send_with_response()
+		.await
+		.map_err(failure)?;
+
+	Ok(if json {
+		json!({
+			"success": true,
+			"nullifier_hash": proof.nullifier_hash,
+			"uses": response.uses,
+		})
+		.to_string()
+	} else {
+		format!("Proof verified, nullifier hash {}", proof.nullifier_hash)
+	})
+}
@@ -0,0 +1,177 @@
+//! A compact binary encoding of proofs, for storing them in bulk.
+//!
+//! The layout is, in order:
+//!
+//! | Bytes | Field |
+//! |-------|-------|
+//! | 1     | format version, currently `1` |
+//! | 1     | verification level tag: `0` for orb, `1` for device |
+//! | 256   | the proof, as the eight 32-byte big-endian words of `uint256[8]` |
+//! | 32    | the merkle root, big-endian |
+//! | 32    | the nullifier hash, big-endian |
+//!
+//! New verification levels get new tags without bumping the format version, so readers built before a level existed fail with `BinaryError::UnknownVerificationLevel` instead of misreading it. The version only changes if the layout itself does.
+
+use alloc::vec::Vec;
+use ruint::aliases::U256;
+
+use crate::{protocol::PROOF_WORDS, session::VerificationLevel, DecodeError, DecodedProof, Proof};
+
+const VERSION: u8 = 1;
+const ENCODED_LEN: usize = 2 + PROOF_WORDS * 32 + 32 + 32;
+
+/// An error when decoding a proof from its binary encoding.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BinaryError {
+	#[error("unsupported binary proof format version {0}")]
+	UnsupportedVersion(u8),
+
+	#[error("unknown verification level tag {0}")]
+	UnknownVerificationLevel(u8),
+
+	#[error("binary proof should be {expected} bytes long, got {actual}")]
+	InvalidLength { expected: usize, actual: usize },
+}
+
+impl Proof {
+	/// Encodes the proof in a compact, versioned binary form: a version byte, a verification level tag byte, then the proof, merkle root and nullifier hash as raw 32-byte big-endian words, 322 bytes in total.
+	///
+	/// The hex fields are stored as raw bytes, so decoding the result produces lowercase, zero-padded hex strings.
+	///
+	/// # Errors
+	///
+	/// Errors if the fields of the proof can't be decoded.
+	pub fn to_bytes(&self) -> Result<Vec<u8>, DecodeError> {
+		let decoded = self.decode()?;
+
+		let mut bytes = Vec::with_capacity(ENCODED_LEN);
+		bytes.push(VERSION);
+		bytes.push(match decoded.verification_level {
+			VerificationLevel::Orb => 0,
+			VerificationLevel::Device => 1,
+		});
+		for word in decoded
+			.proof
+			.iter()
+			.chain([&decoded.merkle_root, &decoded.nullifier_hash])
+		{
+			bytes.extend_from_slice(&word.to_be_bytes::<32>());
+		}
+
+		Ok(bytes)
+	}
+
+	/// Decodes a proof from the binary form produced by `Proof::to_bytes`.
+	///
+	/// # Errors
+	///
+	/// Errors if the format version is unknown (`BinaryError::UnsupportedVersion`), if the verification level is unknown (`BinaryError::UnknownVerificationLevel`), or if the input has the wrong length (`BinaryError::InvalidLength`).
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryError> {
+		match bytes.first() {
+			Some(&VERSION) => {},
+			Some(&version) => return Err(BinaryError::UnsupportedVersion(version)),
+			None => {
+				return Err(BinaryError::InvalidLength {
+					expected: ENCODED_LEN,
+					actual: 0,
+				})
+			},
+		}
+
+		if bytes.len() != ENCODED_LEN {
+			return Err(BinaryError::InvalidLength {
+				expected: ENCODED_LEN,
+				actual: bytes.len(),
+			});
+		}
+
+		let verification_level = match bytes[1] {
+			0 => VerificationLevel::Orb,
+			1 => VerificationLevel::Device,
+			tag => return Err(BinaryError::UnknownVerificationLevel(tag)),
+		};
+
+		let words: Vec<U256> = bytes[2..]
+			.chunks_exact(32)
+			.map(|word| U256::from_be_slice(word))
+			.collect();
+
+		Ok(DecodedProof {
+			proof: core::array::from_fn(|i| words[i]),
+			merkle_root: words[8],
+			nullifier_hash: words[9],
+			verification_level,
+		}
+		.encode())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+
+	#[test]
+	fn test_roundtrip() {
+		for verification_level in [VerificationLevel::Orb, VerificationLevel::Device] {
+			let proof = Proof {
+				verification_level,
+				..fixtures::proof()
+			};
+
+			assert_eq!(
+				Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap(),
+				proof
+			);
+		}
+	}
+
+	#[test]
+	fn test_size() {
+		let bytes = fixtures::proof().to_bytes().unwrap();
+
+		assert_eq!(bytes.len(), 322);
+		assert!(serde_json::to_vec(&fixtures::proof()).unwrap().len() > 2 * bytes.len());
+	}
+
+	#[test]
+	fn test_corrupted_input() {
+		let bytes = fixtures::proof().to_bytes().unwrap();
+
+		assert_eq!(
+			Proof::from_bytes(&[]),
+			Err(BinaryError::InvalidLength {
+				expected: 322,
+				actual: 0
+			})
+		);
+		assert_eq!(
+			Proof::from_bytes(&bytes[..100]),
+			Err(BinaryError::InvalidLength {
+				expected: 322,
+				actual: 100
+			})
+		);
+		assert_eq!(
+			Proof::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+			Err(BinaryError::InvalidLength {
+				expected: 322,
+				actual: 323
+			})
+		);
+
+		let mut future_version = bytes.clone();
+		future_version[0] = 2;
+		assert_eq!(
+			Proof::from_bytes(&future_version),
+			Err(BinaryError::UnsupportedVersion(2))
+		);
+
+		let mut future_level = bytes;
+		future_level[1] = 7;
+		assert_eq!(
+			Proof::from_bytes(&future_level),
+			Err(BinaryError::UnknownVerificationLevel(7))
+		);
+	}
+}
@@ -0,0 +1,136 @@
+//! Solidity bindings for the World ID contracts, to verify proofs as part of your own contract calls.
+
+use crate::{
+	hashing::{encode_signal, external_nullifier},
+	protocol::group_id,
+	session::{Action, AppId, CredentialType},
+	DecodeError, Proof, Signal,
+};
+
+pub use crate::protocol::ORB_GROUP_ID;
+
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+mod bindings {
+	alloy_sol_types::sol! {
+		#[sol(all_derives)]
+		interface IWorldID {
+			error ProofInvalid();
+			error ExpiredRoot();
+			error NonExistentRoot();
+			error NoSuchGroup(uint256 groupId);
+			error GroupIsDeprecated(uint256 groupId);
+
+			function verifyProof(
+				uint256 root,
+				uint256 groupId,
+				uint256 signalHash,
+				uint256 nullifierHash,
+				uint256 externalNullifierHash,
+				uint256[8] calldata proof
+			) external view;
+		}
+	}
+}
+
+pub use bindings::IWorldID;
+
+impl Proof {
+	/// Builds the `IWorldID.verifyProof` call for this proof, computing the signal hash and external nullifier as the protocol expects.
+	///
+	/// Only Orb proofs can be verified on-chain, so the call always targets the Orb group.
+	///
+	/// # Errors
+	///
+	/// Errors if the fields of the proof can't be decoded.
+	pub fn to_verify_proof_call<V: Signal>(
+		&self,
+		app_id: &AppId,
+		action: &Action,
+		signal: &V,
+	) -> Result<IWorldID::verifyProofCall, DecodeError> {
+		let merkle_root = self.merkle_root()?;
+		let decoded = self.decode()?;
+
+		Ok(IWorldID::verifyProofCall {
+			root: merkle_root.into(),
+			groupId: group_id(CredentialType::Orb),
+			signalHash: encode_signal(signal),
+			nullifierHash: decoded.nullifier_hash,
+			externalNullifierHash: external_nullifier(app_id, action),
+			proof: decoded.proof,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::VerificationLevel;
+	use alloy_sol_types::SolCall;
+	use std::str::FromStr;
+
+	#[test]
+	fn test_verify_proof_call_encoding() {
+		let proof = Proof {
+			proof: (1..=8)
+				.map(|i| format!("{i:064x}"))
+				.fold("0x".to_string(), |acc, part| acc + &part),
+			merkle_root: format!("0x{:064x}", 0xabcd),
+			nullifier_hash: format!("0x{:064x}", 0x1234),
+			verification_level: VerificationLevel::Orb,
+		};
+
+		let calldata = proof
+			.to_verify_proof_call(
+				&AppId::from_str("app_123").unwrap(),
+				&Action::from_str("vote_1").unwrap(),
+				&"test",
+			)
+			.unwrap()
+			.abi_encode();
+
+		// Known-good calldata for `verifyProof`, with the signal hash of "test" and the external nullifier of app_123/vote_1.
+		let expected = concat!(
+			"3bc778e3",
+			"000000000000000000000000000000000000000000000000000000000000abcd",
+			"0000000000000000000000000000000000000000000000000000000000000001",
+			"009c22ff5f21f0b81b113e63f7db6da94fedef11b2119b4088b89664fb9a3cb6",
+			"0000000000000000000000000000000000000000000000000000000000001234",
+			"00feadf361cdc46fb064c4ffa1dc77a7675e6cdc5d934ff65e5722e8bfbcadd3",
+			"0000000000000000000000000000000000000000000000000000000000000001",
+			"0000000000000000000000000000000000000000000000000000000000000002",
+			"0000000000000000000000000000000000000000000000000000000000000003",
+			"0000000000000000000000000000000000000000000000000000000000000004",
+			"0000000000000000000000000000000000000000000000000000000000000005",
+			"0000000000000000000000000000000000000000000000000000000000000006",
+			"0000000000000000000000000000000000000000000000000000000000000007",
+			"0000000000000000000000000000000000000000000000000000000000000008",
+		);
+
+		assert_eq!(
+			calldata
+				.iter()
+				.map(|byte| format!("{byte:02x}"))
+				.collect::<String>(),
+			expected
+		);
+	}
+
+	#[test]
+	fn test_verify_proof_call_rejects_malformed_proof() {
+		let proof = Proof {
+			proof: "0x1234".to_string(),
+			merkle_root: format!("0x{:064x}", 0xabcd),
+			nullifier_hash: format!("0x{:064x}", 0x1234),
+			verification_level: VerificationLevel::Orb,
+		};
+
+		assert!(proof
+			.to_verify_proof_call(
+				&AppId::from_str("app_123").unwrap(),
+				&Action::from_str("vote_1").unwrap(),
+				&"test",
+			)
+			.is_err());
+	}
+}
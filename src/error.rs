@@ -0,0 +1,511 @@
+#[cfg(feature = "session")]
+use crate::session::{self, BridgeProofError, BridgeUrlError, ConnectInfoError};
+use crate::{
+	hashing::FieldElementError,
+	hex::HexError,
+	session::{
+		ActionError, AppError, AppErrorCodeError, AppIdError, CredentialTypeError,
+		VerificationLevelError,
+	},
+	DecodeError, ProofParseError, QueryError,
+};
+#[cfg(feature = "verify")]
+use crate::{
+	portal,
+	verify::{self, ApiKeyError},
+};
+
+/// Any error returned by this crate.
+///
+/// Some variants only exist with the features that return them, so the enum is non-exhaustive: enabling a feature mustn't break matches on it.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+	#[cfg(feature = "session")]
+	#[error(transparent)]
+	Session(#[from] session::Error),
+
+	#[cfg(feature = "verify")]
+	#[error(transparent)]
+	Verify(#[from] verify::Error),
+
+	#[cfg(feature = "verify")]
+	#[error(transparent)]
+	Portal(#[from] portal::Error),
+
+	#[error(transparent)]
+	App(#[from] AppError),
+
+	#[error(transparent)]
+	AppId(#[from] AppIdError),
+
+	#[error(transparent)]
+	Action(#[from] ActionError),
+
+	#[cfg(feature = "session")]
+	#[error(transparent)]
+	BridgeUrl(#[from] BridgeUrlError),
+
+	#[cfg(feature = "session")]
+	#[error(transparent)]
+	BridgeProof(#[from] BridgeProofError),
+
+	#[cfg(feature = "session")]
+	#[error(transparent)]
+	ConnectInfo(#[from] ConnectInfoError),
+
+	#[error(transparent)]
+	CredentialType(#[from] CredentialTypeError),
+
+	#[error(transparent)]
+	VerificationLevel(#[from] VerificationLevelError),
+
+	#[error(transparent)]
+	AppErrorCode(#[from] AppErrorCodeError),
+
+	#[error(transparent)]
+	Hex(#[from] HexError),
+
+	#[error(transparent)]
+	Decode(#[from] DecodeError),
+
+	#[error(transparent)]
+	FieldElement(#[from] FieldElementError),
+
+	#[error(transparent)]
+	ProofParse(#[from] ProofParseError),
+
+	#[error(transparent)]
+	Query(#[from] QueryError),
+
+	#[cfg(feature = "binary")]
+	#[error(transparent)]
+	Binary(#[from] crate::BinaryError),
+
+	#[cfg(feature = "verify")]
+	#[error(transparent)]
+	ApiKey(#[from] ApiKeyError),
+
+	#[cfg(any(feature = "session", feature = "verify"))]
+	#[error(transparent)]
+	TraceContext(#[from] crate::trace::TraceContextError),
+
+	#[cfg(feature = "onchain")]
+	#[error(transparent)]
+	Onchain(#[from] crate::onchain::Error),
+
+	#[cfg(feature = "local-verify")]
+	#[error(transparent)]
+	Offline(#[from] crate::offline::Error),
+
+	#[cfg(feature = "oidc")]
+	#[error(transparent)]
+	Oidc(#[from] crate::oidc::Error),
+
+	#[cfg(feature = "minikit")]
+	#[error(transparent)]
+	Minikit(#[from] crate::minikit::Error),
+
+	/// An error from `verify_proof_unique`, with the error of the nullifier store boxed.
+	#[cfg(feature = "verify")]
+	#[error(transparent)]
+	Nullifier(crate::nullifier::Error<Box<dyn std::error::Error + Send + Sync>>),
+}
+
+#[cfg(feature = "verify")]
+impl<E: std::error::Error + Send + Sync + 'static> From<crate::nullifier::Error<E>> for Error {
+	fn from(error: crate::nullifier::Error<E>) -> Self {
+		Self::Nullifier(match error {
+			crate::nullifier::Error::AlreadyUsed => crate::nullifier::Error::AlreadyUsed,
+			crate::nullifier::Error::Verify(error) => crate::nullifier::Error::Verify(error),
+			crate::nullifier::Error::Store(error) => {
+				crate::nullifier::Error::Store(Box::new(error))
+			},
+		})
+	}
+}
+
+/// A broad category of errors, for handling them uniformly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+	/// A network error, timeout or rate limit when reaching a remote service.
+	Transport,
+	/// A remote service responded in an unexpected way.
+	Protocol,
+	/// A key couldn't be generated, or a payload couldn't be encrypted or decrypted.
+	Crypto,
+	/// The user or their proof was rejected.
+	User,
+	/// The crate was misconfigured, e.g. with an invalid app id or URL.
+	Configuration,
+}
+
+impl Error {
+	/// The category this error belongs to.
+	#[must_use]
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			#[cfg(feature = "session")]
+			Self::Session(error) => error.kind(),
+			#[cfg(feature = "verify")]
+			Self::Verify(error) => error.kind(),
+			#[cfg(feature = "verify")]
+			Self::Portal(error) => portal_kind(error),
+			Self::App(error) => app_kind(*error),
+			Self::AppId(_) | Self::Action(_) => ErrorKind::Configuration,
+			#[cfg(feature = "session")]
+			Self::BridgeUrl(error) => error.kind(),
+			#[cfg(feature = "session")]
+			Self::BridgeProof(_) => ErrorKind::User,
+			#[cfg(feature = "session")]
+			Self::ConnectInfo(_) => ErrorKind::Protocol,
+			Self::CredentialType(_) | Self::VerificationLevel(_) => ErrorKind::Configuration,
+			Self::AppErrorCode(_) => ErrorKind::Protocol,
+			Self::Hex(_)
+			| Self::Decode(_)
+			| Self::FieldElement(_)
+			| Self::ProofParse(_)
+			| Self::Query(_) => ErrorKind::User,
+			#[cfg(feature = "binary")]
+			Self::Binary(_) => ErrorKind::User,
+			#[cfg(feature = "verify")]
+			Self::ApiKey(_) => ErrorKind::Configuration,
+			#[cfg(any(feature = "session", feature = "verify"))]
+			Self::TraceContext(_) => ErrorKind::Configuration,
+			#[cfg(feature = "onchain")]
+			Self::Onchain(error) => match error {
+				crate::onchain::Error::Transport(_) => ErrorKind::Transport,
+				crate::onchain::Error::Reverted(_) | crate::onchain::Error::Decode(_) => {
+					ErrorKind::Protocol
+				},
+				crate::onchain::Error::MalformedProof(_)
+				| crate::onchain::Error::UnsupportedVerificationLevel(_)
+				| crate::onchain::Error::ProofInvalid
+				| crate::onchain::Error::ExpiredRoot
+				| crate::onchain::Error::NonExistentRoot
+				| crate::onchain::Error::RootTooOld { .. } => ErrorKind::User,
+				crate::onchain::Error::NoSuchGroup(_)
+				| crate::onchain::Error::GroupIsDeprecated(_)
+				| crate::onchain::Error::UnsupportedNetwork(_)
+				| crate::onchain::Error::InvalidAction(_) => ErrorKind::Configuration,
+			},
+			#[cfg(feature = "local-verify")]
+			Self::Offline(error) => match error {
+				crate::offline::Error::MalformedProof(_)
+				| crate::offline::Error::UnknownRoot
+				| crate::offline::Error::InvalidProof => ErrorKind::User,
+				crate::offline::Error::InvalidVerifyingKey(_)
+				| crate::offline::Error::InvalidAction(_) => ErrorKind::Configuration,
+			},
+			#[cfg(feature = "oidc")]
+			Self::Oidc(error) => oidc_kind(error),
+			#[cfg(feature = "minikit")]
+			Self::Minikit(error) => minikit_kind(error),
+			#[cfg(feature = "verify")]
+			Self::Nullifier(error) => match error {
+				crate::nullifier::Error::AlreadyUsed => ErrorKind::User,
+				crate::nullifier::Error::Verify(error) => error.kind(),
+				crate::nullifier::Error::Store(_) => ErrorKind::Transport,
+			},
+		}
+	}
+}
+
+const fn app_kind(error: AppError) -> ErrorKind {
+	match error {
+		AppError::ConnectionFailed => ErrorKind::Transport,
+		AppError::UnexpectedResponse | AppError::GenericError => ErrorKind::Protocol,
+		AppError::MalformedRequest | AppError::InvalidNetwork => ErrorKind::Configuration,
+		AppError::VerificationRejected
+		| AppError::MaxVerificationsReached
+		| AppError::CredentialUnavailable
+		| AppError::InclusionProofFailed
+		| AppError::InclusionProofPending
+		| AppError::FailedByHostApp => ErrorKind::User,
+	}
+}
+
+#[cfg(feature = "verify")]
+fn portal_kind(error: &portal::Error) -> ErrorKind {
+	match error {
+		portal::Error::InvalidApiKey | portal::Error::AppNotFound(_) => ErrorKind::Configuration,
+		portal::Error::DuplicateAction(_) | portal::Error::Rejected(_) => ErrorKind::User,
+		portal::Error::Request(error) => error.kind(),
+	}
+}
+
+#[cfg(feature = "minikit")]
+fn minikit_kind(error: &crate::minikit::Error) -> ErrorKind {
+	match error {
+		crate::minikit::Error::App(error) => app_kind(*error),
+		crate::minikit::Error::Verify(error) => error.kind(),
+		crate::minikit::Error::Portal(error) => portal_kind(error),
+		crate::minikit::Error::TransactionNotFound(_) => ErrorKind::User,
+		crate::minikit::Error::PaymentPending(_) => ErrorKind::Transport,
+		crate::minikit::Error::Random => ErrorKind::Crypto,
+		crate::minikit::Error::MalformedMessage(_) => ErrorKind::Protocol,
+		crate::minikit::Error::AddressMismatch
+		| crate::minikit::Error::InvalidDomain
+		| crate::minikit::Error::InvalidUri
+		| crate::minikit::Error::InvalidChainId
+		| crate::minikit::Error::InvalidNonce
+		| crate::minikit::Error::InvalidStatement
+		| crate::minikit::Error::InvalidRequestId
+		| crate::minikit::Error::Expired
+		| crate::minikit::Error::NotYetValid
+		| crate::minikit::Error::IssuedInFuture
+		| crate::minikit::Error::InvalidSignature => ErrorKind::User,
+		#[cfg(feature = "onchain")]
+		crate::minikit::Error::Provider(_) => ErrorKind::Transport,
+	}
+}
+
+#[cfg(feature = "oidc")]
+const fn oidc_kind(error: &crate::oidc::Error) -> ErrorKind {
+	match error {
+		crate::oidc::Error::InvalidRedirectUri { .. } | crate::oidc::Error::InvalidVerifier => {
+			ErrorKind::Configuration
+		},
+		crate::oidc::Error::Random => ErrorKind::Crypto,
+		crate::oidc::Error::Request(_) | crate::oidc::Error::Timeout => ErrorKind::Transport,
+		crate::oidc::Error::OAuth(response) => match response.error {
+			crate::oidc::OAuthErrorCode::InvalidGrant => ErrorKind::User,
+			crate::oidc::OAuthErrorCode::Other(_) => ErrorKind::Protocol,
+			_ => ErrorKind::Configuration,
+		},
+		crate::oidc::Error::MalformedResponse(_)
+		| crate::oidc::Error::UnexpectedResponse { .. }
+		| crate::oidc::Error::MalformedToken(_)
+		| crate::oidc::Error::UnsupportedAlgorithm(_) => ErrorKind::Protocol,
+		crate::oidc::Error::UnknownKey(_)
+		| crate::oidc::Error::InvalidSignature
+		| crate::oidc::Error::InvalidIssuer(_)
+		| crate::oidc::Error::InvalidAudience
+		| crate::oidc::Error::Expired
+		| crate::oidc::Error::InvalidNonce
+		| crate::oidc::Error::InvalidToken => ErrorKind::User,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::AppId;
+	use std::str::FromStr;
+
+	#[cfg(any(feature = "session", feature = "verify"))]
+	fn reqwest_error() -> reqwest::Error {
+		reqwest::Client::new()
+			.get("http://[::1")
+			.build()
+			.unwrap_err()
+	}
+
+	#[cfg(any(feature = "session", feature = "verify"))]
+	fn serde_error() -> serde_json::Error {
+		serde_json::from_str::<()>("invalid").unwrap_err()
+	}
+
+	fn kind(error: impl Into<Error>) -> ErrorKind {
+		error.into().kind()
+	}
+
+	#[cfg(feature = "session")]
+	#[test]
+	fn test_session_errors() {
+		use crate::hashing::base64_decode;
+
+		assert_eq!(
+			kind(session::Error::Bridge(reqwest_error())),
+			ErrorKind::Transport
+		);
+		assert_eq!(
+			kind(session::Error::Connect {
+				source: reqwest_error()
+			}),
+			ErrorKind::Transport
+		);
+		assert_eq!(
+			kind(session::Error::Json(serde_error())),
+			ErrorKind::Protocol
+		);
+		assert_eq!(
+			kind(session::Error::Base64(base64_decode("!").unwrap_err())),
+			ErrorKind::Protocol
+		);
+		assert_eq!(
+			kind(session::Error::Encryption("Invalid IV")),
+			ErrorKind::Crypto
+		);
+	}
+
+	#[cfg(feature = "verify")]
+	#[test]
+	fn test_verify_errors() {
+		use reqwest::StatusCode;
+
+		assert_eq!(
+			kind(verify::Error::Reqwest(reqwest_error())),
+			ErrorKind::Configuration
+		);
+		assert_eq!(
+			kind(verify::Error::Timeout {
+				source: reqwest_error()
+			}),
+			ErrorKind::Transport
+		);
+		assert_eq!(
+			kind(verify::Error::Dns {
+				source: reqwest_error()
+			}),
+			ErrorKind::Transport
+		);
+		assert_eq!(
+			kind(verify::Error::RateLimited { retry_after: None }),
+			ErrorKind::Transport
+		);
+		assert_eq!(
+			kind(verify::Error::Serde(serde_error())),
+			ErrorKind::Protocol
+		);
+		assert_eq!(
+			kind(verify::Error::UnexpectedResponse {
+				status: StatusCode::INTERNAL_SERVER_ERROR,
+				body: String::new(),
+			}),
+			ErrorKind::Transport
+		);
+		assert_eq!(
+			kind(verify::Error::UnexpectedResponse {
+				status: StatusCode::UNAUTHORIZED,
+				body: String::new(),
+			}),
+			ErrorKind::Protocol
+		);
+		assert_eq!(
+			kind(verify::Error::MalformedProof {
+				field: "proof",
+				reason: String::new(),
+			}),
+			ErrorKind::User
+		);
+		assert_eq!(
+			kind(verify::Error::Url(url::ParseError::EmptyHost)),
+			ErrorKind::Configuration
+		);
+		assert_eq!(
+			kind(verify::Error::MissingField("proof")),
+			ErrorKind::Configuration
+		);
+		assert_eq!(
+			kind(verify::Error::Verification(verify::ErrorResponse {
+				code: verify::VerifyErrorCode::NotFound,
+				detail: String::new(),
+				attribute: None,
+			})),
+			ErrorKind::Configuration
+		);
+		assert_eq!(
+			kind(verify::Error::Verification(verify::ErrorResponse {
+				code: verify::VerifyErrorCode::InvalidProof,
+				detail: String::new(),
+				attribute: None,
+			})),
+			ErrorKind::User
+		);
+	}
+
+	#[cfg(feature = "verify")]
+	#[test]
+	fn test_portal_errors() {
+		assert_eq!(kind(portal::Error::InvalidApiKey), ErrorKind::Configuration);
+		assert_eq!(
+			kind(portal::Error::DuplicateAction("vote_1".to_string())),
+			ErrorKind::User
+		);
+		assert_eq!(
+			kind(portal::Error::Request(verify::Error::Timeout {
+				source: reqwest_error()
+			})),
+			ErrorKind::Transport
+		);
+	}
+
+	#[test]
+	fn test_app_errors() {
+		assert_eq!(kind(AppError::ConnectionFailed), ErrorKind::Transport);
+		assert_eq!(kind(AppError::GenericError), ErrorKind::Protocol);
+		assert_eq!(kind(AppError::InvalidNetwork), ErrorKind::Configuration);
+		assert_eq!(kind(AppError::VerificationRejected), ErrorKind::User);
+	}
+
+	#[test]
+	fn test_configuration_errors() {
+		assert_eq!(
+			kind(AppId::from_str("invalid").unwrap_err()),
+			ErrorKind::Configuration
+		);
+	}
+
+	#[cfg(feature = "session")]
+	#[test]
+	fn test_bridge_url_errors() {
+		use crate::session::BridgeUrl;
+		use url::Url;
+
+		assert_eq!(
+			kind(BridgeUrl::try_from(Url::parse("http://example.com").unwrap()).unwrap_err()),
+			ErrorKind::Configuration
+		);
+	}
+
+	#[cfg(feature = "verify")]
+	#[test]
+	fn test_api_key_errors() {
+		assert_eq!(
+			kind(crate::verify::ApiKey::from_str("invalid").unwrap_err()),
+			ErrorKind::Configuration
+		);
+	}
+
+	#[test]
+	fn test_proof_errors() {
+		use crate::{hex::decode_prefixed, Proof};
+
+		assert_eq!(kind(decode_prefixed("abc").unwrap_err()), ErrorKind::User);
+		assert_eq!(
+			kind(Proof::from_json_str("{}").unwrap_err()),
+			ErrorKind::User
+		);
+		assert_eq!(
+			kind(crate::session::VerificationLevel::from_str("invalid").unwrap_err()),
+			ErrorKind::Configuration
+		);
+	}
+
+	#[cfg(feature = "verify")]
+	#[test]
+	fn test_nullifier_errors() {
+		use crate::nullifier;
+
+		assert_eq!(
+			kind(nullifier::Error::<std::io::Error>::AlreadyUsed),
+			ErrorKind::User
+		);
+		assert_eq!(
+			kind(nullifier::Error::Store(std::io::Error::other(
+				"unavailable"
+			))),
+			ErrorKind::Transport
+		);
+	}
+
+	#[test]
+	fn test_question_mark_conversion() {
+		fn parse() -> Result<AppId, Error> {
+			Ok(AppId::from_str("invalid")?)
+		}
+
+		assert!(matches!(parse(), Err(Error::AppId(_))));
+	}
+}
@@ -0,0 +1,15 @@
+//! Re-exports of the third-party types that appear in this crate's public API.
+//!
+//! Use these instead of depending on `url`, `uuid`, `ruint` or `alloy` directly, so your types always match the versions `idkit` was built against.
+
+pub use alloy_primitives::{Address, Bytes};
+pub use ruint::aliases::U256;
+#[cfg(feature = "std")]
+pub use url::{ParseError as UrlParseError, Url};
+#[cfg(feature = "session")]
+pub use uuid::Uuid;
+
+/// The bound accepted wherever a signal is, re-exported here for convenience.
+pub use crate::Signal;
+#[cfg(feature = "sol-value")]
+pub use alloy_sol_types::SolValue;
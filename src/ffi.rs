@@ -0,0 +1,326 @@
+//! A [UniFFI](https://mozilla.github.io/uniffi-rs/) facade over `Session`, for Swift and Kotlin apps.
+//!
+//! Build the library as a `cdylib` (or a `staticlib` for iOS), and generate the bindings from it with the `uniffi-bindgen` binary:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features uniffi --crate-type cdylib
+//! cargo run --features uniffi --bin uniffi-bindgen -- generate --library target/release/libidkit.so --language kotlin --out-dir bindings
+//! ```
+//!
+//! `Proof`, `Status`, `AppError` and `VerificationLevel` are exposed as records and enums, and every method of `IdKitSession` is async (`suspend` in Kotlin, `async` in Swift).
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+use url::Url;
+
+use crate::{
+	hashing::FieldElement,
+	session::{AppId, BridgeUrl, Session, Status, VerificationLevel},
+	PreHashedSignal,
+};
+
+/// The signal a proof commits to. Generic signals can't cross the FFI boundary, so the common representations are exposed instead.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum SignalInput {
+	/// A string, hashed as its UTF-8 bytes.
+	String { value: String },
+	/// Raw bytes, hashed as-is.
+	Bytes { value: Vec<u8> },
+	/// An already computed signal hash, as a `0x`-prefixed hex string, sent as-is.
+	PreHashed { hash: String },
+}
+
+/// An error of the bindings, carrying the message of the underlying error.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum IdKitError {
+	#[error("invalid app id: {0}")]
+	InvalidAppId(String),
+	#[error("invalid signal: {0}")]
+	InvalidSignal(String),
+	#[error("invalid bridge url: {0}")]
+	InvalidBridgeUrl(String),
+	#[error(transparent)]
+	Session(#[from] crate::session::Error),
+}
+
+/// Receives the status of a request every time it changes, while `IdKitSession::wait_for_completion` polls the bridge.
+#[uniffi::export(callback_interface)]
+pub trait StatusListener: Send + Sync {
+	/// Called with the new status of the request.
+	fn on_status(&self, status: Status);
+}
+
+/// A session with the Wallet Bridge, see `Session`.
+#[derive(Debug, uniffi::Object)]
+pub struct IdKitSession(Session);
+
+/// Creates a session with the Wallet Bridge, or the default one if `bridge_url` is `None`.
+///
+/// # Errors
+///
+/// Returns an error if one of the arguments is invalid, or if the request to the bridge fails.
+#[uniffi::export(async_runtime = "tokio")]
+pub async fn create_session(
+	app_id: String,
+	action: String,
+	verification_level: VerificationLevel,
+	signal: SignalInput,
+	bridge_url: Option<String>,
+	action_description: Option<String>,
+) -> Result<Arc<IdKitSession>, IdKitError> {
+	let app_id =
+		AppId::from_str(&app_id).map_err(|error| IdKitError::InvalidAppId(error.to_string()))?;
+	let bridge_url = match bridge_url {
+		Some(url) => Url::parse(&url)
+			.map_err(|error| error.to_string())
+			.and_then(|url| BridgeUrl::try_from(url).map_err(|error| error.to_string()))
+			.map_err(IdKitError::InvalidBridgeUrl)?,
+		None => BridgeUrl::default(),
+	};
+	let description = action_description.as_deref();
+
+	let session = match signal {
+		SignalInput::String { value } => {
+			Session::new(
+				&app_id,
+				action.as_str(),
+				verification_level,
+				bridge_url,
+				value,
+				description,
+			)
+			.await
+		},
+		SignalInput::Bytes { value } => {
+			Session::new(
+				&app_id,
+				action.as_str(),
+				verification_level,
+				bridge_url,
+				value,
+				description,
+			)
+			.await
+		},
+		SignalInput::PreHashed { hash } => {
+			let FieldElement(hash) = FieldElement::from_str(&hash)
+				.map_err(|error| IdKitError::InvalidSignal(error.to_string()))?;
+
+			Session::new(
+				&app_id,
+				action.as_str(),
+				verification_level,
+				bridge_url,
+				PreHashedSignal(hash),
+				description,
+			)
+			.await
+		},
+	}?;
+
+	Ok(Arc::new(IdKitSession(session)))
+}
+
+#[uniffi::export(async_runtime = "tokio")]
+impl IdKitSession {
+	/// The URL to open or encode in a QR code for the user to connect their World App, see `Session::connect_url`.
+	#[must_use]
+	pub fn connect_url(&self) -> String {
+		self.0.connect_url().to_string()
+	}
+
+	/// Polls the bridge once for the status of the request, see `Session::poll_for_status`.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the request to the bridge fails, or if the response from the bridge is malformed.
+	pub async fn poll_for_status(&self) -> Result<Status, IdKitError> {
+		Ok(self.0.poll_for_status().await?)
+	}
+
+	/// Polls the bridge every `poll_interval_ms` until the request is confirmed or fails, notifying `listener` of every change of status. Returns the final status.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the request to the bridge fails, or if the response from the bridge is malformed.
+	pub async fn wait_for_completion(
+		&self,
+		listener: Box<dyn StatusListener>,
+		poll_interval_ms: u64,
+	) -> Result<Status, IdKitError> {
+		let mut last_status = None;
+
+		loop {
+			let status = self.0.poll_for_status().await?;
+			if last_status.as_ref() != Some(&status) {
+				listener.on_status(status.clone());
+				last_status = Some(status.clone());
+			}
+
+			match status {
+				Status::WaitingForConnection | Status::AwaitingConfirmation => {
+					tokio::time::sleep(Duration::from_millis(poll_interval_ms)).await;
+				},
+				Status::Confirmed(_) | Status::Failed(_) => return Ok(status),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::{crypto, BridgeProof, ConnectInfo};
+	use serde_json::json;
+	use std::sync::Mutex;
+	use wiremock::{
+		matchers::{method, path, path_regex},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	#[derive(Default)]
+	struct Recorder(Mutex<Vec<Status>>);
+
+	impl StatusListener for Arc<Recorder> {
+		fn on_status(&self, status: Status) {
+			self.0.lock().unwrap().push(status);
+		}
+	}
+
+	async fn bridge_server() -> MockServer {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/request"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"request_id": "00000000-0000-0000-0000-000000000000",
+			})))
+			.mount(&server)
+			.await;
+
+		server
+	}
+
+	async fn create(
+		server: &MockServer,
+		signal: SignalInput,
+	) -> Result<Arc<IdKitSession>, IdKitError> {
+		create_session(
+			"app_123".to_string(),
+			"vote_1".to_string(),
+			VerificationLevel::Orb,
+			signal,
+			Some(server.uri()),
+			None,
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_signal_inputs() {
+		let server = bridge_server().await;
+
+		for signal in [
+			SignalInput::String {
+				value: "yes".to_string(),
+			},
+			SignalInput::Bytes { value: vec![1, 2] },
+			SignalInput::PreHashed {
+				hash: format!("0x{}", "00".repeat(32)),
+			},
+		] {
+			create(&server, signal).await.unwrap();
+		}
+
+		assert!(matches!(
+			create(
+				&server,
+				SignalInput::PreHashed {
+					hash: "yes".to_string()
+				}
+			)
+			.await,
+			Err(IdKitError::InvalidSignal(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_invalid_bridge_url() {
+		for url in [
+			"not a url",
+			"data:text/plain,bridge",
+			"http://bridge.example",
+		] {
+			let result = create_session(
+				"app_123".to_string(),
+				"vote_1".to_string(),
+				VerificationLevel::Orb,
+				SignalInput::String {
+					value: String::new(),
+				},
+				Some(url.to_string()),
+				None,
+			)
+			.await;
+
+			assert!(
+				matches!(result, Err(IdKitError::InvalidBridgeUrl(_))),
+				"{url}"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_wait_for_completion() {
+		let server = bridge_server().await;
+		let session = create(
+			&server,
+			SignalInput::String {
+				value: String::new(),
+			},
+		)
+		.await
+		.unwrap();
+
+		let proof = crate::Proof {
+			proof: "0x1".to_string(),
+			merkle_root: "0x2".to_string(),
+			nullifier_hash: "0x3".to_string(),
+			verification_level: VerificationLevel::Orb,
+		};
+		let key: [u8; 32] = ConnectInfo::from_url(&Url::parse(&session.connect_url()).unwrap())
+			.unwrap()
+			.key_bytes()
+			.unwrap()
+			.try_into()
+			.unwrap();
+		let response = serde_json::to_vec(&BridgeProof::try_from(proof.clone()).unwrap()).unwrap();
+
+		for body in [
+			json!({ "status": "retrieved", "response": null }),
+			json!({ "status": "retrieved", "response": null }),
+			json!({
+				"status": "completed",
+				"response": crypto::encrypt(&key, &[9; 12], &response).unwrap(),
+			}),
+		] {
+			Mock::given(method("GET"))
+				.and(path_regex("^/response/"))
+				.respond_with(ResponseTemplate::new(200).set_body_json(body))
+				.up_to_n_times(1)
+				.mount(&server)
+				.await;
+		}
+
+		let recorder = Arc::new(Recorder::default());
+		let status = session
+			.wait_for_completion(Box::new(recorder.clone()), 1)
+			.await
+			.unwrap();
+
+		assert_eq!(status, Status::Confirmed(proof.clone()));
+		assert_eq!(
+			*recorder.0.lock().unwrap(),
+			[Status::AwaitingConfirmation, Status::Confirmed(proof)]
+		);
+	}
+}
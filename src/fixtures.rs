@@ -0,0 +1,15 @@
+//! Values shared by the tests of the crate.
+
+use alloc::format;
+
+use crate::{session::VerificationLevel, Proof};
+
+/// A well-formed Orb proof. It isn't valid, so tests mock the Developer Portal's verdict on it.
+pub(crate) fn proof() -> Proof {
+	Proof {
+		proof: format!("0x{}", "ab".repeat(256)),
+		merkle_root: format!("0x{}", "cd".repeat(32)),
+		nullifier_hash: format!("0x{}", "ef".repeat(32)),
+		verification_level: VerificationLevel::Orb,
+	}
+}
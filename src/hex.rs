@@ -0,0 +1,156 @@
+//! Hex encoding with the `0x` prefix used by every hex field of the World ID protocol.
+//!
+//! Decoding requires the lowercase `0x` prefix and an even number of hex digits, in any case. Whitespace isn't trimmed, so it's reported as an invalid character.
+
+use alloc::{string::String, vec::Vec};
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// An error when decoding a hex string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum HexError {
+	#[error("missing 0x prefix")]
+	MissingPrefix,
+
+	#[error("invalid hex character {character:?} at position {index}")]
+	InvalidCharacter { character: char, index: usize },
+
+	#[error("odd number of hex digits: {0}")]
+	OddLength(usize),
+
+	#[error("expected {expected} bytes, got {actual}")]
+	InvalidLength { expected: usize, actual: usize },
+}
+
+/// Decodes a `0x`-prefixed hex string into bytes.
+///
+/// # Errors
+///
+/// Errors if the prefix is missing, if the string contains anything but hex digits after it, or if it has an odd number of digits.
+pub fn decode_prefixed(value: &str) -> Result<Vec<u8>, HexError> {
+	let hex = value.strip_prefix("0x").ok_or(HexError::MissingPrefix)?;
+
+	if let Some((index, character)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+		return Err(HexError::InvalidCharacter {
+			character,
+			index: index + 2,
+		});
+	}
+
+	if hex.len() % 2 != 0 {
+		return Err(HexError::OddLength(hex.len()));
+	}
+
+	Ok(hex
+		.as_bytes()
+		.chunks(2)
+		.map(|pair| (nibble(pair[0]) << 4) | nibble(pair[1]))
+		.collect())
+}
+
+/// Decodes a `0x`-prefixed hex string of exactly `N` bytes.
+///
+/// # Errors
+///
+/// Errors if the string can't be decoded with `decode_prefixed`, or if it doesn't decode to exactly `N` bytes.
+pub fn decode_fixed<const N: usize>(value: &str) -> Result<[u8; N], HexError> {
+	let bytes = decode_prefixed(value)?;
+
+	bytes
+		.try_into()
+		.map_err(|bytes: Vec<u8>| HexError::InvalidLength {
+			expected: N,
+			actual: bytes.len(),
+		})
+}
+
+/// Encodes bytes as a lowercase, `0x`-prefixed hex string.
+#[must_use]
+pub fn encode_prefixed(bytes: &[u8]) -> String {
+	let mut hex = String::with_capacity(2 + bytes.len() * 2);
+	hex.push_str("0x");
+
+	for byte in bytes {
+		hex.push(DIGITS[usize::from(byte >> 4)].into());
+		hex.push(DIGITS[usize::from(byte & 0xf)].into());
+	}
+
+	hex
+}
+
+/// The value of an ASCII hex digit, which must already have been validated.
+const fn nibble(digit: u8) -> u8 {
+	match digit {
+		b'0'..=b'9' => digit - b'0',
+		b'a'..=b'f' => digit - b'a' + 10,
+		_ => digit - b'A' + 10,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_decode_prefixed() {
+		assert_eq!(decode_prefixed("0x"), Ok(vec![]));
+		assert_eq!(decode_prefixed("0x00ff7a"), Ok(vec![0x00, 0xff, 0x7a]));
+		assert_eq!(decode_prefixed("0xABcdEF"), Ok(vec![0xab, 0xcd, 0xef]));
+	}
+
+	#[test]
+	fn test_decode_prefixed_errors() {
+		assert_eq!(decode_prefixed("abcd"), Err(HexError::MissingPrefix));
+		assert_eq!(decode_prefixed("0Xabcd"), Err(HexError::MissingPrefix));
+		assert_eq!(decode_prefixed(""), Err(HexError::MissingPrefix));
+		assert_eq!(decode_prefixed("0xabc"), Err(HexError::OddLength(3)));
+		assert_eq!(
+			decode_prefixed("0xab cd"),
+			Err(HexError::InvalidCharacter {
+				character: ' ',
+				index: 4
+			})
+		);
+		assert_eq!(decode_prefixed(" 0xabcd"), Err(HexError::MissingPrefix));
+		assert_eq!(
+			decode_prefixed("0xabcd\n"),
+			Err(HexError::InvalidCharacter {
+				character: '\n',
+				index: 6
+			})
+		);
+		assert_eq!(
+			decode_prefixed("0xabcé"),
+			Err(HexError::InvalidCharacter {
+				character: 'é',
+				index: 5
+			})
+		);
+		assert_eq!(
+			decode_prefixed("0xzz").unwrap_err().to_string(),
+			"invalid hex character 'z' at position 2"
+		);
+	}
+
+	#[test]
+	fn test_decode_fixed() {
+		assert_eq!(decode_fixed::<2>("0xBEEF"), Ok([0xbe, 0xef]));
+		assert_eq!(
+			decode_fixed::<32>("0xbeef"),
+			Err(HexError::InvalidLength {
+				expected: 32,
+				actual: 2
+			})
+		);
+		assert_eq!(decode_fixed::<2>("0xbee"), Err(HexError::OddLength(3)));
+	}
+
+	#[test]
+	fn test_encode_prefixed() {
+		assert_eq!(encode_prefixed(&[]), "0x");
+		assert_eq!(encode_prefixed(&[0x00, 0x0f, 0xab, 0xff]), "0x000fabff");
+
+		let bytes: Vec<u8> = (0..=255).collect();
+		assert_eq!(decode_prefixed(&encode_prefixed(&bytes)), Ok(bytes));
+	}
+}
@@ -0,0 +1,150 @@
+//! The success payload of the IDKit JS widget, for backends that receive it as-is from the frontend.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Proof;
+
+/// The `ISuccessResult` object IDKit JS passes to `handleVerify` and `onSuccess`.
+///
+/// IDKit JS 1.x sends `verification_level`, while 0.x sent `credential_type` instead. Both are accepted, and `verification_level` is preferred when both are present.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuccessResult {
+	/// The proof of verification.
+	#[serde(flatten)]
+	pub proof: Proof,
+	/// The signal the proof commits to, for frontends that forward it alongside the result.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub signal: Option<String>,
+}
+
+impl From<SuccessResult> for Proof {
+	fn from(result: SuccessResult) -> Self {
+		result.proof
+	}
+}
+
+impl Proof {
+	/// Parses the JSON success payload of the IDKit JS widget, from either the current or the previous major version.
+	///
+	/// # Errors
+	///
+	/// Errors if the payload isn't valid JSON, or is missing one of the proof fields.
+	pub fn from_idkit_js(value: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str::<SuccessResult>(value).map(Into::into)
+	}
+
+	/// Serializes the proof as the success payload of the current IDKit JS widget.
+	#[must_use]
+	pub fn to_idkit_js(&self) -> String {
+		serde_json::json!({
+			"proof": self.proof,
+			"merkle_root": self.merkle_root,
+			"nullifier_hash": self.nullifier_hash,
+			"verification_level": self.verification_level,
+		})
+		.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::VerificationLevel;
+
+	const MERKLE_ROOT: &str = "0x2264a66d162d7893e12ea8e3c072c51e785bc085ad655f64c10c1a61e00f0bc2";
+	const NULLIFIER_HASH: &str =
+		"0x2bf8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8";
+
+	fn proof() -> String {
+		format!(
+			"0x{}",
+			[
+				"1aa8b8f3b2d2de5ff452c0e1a83e29d6bf46fb83ef35dc5957121ff3d3698a11",
+				"12c6c3df3c9b8e0e3df45a2c2a0e4e1fa52bb7d6e7b5f9c6e3a2a2b0e8f4c3d2",
+				"0e7f1cb2b8e3a6f5d4c3b2a19f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c",
+				"2a91b7c6d5e4f30211a0b9c8d7e6f5041322b1a0c9d8e7f6051423c2b1a0d9e8",
+				"07f6e5d4c3b2a1908f7e6d5c4b3a29180f7e6d5c4b3a29180f7e6d5c4b3a2918",
+				"1f0e2d3c4b5a69788796a5b4c3d2e1f00f1e2d3c4b5a69788796a5b4c3d2e1f0",
+				"0c1b2a39485766758493a2b1c0d9e8f70c1b2a39485766758493a2b1c0d9e8f7",
+				"25e4d3c2b1a0f9e8d7c6b5a4938271605f4e3d2c1b0a99887766554433221100",
+			]
+			.concat()
+		)
+	}
+
+	#[test]
+	fn test_from_idkit_js_v1() {
+		let payload = format!(
+			r#"{{"proof":"{}","merkle_root":"{MERKLE_ROOT}","nullifier_hash":"{NULLIFIER_HASH}","verification_level":"device"}}"#,
+			proof()
+		);
+
+		let parsed = Proof::from_idkit_js(&payload).unwrap();
+		assert_eq!(parsed.proof, proof());
+		assert_eq!(parsed.merkle_root, MERKLE_ROOT);
+		assert_eq!(parsed.nullifier_hash, NULLIFIER_HASH);
+		assert_eq!(parsed.verification_level, VerificationLevel::Device);
+		#[cfg(feature = "verify")]
+		assert!(parsed.validate_format().is_ok());
+	}
+
+	#[test]
+	fn test_from_idkit_js_v0() {
+		let payload = format!(
+			r#"{{"merkle_root":"{MERKLE_ROOT}","nullifier_hash":"{NULLIFIER_HASH}","proof":"{}","credential_type":"orb"}}"#,
+			proof()
+		);
+
+		let parsed = Proof::from_idkit_js(&payload).unwrap();
+		assert_eq!(parsed.verification_level, VerificationLevel::Orb);
+		assert_eq!(parsed.nullifier_hash, NULLIFIER_HASH);
+	}
+
+	#[test]
+	fn test_success_result_signal() {
+		let payload = format!(
+			r#"{{"proof":"{}","merkle_root":"{MERKLE_ROOT}","nullifier_hash":"{NULLIFIER_HASH}","verification_level":"orb","signal":"0x12ae"}}"#,
+			proof()
+		);
+
+		let result: SuccessResult = serde_json::from_str(&payload).unwrap();
+		assert_eq!(result.signal.as_deref(), Some("0x12ae"));
+		assert_eq!(
+			serde_json::to_value(&result).unwrap(),
+			serde_json::from_str::<serde_json::Value>(&payload).unwrap()
+		);
+		assert_eq!(Proof::from(result), Proof::from_idkit_js(&payload).unwrap());
+	}
+
+	#[test]
+	fn test_to_idkit_js() {
+		let proof = Proof {
+			proof: proof(),
+			merkle_root: MERKLE_ROOT.to_string(),
+			nullifier_hash: NULLIFIER_HASH.to_string(),
+			verification_level: VerificationLevel::Orb,
+		};
+		let payload: serde_json::Value = serde_json::from_str(&proof.to_idkit_js()).unwrap();
+
+		assert_eq!(
+			payload,
+			serde_json::json!({
+				"proof": proof.proof,
+				"merkle_root": MERKLE_ROOT,
+				"nullifier_hash": NULLIFIER_HASH,
+				"verification_level": "orb",
+			})
+		);
+		assert_eq!(Proof::from_idkit_js(&proof.to_idkit_js()).unwrap(), proof);
+	}
+
+	#[test]
+	fn test_from_idkit_js_errors() {
+		assert!(Proof::from_idkit_js("not json").is_err());
+		assert!(Proof::from_idkit_js(r#"{"proof":"0x1","merkle_root":"0x2"}"#).is_err());
+		assert!(Proof::from_idkit_js(
+			r#"{"proof":"0x1","merkle_root":"0x2","nullifier_hash":"0x3"}"#
+		)
+		.is_err());
+	}
+}
@@ -0,0 +1,200 @@
+//! Parsing proofs from the JSON strings handlers receive, and emitting them in canonical form.
+
+use crate::Proof;
+
+/// An error parsing a proof from JSON.
+#[derive(Debug, thiserror::Error)]
+pub enum ProofParseError {
+	/// The input isn't valid JSON, or is missing a field or has one of the wrong type.
+	#[error("invalid proof JSON: {0}")]
+	Json(#[from] serde_json::Error),
+	/// One of the hex fields is malformed.
+	#[error("invalid {field}: {reason}")]
+	InvalidField {
+		/// The name of the malformed field.
+		field: &'static str,
+		/// Why the field is malformed.
+		reason: String,
+	},
+}
+
+impl Proof {
+	/// Parses a proof from a JSON string, validates the format of its hex fields and normalizes them to lowercase.
+	///
+	/// Unknown fields are ignored, and the legacy `credential_type` is accepted in place of `verification_level`.
+	///
+	/// # Errors
+	///
+	/// Returns `ProofParseError::Json` if the input isn't a valid proof object, or `ProofParseError::InvalidField` naming the first malformed hex field.
+	pub fn from_json_str(json: &str) -> Result<Self, ProofParseError> {
+		let proof: Self = serde_json::from_str(json)?;
+
+		proof
+			.check_format()
+			.map_err(|(field, reason)| ProofParseError::InvalidField { field, reason })?;
+
+		Ok(proof.normalized())
+	}
+
+	/// Serializes the proof as canonical JSON, with sorted keys, lowercase hex fields and the verification level under `verification_level`.
+	#[must_use]
+	pub fn to_json_string(&self) -> String {
+		serde_json::json!(self.clone().normalized()).to_string()
+	}
+
+	fn normalized(self) -> Self {
+		Self {
+			proof: self.proof.to_ascii_lowercase(),
+			merkle_root: self.merkle_root.to_ascii_lowercase(),
+			nullifier_hash: self.nullifier_hash.to_ascii_lowercase(),
+			..self
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use proptest::prelude::*;
+
+	fn payload(proof: &str, merkle_root: &str, nullifier_hash: &str) -> String {
+		format!(
+			r#"{{"proof":"{proof}","merkle_root":"{merkle_root}","nullifier_hash":"{nullifier_hash}","verification_level":"orb"}}"#
+		)
+	}
+
+	fn invalid_field(json: &str) -> &'static str {
+		match Proof::from_json_str(json).unwrap_err() {
+			ProofParseError::InvalidField { field, .. } => field,
+			error @ ProofParseError::Json(_) => panic!("unexpected error: {error}"),
+		}
+	}
+
+	proptest! {
+		#[test]
+		fn test_from_json_str(proof in any::<Proof>()) {
+			prop_assert_eq!(Proof::from_json_str(&proof.to_json_string()).unwrap(), proof);
+		}
+
+		#[test]
+		fn test_from_json_str_uppercase(proof in any::<Proof>()) {
+			let json = serde_json::to_string(&Proof {
+				proof: proof.proof.to_uppercase().replacen("0X", "0x", 1),
+				..proof.clone()
+			})
+			.unwrap();
+
+			prop_assert_eq!(Proof::from_json_str(&json).unwrap(), proof);
+		}
+	}
+
+	#[test]
+	fn test_from_json_str_uppercase_hex() {
+		let json = payload(
+			&format!("0x{}", "AB".repeat(256)),
+			&format!("0x{}", "Cd".repeat(32)),
+			&format!("0x{}", "EF".repeat(32)),
+		);
+
+		assert_eq!(Proof::from_json_str(&json).unwrap(), fixtures::proof());
+	}
+
+	#[test]
+	fn test_from_json_str_unknown_fields() {
+		let json = format!(
+			r#"{{"proof":"{}","merkle_root":"{}","nullifier_hash":"{}","credential_type":"orb","signal":"my_signal","extra":{{"nested":true}}}}"#,
+			fixtures::proof().proof,
+			fixtures::proof().merkle_root,
+			fixtures::proof().nullifier_hash
+		);
+
+		assert_eq!(Proof::from_json_str(&json).unwrap(), fixtures::proof());
+	}
+
+	#[test]
+	fn test_from_json_str_invalid_fields() {
+		let Proof {
+			proof,
+			merkle_root,
+			nullifier_hash,
+			..
+		} = fixtures::proof();
+
+		assert_eq!(
+			invalid_field(&payload(&"ab".repeat(256), &merkle_root, &nullifier_hash)),
+			"proof"
+		);
+		assert_eq!(
+			invalid_field(&payload(
+				&proof,
+				&format!("0x{}", "cd".repeat(31)),
+				&nullifier_hash
+			)),
+			"merkle_root"
+		);
+		assert_eq!(
+			invalid_field(&payload(
+				&proof,
+				&merkle_root,
+				&format!("0x{}zz", "ef".repeat(31))
+			)),
+			"nullifier_hash"
+		);
+	}
+
+	#[test]
+	fn test_from_json_str_invalid_json() {
+		let canonical = fixtures::proof();
+
+		for json in [
+			"",
+			"not json",
+			"[]",
+			r#"{"proof":"0x00"}"#,
+			&format!(
+				r#"{{"proof":"{}","merkle_root":"{}","nullifier_hash":"{}","verification_level":"unknown"}}"#,
+				canonical.proof, canonical.merkle_root, canonical.nullifier_hash
+			)
+			.as_str(),
+		] {
+			assert!(matches!(
+				Proof::from_json_str(json),
+				Err(ProofParseError::Json(_))
+			));
+		}
+	}
+
+	#[test]
+	fn test_invalid_field_error_message() {
+		let error = Proof::from_json_str(&payload(
+			"ab",
+			&fixtures::proof().merkle_root,
+			&fixtures::proof().nullifier_hash,
+		))
+		.unwrap_err();
+
+		assert_eq!(error.to_string(), "invalid proof: missing 0x prefix");
+	}
+
+	#[test]
+	fn test_to_json_string() {
+		let proof = Proof {
+			proof: fixtures::proof()
+				.proof
+				.to_uppercase()
+				.replacen("0X", "0x", 1),
+			..fixtures::proof()
+		};
+
+		let canonical = fixtures::proof();
+
+		assert_eq!(
+			proof.to_json_string(),
+			format!(
+				r#"{{"merkle_root":"{}","nullifier_hash":"{}","proof":"{}","verification_level":"orb"}}"#,
+				canonical.merkle_root, canonical.nullifier_hash, canonical.proof
+			)
+		);
+	}
+}
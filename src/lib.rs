@@ -1,15 +1,96 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
+extern crate alloc;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "binary")]
+mod binary;
+#[cfg(feature = "contracts")]
+pub mod contracts;
+#[cfg(feature = "std")]
+mod error;
+pub mod exports;
+#[cfg(feature = "uniffi")]
+pub mod ffi;
+#[cfg(all(test, any(feature = "std", feature = "binary")))]
+mod fixtures;
 pub mod hashing;
+pub mod hex;
+#[cfg(feature = "std")]
+mod idkit_js;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "verify")]
+pub mod metrics;
+#[cfg(feature = "minikit")]
+pub mod minikit;
+#[cfg(any(feature = "session", feature = "verify"))]
+mod network;
+#[cfg(feature = "verify")]
+pub mod nullifier;
+#[cfg(feature = "local-verify")]
+pub mod offline;
+#[cfg(feature = "oidc")]
+pub mod oidc;
+#[cfg(feature = "onchain")]
+pub mod onchain;
+#[cfg(feature = "verify")]
+pub mod portal;
+mod proof;
+pub mod protocol;
+#[cfg(feature = "std")]
+mod query;
 pub mod session;
+mod signal;
+#[cfg(any(test, feature = "test-strategies"))]
+pub mod strategies;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(any(feature = "session", feature = "verify"))]
+pub mod trace;
+#[cfg(feature = "verify")]
 pub mod verify;
+#[cfg(feature = "warp")]
+pub mod warp;
+#[cfg(all(feature = "wasm-bindgen", target_arch = "wasm32"))]
+pub mod wasm;
 
+use alloc::string::String;
+#[cfg(feature = "binary")]
+pub use binary::BinaryError;
+#[cfg(feature = "std")]
+pub use error::{Error, ErrorKind};
+#[cfg(feature = "std")]
+pub use idkit_js::SuccessResult;
+#[cfg(feature = "std")]
+pub use json::ProofParseError;
+pub use proof::{DecodeError, DecodedProof, MerkleRoot, NullifierHash};
+#[cfg(feature = "std")]
+pub use query::QueryError;
+#[cfg(feature = "session")]
 pub use session::Session;
 use session::VerificationLevel;
+#[cfg(feature = "sol-value")]
+pub use signal::SolSignal;
+pub use signal::{PreHashedSignal, Signal, SignalBuilder};
+#[cfg(feature = "verify")]
 pub use verify::verify_proof;
 
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+/// The `User-Agent` sent with every request made by this crate, unless configured otherwise.
+pub const USER_AGENT: &str = concat!("idkit-rs/", env!("CARGO_PKG_VERSION"));
+
 /// The proof of verification returned by the World ID protocol.
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+///
+/// Deserializes the verification level from either `verification_level` or the legacy `credential_type`, preferring the former when both are present.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct Proof {
 	/// The Zero-knowledge proof of the verification. A hex string, ABI encoded.
 	pub proof: String,
@@ -20,3 +101,130 @@ pub struct Proof {
 	/// Either orb or device.
 	pub verification_level: VerificationLevel,
 }
+
+#[cfg(feature = "verify")]
+impl Proof {
+	/// Checks that the proof fields are well-formed hex strings of the expected length, without contacting the Developer Portal.
+	///
+	/// # Errors
+	///
+	/// Returns `verify::Error::MalformedProof` describing the first malformed field.
+	pub fn validate_format(&self) -> Result<(), verify::Error> {
+		self.check_format()
+			.map_err(|(field, reason)| verify::Error::MalformedProof { field, reason })
+	}
+}
+
+#[cfg(feature = "std")]
+impl Proof {
+	/// Returns the name of the first malformed field, and why it is malformed.
+	pub(crate) fn check_format(&self) -> Result<(), (&'static str, String)> {
+		[
+			("proof", &self.proof, protocol::PROOF_WORDS * 32),
+			("merkle_root", &self.merkle_root, 32),
+			("nullifier_hash", &self.nullifier_hash, 32),
+		]
+		.into_iter()
+		.try_for_each(|(field, value, len)| {
+			validate_hex(value, len).map_err(|reason| (field, reason))
+		})
+	}
+}
+
+#[cfg(feature = "std")]
+fn validate_hex(value: &str, len: usize) -> Result<(), String> {
+	let actual = hex::decode_prefixed(value)
+		.map_err(|error| error.to_string())?
+		.len();
+
+	if actual != len {
+		return Err(hex::HexError::InvalidLength {
+			expected: len,
+			actual,
+		}
+		.to_string());
+	}
+
+	Ok(())
+}
+
+#[cfg(all(test, feature = "verify"))]
+mod tests {
+	use super::*;
+
+	fn malformed_field(proof: &Proof) -> &'static str {
+		match proof.validate_format().unwrap_err() {
+			verify::Error::MalformedProof { field, .. } => field,
+			error => panic!("unexpected error: {error}"),
+		}
+	}
+
+	#[test]
+	fn test_validate_format() {
+		assert!(fixtures::proof().validate_format().is_ok());
+	}
+
+	#[test]
+	fn test_validate_format_proof() {
+		for proof in [
+			"ab".repeat(256),
+			format!("0x{}", "ab".repeat(255)),
+			format!("0x{}zz", "ab".repeat(255)),
+		] {
+			assert_eq!(
+				malformed_field(&Proof {
+					proof,
+					..fixtures::proof()
+				}),
+				"proof"
+			);
+		}
+	}
+
+	#[test]
+	fn test_validate_format_agrees_with_decode() {
+		let proof = fixtures::proof();
+		let dynamic = Proof {
+			proof: format!("0x{:064x}{:064x}{}", 32, 8, &proof.proof[2..]),
+			..proof.clone()
+		};
+
+		assert!(proof.validate_format().is_ok() && proof.decode().is_ok());
+		assert_eq!(malformed_field(&dynamic), "proof");
+		assert!(dynamic.decode().is_err());
+	}
+
+	#[test]
+	fn test_validate_format_merkle_root() {
+		for merkle_root in [
+			"cd".repeat(32),
+			format!("0x{}", "cd".repeat(31)),
+			format!("0x{}zz", "cd".repeat(31)),
+		] {
+			assert_eq!(
+				malformed_field(&Proof {
+					merkle_root,
+					..fixtures::proof()
+				}),
+				"merkle_root"
+			);
+		}
+	}
+
+	#[test]
+	fn test_validate_format_nullifier_hash() {
+		for nullifier_hash in [
+			"ef".repeat(32),
+			format!("0x{}", "ef".repeat(33)),
+			format!("0x{}zz", "ef".repeat(31)),
+		] {
+			assert_eq!(
+				malformed_field(&Proof {
+					nullifier_hash,
+					..fixtures::proof()
+				}),
+				"nullifier_hash"
+			);
+		}
+	}
+}
@@ -0,0 +1,180 @@
+//! Hooks to monitor sessions and verifications, e.g. to export them to a metrics backend.
+//!
+//! Metrics are labelled with the app id and action, never with nullifier hashes or anything else identifying users.
+
+#[cfg(feature = "session")]
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc,
+};
+use std::{fmt, time::Duration};
+
+use crate::verify::AuditOutcome;
+#[cfg(feature = "session")]
+use crate::{
+	session::{Error, Status},
+	verify::time::Instant,
+};
+
+#[cfg(feature = "prometheus")]
+mod prometheus;
+
+#[cfg(feature = "prometheus")]
+pub use self::prometheus::PrometheusMetrics;
+
+/// The labels metrics are recorded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Labels<'a> {
+	/// The app the session or verification is for.
+	pub app_id: &'a str,
+	/// The action the session or verification is for.
+	pub action: &'a str,
+}
+
+/// The outcome of a single poll of the Wallet Bridge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PollOutcome {
+	/// The World App hasn't retrieved the request yet.
+	WaitingForConnection,
+	/// The user hasn't confirmed the request yet.
+	AwaitingConfirmation,
+	/// The user confirmed the request.
+	Confirmed,
+	/// The request failed.
+	Failed,
+	/// The bridge couldn't be reached, or responded unexpectedly.
+	Error,
+}
+
+impl PollOutcome {
+	/// The snake case name of the outcome, like `awaiting_confirmation`.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::WaitingForConnection => "waiting_for_connection",
+			Self::AwaitingConfirmation => "awaiting_confirmation",
+			Self::Confirmed => "confirmed",
+			Self::Failed => "failed",
+			Self::Error => "error",
+		}
+	}
+}
+
+/// How a session ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionOutcome {
+	/// The user confirmed the request.
+	Confirmed,
+	/// The request failed.
+	Failed,
+	/// The session was dropped before the request was confirmed or failed.
+	Abandoned,
+}
+
+impl SessionOutcome {
+	/// The snake case name of the outcome, like `confirmed`.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Confirmed => "confirmed",
+			Self::Failed => "failed",
+			Self::Abandoned => "abandoned",
+		}
+	}
+}
+
+/// A destination for metrics about sessions and verifications. Every method does nothing by default, so implementations only need to handle the events they care about.
+///
+/// Sessions report to it once `Session::set_metrics` is called, and verifications when it's configured in `VerifyConfig::metrics`. Methods are called inline, so they shouldn't block.
+pub trait Metrics: Send + Sync {
+	/// A session started being monitored.
+	fn session_started(&self, _labels: Labels<'_>) {}
+
+	/// A session polled the Wallet Bridge.
+	fn bridge_polled(&self, _labels: Labels<'_>, _outcome: PollOutcome) {}
+
+	/// A session ended, `duration` after it started being monitored.
+	fn session_ended(&self, _labels: Labels<'_>, _outcome: SessionOutcome, _duration: Duration) {}
+
+	/// A proof was verified with the Developer Portal, taking `duration` including retries.
+	fn verification_completed(
+		&self,
+		_labels: Labels<'_>,
+		_outcome: AuditOutcome,
+		_duration: Duration,
+	) {
+	}
+}
+
+impl fmt::Debug for dyn Metrics {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("Metrics")
+	}
+}
+
+/// Reports the lifecycle of a session to its metrics, ending it as abandoned if it's dropped before confirming or failing.
+#[cfg(feature = "session")]
+#[derive(Debug)]
+pub(crate) struct SessionMetrics {
+	metrics: Arc<dyn Metrics>,
+	app_id: String,
+	action: String,
+	started_at: Instant,
+	ended: AtomicBool,
+}
+
+#[cfg(feature = "session")]
+impl SessionMetrics {
+	pub(crate) fn start(metrics: Arc<dyn Metrics>, app_id: &str, action: &str) -> Self {
+		let session = Self {
+			metrics,
+			app_id: app_id.to_string(),
+			action: action.to_string(),
+			started_at: Instant::now(),
+			ended: AtomicBool::new(false),
+		};
+		session.metrics.session_started(session.labels());
+
+		session
+	}
+
+	/// Records a poll of the bridge, ending the session if the status is final.
+	pub(crate) fn polled(&self, result: &Result<Status, Error>) {
+		let outcome = match result {
+			Ok(Status::WaitingForConnection) => PollOutcome::WaitingForConnection,
+			Ok(Status::AwaitingConfirmation) => PollOutcome::AwaitingConfirmation,
+			Ok(Status::Confirmed(_)) => PollOutcome::Confirmed,
+			Ok(Status::Failed(_)) => PollOutcome::Failed,
+			Err(_) => PollOutcome::Error,
+		};
+		self.metrics.bridge_polled(self.labels(), outcome);
+
+		match outcome {
+			PollOutcome::Confirmed => self.end(SessionOutcome::Confirmed),
+			PollOutcome::Failed => self.end(SessionOutcome::Failed),
+			_ => {},
+		}
+	}
+
+	/// Ends the session, unless it already ended.
+	fn end(&self, outcome: SessionOutcome) {
+		if !self.ended.swap(true, Ordering::Relaxed) {
+			self.metrics
+				.session_ended(self.labels(), outcome, self.started_at.elapsed());
+		}
+	}
+
+	fn labels(&self) -> Labels<'_> {
+		Labels {
+			app_id: &self.app_id,
+			action: &self.action,
+		}
+	}
+}
+
+#[cfg(feature = "session")]
+impl Drop for SessionMetrics {
+	fn drop(&mut self) {
+		self.end(SessionOutcome::Abandoned);
+	}
+}
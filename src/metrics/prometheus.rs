@@ -0,0 +1,320 @@
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+use std::time::Duration;
+
+use super::{Labels, Metrics, PollOutcome, SessionOutcome};
+use crate::verify::AuditOutcome;
+
+/// The buckets of `idkit_session_duration_seconds`, as users take seconds to minutes to scan the QR code and confirm.
+const SESSION_BUCKETS: [f64; 10] = [1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0];
+
+/// `Metrics` exported to Prometheus. All metrics are labelled with `app_id` and `action`:
+///
+/// - `idkit_bridge_polls_total`: a counter of the polls of the Wallet Bridge, by `outcome` (`waiting_for_connection`, `awaiting_confirmation`, `confirmed`, `failed` or `error`).
+/// - `idkit_sessions_in_flight`: a gauge of the sessions that started but haven't ended yet.
+/// - `idkit_session_duration_seconds`: a histogram of the time from the start of a session to its end, by `outcome` (`confirmed`, `failed` or `abandoned`). The `confirmed` outcome is the time to confirmation.
+/// - `idkit_verify_duration_seconds`: a histogram of the time taken to verify a proof with the Developer Portal, by `outcome` (`verified`, `rejected` or `failed`).
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone)]
+pub struct PrometheusMetrics {
+	bridge_polls: IntCounterVec,
+	sessions_in_flight: IntGaugeVec,
+	session_duration: HistogramVec,
+	verify_duration: HistogramVec,
+}
+
+impl std::fmt::Debug for PrometheusMetrics {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("PrometheusMetrics").finish_non_exhaustive()
+	}
+}
+
+impl PrometheusMetrics {
+	/// Create the metrics and register them with `registry`.
+	///
+	/// # Errors
+	///
+	/// Errors if metrics with the same names are already registered.
+	pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+		let metrics = Self {
+			bridge_polls: IntCounterVec::new(
+				Opts::new(
+					"idkit_bridge_polls_total",
+					"Polls of the Wallet Bridge, by outcome.",
+				),
+				&["app_id", "action", "outcome"],
+			)?,
+			sessions_in_flight: IntGaugeVec::new(
+				Opts::new(
+					"idkit_sessions_in_flight",
+					"Sessions with the Wallet Bridge that haven't ended yet.",
+				),
+				&["app_id", "action"],
+			)?,
+			session_duration: HistogramVec::new(
+				HistogramOpts::new(
+					"idkit_session_duration_seconds",
+					"Time from the start of a session to its end, by outcome.",
+				)
+				.buckets(SESSION_BUCKETS.to_vec()),
+				&["app_id", "action", "outcome"],
+			)?,
+			verify_duration: HistogramVec::new(
+				HistogramOpts::new(
+					"idkit_verify_duration_seconds",
+					"Time taken to verify a proof with the Developer Portal, by outcome.",
+				),
+				&["app_id", "action", "outcome"],
+			)?,
+		};
+
+		registry.register(Box::new(metrics.bridge_polls.clone()))?;
+		registry.register(Box::new(metrics.sessions_in_flight.clone()))?;
+		registry.register(Box::new(metrics.session_duration.clone()))?;
+		registry.register(Box::new(metrics.verify_duration.clone()))?;
+
+		Ok(metrics)
+	}
+}
+
+impl Metrics for PrometheusMetrics {
+	fn session_started(&self, labels: Labels<'_>) {
+		self.sessions_in_flight
+			.with_label_values(&[labels.app_id, labels.action])
+			.inc();
+	}
+
+	fn bridge_polled(&self, labels: Labels<'_>, outcome: PollOutcome) {
+		self.bridge_polls
+			.with_label_values(&[labels.app_id, labels.action, outcome.as_str()])
+			.inc();
+	}
+
+	fn session_ended(&self, labels: Labels<'_>, outcome: SessionOutcome, duration: Duration) {
+		self.sessions_in_flight
+			.with_label_values(&[labels.app_id, labels.action])
+			.dec();
+		self.session_duration
+			.with_label_values(&[labels.app_id, labels.action, outcome.as_str()])
+			.observe(duration.as_secs_f64());
+	}
+
+	fn verification_completed(
+		&self,
+		labels: Labels<'_>,
+		outcome: AuditOutcome,
+		duration: Duration,
+	) {
+		self.verify_duration
+			.with_label_values(&[labels.app_id, labels.action, outcome.as_str()])
+			.observe(duration.as_secs_f64());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		fixtures,
+		verify::{verify_proof_with_config, VerifyConfig},
+	};
+	use std::{str::FromStr, sync::Arc};
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn polls(metrics: &PrometheusMetrics, outcome: &str) -> u64 {
+		metrics
+			.bridge_polls
+			.with_label_values(&["app_123", "vote_1", outcome])
+			.get()
+	}
+
+	fn sessions(metrics: &PrometheusMetrics, outcome: &str) -> u64 {
+		metrics
+			.session_duration
+			.with_label_values(&["app_123", "vote_1", outcome])
+			.get_sample_count()
+	}
+
+	fn in_flight(metrics: &PrometheusMetrics) -> i64 {
+		metrics
+			.sessions_in_flight
+			.with_label_values(&["app_123", "vote_1"])
+			.get()
+	}
+
+	#[test]
+	fn test_register() {
+		let registry = Registry::new();
+		PrometheusMetrics::new(&registry).unwrap();
+
+		assert!(PrometheusMetrics::new(&registry).is_err());
+	}
+
+	#[cfg(feature = "session")]
+	#[tokio::test]
+	async fn test_session_metrics() {
+		use crate::session::{crypto, AppId, BridgeUrl, Session, Status, VerificationLevel};
+		use serde_json::json;
+
+		let registry = Registry::new();
+		let metrics = PrometheusMetrics::new(&registry).unwrap();
+
+		let server = MockServer::start().await;
+		let request_id = uuid::Uuid::new_v4();
+		Mock::given(method("POST"))
+			.and(path("/request"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_json(json!({ "request_id": request_id })),
+			)
+			.mount(&server)
+			.await;
+
+		let mut session = Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			BridgeUrl::try_from(Url::parse(&server.uri()).unwrap()).unwrap(),
+			"",
+			None,
+		)
+		.await
+		.unwrap();
+		session.set_metrics(Arc::new(metrics.clone()));
+		assert_eq!(in_flight(&metrics), 1);
+
+		// The World App connects, then the user confirms.
+		let key: [u8; 32] = session
+			.connect_info()
+			.key_bytes()
+			.unwrap()
+			.try_into()
+			.unwrap();
+		let proof = crypto::encrypt(
+			&key,
+			&[1; 12],
+			&serde_json::to_vec(&json!({
+				"proof": "0x1",
+				"merkle_root": "0x2",
+				"nullifier_hash": "0x3",
+				"credential_type": "orb",
+			}))
+			.unwrap(),
+		)
+		.unwrap();
+		for (status, response) in [
+			("initialized", None),
+			("retrieved", None),
+			("completed", Some(proof)),
+		] {
+			Mock::given(method("GET"))
+				.and(path(format!("/response/{request_id}")))
+				.respond_with(
+					ResponseTemplate::new(200)
+						.set_body_json(json!({ "status": status, "response": response })),
+				)
+				.up_to_n_times(1)
+				.mount(&server)
+				.await;
+		}
+
+		assert_eq!(
+			session.poll_for_status().await.unwrap(),
+			Status::WaitingForConnection
+		);
+		assert_eq!(
+			session.poll_for_status().await.unwrap(),
+			Status::AwaitingConfirmation
+		);
+		assert!(matches!(
+			session.poll_for_status().await.unwrap(),
+			Status::Confirmed(_)
+		));
+		// The bridge forgets the request once its response is retrieved.
+		assert!(session.poll_for_status().await.is_ok());
+
+		assert_eq!(polls(&metrics, "waiting_for_connection"), 1);
+		assert_eq!(polls(&metrics, "awaiting_confirmation"), 1);
+		assert_eq!(polls(&metrics, "confirmed"), 1);
+		assert_eq!(polls(&metrics, "failed"), 1);
+		assert_eq!(sessions(&metrics, "confirmed"), 1);
+		assert_eq!(in_flight(&metrics), 0);
+
+		// Ending the session once doesn't count it again when it's dropped.
+		drop(session);
+		assert_eq!(sessions(&metrics, "abandoned"), 0);
+		assert_eq!(in_flight(&metrics), 0);
+
+		// A session dropped before being confirmed is abandoned.
+		let mut session = Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			BridgeUrl::try_from(Url::parse(&server.uri()).unwrap()).unwrap(),
+			"",
+			None,
+		)
+		.await
+		.unwrap();
+		session.set_metrics(Arc::new(metrics.clone()));
+		assert_eq!(in_flight(&metrics), 1);
+		drop(session);
+		assert_eq!(sessions(&metrics, "abandoned"), 1);
+		assert_eq!(in_flight(&metrics), 0);
+
+		let families = registry.gather();
+		assert!(families
+			.iter()
+			.flat_map(|family| family.get_metric())
+			.flat_map(|metric| metric.get_label())
+			.all(|label| ["app_id", "action", "outcome"].contains(&label.get_name())));
+	}
+
+	#[tokio::test]
+	async fn test_verify_metrics() {
+		let registry = Registry::new();
+		let metrics = PrometheusMetrics::new(&registry).unwrap();
+
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.up_to_n_times(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			metrics: Some(Arc::new(metrics.clone())),
+			..VerifyConfig::default()
+		};
+		let proof = fixtures::proof();
+		let app_id = crate::session::AppId::from_str("app_123").unwrap();
+
+		for _ in 0..2 {
+			let _ = verify_proof_with_config(proof.clone(), app_id.clone(), "vote_1", "", &config)
+				.await;
+		}
+
+		for (outcome, count) in [("verified", 1), ("rejected", 1), ("failed", 0)] {
+			assert_eq!(
+				metrics
+					.verify_duration
+					.with_label_values(&["app_123", "vote_1", outcome])
+					.get_sample_count(),
+				count
+			);
+		}
+	}
+}
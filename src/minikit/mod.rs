@@ -0,0 +1,80 @@
+//! Backend support for [MiniKit](https://docs.world.org/mini-apps) commands, for mini apps running inside World App.
+//!
+//! Mini apps don't go through the Wallet Bridge: the frontend receives the result of a command from World App, and forwards it to the backend to be checked.
+
+use crate::{portal, session::AppError, verify};
+
+mod payment;
+mod verify_command;
+pub mod wallet_auth;
+
+pub use payment::{
+	confirm_payment, confirm_payment_with_config, wait_for_payment, PaymentState, TransactionStatus,
+};
+pub use verify_command::{
+	verify_minikit_payload, verify_minikit_payload_with_config, MiniAppVerifyError,
+	MiniAppVerifyPayload, MiniAppVerifySuccess,
+};
+
+/// An error of a MiniKit command.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// World App reported that the command failed.
+	#[error(transparent)]
+	App(#[from] AppError),
+
+	#[error(transparent)]
+	Verify(#[from] verify::Error),
+
+	#[error(transparent)]
+	Portal(#[from] portal::Error),
+
+	#[error("Transaction not found: {0}")]
+	TransactionNotFound(String),
+
+	#[error("Transaction {0} is still pending")]
+	PaymentPending(String),
+
+	#[error("Failed to generate random bytes")]
+	Random,
+
+	#[error("Malformed SIWE message: {0}")]
+	MalformedMessage(String),
+
+	#[error("The signed message is for another address than the payload")]
+	AddressMismatch,
+
+	#[error("The signed message domain doesn't match the one of the request")]
+	InvalidDomain,
+
+	#[error("The signed message URI doesn't match the one of the request")]
+	InvalidUri,
+
+	#[error("The signed message chain id doesn't match the one of the request")]
+	InvalidChainId,
+
+	#[error("The signed message nonce doesn't match the one of the request")]
+	InvalidNonce,
+
+	#[error("The signed message statement doesn't match the one of the request")]
+	InvalidStatement,
+
+	#[error("The signed message request id doesn't match the one of the request")]
+	InvalidRequestId,
+
+	#[error("The signed message expired")]
+	Expired,
+
+	#[error("The signed message isn't valid yet")]
+	NotYetValid,
+
+	#[error("The signed message was issued in the future")]
+	IssuedInFuture,
+
+	#[error("Invalid signature of the message")]
+	InvalidSignature,
+
+	#[cfg(feature = "onchain")]
+	#[error("Failed to call the wallet contract: {0}")]
+	Provider(alloy_transport::TransportError),
+}
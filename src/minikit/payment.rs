@@ -0,0 +1,286 @@
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+use super::Error;
+use crate::{
+	portal::{self, authenticated, require_api_key},
+	session::AppId,
+	verify::{self, send_request, time, ApiKey, VerifyConfig},
+};
+
+/// The state of a MiniKit payment on-chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentState {
+	/// The transaction was submitted, but isn't mined yet.
+	Pending,
+	/// The transaction was mined, and the payment went through.
+	Mined,
+	/// The transaction failed, and the payment didn't go through.
+	Failed,
+}
+
+/// A MiniKit payment, as tracked by the Developer Portal.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct TransactionStatus {
+	/// The state of the transaction.
+	#[serde(rename = "transaction_status")]
+	pub status: PaymentState,
+	/// The reference the payment was initiated with, to match it to an order.
+	pub reference: String,
+	/// The hash of the transaction, once it was submitted on-chain.
+	#[serde(default)]
+	pub transaction_hash: Option<String>,
+	/// The amount paid, in the smallest unit of the token, as a decimal string.
+	pub token_amount: String,
+	/// The token the payment was made in, e.g. `WLD` or `USDCE`.
+	pub token: String,
+	/// The address the payment was made from.
+	pub from: String,
+	/// The address the payment was made to.
+	pub to: String,
+	/// The chain the transaction was submitted to.
+	#[serde(default)]
+	pub chain: Option<String>,
+}
+
+impl TransactionStatus {
+	/// Whether the payment went through for the given reference, and the order can be fulfilled.
+	#[must_use]
+	pub fn is_confirmed(&self, reference: &str) -> bool {
+		self.status == PaymentState::Mined && self.reference == reference
+	}
+}
+
+/// Fetch the status of a MiniKit payment from the Developer Portal.
+///
+/// The status must be checked before fulfilling the order: the frontend only knows that the user submitted the transaction. Use `wait_for_payment` to wait for pending transactions to be mined.
+///
+/// # Errors
+///
+/// Errors if the transaction doesn't exist (`Error::TransactionNotFound`), if the API key is invalid, or if the request fails.
+pub async fn confirm_payment(
+	api_key: ApiKey,
+	app_id: &AppId,
+	transaction_id: &str,
+) -> Result<TransactionStatus, Error> {
+	confirm_payment_with_config(app_id, transaction_id, &authenticated(api_key)).await
+}
+
+/// Fetch the status of a MiniKit payment from the Developer Portal, with a custom configuration. The configuration must have an API key.
+///
+/// # Errors
+///
+/// Errors if the transaction doesn't exist (`Error::TransactionNotFound`), if the API key is missing or invalid, or if the request fails.
+pub async fn confirm_payment_with_config(
+	app_id: &AppId,
+	transaction_id: &str,
+	config: &VerifyConfig,
+) -> Result<TransactionStatus, Error> {
+	require_api_key(config)?;
+
+	let response = send_request::<()>(
+		config,
+		Method::GET,
+		&format!(
+			"/api/v2/minikit/transaction/{}?app_id={}&type=payment",
+			urlencoding::encode(transaction_id),
+			urlencoding::encode(&app_id.0)
+		),
+		None,
+	)
+	.await
+	.map_err(|error| match error {
+		verify::Error::UnexpectedResponse {
+			status: StatusCode::NOT_FOUND,
+			..
+		} => Error::TransactionNotFound(transaction_id.to_string()),
+		error => portal::Error::from(error).into(),
+	})?;
+
+	Ok(response.json().await.map_err(verify::Error::from)?)
+}
+
+/// Fetch the status of a MiniKit payment like `confirm_payment_with_config`, checking again every `interval` while it's pending.
+///
+/// # Errors
+///
+/// Errors with `Error::PaymentPending` if the transaction is still pending after `timeout`, or like `confirm_payment_with_config`.
+pub async fn wait_for_payment(
+	app_id: &AppId,
+	transaction_id: &str,
+	config: &VerifyConfig,
+	interval: Duration,
+	timeout: Duration,
+) -> Result<TransactionStatus, Error> {
+	let started_at = time::Instant::now();
+
+	loop {
+		let status = confirm_payment_with_config(app_id, transaction_id, config).await?;
+		if status.status != PaymentState::Pending {
+			return Ok(status);
+		}
+
+		let elapsed = started_at.elapsed();
+		if elapsed >= timeout {
+			return Err(Error::PaymentPending(transaction_id.to_string()));
+		}
+
+		time::sleep(interval.min(timeout.saturating_sub(elapsed))).await;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::{json, Value};
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{header, method, path, query_param},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn transaction(status: &str) -> Value {
+		json!({
+			"transaction_id": "tx_123",
+			"reference": "order_456",
+			"transaction_hash": "0x8b2c4e6f3a1d9e7b5c3a1f9e7d5b3a1c9e7f5d3b1a9c7e5f3d1b9a7c5e3f1d9b",
+			"transaction_status": status,
+			"from": "0x1234567890abcdef1234567890abcdef12345678",
+			"to": "0xabcdefabcdefabcdefabcdefabcdefabcdefabcd",
+			"chain": "worldchain",
+			"token": "WLD",
+			"token_amount": "500000000000000000",
+			"timestamp": "2024-09-01T12:00:00.000Z",
+			"app_id": "app_123",
+		})
+	}
+
+	async fn portal_server(responses: &[Value]) -> (MockServer, VerifyConfig) {
+		let server = MockServer::start().await;
+		// Every response is served once, except the last one which is served from then on.
+		for (i, response) in responses.iter().enumerate() {
+			let mock = Mock::given(method("GET"))
+				.and(path("/api/v2/minikit/transaction/tx_123"))
+				.and(query_param("app_id", "app_123"))
+				.and(query_param("type", "payment"))
+				.and(header("authorization", "Bearer api_secret"))
+				.respond_with(ResponseTemplate::new(200).set_body_json(response));
+
+			if i + 1 < responses.len() {
+				mock.up_to_n_times(1).mount(&server).await;
+			} else {
+				mock.mount(&server).await;
+			}
+		}
+
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			api_key: Some(ApiKey::from_str("api_secret").unwrap()),
+			..VerifyConfig::default()
+		};
+
+		(server, config)
+	}
+
+	fn app_id() -> AppId {
+		AppId::from_str("app_123").unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_confirm_payment() {
+		for (status, expected) in [
+			("pending", PaymentState::Pending),
+			("mined", PaymentState::Mined),
+			("failed", PaymentState::Failed),
+		] {
+			let (_server, config) = portal_server(&[transaction(status)]).await;
+
+			let transaction = confirm_payment_with_config(&app_id(), "tx_123", &config)
+				.await
+				.unwrap();
+			assert_eq!(transaction.status, expected);
+			assert_eq!(transaction.reference, "order_456");
+			assert_eq!(transaction.token, "WLD");
+			assert_eq!(transaction.token_amount, "500000000000000000");
+			assert_eq!(
+				transaction.is_confirmed("order_456"),
+				expected == PaymentState::Mined
+			);
+			assert!(!transaction.is_confirmed("order_789"));
+		}
+	}
+
+	#[tokio::test]
+	async fn test_unknown_transaction() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.respond_with(ResponseTemplate::new(404).set_body_json(json!({
+				"code": "not_found",
+				"detail": "Transaction not found.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			api_key: Some(ApiKey::from_str("api_secret").unwrap()),
+			..VerifyConfig::default()
+		};
+
+		assert!(matches!(
+			confirm_payment_with_config(&app_id(), "tx_123", &config).await,
+			Err(Error::TransactionNotFound(id)) if id == "tx_123"
+		));
+	}
+
+	#[tokio::test]
+	async fn test_missing_api_key() {
+		assert!(matches!(
+			confirm_payment_with_config(&app_id(), "tx_123", &VerifyConfig::default()).await,
+			Err(Error::Portal(portal::Error::Request(
+				verify::Error::MissingField("api_key")
+			)))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_wait_for_payment() {
+		let (server, config) = portal_server(&[
+			transaction("pending"),
+			transaction("pending"),
+			transaction("mined"),
+		])
+		.await;
+
+		let transaction = wait_for_payment(
+			&app_id(),
+			"tx_123",
+			&config,
+			Duration::from_millis(10),
+			Duration::from_secs(5),
+		)
+		.await
+		.unwrap();
+		assert_eq!(transaction.status, PaymentState::Mined);
+		assert_eq!(server.received_requests().await.unwrap().len(), 3);
+	}
+
+	#[tokio::test]
+	async fn test_wait_for_payment_timeout() {
+		let (_server, config) = portal_server(&[transaction("pending")]).await;
+
+		assert!(matches!(
+			wait_for_payment(
+				&app_id(),
+				"tx_123",
+				&config,
+				Duration::from_millis(10),
+				Duration::from_millis(50),
+			)
+			.await,
+			Err(Error::PaymentPending(id)) if id == "tx_123"
+		));
+	}
+}
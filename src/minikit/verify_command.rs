@@ -0,0 +1,237 @@
+use std::str::FromStr;
+
+use super::Error;
+use crate::{
+	session::{Action, AppError, AppId, VerificationLevel},
+	verify::{self, Verification, VerifyConfig, VerifyResponse},
+	Proof, Signal,
+};
+
+/// The payload World App returns to a mini app for the verify command, as `finalPayload` of `MiniKit.commandsAsync.verify`.
+///
+/// Deserializes from the JSON the frontend forwards as-is, tagged by its `status`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MiniAppVerifyPayload {
+	/// The user verified, and World App generated a proof.
+	Success(MiniAppVerifySuccess),
+	/// The verification failed or was rejected by the user.
+	Error(MiniAppVerifyError),
+}
+
+/// The payload of a successful verify command.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct MiniAppVerifySuccess {
+	pub proof: String,
+	pub merkle_root: String,
+	pub nullifier_hash: String,
+	pub verification_level: VerificationLevel,
+	/// The version of the payload format.
+	#[serde(default)]
+	pub version: u32,
+}
+
+impl From<MiniAppVerifySuccess> for Proof {
+	fn from(payload: MiniAppVerifySuccess) -> Self {
+		Self {
+			proof: payload.proof,
+			merkle_root: payload.merkle_root,
+			nullifier_hash: payload.nullifier_hash,
+			verification_level: payload.verification_level,
+		}
+	}
+}
+
+/// The payload of a failed verify command.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct MiniAppVerifyError {
+	/// Why the command failed. MiniKit uses the same error codes as the Wallet Bridge, and codes unknown to this crate are mapped to `AppError::GenericError`.
+	#[serde(deserialize_with = "deserialize_error_code")]
+	pub error_code: AppError,
+	/// The version of the payload format.
+	#[serde(default)]
+	pub version: u32,
+}
+
+fn deserialize_error_code<'de, D: serde::Deserializer<'de>>(
+	deserializer: D,
+) -> Result<AppError, D::Error> {
+	let code = <String as serde::Deserialize>::deserialize(deserializer)?;
+
+	Ok(AppError::from_str(&code).unwrap_or(AppError::GenericError))
+}
+
+/// Verifies the payload of a MiniKit verify command with the Developer Portal, like `verify::verify_proof` does for proofs from the Wallet Bridge.
+///
+/// # Errors
+///
+/// Returns `Error::App` if the payload reports a failure, or `Error::Verify` if the action is invalid or the proof couldn't be verified.
+pub async fn verify_minikit_payload<A, V>(
+	payload: MiniAppVerifyPayload,
+	app_id: AppId,
+	action: A,
+	signal: V,
+) -> Result<VerifyResponse, Error>
+where
+	A: TryInto<Action> + Send,
+	verify::Error: From<A::Error>,
+	V: Signal + Send,
+{
+	verify_minikit_payload_with_config(
+		payload,
+		app_id,
+		action,
+		signal,
+		VerifyConfig::try_default()?,
+	)
+	.await
+}
+
+/// Verifies the payload of a MiniKit verify command with the Developer Portal, with a custom configuration.
+///
+/// # Errors
+///
+/// Returns `Error::App` if the payload reports a failure, or `Error::Verify` if the action is invalid or the proof couldn't be verified.
+pub async fn verify_minikit_payload_with_config<A, V>(
+	payload: MiniAppVerifyPayload,
+	app_id: AppId,
+	action: A,
+	signal: V,
+	config: VerifyConfig,
+) -> Result<VerifyResponse, Error>
+where
+	A: TryInto<Action> + Send,
+	verify::Error: From<A::Error>,
+	V: Signal + Send,
+{
+	let proof = match payload {
+		MiniAppVerifyPayload::Success(payload) => Proof::from(payload),
+		MiniAppVerifyPayload::Error(payload) => return Err(Error::App(payload.error_code)),
+	};
+
+	Ok(Verification::with_config(app_id, action, config)?
+		.proof(proof)
+		.signal(signal)
+		.send_with_response()
+		.await?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::{json, Value};
+	use url::Url;
+	use wiremock::{
+		matchers::{body_partial_json, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn fixtures() -> Vec<Value> {
+		serde_json::from_str(include_str!(
+			"../../tests/vectors/minikit-verify-payloads.json"
+		))
+		.unwrap()
+	}
+
+	async fn portal_server(payload: &Value) -> MockServer {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(json!({
+				"action": "vote_1",
+				"proof": payload["proof"],
+				"merkle_root": payload["merkle_root"],
+				"nullifier_hash": payload["nullifier_hash"],
+				"verification_level": payload["verification_level"],
+			})))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"success": true,
+				"action": "vote_1",
+				"nullifier_hash": payload["nullifier_hash"],
+				"created_at": "2024-09-01T00:00:00.000Z",
+			})))
+			.mount(&server)
+			.await;
+
+		server
+	}
+
+	async fn verify(
+		payload: MiniAppVerifyPayload,
+		server: &MockServer,
+	) -> Result<VerifyResponse, Error> {
+		let mut config = VerifyConfig::default();
+		config.base_url = Url::parse(&server.uri()).unwrap();
+
+		verify_minikit_payload_with_config(
+			payload,
+			AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			"",
+			config,
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_fixtures() {
+		for fixture in fixtures() {
+			let description = fixture["description"].as_str().unwrap();
+			let payload: MiniAppVerifyPayload =
+				serde_json::from_value(fixture["payload"].clone()).unwrap();
+			let server = portal_server(&fixture["payload"]).await;
+
+			match fixture.get("error_code") {
+				None => {
+					let response = verify(payload, &server).await.unwrap();
+					assert_eq!(
+						response.nullifier_hash.as_deref(),
+						fixture["payload"]["nullifier_hash"].as_str(),
+						"{description}"
+					);
+				},
+				Some(code) => {
+					let error = verify(payload, &server).await.unwrap_err();
+					assert!(
+						matches!(error, Error::App(error) if error.code() == *code),
+						"{description}"
+					);
+					assert!(server.received_requests().await.unwrap().is_empty());
+				},
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn test_rejected_proof() {
+		let fixture = &fixtures()[0]["payload"];
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(json!({
+				"code": "invalid_merkle_root",
+				"detail": "The provided Merkle root is invalid.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let error = verify(serde_json::from_value(fixture.clone()).unwrap(), &server)
+			.await
+			.unwrap_err();
+		assert!(matches!(
+			error,
+			Error::Verify(crate::verify::Error::Verification(_))
+		));
+	}
+
+	#[test]
+	fn test_malformed_payloads() {
+		for payload in [
+			json!({ "status": "pending" }),
+			json!({ "status": "success", "proof": "0x1" }),
+			json!({ "status": "error" }),
+		] {
+			assert!(serde_json::from_value::<MiniAppVerifyPayload>(payload).is_err());
+		}
+	}
+}
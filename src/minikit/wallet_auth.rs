@@ -0,0 +1,775 @@
+use alloy_primitives::{Address, Signature};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::{fmt::Write, str::FromStr};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::Error;
+use crate::{hex::decode_prefixed, verify::time::now};
+
+/// The header ending the first line of a SIWE message, after the domain.
+const HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// The chain id of World Chain, where World App wallets live.
+pub const WORLD_CHAIN_ID: u64 = 480;
+
+/// How far in the future the issued at time of a message may be, to allow for the clock of the phone being ahead of the server's.
+const MAX_CLOCK_SKEW: time::Duration = time::Duration::minutes(5);
+
+/// The parameters of a MiniKit wallet auth command, as passed to `MiniKit.commandsAsync.walletAuth`.
+///
+/// Create one per sign in attempt, send it to the frontend as JSON, and keep it to verify the signed message with. The domain, URI and chain id aren't parameters of the command, but what the signed message is checked against, so a message signed for another site is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[must_use]
+pub struct WalletAuthRequest {
+	/// A random alphanumeric nonce, which the signed message must contain.
+	pub nonce: String,
+	/// The domain of the mini app, like `example.com`, which the signed message must be for.
+	pub domain: String,
+	/// The URI the signed message must be for, if it should be checked.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub uri: Option<String>,
+	/// The chain the signed message must be for, if it should be checked. Defaults to World Chain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub chain_id: Option<u64>,
+	/// The statement shown to the user, which the signed message must contain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub statement: Option<String>,
+	/// An identifier of the request, which the signed message must contain.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub request_id: Option<String>,
+	/// When the signed message stops being valid.
+	#[serde(
+		default,
+		with = "time::serde::rfc3339::option",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub expiration_time: Option<OffsetDateTime>,
+	/// When the signed message starts being valid.
+	#[serde(
+		default,
+		with = "time::serde::rfc3339::option",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub not_before: Option<OffsetDateTime>,
+}
+
+impl WalletAuthRequest {
+	/// Creates a request for the mini app at `domain` with a fresh random nonce.
+	///
+	/// # Errors
+	///
+	/// Returns `Error::Random` if the nonce couldn't be generated.
+	pub fn new(domain: impl Into<String>) -> Result<Self, Error> {
+		let mut bytes = [0; 16];
+		SystemRandom::new()
+			.fill(&mut bytes)
+			.map_err(|_| Error::Random)?;
+
+		let nonce = bytes.iter().fold(String::new(), |mut nonce, byte| {
+			let _ = write!(nonce, "{byte:02x}");
+			nonce
+		});
+
+		Ok(Self::with_nonce(nonce, domain))
+	}
+
+	/// Creates a request for the mini app at `domain` with the given nonce, e.g. one stored in the user's session. SIWE nonces must be at least 8 letters or digits.
+	pub fn with_nonce(nonce: impl Into<String>, domain: impl Into<String>) -> Self {
+		Self {
+			nonce: nonce.into(),
+			domain: domain.into(),
+			uri: None,
+			chain_id: Some(WORLD_CHAIN_ID),
+			statement: None,
+			request_id: None,
+			expiration_time: None,
+			not_before: None,
+		}
+	}
+
+	/// The URI the signed message must be for.
+	pub fn uri(mut self, uri: impl Into<String>) -> Self {
+		self.uri = Some(uri.into());
+		self
+	}
+
+	/// The chain the signed message must be for, or `None` to accept any chain.
+	pub const fn chain_id(mut self, chain_id: Option<u64>) -> Self {
+		self.chain_id = chain_id;
+		self
+	}
+
+	/// The statement shown to the user.
+	pub fn statement(mut self, statement: impl Into<String>) -> Self {
+		self.statement = Some(statement.into());
+		self
+	}
+
+	/// An identifier of the request.
+	pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+		self.request_id = Some(request_id.into());
+		self
+	}
+
+	/// When the signed message stops being valid. MiniKit defaults to a week after the command.
+	pub const fn expiration_time(mut self, expiration_time: OffsetDateTime) -> Self {
+		self.expiration_time = Some(expiration_time);
+		self
+	}
+
+	/// When the signed message starts being valid.
+	pub const fn not_before(mut self, not_before: OffsetDateTime) -> Self {
+		self.not_before = Some(not_before);
+		self
+	}
+}
+
+/// The payload World App returns to a mini app for a successful wallet auth command, as `finalPayload` of `MiniKit.commandsAsync.walletAuth`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct MiniAppWalletAuthSuccess {
+	/// The SIWE message the user signed.
+	pub message: String,
+	/// The `0x`-prefixed signature of the message.
+	pub signature: String,
+	/// The address of the user's wallet.
+	pub address: String,
+	/// The version of the payload format.
+	#[serde(default)]
+	pub version: u32,
+}
+
+/// A [Sign-In with Ethereum](https://eips.ethereum.org/EIPS/eip-4361) message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiweMessage {
+	/// The domain requesting the sign in.
+	pub domain: String,
+	/// The address signing in.
+	pub address: Address,
+	/// The statement shown to the user.
+	pub statement: Option<String>,
+	/// The URI the sign in is for.
+	pub uri: String,
+	/// The version of the message format, always `1`.
+	pub version: String,
+	/// The chain the address lives on.
+	pub chain_id: u64,
+	/// The nonce of the request.
+	pub nonce: String,
+	/// When the message was signed.
+	pub issued_at: OffsetDateTime,
+	/// When the message stops being valid.
+	pub expiration_time: Option<OffsetDateTime>,
+	/// When the message starts being valid.
+	pub not_before: Option<OffsetDateTime>,
+	/// The identifier of the request.
+	pub request_id: Option<String>,
+	/// The resources the user agreed to share.
+	pub resources: Vec<String>,
+}
+
+impl FromStr for SiweMessage {
+	type Err = Error;
+
+	fn from_str(message: &str) -> Result<Self, Self::Err> {
+		let mut lines = message.lines().peekable();
+
+		let domain = lines
+			.next()
+			.and_then(|line| line.strip_suffix(HEADER_SUFFIX))
+			.ok_or_else(|| malformed("missing header"))?;
+		let address = lines
+			.next()
+			.and_then(|line| Address::from_str(line).ok())
+			.ok_or_else(|| malformed("missing or invalid address"))?;
+
+		// The statement is surrounded by empty lines, which remain when it's omitted.
+		let mut statement = None;
+		while let Some(line) = lines.next_if(|line| !line.starts_with("URI: ")) {
+			if !line.is_empty() {
+				if statement.is_some() {
+					return Err(malformed("unexpected line before URI"));
+				}
+				statement = Some(line.to_string());
+			}
+		}
+
+		let mut field = |tag: &str| {
+			lines
+				.next_if(|line| line.starts_with(tag))
+				.map(|line| &line[tag.len()..])
+		};
+		let uri = field("URI: ").ok_or_else(|| malformed("missing URI"))?;
+		let version = field("Version: ").ok_or_else(|| malformed("missing version"))?;
+		let chain_id = field("Chain ID: ")
+			.and_then(|chain_id| chain_id.parse().ok())
+			.ok_or_else(|| malformed("missing or invalid chain id"))?;
+		let nonce = field("Nonce: ").ok_or_else(|| malformed("missing nonce"))?;
+		let issued_at = field("Issued At: ")
+			.map(parse_time)
+			.ok_or_else(|| malformed("missing issued at time"))??;
+		let expiration_time = field("Expiration Time: ").map(parse_time).transpose()?;
+		let not_before = field("Not Before: ").map(parse_time).transpose()?;
+		let request_id = field("Request ID: ").map(ToString::to_string);
+		let has_resources = field("Resources:").is_some();
+
+		let resources = if has_resources {
+			lines
+				.by_ref()
+				.map(|line| line.strip_prefix("- ").map(ToString::to_string))
+				.collect::<Option<_>>()
+				.ok_or_else(|| malformed("invalid resource"))?
+		} else {
+			Vec::new()
+		};
+		if lines.next().is_some() {
+			return Err(malformed("unexpected trailing lines"));
+		}
+
+		if version != "1" {
+			return Err(malformed("unsupported version"));
+		}
+		if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+			return Err(malformed("invalid nonce"));
+		}
+
+		Ok(Self {
+			domain: domain.to_string(),
+			address,
+			statement,
+			uri: uri.to_string(),
+			version: version.to_string(),
+			chain_id,
+			nonce: nonce.to_string(),
+			issued_at,
+			expiration_time,
+			not_before,
+			request_id,
+			resources,
+		})
+	}
+}
+
+fn malformed(reason: &str) -> Error {
+	Error::MalformedMessage(reason.to_string())
+}
+
+fn parse_time(value: &str) -> Result<OffsetDateTime, Error> {
+	OffsetDateTime::parse(value, &Rfc3339)
+		.map_err(|error| Error::MalformedMessage(error.to_string()))
+}
+
+/// Verifies the payload of a MiniKit wallet auth command, signed by an externally owned account, and returns the address that signed in.
+///
+/// The message must be for the domain, URI and chain of `request`, contain its nonce, statement and request id, be within its validity window, and be signed by the address of the payload.
+///
+/// World App wallets are smart contract wallets, whose signatures can only be checked on-chain: use `verify_siwe_message_with_provider` for those.
+///
+/// # Errors
+///
+/// Returns `Error::MalformedMessage` if the message isn't a valid SIWE message, or an error describing the failed check.
+pub fn verify_siwe_message(
+	payload: &MiniAppWalletAuthSuccess,
+	request: &WalletAuthRequest,
+) -> Result<Address, Error> {
+	let message = check_message(payload, request)?;
+
+	if recover_signer(payload)? != Some(message.address) {
+		return Err(Error::InvalidSignature);
+	}
+
+	Ok(message.address)
+}
+
+/// Verifies the payload of a MiniKit wallet auth command like `verify_siwe_message`, falling back to [ERC-1271](https://eips.ethereum.org/EIPS/eip-1271) for signatures of smart contract wallets.
+///
+/// `provider` must be connected to the chain of the wallet, World Chain for World App wallets.
+///
+/// # Errors
+///
+/// Returns `Error::MalformedMessage` if the message isn't a valid SIWE message, `Error::Provider` if the wallet couldn't be called, or an error describing the failed check.
+#[cfg(feature = "onchain")]
+pub async fn verify_siwe_message_with_provider<P, T>(
+	payload: &MiniAppWalletAuthSuccess,
+	request: &WalletAuthRequest,
+	provider: &P,
+) -> Result<Address, Error>
+where
+	P: alloy_provider::Provider<T>,
+	T: alloy_transport::Transport + Clone,
+{
+	let message = check_message(payload, request)?;
+
+	if recover_signer(payload)? == Some(message.address) {
+		return Ok(message.address);
+	}
+
+	if !erc1271::is_valid_signature(provider, message.address, payload).await? {
+		return Err(Error::InvalidSignature);
+	}
+
+	Ok(message.address)
+}
+
+/// Parses the message of the payload, and checks it against the request.
+fn check_message(
+	payload: &MiniAppWalletAuthSuccess,
+	request: &WalletAuthRequest,
+) -> Result<SiweMessage, Error> {
+	let message = SiweMessage::from_str(&payload.message)?;
+
+	if Address::from_str(&payload.address).ok() != Some(message.address) {
+		return Err(Error::AddressMismatch);
+	}
+	if message.domain != request.domain {
+		return Err(Error::InvalidDomain);
+	}
+	if request.uri.is_some() && Some(&message.uri) != request.uri.as_ref() {
+		return Err(Error::InvalidUri);
+	}
+	if request
+		.chain_id
+		.is_some_and(|chain_id| message.chain_id != chain_id)
+	{
+		return Err(Error::InvalidChainId);
+	}
+	if message.nonce != request.nonce {
+		return Err(Error::InvalidNonce);
+	}
+	if request.statement.is_some() && message.statement != request.statement {
+		return Err(Error::InvalidStatement);
+	}
+	if request.request_id.is_some() && message.request_id != request.request_id {
+		return Err(Error::InvalidRequestId);
+	}
+
+	let now = OffsetDateTime::from(now());
+	if message.issued_at > now + MAX_CLOCK_SKEW {
+		return Err(Error::IssuedInFuture);
+	}
+	if message
+		.expiration_time
+		.is_some_and(|expiration| now >= expiration)
+	{
+		return Err(Error::Expired);
+	}
+	if message
+		.not_before
+		.is_some_and(|not_before| now < not_before)
+	{
+		return Err(Error::NotYetValid);
+	}
+
+	Ok(message)
+}
+
+/// Recovers the address that signed the message of the payload as an [EIP-191](https://eips.ethereum.org/EIPS/eip-191) personal message, or `None` if the signature isn't an ECDSA signature.
+fn recover_signer(payload: &MiniAppWalletAuthSuccess) -> Result<Option<Address>, Error> {
+	let signature = decode_prefixed(&payload.signature).map_err(|_| Error::InvalidSignature)?;
+
+	Ok(Signature::try_from(signature.as_slice())
+		.ok()
+		.and_then(|signature| signature.recover_address_from_msg(&payload.message).ok()))
+}
+
+#[cfg(feature = "onchain")]
+mod erc1271 {
+	use alloy_primitives::{eip191_hash_message, Address, FixedBytes};
+	use alloy_provider::Provider;
+	use alloy_rpc_types_eth::TransactionRequest;
+	use alloy_sol_types::SolCall;
+	use alloy_transport::Transport;
+
+	use super::{Error, MiniAppWalletAuthSuccess};
+	use crate::hex::decode_prefixed;
+
+	#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+	mod bindings {
+		alloy_sol_types::sol! {
+			interface IERC1271 {
+				function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+			}
+		}
+	}
+
+	use bindings::IERC1271;
+
+	/// The value returned by `isValidSignature` for valid signatures.
+	const MAGIC_VALUE: FixedBytes<4> = FixedBytes([0x16, 0x26, 0xba, 0x7e]);
+
+	/// Asks the wallet at `address` whether it signed the message of the payload. Calls that revert or return garbage, e.g. because there's no contract at the address, count as a no.
+	pub(super) async fn is_valid_signature<P, T>(
+		provider: &P,
+		address: Address,
+		payload: &MiniAppWalletAuthSuccess,
+	) -> Result<bool, Error>
+	where
+		P: Provider<T>,
+		T: Transport + Clone,
+	{
+		let call = IERC1271::isValidSignatureCall {
+			hash: eip191_hash_message(&payload.message),
+			signature: decode_prefixed(&payload.signature)
+				.map_err(|_| Error::InvalidSignature)?
+				.into(),
+		};
+
+		let data = match provider
+			.call(
+				&TransactionRequest::default()
+					.to(address)
+					.input(call.abi_encode().into()),
+			)
+			.await
+		{
+			Ok(data) => data,
+			Err(error) if error.as_error_resp().is_some() => return Ok(false),
+			Err(error) => return Err(Error::Provider(error)),
+		};
+
+		Ok(
+			IERC1271::isValidSignatureCall::abi_decode_returns(&data, true)
+				.is_ok_and(|result| result.magicValue == MAGIC_VALUE),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use alloy_primitives::eip191_hash_message;
+	use k256::ecdsa::SigningKey;
+	use time::Duration;
+
+	/// The first development account of Hardhat and Anvil.
+	const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+	const ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+	/// The second development account of Hardhat and Anvil.
+	const OTHER_PRIVATE_KEY: &str =
+		"59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+	fn sign(private_key: &str, message: &str) -> String {
+		let key =
+			SigningKey::from_slice(&decode_prefixed(&format!("0x{private_key}")).unwrap()).unwrap();
+		let (signature, recovery_id) = key
+			.sign_prehash_recoverable(eip191_hash_message(message).as_slice())
+			.unwrap();
+
+		let mut bytes = signature.to_bytes().to_vec();
+		bytes.push(27 + recovery_id.to_byte());
+		crate::hex::encode_prefixed(&bytes)
+	}
+
+	fn timestamp(offset: Duration) -> String {
+		(OffsetDateTime::now_utc() + offset)
+			.format(&Rfc3339)
+			.unwrap()
+	}
+
+	fn message(nonce: &str, expires_in: Duration, valid_in: Duration) -> String {
+		message_issued(nonce, Duration::ZERO, expires_in, valid_in)
+	}
+
+	fn message_issued(
+		nonce: &str,
+		issued_in: Duration,
+		expires_in: Duration,
+		valid_in: Duration,
+	) -> String {
+		format!(
+			"example.com wants you to sign in with your Ethereum account:\n{ADDRESS}\n\nSign in to Example\n\nURI: https://example.com\nVersion: 1\nChain ID: 480\nNonce: {nonce}\nIssued At: {}\nExpiration Time: {}\nNot Before: {}\nRequest ID: request_1",
+			timestamp(issued_in),
+			timestamp(expires_in),
+			timestamp(valid_in),
+		)
+	}
+
+	fn payload(private_key: &str, message: String) -> MiniAppWalletAuthSuccess {
+		MiniAppWalletAuthSuccess {
+			signature: sign(private_key, &message),
+			message,
+			address: ADDRESS.to_string(),
+			version: 2,
+		}
+	}
+
+	fn payload_issued(issued_in: Duration) -> MiniAppWalletAuthSuccess {
+		payload(
+			PRIVATE_KEY,
+			message_issued(
+				"a1b2c3d4e5f6",
+				issued_in,
+				Duration::hours(2),
+				-Duration::minutes(1),
+			),
+		)
+	}
+
+	fn request() -> WalletAuthRequest {
+		WalletAuthRequest::with_nonce("a1b2c3d4e5f6", "example.com")
+			.uri("https://example.com")
+			.statement("Sign in to Example")
+			.request_id("request_1")
+	}
+
+	#[test]
+	fn test_new_request() {
+		let request = WalletAuthRequest::new("example.com")
+			.unwrap()
+			.expiration_time(OffsetDateTime::from_unix_timestamp(1_725_192_000).unwrap());
+
+		assert_eq!(request.nonce.len(), 32);
+		assert!(request.nonce.chars().all(|c| c.is_ascii_alphanumeric()));
+		assert_eq!(request.chain_id, Some(WORLD_CHAIN_ID));
+		assert_ne!(
+			WalletAuthRequest::new("example.com").unwrap().nonce,
+			request.nonce
+		);
+		assert_eq!(
+			serde_json::to_value(&request).unwrap(),
+			serde_json::json!({
+				"nonce": request.nonce,
+				"domain": "example.com",
+				"chainId": 480,
+				"expirationTime": "2024-09-01T12:00:00Z",
+			})
+		);
+	}
+
+	#[test]
+	fn test_parse_message() {
+		let message = SiweMessage::from_str(
+			"example.com wants you to sign in with your Ethereum account:\n0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266\n\n\nURI: https://example.com/login\nVersion: 1\nChain ID: 480\nNonce: a1b2c3d4e5f6\nIssued At: 2024-09-01T12:00:00Z\nResources:\n- https://example.com/terms\n- ipfs://bafybeiemxf5abjwjbikoz4mc3a3dla6ual3jsgpdr4cjr3oz3evfyavhwq",
+		)
+		.unwrap();
+
+		assert_eq!(message.domain, "example.com");
+		assert_eq!(message.address, Address::from_str(ADDRESS).unwrap());
+		assert_eq!(message.statement, None);
+		assert_eq!(message.uri, "https://example.com/login");
+		assert_eq!(message.chain_id, 480);
+		assert_eq!(message.nonce, "a1b2c3d4e5f6");
+		assert_eq!(message.issued_at.unix_timestamp(), 1_725_192_000);
+		assert_eq!(message.expiration_time, None);
+		assert_eq!(message.resources.len(), 2);
+	}
+
+	#[test]
+	fn test_verify_siwe_message() {
+		let payload = payload(
+			PRIVATE_KEY,
+			message("a1b2c3d4e5f6", Duration::hours(1), -Duration::minutes(1)),
+		);
+
+		assert_eq!(
+			verify_siwe_message(&payload, &request()).unwrap(),
+			Address::from_str(ADDRESS).unwrap()
+		);
+		// Only the nonce, domain and chain are required to match.
+		assert!(verify_siwe_message(
+			&payload,
+			&WalletAuthRequest::with_nonce("a1b2c3d4e5f6", "example.com")
+		)
+		.is_ok());
+		// A message issued slightly in the future is accepted, as clocks drift.
+		assert!(verify_siwe_message(&payload_issued(Duration::minutes(1)), &request()).is_ok());
+	}
+
+	#[test]
+	fn test_failed_checks() {
+		let valid = message("a1b2c3d4e5f6", Duration::hours(1), -Duration::minutes(1));
+
+		let check = |payload: MiniAppWalletAuthSuccess, request: WalletAuthRequest| {
+			verify_siwe_message(&payload, &request).unwrap_err()
+		};
+
+		assert!(matches!(
+			check(
+				payload(PRIVATE_KEY, valid.clone()),
+				WalletAuthRequest::with_nonce("f6e5d4c3b2a1", "example.com")
+			),
+			Error::InvalidNonce
+		));
+		assert!(matches!(
+			check(
+				payload(PRIVATE_KEY, valid.clone()),
+				WalletAuthRequest::with_nonce("a1b2c3d4e5f6", "evil.example")
+			),
+			Error::InvalidDomain
+		));
+		assert!(matches!(
+			check(
+				payload(PRIVATE_KEY, valid.clone()),
+				request().uri("https://evil.example")
+			),
+			Error::InvalidUri
+		));
+		assert!(matches!(
+			check(
+				payload(PRIVATE_KEY, valid.clone()),
+				request().chain_id(Some(1))
+			),
+			Error::InvalidChainId
+		));
+		assert!(matches!(
+			check(payload_issued(Duration::hours(1)), request()),
+			Error::IssuedInFuture
+		));
+		assert!(matches!(
+			check(
+				payload(PRIVATE_KEY, valid.clone()),
+				request().statement("Sign in to Other")
+			),
+			Error::InvalidStatement
+		));
+		assert!(matches!(
+			check(
+				payload(PRIVATE_KEY, valid.clone()),
+				request().request_id("request_2")
+			),
+			Error::InvalidRequestId
+		));
+		assert!(matches!(
+			check(
+				payload(
+					PRIVATE_KEY,
+					message("a1b2c3d4e5f6", -Duration::minutes(1), -Duration::hours(1))
+				),
+				request()
+			),
+			Error::Expired
+		));
+		assert!(matches!(
+			check(
+				payload(
+					PRIVATE_KEY,
+					message("a1b2c3d4e5f6", Duration::hours(2), Duration::hours(1))
+				),
+				request()
+			),
+			Error::NotYetValid
+		));
+		assert!(matches!(
+			check(payload(OTHER_PRIVATE_KEY, valid.clone()), request()),
+			Error::InvalidSignature
+		));
+		assert!(matches!(
+			check(
+				MiniAppWalletAuthSuccess {
+					address: "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(),
+					..payload(PRIVATE_KEY, valid.clone())
+				},
+				request()
+			),
+			Error::AddressMismatch
+		));
+		assert!(matches!(
+			check(
+				MiniAppWalletAuthSuccess {
+					signature: "0x1234".to_string(),
+					..payload(PRIVATE_KEY, valid.clone())
+				},
+				request()
+			),
+			Error::InvalidSignature
+		));
+	}
+
+	#[test]
+	fn test_malformed_messages() {
+		let valid = message("a1b2c3d4e5f6", Duration::hours(1), -Duration::minutes(1));
+
+		for message in [
+			valid.replacen(" wants you", " asks you", 1),
+			valid.replacen(ADDRESS, "0x1234", 1),
+			valid.replacen("Version: 1", "Version: 2", 1),
+			valid.replacen("Chain ID: 480", "Chain ID: world", 1),
+			valid.replacen("Nonce: a1b2c3d4e5f6", "Nonce: a1b2", 1),
+			valid.replacen("Issued At: ", "Issued At: yesterday ", 1),
+			valid.replacen("\nNonce: a1b2c3d4e5f6", "", 1),
+			format!("{valid}\nSigned by me"),
+		] {
+			assert!(
+				matches!(
+					verify_siwe_message(&payload(PRIVATE_KEY, message.clone()), &request()),
+					Err(Error::MalformedMessage(_))
+				),
+				"{message}"
+			);
+		}
+	}
+
+	#[cfg(feature = "onchain")]
+	mod erc1271 {
+		use super::*;
+		use alloy_provider::ProviderBuilder;
+		use url::Url;
+		use wiremock::{matchers::method, Mock, MockServer, Request, ResponseTemplate};
+
+		/// A node where the wallet at `ADDRESS` is a contract answering `isValidSignature` with `magic_value`.
+		async fn node(magic_value: &'static str) -> MockServer {
+			let server = MockServer::start().await;
+			Mock::given(method("POST"))
+				.respond_with(move |request: &Request| {
+					let body: serde_json::Value = request.body_json().unwrap();
+					assert!(body["params"][0]["to"]
+						.as_str()
+						.unwrap()
+						.eq_ignore_ascii_case(ADDRESS));
+
+					ResponseTemplate::new(200).set_body_json(serde_json::json!({
+						"jsonrpc": "2.0",
+						"id": body["id"],
+						"result": format!("0x{magic_value:0<64}"),
+					}))
+				})
+				.mount(&server)
+				.await;
+
+			server
+		}
+
+		#[tokio::test]
+		async fn test_contract_wallet() {
+			// A Safe signature, which isn't a 65-byte ECDSA signature.
+			let payload = MiniAppWalletAuthSuccess {
+				signature: format!("0x{}", "ab".repeat(77)),
+				..payload(
+					PRIVATE_KEY,
+					message("a1b2c3d4e5f6", Duration::hours(1), -Duration::minutes(1)),
+				)
+			};
+
+			let server = node("1626ba7e").await;
+			let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+			assert_eq!(
+				verify_siwe_message_with_provider(&payload, &request(), &provider)
+					.await
+					.unwrap(),
+				Address::from_str(ADDRESS).unwrap()
+			);
+
+			let server = node("ffffffff").await;
+			let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+			assert!(matches!(
+				verify_siwe_message_with_provider(&payload, &request(), &provider).await,
+				Err(Error::InvalidSignature)
+			));
+		}
+
+		#[tokio::test]
+		async fn test_eoa_skips_provider() {
+			let payload = payload(
+				PRIVATE_KEY,
+				message("a1b2c3d4e5f6", Duration::hours(1), -Duration::minutes(1)),
+			);
+
+			let server = node("ffffffff").await;
+			let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+			verify_siwe_message_with_provider(&payload, &request(), &provider)
+				.await
+				.unwrap();
+			assert!(server.received_requests().await.unwrap().is_empty());
+		}
+	}
+}
@@ -0,0 +1,116 @@
+//! Classifies the errors of the HTTP client, so every module reports network failures the same way.
+
+use reqwest::StatusCode;
+#[cfg(not(target_arch = "wasm32"))]
+use std::error::Error as _;
+
+/// Why a request failed, as far as it can be told from a `reqwest::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+	/// The request or the connection timed out.
+	Timeout,
+	/// The host name couldn't be resolved.
+	Dns,
+	/// A connection couldn't be established, e.g. because it was refused or the TLS handshake failed.
+	Connect,
+	/// The server responded with an error status.
+	Status(StatusCode),
+	/// Anything else, like a body that couldn't be decoded.
+	Other,
+}
+
+impl Failure {
+	pub fn of(error: &reqwest::Error) -> Self {
+		if error.is_timeout() {
+			return Self::Timeout;
+		}
+
+		// Connections are managed by `fetch` on wasm, which doesn't say why they failed.
+		#[cfg(not(target_arch = "wasm32"))]
+		if error.is_connect() {
+			return if is_dns(error) {
+				Self::Dns
+			} else {
+				Self::Connect
+			};
+		}
+
+		error.status().map_or(Self::Other, Self::Status)
+	}
+}
+
+/// Whether a connection error was caused by a failed lookup.
+///
+/// This is a heuristic: the resolver's error is an `io::Error` like any other connection failure, and the error of hyper's connector wrapping it isn't public, so the only marker of a lookup failure is the `dns error` message of that wrapper somewhere in the source chain. `test_failures` covers it, so a change of the message is caught when hyper is upgraded.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_dns(error: &reqwest::Error) -> bool {
+	let mut source = error.source();
+	while let Some(error) = source {
+		if error.to_string().starts_with("dns error") {
+			return true;
+		}
+		source = error.source();
+	}
+
+	false
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+	use wiremock::{Mock, MockServer, ResponseTemplate};
+
+	#[tokio::test]
+	async fn test_failures() {
+		let client = reqwest::Client::new();
+
+		let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		drop(listener);
+		let refused = client
+			.get(format!("http://{address}"))
+			.send()
+			.await
+			.unwrap_err();
+		assert_eq!(Failure::of(&refused), Failure::Connect);
+
+		// `.invalid` never resolves, so this relies on the `dns error` message of hyper's connector.
+		let unresolved = client.get("http://idkit.invalid").send().await.unwrap_err();
+		assert!(unresolved.is_connect());
+		assert_eq!(Failure::of(&unresolved), Failure::Dns);
+
+		let server = MockServer::start().await;
+		Mock::given(wiremock::matchers::path("/slow"))
+			.respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+			.mount(&server)
+			.await;
+		Mock::given(wiremock::matchers::path("/error"))
+			.respond_with(ResponseTemplate::new(500))
+			.mount(&server)
+			.await;
+
+		let slow = client
+			.get(format!("{}/slow", server.uri()))
+			.timeout(Duration::from_millis(100))
+			.send()
+			.await
+			.unwrap_err();
+		assert_eq!(Failure::of(&slow), Failure::Timeout);
+
+		let status = client
+			.get(format!("{}/error", server.uri()))
+			.send()
+			.await
+			.unwrap()
+			.error_for_status()
+			.unwrap_err();
+		assert_eq!(
+			Failure::of(&status),
+			Failure::Status(StatusCode::INTERNAL_SERVER_ERROR)
+		);
+
+		let builder = client.get("http://[::1").build().unwrap_err();
+		assert_eq!(Failure::of(&builder), Failure::Other);
+	}
+}
@@ -0,0 +1,474 @@
+//! Sybil and replay protection, by making sure each nullifier hash is only accepted once per action.
+
+use futures_util::FutureExt;
+use std::{
+	collections::HashSet,
+	convert::Infallible,
+	future::{ready, Future},
+	sync::{Mutex, PoisonError},
+};
+
+use crate::{
+	session::{Action, AppId},
+	verify::{self, VerifyConfig},
+	NullifierHash, Proof, Signal,
+};
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "postgres")]
+pub use postgres::{PostgresNullifierStore, Uniqueness};
+#[cfg(feature = "redis")]
+pub use redis::{RedisNullifierStore, RedisStoreError};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{migrate as migrate_sqlite, SqliteNullifierStore};
+
+/// The outcome of recording a nullifier hash in a `NullifierStore`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Insertion {
+	/// The nullifier hash wasn't used for this action yet, and has now been recorded.
+	Inserted,
+	/// The nullifier hash was already used for this action.
+	AlreadyUsed,
+}
+
+/// Storage for the nullifier hashes that have been used for each action.
+///
+/// Implementations must make `check_and_insert` atomic: when called concurrently with the same nullifier hash and action, exactly one call returns `Insertion::Inserted`. Nullifier hashes must be compared by value, e.g. by storing them in the canonical form returned by `canonical_nullifier_hash`, so the same nullifier hash can't be replayed with a different case or padding.
+pub trait NullifierStore {
+	type Error: std::error::Error + Send + Sync + 'static;
+
+	/// Records the nullifier hash for the action, unless it was already recorded.
+	fn check_and_insert(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<Insertion, Self::Error>> + Send;
+
+	/// Forgets a previously recorded nullifier hash, so it can be used again for the action.
+	///
+	/// The returned future must not borrow the store, so the nullifier hash of a cancelled verification can be released in the background.
+	fn remove(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static;
+}
+
+/// The canonical form of a nullifier hash, as displayed by `NullifierHash`: lowercase and zero-padded to 32 bytes. Malformed nullifier hashes are returned as-is, as they can't be verified anyway.
+#[must_use]
+pub fn canonical_nullifier_hash(nullifier_hash: &str) -> String {
+	nullifier_hash
+		.parse::<NullifierHash>()
+		.map_or_else(|_| nullifier_hash.to_string(), |hash| hash.to_string())
+}
+
+/// A `NullifierStore` keeping nullifier hashes in memory. Useful for tests and single-instance deployments, as its contents are lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryNullifierStore {
+	used: Mutex<HashSet<(String, String)>>,
+}
+
+impl MemoryNullifierStore {
+	/// Create an empty store.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+impl NullifierStore for MemoryNullifierStore {
+	type Error = Infallible;
+
+	fn check_and_insert(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<Insertion, Self::Error>> + Send {
+		let inserted = self
+			.used
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.insert((canonical_nullifier_hash(nullifier_hash), action.to_string()));
+
+		ready(Ok(if inserted {
+			Insertion::Inserted
+		} else {
+			Insertion::AlreadyUsed
+		}))
+	}
+
+	fn remove(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+		self.used
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.remove(&(canonical_nullifier_hash(nullifier_hash), action.to_string()));
+
+		ready(Ok(()))
+	}
+}
+
+/// An error when verifying a proof that must only be accepted once.
+#[derive(Debug, thiserror::Error)]
+pub enum Error<E> {
+	#[error("the nullifier hash was already used for this action")]
+	AlreadyUsed,
+
+	#[error(transparent)]
+	Verify(#[from] verify::Error),
+
+	#[error("failed to access the nullifier store: {0}")]
+	Store(E),
+}
+
+/// A nullifier hash claimed in a store for a verification, released again when dropped unless the verification succeeded. This covers verifications that are cancelled, e.g. because the client disconnected, as well as failed releases, which are retried in the background.
+struct Claim<'a, S: NullifierStore> {
+	store: &'a S,
+	nullifier_hash: String,
+	action: &'a str,
+	release_on_drop: bool,
+}
+
+impl<S: NullifierStore + Sync> Claim<'_, S> {
+	/// Releases the nullifier hash now, so it can be used again as soon as this returns.
+	async fn release(mut self) {
+		if self
+			.store
+			.remove(&self.nullifier_hash, self.action)
+			.await
+			.is_ok()
+		{
+			self.release_on_drop = false;
+		}
+	}
+}
+
+impl<S: NullifierStore> Drop for Claim<'_, S> {
+	fn drop(&mut self) {
+		if !self.release_on_drop {
+			return;
+		}
+
+		let release = self.store.remove(&self.nullifier_hash, self.action);
+
+		#[cfg(not(target_arch = "wasm32"))]
+		if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+			runtime.spawn(release);
+			return;
+		}
+
+		// Without a runtime to spawn it on, the release only completes if it doesn't have to wait.
+		drop(release.now_or_never());
+	}
+}
+
+/// Verify a World ID proof using the Developer Portal API, accepting each nullifier hash only once per action.
+///
+/// The nullifier hash is claimed in the store before contacting the Developer Portal, so concurrent verifications of the same proof can't both succeed, and released again if the verification fails or its future is dropped before it completes.
+///
+/// # Errors
+///
+/// Errors if the nullifier hash was already used (`Error::AlreadyUsed`), if the action or the proof is invalid (`Error::Verify`), or if the store fails (`Error::Store`).
+pub async fn verify_proof_unique<S, A, V>(
+	store: &S,
+	proof: Proof,
+	app_id: AppId,
+	action: A,
+	signal: V,
+) -> Result<(), Error<S::Error>>
+where
+	S: NullifierStore + Sync,
+	A: TryInto<Action> + Send,
+	verify::Error: From<A::Error>,
+	V: Signal + Send,
+{
+	verify_proof_unique_with_config(
+		store,
+		proof,
+		app_id,
+		action,
+		signal,
+		&VerifyConfig::try_default()?,
+	)
+	.await
+}
+
+/// Verify a World ID proof using the Developer Portal API, accepting each nullifier hash only once per action, with a custom configuration.
+///
+/// # Errors
+///
+/// Errors if the nullifier hash was already used (`Error::AlreadyUsed`), if the action or the proof is invalid (`Error::Verify`), or if the store fails (`Error::Store`).
+pub async fn verify_proof_unique_with_config<S, A, V>(
+	store: &S,
+	proof: Proof,
+	app_id: AppId,
+	action: A,
+	signal: V,
+	config: &VerifyConfig,
+) -> Result<(), Error<S::Error>>
+where
+	S: NullifierStore + Sync,
+	A: TryInto<Action> + Send,
+	verify::Error: From<A::Error>,
+	V: Signal + Send,
+{
+	let action: Action = action.try_into().map_err(verify::Error::from)?;
+	let nullifier_hash = proof
+		.nullifier_hash()
+		.map_err(|error| verify::Error::MalformedProof {
+			field: "nullifier_hash",
+			reason: error.to_string(),
+		})?
+		.to_string();
+
+	match store
+		.check_and_insert(&nullifier_hash, &action)
+		.await
+		.map_err(Error::Store)?
+	{
+		Insertion::Inserted => {},
+		Insertion::AlreadyUsed => return Err(Error::AlreadyUsed),
+	}
+
+	let mut claim = Claim {
+		store,
+		nullifier_hash,
+		action: &action,
+		release_on_drop: true,
+	};
+
+	match verify::verify_proof_with_config::<Action, _>(
+		proof,
+		app_id,
+		action.clone(),
+		signal,
+		config,
+	)
+	.await
+	{
+		Ok(()) => {
+			claim.release_on_drop = false;
+
+			Ok(())
+		},
+		Err(error) => {
+			claim.release().await;
+
+			Err(error.into())
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use futures_util::future::join_all;
+	use std::{str::FromStr, time::Duration};
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	#[tokio::test]
+	async fn test_memory_store() {
+		let store = MemoryNullifierStore::new();
+
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await,
+			Ok(Insertion::Inserted)
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await,
+			Ok(Insertion::AlreadyUsed)
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "b").await,
+			Ok(Insertion::Inserted)
+		);
+
+		store.remove("0x1", "a").await.unwrap();
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await,
+			Ok(Insertion::Inserted)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_concurrent_verifications_succeed_once() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let store = MemoryNullifierStore::new();
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		};
+
+		let results = join_all((0..10).map(|_| {
+			verify_proof_unique_with_config(
+				&store,
+				fixtures::proof(),
+				AppId::from_str("app_123").unwrap(),
+				"test-action",
+				"",
+				&config,
+			)
+		}))
+		.await;
+
+		assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+		assert!(results
+			.iter()
+			.filter_map(|result| result.as_ref().err())
+			.all(|error| matches!(error, Error::AlreadyUsed)));
+	}
+
+	#[tokio::test]
+	async fn test_failed_verification_releases_nullifier() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(500))
+			.up_to_n_times(1)
+			.with_priority(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.mount(&server)
+			.await;
+
+		let store = MemoryNullifierStore::new();
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		};
+		let verify = || {
+			verify_proof_unique_with_config(
+				&store,
+				fixtures::proof(),
+				AppId::from_str("app_123").unwrap(),
+				"test-action",
+				"",
+				&config,
+			)
+		};
+
+		assert!(matches!(verify().await, Err(Error::Verify(_))));
+		verify().await.unwrap();
+		assert!(matches!(verify().await, Err(Error::AlreadyUsed)));
+	}
+
+	#[tokio::test]
+	async fn test_cancelled_verification_releases_nullifier() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+			.up_to_n_times(1)
+			.with_priority(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.mount(&server)
+			.await;
+
+		let store = MemoryNullifierStore::new();
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		};
+		let verify = || {
+			verify_proof_unique_with_config(
+				&store,
+				fixtures::proof(),
+				AppId::from_str("app_123").unwrap(),
+				"test-action",
+				"",
+				&config,
+			)
+		};
+
+		// Dropped while waiting for the Developer Portal, like a request whose client disconnected.
+		assert!(tokio::time::timeout(Duration::from_millis(100), verify())
+			.await
+			.is_err());
+		tokio::task::yield_now().await;
+
+		verify().await.unwrap();
+		assert!(matches!(verify().await, Err(Error::AlreadyUsed)));
+	}
+
+	#[tokio::test]
+	async fn test_nullifier_hash_variants_are_used() {
+		let store = MemoryNullifierStore::new();
+		assert_eq!(
+			store.check_and_insert("0xabc", "a").await,
+			Ok(Insertion::Inserted)
+		);
+		let padded = format!("0x{:0>64}", "abc");
+		for variant in ["0xABC", "0x0abc", padded.as_str()] {
+			assert_eq!(
+				store.check_and_insert(variant, "a").await,
+				Ok(Insertion::AlreadyUsed),
+				"{variant}"
+			);
+		}
+
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		};
+		let verify = |nullifier_hash: String| {
+			verify_proof_unique_with_config(
+				&store,
+				Proof {
+					nullifier_hash,
+					..fixtures::proof()
+				},
+				AppId::from_str("app_123").unwrap(),
+				"test-action",
+				"",
+				&config,
+			)
+		};
+
+		verify(fixtures::proof().nullifier_hash).await.unwrap();
+		assert!(matches!(
+			verify(
+				fixtures::proof()
+					.nullifier_hash
+					.to_uppercase()
+					.replace("0X", "0x")
+			)
+			.await,
+			Err(Error::AlreadyUsed)
+		));
+	}
+}
@@ -0,0 +1,217 @@
+use sqlx::PgPool;
+use std::future::Future;
+
+use super::{canonical_nullifier_hash, Insertion, NullifierStore};
+
+/// Which nullifier hashes conflict in a `PostgresNullifierStore`, enforced by the unique constraint created by `PostgresNullifierStore::migrate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Uniqueness {
+	/// A nullifier hash can be used once per action, like `MemoryNullifierStore`.
+	#[default]
+	PerAction,
+	/// A nullifier hash can only be used once across all actions. Nullifier hashes already differ between actions, so this only matters for stores shared by apps with overlapping action names.
+	Global,
+}
+
+/// A `NullifierStore` keeping nullifier hashes in the `nullifiers` table of a Postgres database, so replicas of a service share them.
+///
+/// Nullifier hashes are inserted with `INSERT ... ON CONFLICT DO NOTHING RETURNING`, so concurrent insertions are resolved by the unique constraint of the table.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub struct PostgresNullifierStore {
+	pool: PgPool,
+}
+
+impl PostgresNullifierStore {
+	/// Create a store using connections from `pool`. The table must exist, see `migrate`.
+	#[must_use]
+	pub const fn new(pool: PgPool) -> Self {
+		Self { pool }
+	}
+
+	/// Create the `nullifiers` table if it doesn't exist yet, with a unique constraint matching `uniqueness`.
+	///
+	/// The constraint of an existing table isn't changed.
+	///
+	/// # Errors
+	///
+	/// Errors if the table can't be created.
+	pub async fn migrate(&self, uniqueness: Uniqueness) -> Result<(), sqlx::Error> {
+		let unique = match uniqueness {
+			Uniqueness::PerAction => "UNIQUE (nullifier, action)",
+			Uniqueness::Global => "UNIQUE (nullifier)",
+		};
+
+		sqlx::query(&format!(
+			"CREATE TABLE IF NOT EXISTS nullifiers (
+				nullifier TEXT NOT NULL,
+				action TEXT NOT NULL,
+				created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+				{unique}
+			)"
+		))
+		.execute(&self.pool)
+		.await?;
+
+		Ok(())
+	}
+}
+
+impl NullifierStore for PostgresNullifierStore {
+	type Error = sqlx::Error;
+
+	fn check_and_insert(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<Insertion, Self::Error>> + Send {
+		let query = sqlx::query(
+			"INSERT INTO nullifiers (nullifier, action) VALUES ($1, $2) ON CONFLICT DO NOTHING RETURNING 1",
+		)
+		.bind(canonical_nullifier_hash(nullifier_hash))
+		.bind(action.to_string());
+
+		async move {
+			Ok(match query.fetch_optional(&self.pool).await? {
+				Some(_) => Insertion::Inserted,
+				None => Insertion::AlreadyUsed,
+			})
+		}
+	}
+
+	fn remove(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+		let query = sqlx::query("DELETE FROM nullifiers WHERE nullifier = $1 AND action = $2")
+			.bind(canonical_nullifier_hash(nullifier_hash))
+			.bind(action.to_string());
+		let pool = self.pool.clone();
+
+		async move {
+			query.execute(&pool).await?;
+
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures_util::future::join_all;
+	use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+	use std::{
+		str::FromStr,
+		time::{SystemTime, UNIX_EPOCH},
+	};
+
+	/// A schema of the database at `IDKIT_TEST_DATABASE_URL`, dropped at the end of the test. Tests needing a database pass without it.
+	async fn store(uniqueness: Uniqueness) -> Option<(PostgresNullifierStore, String)> {
+		let url = std::env::var("IDKIT_TEST_DATABASE_URL").ok()?;
+		let schema = format!(
+			"idkit_test_{}",
+			SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.unwrap()
+				.as_nanos()
+		);
+
+		let admin = PgPool::connect(&url).await.unwrap();
+		sqlx::query(&format!("CREATE SCHEMA {schema}"))
+			.execute(&admin)
+			.await
+			.unwrap();
+
+		let options = PgConnectOptions::from_str(&url)
+			.unwrap()
+			.options([("search_path", schema.as_str())]);
+		let pool = PgPoolOptions::new()
+			.max_connections(10)
+			.connect_with(options)
+			.await
+			.unwrap();
+
+		let store = PostgresNullifierStore::new(pool);
+		store.migrate(uniqueness).await.unwrap();
+
+		Some((store, schema))
+	}
+
+	async fn drop_schema(store: PostgresNullifierStore, schema: &str) {
+		sqlx::query(&format!("DROP SCHEMA {schema} CASCADE"))
+			.execute(&store.pool)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_postgres_store() {
+		let Some((store, schema)) = store(Uniqueness::PerAction).await else {
+			return;
+		};
+
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::AlreadyUsed
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "b").await.unwrap(),
+			Insertion::Inserted
+		);
+
+		store.remove("0x1", "a").await.unwrap();
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+		assert_eq!(
+			store.check_and_insert("0X0001", "a").await.unwrap(),
+			Insertion::AlreadyUsed
+		);
+
+		drop_schema(store, &schema).await;
+	}
+
+	#[tokio::test]
+	async fn test_global_uniqueness() {
+		let Some((store, schema)) = store(Uniqueness::Global).await else {
+			return;
+		};
+
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "b").await.unwrap(),
+			Insertion::AlreadyUsed
+		);
+
+		drop_schema(store, &schema).await;
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn test_concurrent_inserts() {
+		let Some((store, schema)) = store(Uniqueness::PerAction).await else {
+			return;
+		};
+
+		let results = join_all((0..100).map(|_| store.check_and_insert("0x2", "concurrent"))).await;
+
+		assert_eq!(
+			results
+				.into_iter()
+				.filter(|result| *result.as_ref().unwrap() == Insertion::Inserted)
+				.count(),
+			1
+		);
+
+		drop_schema(store, &schema).await;
+	}
+}
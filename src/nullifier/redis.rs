@@ -0,0 +1,234 @@
+use deadpool_redis::{redis, Pool, PoolError};
+use std::{future::Future, time::Duration};
+
+use super::{canonical_nullifier_hash, Insertion, NullifierStore};
+
+/// The prefix of the keys nullifier hashes are stored under by default.
+const DEFAULT_PREFIX: &str = "idkit:nullifier:";
+
+/// An error when accessing a `RedisNullifierStore`.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, thiserror::Error)]
+pub enum RedisStoreError {
+	#[error("failed to get a Redis connection: {0}")]
+	Pool(#[from] PoolError),
+
+	#[error("Redis command failed: {0}")]
+	Redis(#[from] redis::RedisError),
+}
+
+/// A `NullifierStore` keeping nullifier hashes in Redis, so replicas of a service share them.
+///
+/// Each nullifier hash is stored under its own key, set with `SET NX` so concurrent insertions from different processes are resolved by Redis.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone)]
+pub struct RedisNullifierStore {
+	pool: Pool,
+	prefix: String,
+	ttl: Option<Duration>,
+}
+
+impl std::fmt::Debug for RedisNullifierStore {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("RedisNullifierStore")
+			.field("prefix", &self.prefix)
+			.field("ttl", &self.ttl)
+			.finish_non_exhaustive()
+	}
+}
+
+impl RedisNullifierStore {
+	/// Create a store using connections from `pool`, with keys prefixed with `idkit:nullifier:` that never expire.
+	#[must_use]
+	pub fn new(pool: Pool) -> Self {
+		Self {
+			pool,
+			prefix: DEFAULT_PREFIX.to_string(),
+			ttl: None,
+		}
+	}
+
+	/// Set the prefix of the keys, e.g. to share a Redis database between apps or environments.
+	#[must_use]
+	pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+		self.prefix = prefix.into();
+		self
+	}
+
+	/// Expire nullifier hashes after `ttl`, after which they can be used again. Only set this for actions whose proofs can't be replayed after that long, e.g. because their signal includes a timestamp.
+	#[must_use]
+	pub const fn ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = Some(ttl);
+		self
+	}
+
+	/// The key the nullifier hash is stored under for the action, in its canonical form. Nullifier hashes are hex, so the first `:` after the prefix separates them from the action.
+	fn key(&self, nullifier_hash: &str, action: &str) -> String {
+		format!(
+			"{}{}:{action}",
+			self.prefix,
+			canonical_nullifier_hash(nullifier_hash)
+		)
+	}
+}
+
+impl NullifierStore for RedisNullifierStore {
+	type Error = RedisStoreError;
+
+	fn check_and_insert(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<Insertion, Self::Error>> + Send {
+		let mut command = redis::cmd("SET");
+		command
+			.arg(self.key(nullifier_hash, action))
+			.arg(1)
+			.arg("NX");
+		if let Some(ttl) = self.ttl {
+			command
+				.arg("PX")
+				.arg(u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1));
+		}
+
+		async move {
+			let mut connection = self.pool.get().await?;
+			let reply: Option<String> = command.query_async(&mut connection).await?;
+
+			Ok(if reply.is_some() {
+				Insertion::Inserted
+			} else {
+				Insertion::AlreadyUsed
+			})
+		}
+	}
+
+	fn remove(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+		let mut command = redis::cmd("DEL");
+		command.arg(self.key(nullifier_hash, action));
+		let pool = self.pool.clone();
+
+		async move {
+			let mut connection = pool.get().await?;
+			command.query_async::<()>(&mut connection).await?;
+
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use deadpool_redis::{Config, Runtime};
+	use futures_util::future::join_all;
+	use std::time::{SystemTime, UNIX_EPOCH};
+
+	/// Connects to the Redis server at `IDKIT_TEST_REDIS_URL`, if set. Tests needing a server pass without it.
+	fn store() -> Option<RedisNullifierStore> {
+		let url = std::env::var("IDKIT_TEST_REDIS_URL").ok()?;
+		let pool = Config::from_url(url)
+			.create_pool(Some(Runtime::Tokio1))
+			.unwrap();
+		let run = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_nanos();
+
+		Some(RedisNullifierStore::new(pool).prefix(format!("idkit-test:{run}:")))
+	}
+
+	#[test]
+	fn test_key() {
+		let pool = Config::from_url("redis://localhost")
+			.create_pool(Some(Runtime::Tokio1))
+			.unwrap();
+		let store = RedisNullifierStore::new(pool);
+
+		let nullifier_hash = format!("0x{:0>64}", "1");
+		assert_eq!(
+			store.key("0x1", "vote:1"),
+			format!("idkit:nullifier:{nullifier_hash}:vote:1")
+		);
+		assert_eq!(store.key("0X01", "vote:1"), store.key("0x1", "vote:1"));
+		assert_eq!(
+			store.prefix("app_123:").key("0x1", "vote"),
+			format!("app_123:{nullifier_hash}:vote")
+		);
+	}
+
+	#[tokio::test]
+	async fn test_redis_store() {
+		let Some(store) = store() else { return };
+
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::AlreadyUsed
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "b").await.unwrap(),
+			Insertion::Inserted
+		);
+
+		store.remove("0x1", "a").await.unwrap();
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+	}
+
+	#[tokio::test]
+	async fn test_concurrent_inserts() {
+		let Some(store) = store() else { return };
+		// Two stores with their own pools, like two replicas of a service.
+		let other = RedisNullifierStore::new(
+			Config::from_url(std::env::var("IDKIT_TEST_REDIS_URL").unwrap())
+				.create_pool(Some(Runtime::Tokio1))
+				.unwrap(),
+		)
+		.prefix(store.prefix.clone());
+
+		let results = join_all((0..20).map(|i| {
+			let store = if i % 2 == 0 { &store } else { &other };
+			store.check_and_insert("0x2", "concurrent")
+		}))
+		.await;
+
+		assert_eq!(
+			results
+				.into_iter()
+				.filter(|result| *result.as_ref().unwrap() == Insertion::Inserted)
+				.count(),
+			1
+		);
+	}
+
+	#[tokio::test]
+	async fn test_ttl() {
+		let Some(store) = store() else { return };
+		let store = store.ttl(Duration::from_millis(100));
+
+		assert_eq!(
+			store.check_and_insert("0x3", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+		assert_eq!(
+			store.check_and_insert("0x3", "a").await.unwrap(),
+			Insertion::AlreadyUsed
+		);
+
+		tokio::time::sleep(Duration::from_millis(200)).await;
+		assert_eq!(
+			store.check_and_insert("0x3", "a").await.unwrap(),
+			Insertion::Inserted
+		);
+	}
+}
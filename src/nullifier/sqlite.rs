@@ -0,0 +1,312 @@
+use rusqlite::{params, Connection, TransactionBehavior};
+use std::{
+	future::Future,
+	panic,
+	path::Path,
+	sync::{Arc, Mutex, PoisonError},
+	time::{Duration, UNIX_EPOCH},
+};
+
+use super::{canonical_nullifier_hash, Insertion, NullifierStore};
+use crate::verify::time;
+
+/// How long a connection waits for another one to release its lock before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Creates the `nullifiers` table used by `SqliteNullifierStore`, if it doesn't exist yet.
+///
+/// `SqliteNullifierStore` runs this itself, it's only needed to manage the schema separately.
+///
+/// # Errors
+///
+/// Errors if the table can't be created.
+pub fn migrate(connection: &Connection) -> Result<(), rusqlite::Error> {
+	connection.execute_batch(
+		"CREATE TABLE IF NOT EXISTS nullifiers (
+			nullifier TEXT NOT NULL,
+			action TEXT NOT NULL,
+			created_at INTEGER NOT NULL,
+			UNIQUE (nullifier, action)
+		);
+		CREATE INDEX IF NOT EXISTS nullifiers_created_at ON nullifiers (created_at);",
+	)
+}
+
+/// A `NullifierStore` keeping nullifier hashes in a SQLite database, for durable replay protection in single-machine deployments.
+///
+/// Nullifier hashes are inserted with `INSERT OR IGNORE` against a unique constraint, so concurrent insertions are resolved by SQLite, including from other connections to the same database.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, Clone)]
+pub struct SqliteNullifierStore {
+	connection: Arc<Mutex<Connection>>,
+	ttl: Option<Duration>,
+}
+
+impl SqliteNullifierStore {
+	/// Open the database at `path`, creating it if needed, in WAL mode so readers don't block the writer.
+	///
+	/// # Errors
+	///
+	/// Errors if the database can't be opened or migrated.
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, rusqlite::Error> {
+		let connection = Connection::open(path)?;
+		connection
+			.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+		connection.busy_timeout(BUSY_TIMEOUT)?;
+
+		Self::from_connection(connection)
+	}
+
+	/// Use an already opened connection, creating the `nullifiers` table if needed.
+	///
+	/// # Errors
+	///
+	/// Errors if the database can't be migrated.
+	pub fn from_connection(connection: Connection) -> Result<Self, rusqlite::Error> {
+		migrate(&connection)?;
+
+		Ok(Self {
+			connection: Arc::new(Mutex::new(connection)),
+			ttl: None,
+		})
+	}
+
+	/// Expire nullifier hashes after `ttl`, after which they can be used again. Only set this for actions whose proofs can't be replayed after that long, e.g. because their signal includes a timestamp.
+	#[must_use]
+	pub const fn ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = Some(ttl);
+		self
+	}
+
+	/// Delete the nullifier hashes that expired, returning how many were deleted. Expired nullifier hashes can be used again either way, this only reclaims space.
+	///
+	/// # Errors
+	///
+	/// Errors if the rows can't be deleted.
+	pub async fn remove_expired(&self) -> Result<usize, rusqlite::Error> {
+		let Some(cutoff) = self.cutoff() else {
+			return Ok(0);
+		};
+
+		self.run(move |connection| {
+			connection.execute(
+				"DELETE FROM nullifiers WHERE created_at <= ?1",
+				params![cutoff],
+			)
+		})
+		.await
+	}
+
+	/// The creation time before which nullifier hashes are expired, if they expire.
+	fn cutoff(&self) -> Option<i64> {
+		self.ttl
+			.map(|ttl| unix_now().saturating_sub(i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX)))
+	}
+
+	/// Runs `f` with the connection on the blocking thread pool, as SQLite calls block.
+	fn run<T: Send + 'static>(
+		&self,
+		f: impl FnOnce(&mut Connection) -> Result<T, rusqlite::Error> + Send + 'static,
+	) -> impl Future<Output = Result<T, rusqlite::Error>> + Send {
+		let connection = Arc::clone(&self.connection);
+
+		async move {
+			tokio::task::spawn_blocking(move || {
+				f(&mut connection.lock().unwrap_or_else(PoisonError::into_inner))
+			})
+			.await
+			.unwrap_or_else(|error| panic::resume_unwind(error.into_panic()))
+		}
+	}
+}
+
+impl NullifierStore for SqliteNullifierStore {
+	type Error = rusqlite::Error;
+
+	fn check_and_insert(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<Insertion, Self::Error>> + Send {
+		let (nullifier_hash, action) =
+			(canonical_nullifier_hash(nullifier_hash), action.to_string());
+		let cutoff = self.cutoff();
+
+		self.run(move |connection| {
+			let transaction =
+				connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+			if let Some(cutoff) = cutoff {
+				transaction.execute(
+					"DELETE FROM nullifiers WHERE nullifier = ?1 AND action = ?2 AND created_at <= ?3",
+					params![nullifier_hash, action, cutoff],
+				)?;
+			}
+			let inserted = transaction.execute(
+				"INSERT OR IGNORE INTO nullifiers (nullifier, action, created_at) VALUES (?1, ?2, ?3)",
+				params![nullifier_hash, action, unix_now()],
+			)?;
+			transaction.commit()?;
+
+			Ok(if inserted == 1 {
+				Insertion::Inserted
+			} else {
+				Insertion::AlreadyUsed
+			})
+		})
+	}
+
+	fn remove(
+		&self,
+		nullifier_hash: &str,
+		action: &str,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+		let (nullifier_hash, action) =
+			(canonical_nullifier_hash(nullifier_hash), action.to_string());
+
+		self.run(move |connection| {
+			connection.execute(
+				"DELETE FROM nullifiers WHERE nullifier = ?1 AND action = ?2",
+				params![nullifier_hash, action],
+			)?;
+
+			Ok(())
+		})
+	}
+}
+
+fn unix_now() -> i64 {
+	let seconds = time::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+
+	i64::try_from(seconds).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures_util::future::join_all;
+	use std::path::PathBuf;
+
+	/// A database file removed when dropped.
+	struct TempDatabase(PathBuf);
+
+	impl TempDatabase {
+		fn new(name: &str) -> Self {
+			let run = time::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+
+			Self(std::env::temp_dir().join(format!("idkit-{name}-{run}.sqlite")))
+		}
+	}
+
+	impl Drop for TempDatabase {
+		fn drop(&mut self) {
+			for suffix in ["", "-wal", "-shm"] {
+				let _ = std::fs::remove_file(format!("{}{suffix}", self.0.display()));
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn test_sqlite_store() {
+		let database = TempDatabase::new("store");
+		let store = SqliteNullifierStore::open(&database.0).unwrap();
+
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await,
+			Ok(Insertion::Inserted)
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await,
+			Ok(Insertion::AlreadyUsed)
+		);
+		assert_eq!(
+			store.check_and_insert("0x1", "b").await,
+			Ok(Insertion::Inserted)
+		);
+
+		store.remove("0x1", "a").await.unwrap();
+		assert_eq!(
+			store.check_and_insert("0x1", "a").await,
+			Ok(Insertion::Inserted)
+		);
+		assert_eq!(
+			store.check_and_insert("0X0001", "a").await,
+			Ok(Insertion::AlreadyUsed)
+		);
+
+		// The nullifier hashes survive reopening the database.
+		drop(store);
+		let store = SqliteNullifierStore::open(&database.0).unwrap();
+		assert_eq!(
+			store.check_and_insert("0x1", "b").await,
+			Ok(Insertion::AlreadyUsed)
+		);
+
+		let journal_mode: String = store
+			.run(|connection| connection.query_row("PRAGMA journal_mode", [], |row| row.get(0)))
+			.await
+			.unwrap();
+		assert_eq!(journal_mode, "wal");
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn test_concurrent_inserts() {
+		let database = TempDatabase::new("concurrent");
+		let stores = (0..4)
+			.map(|_| SqliteNullifierStore::open(&database.0).unwrap())
+			.collect::<Vec<_>>();
+
+		let results = join_all(
+			(0..20).map(|i| stores[i % stores.len()].check_and_insert("0x2", "concurrent")),
+		)
+		.await;
+
+		assert_eq!(
+			results
+				.into_iter()
+				.filter(|result| *result.as_ref().unwrap() == Insertion::Inserted)
+				.count(),
+			1
+		);
+	}
+
+	#[tokio::test]
+	async fn test_ttl() {
+		let database = TempDatabase::new("ttl");
+		let store = SqliteNullifierStore::open(&database.0)
+			.unwrap()
+			.ttl(Duration::from_secs(60));
+
+		for nullifier_hash in ["0x3", "0x4", "0x5"] {
+			store.check_and_insert(nullifier_hash, "a").await.unwrap();
+		}
+		// Backdate two of them past the TTL.
+		store
+			.run(|connection| {
+				connection.execute(
+					"UPDATE nullifiers SET created_at = created_at - 120 WHERE nullifier IN (?1, ?2)",
+					params![canonical_nullifier_hash("0x3"), canonical_nullifier_hash("0x4")],
+				)
+			})
+			.await
+			.unwrap();
+
+		assert_eq!(
+			store.check_and_insert("0x3", "a").await,
+			Ok(Insertion::Inserted)
+		);
+		assert_eq!(
+			store.check_and_insert("0x5", "a").await,
+			Ok(Insertion::AlreadyUsed)
+		);
+
+		assert_eq!(store.remove_expired().await, Ok(1));
+		assert_eq!(
+			store.check_and_insert("0x4", "a").await,
+			Ok(Insertion::Inserted)
+		);
+	}
+}
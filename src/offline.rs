@@ -0,0 +1,423 @@
+//! Verify World ID proofs locally, by checking the Groth16 proof against the semaphore verifying key, without contacting the Developer Portal.
+//!
+//! World ID proofs are checked against the verifying key of the semaphore circuit for identity trees of depth 30, which is published with the World ID contracts in the `verification_key.json` format of `snarkjs`. Load it with `VerifyingKey::from_snarkjs_json`:
+//!
+//! ```ignore
+//! let key = VerifyingKey::from_snarkjs_json(include_str!("semaphore_30.json"))?;
+//!
+//! verify_proof_offline(&key, &proof, &app_id, "vote_1", "my_signal", &roots)?;
+//! ```
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInt, PrimeField};
+use ark_groth16::{Groth16, PreparedVerifyingKey};
+use ruint::aliases::U256;
+use std::{collections::HashSet, convert::Infallible, hash::BuildHasher};
+
+use crate::{
+	hashing::{encode_signal, external_nullifier},
+	session::{Action, ActionError, AppId},
+	Proof, Signal,
+};
+
+/// An error when verifying a proof locally.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("malformed proof: {0}")]
+	MalformedProof(String),
+
+	#[error("invalid verifying key: {0}")]
+	InvalidVerifyingKey(String),
+
+	#[error("the merkle root is not known to the root source")]
+	UnknownRoot,
+
+	#[error("the proof is invalid")]
+	InvalidProof,
+
+	#[error("invalid action: {0}")]
+	InvalidAction(#[from] ActionError),
+}
+
+impl From<Infallible> for Error {
+	fn from(error: Infallible) -> Self {
+		match error {}
+	}
+}
+
+/// Decides which merkle roots are valid, usually backed by the roots recently published on-chain.
+pub trait RootSource {
+	/// Whether the given merkle root is a valid root of the World ID identity tree.
+	fn is_valid_root(&self, root: U256) -> bool;
+}
+
+impl<H: BuildHasher> RootSource for HashSet<U256, H> {
+	fn is_valid_root(&self, root: U256) -> bool {
+		self.contains(&root)
+	}
+}
+
+impl RootSource for [U256] {
+	fn is_valid_root(&self, root: U256) -> bool {
+		self.contains(&root)
+	}
+}
+
+impl<F: Fn(U256) -> bool> RootSource for F {
+	fn is_valid_root(&self, root: U256) -> bool {
+		self(root)
+	}
+}
+
+/// The semaphore verifying key used to check World ID proofs.
+#[derive(Debug, Clone)]
+pub struct VerifyingKey(PreparedVerifyingKey<Bn254>);
+
+#[derive(Debug, serde::Deserialize)]
+struct SnarkJsVerifyingKey {
+	vk_alpha_1: [String; 3],
+	vk_beta_2: [[String; 2]; 3],
+	vk_gamma_2: [[String; 2]; 3],
+	vk_delta_2: [[String; 2]; 3],
+	#[serde(rename = "IC")]
+	ic: Vec<[String; 3]>,
+}
+
+impl VerifyingKey {
+	/// Load a verifying key from the `verification_key.json` format produced by `snarkjs`, in which the World ID verifying key is published.
+	///
+	/// # Errors
+	///
+	/// Errors if the JSON is malformed or contains invalid curve points.
+	pub fn from_snarkjs_json(json: &str) -> Result<Self, Error> {
+		let key: SnarkJsVerifyingKey = serde_json::from_str(json)
+			.map_err(|error| Error::InvalidVerifyingKey(error.to_string()))?;
+
+		let g1 = |point: &[String; 3]| {
+			g1_point(parse_decimal(&point[0])?, parse_decimal(&point[1])?)
+				.ok_or_else(|| Error::InvalidVerifyingKey("invalid G1 point".to_string()))
+		};
+		let g2 = |point: &[[String; 2]; 3]| {
+			g2_point(
+				[parse_decimal(&point[0][0])?, parse_decimal(&point[0][1])?],
+				[parse_decimal(&point[1][0])?, parse_decimal(&point[1][1])?],
+			)
+			.ok_or_else(|| Error::InvalidVerifyingKey("invalid G2 point".to_string()))
+		};
+
+		Ok(Self::from_ark(&ark_groth16::VerifyingKey {
+			alpha_g1: g1(&key.vk_alpha_1)?,
+			beta_g2: g2(&key.vk_beta_2)?,
+			gamma_g2: g2(&key.vk_gamma_2)?,
+			delta_g2: g2(&key.vk_delta_2)?,
+			gamma_abc_g1: key.ic.iter().map(g1).collect::<Result<_, _>>()?,
+		}))
+	}
+
+	/// Use a verifying key already loaded with `ark-groth16`.
+	#[must_use]
+	pub fn from_ark(key: &ark_groth16::VerifyingKey<Bn254>) -> Self {
+		Self(ark_groth16::prepare_verifying_key(key))
+	}
+}
+
+/// Verify a World ID proof locally against the semaphore verifying key, accepting only merkle roots allowed by `root_source`.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is malformed, if the merkle root isn't known (`Error::UnknownRoot`), or if the proof doesn't verify (`Error::InvalidProof`).
+pub fn verify_proof_offline<A, V, R>(
+	key: &VerifyingKey,
+	proof: &Proof,
+	app_id: &AppId,
+	action: A,
+	signal: V,
+	root_source: &R,
+) -> Result<(), Error>
+where
+	A: TryInto<Action>,
+	Error: From<A::Error>,
+	V: Signal,
+	R: RootSource + ?Sized,
+{
+	let action: Action = action.try_into()?;
+
+	proof
+		.validate_format()
+		.map_err(|error| Error::MalformedProof(error.to_string()))?;
+
+	let root = parse_hex(&proof.merkle_root);
+	if !root_source.is_valid_root(root) {
+		return Err(Error::UnknownRoot);
+	}
+
+	let public_inputs = [
+		root,
+		parse_hex(&proof.nullifier_hash),
+		encode_signal(&signal),
+		external_nullifier(app_id, &action),
+	]
+	.into_iter()
+	.map(|input| {
+		Fr::from_bigint(BigInt::new(input.into_limbs()))
+			.ok_or_else(|| Error::MalformedProof("public input out of range".to_string()))
+	})
+	.collect::<Result<Vec<_>, _>>()?;
+
+	let is_valid =
+		Groth16::<Bn254>::verify_proof(&key.0, &unpack_proof(&proof.proof)?, &public_inputs)
+			.map_err(|_| Error::InvalidProof)?;
+
+	if is_valid {
+		Ok(())
+	} else {
+		Err(Error::InvalidProof)
+	}
+}
+
+/// Unpacks the ABI-encoded proof into its curve points, following the layout used by the semaphore verifier contract.
+fn unpack_proof(proof: &str) -> Result<ark_groth16::Proof<Bn254>, Error> {
+	let hex = proof.trim_start_matches("0x");
+	let p: [U256; 8] = std::array::from_fn(|i| parse_hex(&hex[i * 64..(i + 1) * 64]));
+
+	let invalid = |point: &str| Error::MalformedProof(format!("invalid {point} point"));
+
+	Ok(ark_groth16::Proof {
+		a: g1_point(p[0], p[1]).ok_or_else(|| invalid("A"))?,
+		// The contract expects the coefficients of G2 points in reverse order.
+		b: g2_point([p[3], p[2]], [p[5], p[4]]).ok_or_else(|| invalid("B"))?,
+		c: g1_point(p[6], p[7]).ok_or_else(|| invalid("C"))?,
+	})
+}
+
+fn g1_point(x: U256, y: U256) -> Option<G1Affine> {
+	let point = G1Affine::new_unchecked(fq(x)?, fq(y)?);
+
+	(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+}
+
+fn g2_point(x: [U256; 2], y: [U256; 2]) -> Option<G2Affine> {
+	let point = G2Affine::new_unchecked(
+		Fq2::new(fq(x[0])?, fq(x[1])?),
+		Fq2::new(fq(y[0])?, fq(y[1])?),
+	);
+
+	(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve()).then_some(point)
+}
+
+fn fq(value: U256) -> Option<Fq> {
+	Fq::from_bigint(BigInt::new(value.into_limbs()))
+}
+
+/// Parses a hex string already checked by `Proof::validate_format`.
+fn parse_hex(hex: &str) -> U256 {
+	U256::from_str_radix(hex.trim_start_matches("0x"), 16)
+		.unwrap_or_else(|_| unreachable!("the proof format was validated"))
+}
+
+fn parse_decimal(value: &str) -> Result<U256, Error> {
+	U256::from_str_radix(value, 10).map_err(|error| Error::InvalidVerifyingKey(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::VerificationLevel;
+	use ark_relations::{
+		lc,
+		r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+	};
+	use ark_snark::SNARK;
+	use ark_std::rand::{rngs::StdRng, SeedableRng};
+	use std::str::FromStr;
+
+	/// A circuit exposing the same public inputs as the semaphore circuit, used to produce proof fixtures.
+	struct PublicInputsCircuit([Fr; 4]);
+
+	impl ConstraintSynthesizer<Fr> for PublicInputsCircuit {
+		fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+			for input in self.0 {
+				let variable = cs.new_input_variable(|| Ok(input))?;
+				cs.enforce_constraint(lc!() + variable, lc!() + Variable::One, lc!() + variable)?;
+			}
+
+			Ok(())
+		}
+	}
+
+	fn to_u256<F: PrimeField<BigInt = BigInt<4>>>(value: F) -> U256 {
+		U256::from_limbs(value.into_bigint().0)
+	}
+
+	fn to_fr(value: U256) -> Fr {
+		Fr::from_bigint(BigInt::new(value.into_limbs())).unwrap()
+	}
+
+	/// Serializes a verifying key in the `verification_key.json` format of `snarkjs`.
+	fn snarkjs_json(key: &ark_groth16::VerifyingKey<Bn254>) -> String {
+		let g1 = |point: &G1Affine| {
+			serde_json::json!([
+				to_u256(point.x).to_string(),
+				to_u256(point.y).to_string(),
+				"1"
+			])
+		};
+		let g2 = |point: &G2Affine| {
+			serde_json::json!([
+				[
+					to_u256(point.x.c0).to_string(),
+					to_u256(point.x.c1).to_string()
+				],
+				[
+					to_u256(point.y.c0).to_string(),
+					to_u256(point.y.c1).to_string()
+				],
+				["1", "0"],
+			])
+		};
+
+		serde_json::json!({
+			"protocol": "groth16",
+			"curve": "bn128",
+			"nPublic": key.gamma_abc_g1.len() - 1,
+			"vk_alpha_1": g1(&key.alpha_g1),
+			"vk_beta_2": g2(&key.beta_g2),
+			"vk_gamma_2": g2(&key.gamma_g2),
+			"vk_delta_2": g2(&key.delta_g2),
+			"IC": key.gamma_abc_g1.iter().map(g1).collect::<Vec<_>>(),
+		})
+		.to_string()
+	}
+
+	/// Generates a verifying key and a valid proof for `vote_1` with the signal `test`.
+	fn fixture() -> (VerifyingKey, Proof) {
+		let (key, proof) = ark_fixture();
+
+		(VerifyingKey::from_ark(&key), proof)
+	}
+
+	fn ark_fixture() -> (ark_groth16::VerifyingKey<Bn254>, Proof) {
+		let root = U256::from(0xabcd);
+		let nullifier_hash = U256::from(0x1234);
+		let circuit = || {
+			PublicInputsCircuit([
+				to_fr(root),
+				to_fr(nullifier_hash),
+				to_fr(encode_signal(&"test")),
+				to_fr(external_nullifier(
+					&AppId::from_str("app_123").unwrap(),
+					"vote_1",
+				)),
+			])
+		};
+
+		let mut rng = StdRng::seed_from_u64(0);
+		let (pk, vk) = Groth16::<Bn254>::circuit_specific_setup(circuit(), &mut rng).unwrap();
+		let proof = Groth16::<Bn254>::prove(&pk, circuit(), &mut rng).unwrap();
+
+		let packed = [
+			to_u256(proof.a.x),
+			to_u256(proof.a.y),
+			to_u256(proof.b.x.c1),
+			to_u256(proof.b.x.c0),
+			to_u256(proof.b.y.c1),
+			to_u256(proof.b.y.c0),
+			to_u256(proof.c.x),
+			to_u256(proof.c.y),
+		];
+
+		(
+			vk,
+			Proof {
+				proof: packed
+					.iter()
+					.fold("0x".to_string(), |acc, part| acc + &format!("{part:064x}")),
+				merkle_root: format!("0x{root:064x}"),
+				nullifier_hash: format!("0x{nullifier_hash:064x}"),
+				verification_level: VerificationLevel::Orb,
+			},
+		)
+	}
+
+	#[test]
+	fn test_verify_proof_offline() {
+		let (key, proof) = fixture();
+		let app_id = AppId::from_str("app_123").unwrap();
+		let roots = HashSet::from([U256::from(0xabcd)]);
+
+		verify_proof_offline(&key, &proof, &app_id, "vote_1", "test", &roots).unwrap();
+	}
+
+	#[test]
+	fn test_verify_proof_offline_rejects_wrong_inputs() {
+		let (key, proof) = fixture();
+		let app_id = AppId::from_str("app_123").unwrap();
+		let roots = HashSet::from([U256::from(0xabcd)]);
+
+		assert!(matches!(
+			verify_proof_offline(&key, &proof, &app_id, "vote_1", "other", &roots),
+			Err(Error::InvalidProof)
+		));
+		assert!(matches!(
+			verify_proof_offline(&key, &proof, &app_id, "vote_2", "test", &roots),
+			Err(Error::InvalidProof)
+		));
+		assert!(matches!(
+			verify_proof_offline(&key, &proof, &app_id, "vote_1", "test", &|_: U256| false),
+			Err(Error::UnknownRoot)
+		));
+	}
+
+	#[test]
+	fn test_verify_proof_offline_with_snarkjs_key() {
+		let (key, proof) = ark_fixture();
+		let key = VerifyingKey::from_snarkjs_json(&snarkjs_json(&key)).unwrap();
+		let app_id = AppId::from_str("app_123").unwrap();
+		let roots = HashSet::from([U256::from(0xabcd)]);
+
+		verify_proof_offline(&key, &proof, &app_id, "vote_1", "test", &roots).unwrap();
+		assert!(matches!(
+			verify_proof_offline(&key, &proof, &app_id, "vote_1", "other", &roots),
+			Err(Error::InvalidProof)
+		));
+	}
+
+	#[test]
+	fn test_invalid_snarkjs_key() {
+		let (key, _) = ark_fixture();
+		let mut json: serde_json::Value = serde_json::from_str(&snarkjs_json(&key)).unwrap();
+		json["vk_alpha_1"][1] = "1".into();
+
+		assert!(matches!(
+			VerifyingKey::from_snarkjs_json(&json.to_string()),
+			Err(Error::InvalidVerifyingKey(_))
+		));
+		assert!(matches!(
+			VerifyingKey::from_snarkjs_json("{}"),
+			Err(Error::InvalidVerifyingKey(_))
+		));
+	}
+
+	#[test]
+	fn test_verify_proof_offline_invalid_action() {
+		let (key, proof) = fixture();
+		let app_id = AppId::from_str("app_123").unwrap();
+
+		assert!(matches!(
+			verify_proof_offline(&key, &proof, &app_id, "vote 1", "test", &|_: U256| true),
+			Err(Error::InvalidAction(_))
+		));
+	}
+
+	#[test]
+	fn test_verify_proof_offline_rejects_tampered_proof() {
+		let (key, mut proof) = fixture();
+		let app_id = AppId::from_str("app_123").unwrap();
+		proof.proof.replace_range(2..66, &format!("{:064x}", 1));
+
+		assert!(matches!(
+			verify_proof_offline(&key, &proof, &app_id, "vote_1", "test", &|_: U256| true),
+			Err(Error::MalformedProof(_) | Error::InvalidProof)
+		));
+	}
+}
@@ -0,0 +1,371 @@
+use ring::digest::{digest, SHA256};
+use std::{fmt::Display, str::FromStr};
+use url::Url;
+
+use super::{random_token, Error, DEFAULT_ISSUER};
+use crate::{hashing::base64_url_encode, session::AppId};
+
+/// The number of random bytes in generated states, nonces and PKCE verifiers.
+const RANDOM_TOKEN_LEN: usize = 32;
+
+/// A scope of the claims requested from World ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+	/// The user's identifier and verification level. Always requested.
+	OpenId,
+	/// The user's name, if shared.
+	Profile,
+	/// The user's email, if shared.
+	Email,
+}
+
+impl Scope {
+	/// The scope as sent in the `scope` parameter.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::OpenId => "openid",
+			Self::Profile => "profile",
+			Self::Email => "email",
+		}
+	}
+}
+
+impl Display for Scope {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// What the authorization endpoint returns to the redirect URI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ResponseType {
+	/// An authorization code, to exchange for tokens from the backend. The default, and the only flow using PKCE.
+	#[default]
+	Code,
+	/// An ID token, directly in the redirect.
+	IdToken,
+	/// Both an authorization code and an ID token.
+	CodeIdToken,
+}
+
+impl ResponseType {
+	/// The response type as sent in the `response_type` parameter.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Code => "code",
+			Self::IdToken => "id_token",
+			Self::CodeIdToken => "code id_token",
+		}
+	}
+
+	const fn includes_code(self) -> bool {
+		matches!(self, Self::Code | Self::CodeIdToken)
+	}
+}
+
+/// The secret half of a [PKCE](https://datatracker.ietf.org/doc/html/rfc7636) pair, sent with the authorization code when exchanging it for tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PkceVerifier(String);
+
+impl PkceVerifier {
+	/// Generates a random verifier.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the system's random number generator fails.
+	pub fn generate() -> Result<Self, Error> {
+		random_token(RANDOM_TOKEN_LEN).map(Self)
+	}
+
+	/// The verifier, to persist until the callback.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// The `S256` challenge of the verifier, sent in the authorization URL.
+	#[must_use]
+	pub fn challenge(&self) -> String {
+		base64_url_encode(digest(&SHA256, self.0.as_bytes()))
+	}
+}
+
+/// Parses a persisted verifier, checking it is 43 to 128 unreserved characters long as required by RFC 7636.
+impl FromStr for PkceVerifier {
+	type Err = Error;
+
+	fn from_str(verifier: &str) -> Result<Self, Self::Err> {
+		let is_valid = (43..=128).contains(&verifier.len())
+			&& verifier
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'));
+
+		if !is_valid {
+			return Err(Error::InvalidVerifier);
+		}
+
+		Ok(Self(verifier.to_string()))
+	}
+}
+
+/// A request to the World ID authorization endpoint, built into the URL to redirect the user to.
+///
+/// The state, nonce and PKCE verifier are generated randomly unless provided.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct AuthorizationRequest {
+	client_id: AppId,
+	redirect_uri: String,
+	scopes: Vec<Scope>,
+	state: Option<String>,
+	nonce: Option<String>,
+	response_type: ResponseType,
+	verifier: Option<PkceVerifier>,
+	issuer: Option<Url>,
+}
+
+/// The result of `AuthorizationRequest::build`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Authorization {
+	/// The URL to redirect the user to.
+	pub url: Url,
+	/// The state to compare with the one returned to the redirect URI.
+	pub state: String,
+	/// The nonce to compare with the one in the ID token.
+	pub nonce: String,
+	/// The PKCE verifier to send with the authorization code, unless the response type doesn't include one.
+	pub verifier: Option<PkceVerifier>,
+}
+
+impl AuthorizationRequest {
+	/// Start building an authorization request for the given app, redirecting the user to `redirect_uri` once they signed in.
+	pub fn new(client_id: AppId, redirect_uri: impl Into<String>) -> Self {
+		Self {
+			client_id,
+			redirect_uri: redirect_uri.into(),
+			scopes: vec![Scope::OpenId],
+			state: None,
+			nonce: None,
+			response_type: ResponseType::default(),
+			verifier: None,
+			issuer: None,
+		}
+	}
+
+	/// The scopes to request. `openid` is always requested, even if omitted.
+	pub fn scopes(mut self, scopes: impl IntoIterator<Item = Scope>) -> Self {
+		self.scopes = scopes.into_iter().collect();
+		self
+	}
+
+	/// The opaque value returned to the redirect URI, to protect against CSRF.
+	pub fn state(mut self, state: impl Into<String>) -> Self {
+		self.state = Some(state.into());
+		self
+	}
+
+	/// The value included in the ID token, to protect against replays.
+	pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+		self.nonce = Some(nonce.into());
+		self
+	}
+
+	/// What to return to the redirect URI. Defaults to `ResponseType::Code`.
+	pub const fn response_type(mut self, response_type: ResponseType) -> Self {
+		self.response_type = response_type;
+		self
+	}
+
+	/// The PKCE verifier to derive the challenge from.
+	pub fn pkce_verifier(mut self, verifier: PkceVerifier) -> Self {
+		self.verifier = Some(verifier);
+		self
+	}
+
+	/// The issuer serving the authorization endpoint. Defaults to `https://id.worldcoin.org`.
+	pub fn issuer(mut self, issuer: Url) -> Self {
+		self.issuer = Some(issuer);
+		self
+	}
+
+	/// Builds the authorization URL, generating the missing state, nonce and PKCE verifier.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the redirect URI isn't an absolute `https` URL without a fragment (plain `http` is only allowed for `localhost`), or if the system's random number generator fails.
+	pub fn build(self) -> Result<Authorization, Error> {
+		let redirect_uri = validate_redirect_uri(&self.redirect_uri)?;
+		let state = self
+			.state
+			.map_or_else(|| random_token(RANDOM_TOKEN_LEN), Ok)?;
+		let nonce = self
+			.nonce
+			.map_or_else(|| random_token(RANDOM_TOKEN_LEN), Ok)?;
+		let verifier = if self.response_type.includes_code() {
+			Some(self.verifier.map_or_else(PkceVerifier::generate, Ok)?)
+		} else {
+			None
+		};
+
+		let mut scopes = vec![Scope::OpenId.as_str()];
+		scopes.extend(
+			self.scopes
+				.iter()
+				.filter(|scope| **scope != Scope::OpenId)
+				.map(|scope| scope.as_str()),
+		);
+
+		let mut url = self
+			.issuer
+			.unwrap_or_else(|| Url::parse(DEFAULT_ISSUER).unwrap_or_else(|_| unreachable!()))
+			.join("authorize")
+			.unwrap_or_else(|_| unreachable!());
+		{
+			let mut query = url.query_pairs_mut();
+			query
+				.append_pair("response_type", self.response_type.as_str())
+				.append_pair("client_id", &self.client_id)
+				.append_pair("redirect_uri", redirect_uri.as_str())
+				.append_pair("scope", &scopes.join(" "))
+				.append_pair("state", &state)
+				.append_pair("nonce", &nonce);
+
+			if let Some(verifier) = &verifier {
+				query
+					.append_pair("code_challenge", &verifier.challenge())
+					.append_pair("code_challenge_method", "S256");
+			}
+		}
+
+		Ok(Authorization {
+			url,
+			state,
+			nonce,
+			verifier,
+		})
+	}
+}
+
+fn validate_redirect_uri(uri: &str) -> Result<Url, Error> {
+	let invalid = |reason| Error::InvalidRedirectUri {
+		uri: uri.to_string(),
+		reason,
+	};
+
+	let url = Url::parse(uri).map_err(|_| invalid("expected an absolute URL"))?;
+
+	match url.scheme() {
+		"https" => {},
+		"http" if matches!(url.host_str(), Some("localhost" | "127.0.0.1")) => {},
+		_ => return Err(invalid("expected an https URL")),
+	}
+
+	if url.fragment().is_some() {
+		return Err(invalid("expected no fragment"));
+	}
+
+	Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The verifier and challenge of the example in RFC 7636, appendix B.
+	const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+	const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+	fn request() -> AuthorizationRequest {
+		AuthorizationRequest::new(
+			AppId::from_str("app_123").unwrap(),
+			"https://example.com/callback",
+		)
+		.state("state_123")
+		.nonce("nonce_456")
+		.pkce_verifier(PkceVerifier::from_str(VERIFIER).unwrap())
+	}
+
+	#[test]
+	fn test_authorization_url() {
+		let authorization = request()
+			.scopes([Scope::Profile, Scope::Email])
+			.build()
+			.unwrap();
+
+		assert_eq!(
+			authorization.url.as_str(),
+			format!(
+				"https://id.worldcoin.org/authorize?response_type=code&client_id=app_123&redirect_uri=https%3A%2F%2Fexample.com%2Fcallback&scope=openid+profile+email&state=state_123&nonce=nonce_456&code_challenge={CHALLENGE}&code_challenge_method=S256"
+			)
+		);
+		assert_eq!(authorization.state, "state_123");
+		assert_eq!(authorization.nonce, "nonce_456");
+		assert_eq!(authorization.verifier.unwrap().as_str(), VERIFIER);
+	}
+
+	#[test]
+	fn test_authorization_url_without_code() {
+		let authorization = request()
+			.response_type(ResponseType::IdToken)
+			.issuer(Url::parse("http://localhost:8080/oidc/").unwrap())
+			.build()
+			.unwrap();
+
+		assert_eq!(
+			authorization.url.as_str(),
+			"http://localhost:8080/oidc/authorize?response_type=id_token&client_id=app_123&redirect_uri=https%3A%2F%2Fexample.com%2Fcallback&scope=openid&state=state_123&nonce=nonce_456"
+		);
+		assert_eq!(authorization.verifier, None);
+	}
+
+	#[test]
+	fn test_generated_values() {
+		let authorization = AuthorizationRequest::new(
+			AppId::from_str("app_123").unwrap(),
+			"http://localhost:3000/callback",
+		)
+		.build()
+		.unwrap();
+		let verifier = authorization.verifier.unwrap();
+
+		assert_eq!(authorization.state.len(), 43);
+		assert_eq!(authorization.nonce.len(), 43);
+		assert_ne!(authorization.state, authorization.nonce);
+		assert_eq!(PkceVerifier::from_str(verifier.as_str()).unwrap(), verifier);
+		assert!(authorization
+			.url
+			.query_pairs()
+			.any(|(key, value)| key == "code_challenge" && value == verifier.challenge()));
+	}
+
+	#[test]
+	fn test_invalid_redirect_uris() {
+		for uri in [
+			"/callback",
+			"http://example.com/callback",
+			"custom://callback",
+			"https://example.com/callback#fragment",
+		] {
+			assert!(
+				matches!(
+					AuthorizationRequest::new(AppId::from_str("app_123").unwrap(), uri).build(),
+					Err(Error::InvalidRedirectUri { .. })
+				),
+				"{uri} should be rejected"
+			);
+		}
+	}
+
+	#[test]
+	fn test_pkce_verifier() {
+		assert_eq!(
+			PkceVerifier::from_str(VERIFIER).unwrap().challenge(),
+			CHALLENGE
+		);
+		assert!(PkceVerifier::from_str("too_short").is_err());
+		assert!(PkceVerifier::from_str(&"a".repeat(129)).is_err());
+		assert!(PkceVerifier::from_str(&format!("{}!", "a".repeat(43))).is_err());
+	}
+}
@@ -0,0 +1,367 @@
+use ring::signature::{RsaPublicKeyComponents, RSA_PKCS1_2048_8192_SHA256};
+use serde::de::DeserializeOwned;
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::{Error, JwksCache, WorldIdClaims};
+use crate::{hashing::base64_decode, verify::time};
+
+/// The default clock skew tolerated when checking the expiry of ID tokens.
+const DEFAULT_LEEWAY: Duration = Duration::from_mins(1);
+
+/// What `validate_id_token` expects of an ID token. The issuer and audience are those of the configuration of the `JwksCache`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct ExpectedIdToken<'a> {
+	jwks: &'a JwksCache,
+	nonce: Option<String>,
+	leeway: Duration,
+}
+
+impl<'a> ExpectedIdToken<'a> {
+	/// Expects a token signed by one of the keys of `jwks`.
+	pub const fn new(jwks: &'a JwksCache) -> Self {
+		Self {
+			jwks,
+			nonce: None,
+			leeway: DEFAULT_LEEWAY,
+		}
+	}
+
+	/// The nonce of the authorization request, which the token must contain.
+	pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+		self.nonce = Some(nonce.into());
+		self
+	}
+
+	/// The clock skew tolerated when checking the expiry of the token. Defaults to 60 seconds.
+	pub const fn leeway(mut self, leeway: Duration) -> Self {
+		self.leeway = leeway;
+		self
+	}
+}
+
+#[derive(serde::Deserialize)]
+struct Header {
+	alg: String,
+	#[serde(default)]
+	kid: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct IdTokenClaims {
+	iss: String,
+	aud: Audience,
+	exp: u64,
+	#[serde(default)]
+	nonce: Option<String>,
+	#[serde(flatten)]
+	world_id: WorldIdClaims,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Audience {
+	One(String),
+	Many(Vec<String>),
+}
+
+impl Audience {
+	fn contains(&self, audience: &str) -> bool {
+		match self {
+			Self::One(value) => value == audience,
+			Self::Many(values) => values.iter().any(|value| value == audience),
+		}
+	}
+}
+
+/// Validates an ID token returned by World ID, and returns the claims it makes about the user.
+///
+/// The token must be signed with RS256 by one of the keys of the `JwksCache`, issued by its issuer for its client id, unexpired, and contain the expected nonce if any.
+///
+/// # Errors
+///
+/// Returns an error if the token is malformed, if one of the checks fails, or if the keys couldn't be fetched.
+pub async fn validate_id_token(
+	token: &str,
+	expected: &ExpectedIdToken<'_>,
+) -> Result<WorldIdClaims, Error> {
+	let [header, payload, signature] = token.split('.').collect::<Vec<_>>()[..] else {
+		return Err(Error::MalformedToken(
+			"expected three dot-separated parts".to_string(),
+		));
+	};
+
+	let header: Header = decode_part(header)?;
+	if header.alg != "RS256" {
+		return Err(Error::UnsupportedAlgorithm(header.alg));
+	}
+	let kid = header
+		.kid
+		.ok_or_else(|| Error::MalformedToken("missing key id".to_string()))?;
+
+	let key = expected.jwks.key(&kid).await?;
+	let signature_bytes =
+		base64_decode(signature).map_err(|error| Error::MalformedToken(error.to_string()))?;
+	RsaPublicKeyComponents {
+		n: &key.n,
+		e: &key.e,
+	}
+	.verify(
+		&RSA_PKCS1_2048_8192_SHA256,
+		&token.as_bytes()[..token.len() - signature.len() - 1],
+		&signature_bytes,
+	)
+	.map_err(|_| Error::InvalidSignature)?;
+
+	let claims: IdTokenClaims = decode_part(payload)?;
+	let config = expected.jwks.config();
+
+	let issuer = config.issuer.as_str().trim_end_matches('/');
+	if claims.iss != issuer {
+		return Err(Error::InvalidIssuer(claims.iss));
+	}
+	if !claims.aud.contains(&config.client_id) {
+		return Err(Error::InvalidAudience);
+	}
+
+	let now = time::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+	if now > claims.exp.saturating_add(expected.leeway.as_secs()) {
+		return Err(Error::Expired);
+	}
+
+	if expected.nonce.is_some() && claims.nonce != expected.nonce {
+		return Err(Error::InvalidNonce);
+	}
+
+	Ok(claims.world_id)
+}
+
+fn decode_part<T: DeserializeOwned>(part: &str) -> Result<T, Error> {
+	let bytes = base64_decode(part).map_err(|error| Error::MalformedToken(error.to_string()))?;
+
+	serde_json::from_slice(&bytes).map_err(|error| Error::MalformedToken(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		hashing::base64_url_encode,
+		oidc::OidcConfig,
+		session::{AppId, VerificationLevel},
+	};
+	use ring::{
+		rand::SystemRandom,
+		signature::{RsaKeyPair, RSA_PKCS1_SHA256},
+	};
+	use serde_json::{json, Value};
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn keys() -> Vec<Value> {
+		serde_json::from_str(include_str!("../../tests/vectors/oidc-keys.json")).unwrap()
+	}
+
+	fn jwks(key: &Value) -> Value {
+		json!({ "keys": [{ "kty": "RSA", "use": "sig", "alg": "RS256", "kid": key["kid"], "n": key["n"], "e": key["e"] }] })
+	}
+
+	fn sign(key: &Value, claims: &Value) -> String {
+		let key_pair =
+			RsaKeyPair::from_pkcs8(&base64_decode(key["pkcs8"].as_str().unwrap()).unwrap())
+				.unwrap();
+		let message = format!(
+			"{}.{}",
+			base64_url_encode(
+				json!({ "alg": "RS256", "typ": "JWT", "kid": key["kid"] }).to_string()
+			),
+			base64_url_encode(claims.to_string())
+		);
+
+		let mut signature = vec![0; key_pair.public().modulus_len()];
+		key_pair
+			.sign(
+				&RSA_PKCS1_SHA256,
+				&SystemRandom::new(),
+				message.as_bytes(),
+				&mut signature,
+			)
+			.unwrap();
+
+		format!("{message}.{}", base64_url_encode(signature))
+	}
+
+	fn claims(server: &MockServer) -> Value {
+		let now = time::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+		json!({
+			"iss": server.uri(),
+			"sub": "0x2ae86d6d747702b3b2c81811cd2b39875e8fa6b780ee4a207bdc203a7860b535",
+			"aud": "app_123",
+			"iat": now,
+			"exp": now + 3600,
+			"nonce": "nonce_456",
+			"https://id.worldcoin.org/v1": { "verification_level": "orb" },
+		})
+	}
+
+	async fn jwks_server(responses: &[Value]) -> (MockServer, JwksCache) {
+		let server = MockServer::start().await;
+		// Every response is served once, except the last one which is served from then on.
+		for (i, response) in responses.iter().enumerate() {
+			let mock = Mock::given(method("GET"))
+				.and(path("/jwks.json"))
+				.respond_with(
+					ResponseTemplate::new(200)
+						.insert_header("cache-control", "public, max-age=300")
+						.set_body_json(response),
+				);
+
+			if i + 1 < responses.len() {
+				mock.up_to_n_times(1).mount(&server).await;
+			} else {
+				mock.mount(&server).await;
+			}
+		}
+
+		let mut config = OidcConfig::new(
+			AppId::from_str("app_123").unwrap(),
+			"https://example.com/callback",
+		);
+		config.issuer = Url::parse(&server.uri()).unwrap();
+
+		(server, JwksCache::new(config))
+	}
+
+	#[tokio::test]
+	async fn test_validate_id_token() {
+		let keys = keys();
+		let (server, jwks) = jwks_server(&[jwks(&keys[0])]).await;
+		let expected = ExpectedIdToken::new(&jwks).nonce("nonce_456");
+
+		let token = sign(&keys[0], &claims(&server));
+		assert_eq!(
+			validate_id_token(&token, &expected).await.unwrap(),
+			WorldIdClaims {
+				sub: "0x2ae86d6d747702b3b2c81811cd2b39875e8fa6b780ee4a207bdc203a7860b535"
+					.to_string(),
+				verification_level: VerificationLevel::Orb,
+				name: None,
+				given_name: None,
+				family_name: None,
+				email: None,
+			}
+		);
+
+		// The keys are cached.
+		validate_id_token(&token, &expected).await.unwrap();
+		assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+		let mut claims = claims(&server);
+		claims["nonce"] = json!("another_nonce");
+		assert!(matches!(
+			validate_id_token(&sign(&keys[0], &claims), &expected).await,
+			Err(Error::InvalidNonce)
+		));
+
+		// The header and payload of the token, with the signature of another key.
+		let other = sign(&keys[1], &self::claims(&server));
+		let forged = format!(
+			"{}.{}",
+			token.rsplit_once('.').unwrap().0,
+			other.rsplit_once('.').unwrap().1
+		);
+		assert!(matches!(
+			validate_id_token(&forged, &expected).await,
+			Err(Error::InvalidSignature)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_expired_token() {
+		let keys = keys();
+		let (server, jwks) = jwks_server(&[jwks(&keys[0])]).await;
+
+		let mut claims = claims(&server);
+		claims["exp"] = json!(claims["iat"].as_u64().unwrap() - 120);
+
+		assert!(matches!(
+			validate_id_token(&sign(&keys[0], &claims), &ExpectedIdToken::new(&jwks)).await,
+			Err(Error::Expired)
+		));
+		assert!(validate_id_token(
+			&sign(&keys[0], &claims),
+			&ExpectedIdToken::new(&jwks).leeway(Duration::from_secs(300))
+		)
+		.await
+		.is_ok());
+	}
+
+	#[tokio::test]
+	async fn test_wrong_audience_and_issuer() {
+		let keys = keys();
+		let (server, jwks) = jwks_server(&[jwks(&keys[0])]).await;
+
+		let mut claims = claims(&server);
+		claims["aud"] = json!(["app_456"]);
+		assert!(matches!(
+			validate_id_token(&sign(&keys[0], &claims), &ExpectedIdToken::new(&jwks)).await,
+			Err(Error::InvalidAudience)
+		));
+
+		claims["aud"] = json!(["app_456", "app_123"]);
+		claims["iss"] = json!("https://id.example.com");
+		assert!(matches!(
+			validate_id_token(&sign(&keys[0], &claims), &ExpectedIdToken::new(&jwks)).await,
+			Err(Error::InvalidIssuer(issuer)) if issuer == "https://id.example.com"
+		));
+	}
+
+	#[tokio::test]
+	async fn test_key_rotation() {
+		let keys = keys();
+		let (server, jwks) = jwks_server(&[jwks(&keys[0]), jwks(&keys[1])]).await;
+		let jwks = jwks.min_refresh_interval(Duration::ZERO);
+		let expected = ExpectedIdToken::new(&jwks);
+
+		validate_id_token(&sign(&keys[0], &claims(&server)), &expected)
+			.await
+			.unwrap();
+
+		// The unknown key triggers a refresh, which picks up the new key.
+		validate_id_token(&sign(&keys[1], &claims(&server)), &expected)
+			.await
+			.unwrap();
+		assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+		// The old key was rotated out.
+		assert!(matches!(
+			validate_id_token(&sign(&keys[0], &claims(&server)), &expected).await,
+			Err(Error::UnknownKey(kid)) if kid == "key-1"
+		));
+	}
+
+	#[tokio::test]
+	async fn test_unknown_key_refresh_interval() {
+		let keys = keys();
+		let (server, jwks) = jwks_server(&[jwks(&keys[0]), jwks(&keys[1])]).await;
+		let expected = ExpectedIdToken::new(&jwks);
+
+		validate_id_token(&sign(&keys[0], &claims(&server)), &expected)
+			.await
+			.unwrap();
+
+		assert!(matches!(
+			validate_id_token(&sign(&keys[1], &claims(&server)), &expected).await,
+			Err(Error::UnknownKey(_))
+		));
+		assert_eq!(server.received_requests().await.unwrap().len(), 1);
+	}
+}
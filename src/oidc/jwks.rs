@@ -0,0 +1,180 @@
+use reqwest::{header, Method, StatusCode};
+use std::{
+	collections::HashMap,
+	sync::{Mutex, PoisonError},
+	time::Duration,
+};
+
+use super::{Error, OidcConfig};
+use crate::{
+	hashing::base64_decode,
+	verify::{time::Instant, truncate_body},
+};
+
+/// How long keys are cached when the JWKS response has no `Cache-Control: max-age`.
+const DEFAULT_TTL: Duration = Duration::from_mins(10);
+/// The default minimum time between two fetches triggered by unknown key ids.
+const DEFAULT_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An RSA public key, as the big-endian modulus and exponent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RsaKey {
+	pub(super) n: Vec<u8>,
+	pub(super) e: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct Keys {
+	by_id: HashMap<String, RsaKey>,
+	fetched_at: Instant,
+	expires_at: Instant,
+}
+
+#[derive(serde::Deserialize)]
+struct JwkSet {
+	keys: Vec<Jwk>,
+}
+
+#[derive(serde::Deserialize)]
+struct Jwk {
+	kty: String,
+	#[serde(default)]
+	kid: Option<String>,
+	#[serde(default)]
+	n: Option<String>,
+	#[serde(default)]
+	e: Option<String>,
+}
+
+/// A cache of the signing keys of World ID, fetched from its JWKS endpoint.
+///
+/// Keys are kept for as long as the `Cache-Control: max-age` of the response allows (10 minutes if missing), and refetched early when a token is signed with an unknown key, so key rotations are picked up immediately.
+#[derive(Debug)]
+pub struct JwksCache {
+	config: OidcConfig,
+	min_refresh_interval: Duration,
+	keys: Mutex<Option<Keys>>,
+}
+
+impl JwksCache {
+	/// Creates an empty cache, fetching keys from the issuer of `config` on first use.
+	#[must_use]
+	pub const fn new(config: OidcConfig) -> Self {
+		Self {
+			config,
+			min_refresh_interval: DEFAULT_MIN_REFRESH_INTERVAL,
+			keys: Mutex::new(None),
+		}
+	}
+
+	/// The minimum time between two fetches triggered by tokens signed with unknown keys, so forged tokens can't flood the JWKS endpoint. Defaults to 30 seconds.
+	#[must_use]
+	pub const fn min_refresh_interval(mut self, min_refresh_interval: Duration) -> Self {
+		self.min_refresh_interval = min_refresh_interval;
+		self
+	}
+
+	/// The configuration the keys are fetched with.
+	#[must_use]
+	pub const fn config(&self) -> &OidcConfig {
+		&self.config
+	}
+
+	/// Returns the key with the given id, fetching the keys if they expired or if the key is unknown.
+	pub(super) async fn key(&self, kid: &str) -> Result<RsaKey, Error> {
+		let now = Instant::now();
+		{
+			let keys = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+			if let Some(keys) = keys.as_ref().filter(|keys| now < keys.expires_at) {
+				if let Some(key) = keys.by_id.get(kid) {
+					return Ok(key.clone());
+				}
+				if now < keys.fetched_at + self.min_refresh_interval {
+					return Err(Error::UnknownKey(kid.to_string()));
+				}
+			}
+		}
+
+		let keys = self.fetch().await?;
+		let key = keys.by_id.get(kid).cloned();
+		*self.keys.lock().unwrap_or_else(PoisonError::into_inner) = Some(keys);
+
+		key.ok_or_else(|| Error::UnknownKey(kid.to_string()))
+	}
+
+	async fn fetch(&self) -> Result<Keys, Error> {
+		let response = self.config.request(Method::GET, "jwks.json").send().await?;
+		let status = response.status();
+		let ttl = response
+			.headers()
+			.get(header::CACHE_CONTROL)
+			.and_then(|value| value.to_str().ok())
+			.map_or(DEFAULT_TTL, parse_max_age);
+		let body = response.text().await?;
+
+		if status != StatusCode::OK {
+			return Err(Error::UnexpectedResponse {
+				status,
+				body: truncate_body(body),
+			});
+		}
+
+		let by_id = serde_json::from_str::<JwkSet>(&body)?
+			.keys
+			.into_iter()
+			.filter(|jwk| jwk.kty == "RSA")
+			.filter_map(|jwk| {
+				let key = RsaKey {
+					n: base64_decode(jwk.n?).ok()?,
+					e: base64_decode(jwk.e?).ok()?,
+				};
+				Some((jwk.kid?, key))
+			})
+			.collect();
+
+		let fetched_at = Instant::now();
+		Ok(Keys {
+			by_id,
+			fetched_at,
+			expires_at: fetched_at + ttl,
+		})
+	}
+}
+
+/// Parses how long a response may be cached from its `Cache-Control` header: `no-store` and `no-cache` forbid caching, and `max-age` sets the duration.
+fn parse_max_age(cache_control: &str) -> Duration {
+	let mut ttl = DEFAULT_TTL;
+
+	for directive in cache_control.split(',').map(str::trim) {
+		if directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("no-cache")
+		{
+			return Duration::ZERO;
+		}
+		if let Some(max_age) = directive
+			.strip_prefix("max-age=")
+			.and_then(|seconds| seconds.parse().ok())
+		{
+			ttl = Duration::from_secs(max_age);
+		}
+	}
+
+	ttl
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_max_age() {
+		assert_eq!(
+			parse_max_age("public, max-age=300"),
+			Duration::from_secs(300)
+		);
+		assert_eq!(parse_max_age("max-age=0"), Duration::ZERO);
+		assert_eq!(parse_max_age("no-store"), Duration::ZERO);
+		assert_eq!(parse_max_age("max-age=60, no-cache"), Duration::ZERO);
+		assert_eq!(parse_max_age("public"), DEFAULT_TTL);
+		assert_eq!(parse_max_age("max-age=soon"), DEFAULT_TTL);
+	}
+}
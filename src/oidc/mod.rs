@@ -0,0 +1,177 @@
+//! Sign in with World ID, through its [OpenID Connect](https://openid.net/specs/openid-connect-core-1_0.html) provider.
+//!
+//! ```no_run
+//! # fn example(app_id: idkit::session::AppId) -> Result<(), idkit::oidc::Error> {
+//! use idkit::oidc::{AuthorizationRequest, Scope};
+//!
+//! let authorization = AuthorizationRequest::new(app_id, "https://example.com/callback")
+//!     .scopes([Scope::OpenId, Scope::Profile])
+//!     .build()?;
+//!
+//! // Persist `authorization.state`, `authorization.nonce` and `authorization.verifier` for the callback,
+//! // then redirect the user to `authorization.url`.
+//! # Ok(())
+//! # }
+//! ```
+
+use reqwest::{header, Method, StatusCode};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::time::Duration;
+use url::Url;
+
+use crate::{hashing::base64_url_encode, session::AppId, verify::VerifyConfig};
+
+mod authorize;
+mod id_token;
+mod jwks;
+mod token;
+mod types;
+mod userinfo;
+
+pub use authorize::{Authorization, AuthorizationRequest, PkceVerifier, ResponseType, Scope};
+pub use id_token::{validate_id_token, ExpectedIdToken};
+pub use jwks::JwksCache;
+pub use token::exchange_code;
+pub use types::{OAuthErrorCode, OAuthErrorResponse, TokenResponse, WorldIdClaims};
+pub use userinfo::userinfo;
+
+/// The issuer of World ID tokens, which serves the OIDC endpoints.
+pub const DEFAULT_ISSUER: &str = "https://id.worldcoin.org";
+
+/// An error of the Sign in with World ID flow.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("Invalid redirect URI {uri:?}: {reason}")]
+	InvalidRedirectUri { uri: String, reason: &'static str },
+
+	#[error("Invalid PKCE verifier, expected 43 to 128 letters, digits, '-', '.', '_' or '~'")]
+	InvalidVerifier,
+
+	#[error("Failed to generate random bytes")]
+	Random,
+
+	#[error("Failed to send request to World ID: {0}")]
+	Request(reqwest::Error),
+
+	#[error("Request to World ID timed out")]
+	Timeout,
+
+	#[error("World ID rejected the request: {0}")]
+	OAuth(#[source] OAuthErrorResponse),
+
+	#[error("Malformed response from World ID: {0}")]
+	MalformedResponse(#[from] serde_json::Error),
+
+	#[error("Unexpected response from World ID ({status}): {body}")]
+	UnexpectedResponse { status: StatusCode, body: String },
+
+	#[error("Malformed ID token: {0}")]
+	MalformedToken(String),
+
+	#[error("Unsupported ID token signature algorithm {0}, expected RS256")]
+	UnsupportedAlgorithm(String),
+
+	#[error("ID token signed with unknown key {0:?}")]
+	UnknownKey(String),
+
+	#[error("Invalid ID token signature")]
+	InvalidSignature,
+
+	#[error("ID token issued by {0}, not by World ID")]
+	InvalidIssuer(String),
+
+	#[error("ID token issued for another app")]
+	InvalidAudience,
+
+	#[error("ID token expired")]
+	Expired,
+
+	#[error("ID token nonce doesn't match the one of the authorization request")]
+	InvalidNonce,
+
+	#[error("The access token is invalid or expired")]
+	InvalidToken,
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(error: reqwest::Error) -> Self {
+		if error.is_timeout() {
+			Self::Timeout
+		} else {
+			Self::Request(error)
+		}
+	}
+}
+
+/// Configuration for requests made to the World ID OIDC provider.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+	/// The id of the app signing users in, used as the OIDC client id.
+	pub client_id: AppId,
+	/// The client secret of the app, for confidential clients. Public clients authenticate the token exchange with a PKCE verifier instead.
+	pub client_secret: Option<String>,
+	/// The redirect URI the authorization code was sent to.
+	pub redirect_uri: String,
+	/// The issuer serving the OIDC endpoints. Defaults to `https://id.worldcoin.org`.
+	pub issuer: Url,
+	/// The HTTP client used to send requests.
+	pub client: reqwest::Client,
+	/// The maximum time allowed for each request, applied on top of the client's own settings.
+	pub timeout: Option<Duration>,
+	/// The `User-Agent` header sent with every request. Defaults to `idkit-rs/<version>`.
+	pub user_agent: String,
+}
+
+impl OidcConfig {
+	/// Creates a configuration for the given app, sharing the client and timeouts of `VerifyConfig::default`.
+	#[must_use]
+	pub fn new(client_id: AppId, redirect_uri: impl Into<String>) -> Self {
+		Self::with_http(client_id, redirect_uri, &VerifyConfig::default())
+	}
+
+	/// Creates a configuration for the given app, with the client, timeout and `User-Agent` of `http`.
+	#[must_use]
+	pub fn with_http(
+		client_id: AppId,
+		redirect_uri: impl Into<String>,
+		http: &VerifyConfig,
+	) -> Self {
+		Self {
+			client_id,
+			client_secret: None,
+			redirect_uri: redirect_uri.into(),
+			issuer: Url::parse(DEFAULT_ISSUER).unwrap_or_else(|_| unreachable!()),
+			client: http.client.clone(),
+			timeout: http.timeout,
+			user_agent: http.user_agent.clone(),
+		}
+	}
+
+	/// Starts a request to `path`, relative to the issuer.
+	fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+		let mut issuer = self.issuer.clone();
+		if !issuer.path().ends_with('/') {
+			issuer.set_path(&format!("{}/", issuer.path()));
+		}
+
+		let builder = self
+			.client
+			.request(method, issuer.join(path).unwrap_or_else(|_| unreachable!()))
+			.header(header::USER_AGENT, &self.user_agent);
+
+		match self.timeout {
+			Some(timeout) => builder.timeout(timeout),
+			None => builder,
+		}
+	}
+}
+
+/// Generates `len` random bytes, encoded as unpadded URL-safe base64.
+fn random_token(len: usize) -> Result<String, Error> {
+	let mut bytes = vec![0; len];
+	SystemRandom::new()
+		.fill(&mut bytes)
+		.map_err(|_| Error::Random)?;
+
+	Ok(base64_url_encode(bytes))
+}
@@ -0,0 +1,197 @@
+use reqwest::{Method, StatusCode};
+
+use super::{Error, OAuthErrorResponse, OidcConfig, PkceVerifier, TokenResponse};
+use crate::verify::truncate_body;
+
+/// Exchanges the authorization code returned to the redirect URI for tokens.
+///
+/// Public clients authenticate with the PKCE `verifier` of the authorization request, and confidential ones with the `client_secret` of the configuration.
+///
+/// # Errors
+///
+/// Returns `Error::OAuth` if World ID rejects the exchange, e.g. with `invalid_grant` for an expired or reused code, or an error if the request fails or the response is malformed.
+pub async fn exchange_code(
+	config: &OidcConfig,
+	code: &str,
+	verifier: Option<&PkceVerifier>,
+) -> Result<TokenResponse, Error> {
+	let mut form = vec![
+		("grant_type", "authorization_code"),
+		("code", code),
+		("redirect_uri", config.redirect_uri.as_str()),
+		("client_id", config.client_id.as_ref()),
+	];
+	if let Some(verifier) = verifier {
+		form.push(("code_verifier", verifier.as_str()));
+	}
+
+	let mut request = config.request(Method::POST, "token").form(&form);
+	if let Some(secret) = &config.client_secret {
+		request = request.basic_auth(&*config.client_id, Some(secret));
+	}
+
+	let response = request.send().await?;
+	let status = response.status();
+	let body = response.text().await?;
+
+	match status {
+		status if status.is_success() => Ok(serde_json::from_str(&body)?),
+		StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED => {
+			Err(serde_json::from_str::<OAuthErrorResponse>(&body)
+				.map_or_else(|_| unexpected(status, body), Error::OAuth))
+		},
+		status => Err(unexpected(status, body)),
+	}
+}
+
+fn unexpected(status: StatusCode, body: String) -> Error {
+	Error::UnexpectedResponse {
+		status,
+		body: truncate_body(body),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{oidc::OAuthErrorCode, session::AppId};
+	use serde_json::json;
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{basic_auth, body_string, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+	async fn token_server(status: u16, body: &str) -> (MockServer, OidcConfig) {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/token"))
+			.respond_with(ResponseTemplate::new(status).set_body_raw(body, "application/json"))
+			.mount(&server)
+			.await;
+
+		let mut config = OidcConfig::new(
+			AppId::from_str("app_123").unwrap(),
+			"https://example.com/callback",
+		);
+		config.issuer = Url::parse(&server.uri()).unwrap();
+
+		(server, config)
+	}
+
+	async fn exchange(config: &OidcConfig) -> Result<TokenResponse, Error> {
+		exchange_code(
+			config,
+			"code_123",
+			Some(&PkceVerifier::from_str(VERIFIER).unwrap()),
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_exchange_code() {
+		let (server, config) = token_server(
+			200,
+			&json!({
+				"access_token": "access_123",
+				"id_token": "header.claims.signature",
+				"expires_in": 3600,
+				"token_type": "Bearer",
+				"scope": "openid",
+			})
+			.to_string(),
+		)
+		.await;
+
+		assert_eq!(
+			exchange(&config).await.unwrap(),
+			TokenResponse {
+				access_token: "access_123".to_string(),
+				id_token: "header.claims.signature".to_string(),
+				expires_in: Some(3600),
+				token_type: "Bearer".to_string(),
+				scope: Some("openid".to_string()),
+			}
+		);
+
+		let requests = server.received_requests().await.unwrap();
+		assert_eq!(
+			String::from_utf8_lossy(&requests[0].body),
+			format!("grant_type=authorization_code&code=code_123&redirect_uri=https%3A%2F%2Fexample.com%2Fcallback&client_id=app_123&code_verifier={VERIFIER}")
+		);
+	}
+
+	#[tokio::test]
+	async fn test_exchange_code_with_secret() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/token"))
+			.and(basic_auth("app_123", "secret"))
+			.and(body_string("grant_type=authorization_code&code=code_123&redirect_uri=https%3A%2F%2Fexample.com%2Fcallback&client_id=app_123"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"access_token": "access_123",
+				"id_token": "header.claims.signature",
+				"token_type": "Bearer",
+			})))
+			.mount(&server)
+			.await;
+
+		let mut config = OidcConfig::new(
+			AppId::from_str("app_123").unwrap(),
+			"https://example.com/callback",
+		);
+		config.issuer = Url::parse(&server.uri()).unwrap();
+		config.client_secret = Some("secret".to_string());
+
+		let response = exchange_code(&config, "code_123", None).await.unwrap();
+		assert_eq!(response.expires_in, None);
+	}
+
+	#[tokio::test]
+	async fn test_invalid_grant() {
+		let (_server, config) = token_server(
+			400,
+			r#"{"error":"invalid_grant","error_description":"The authorization code has expired."}"#,
+		)
+		.await;
+
+		let Err(Error::OAuth(response)) = exchange(&config).await else {
+			panic!("expected an OAuth error");
+		};
+		assert_eq!(response.error, OAuthErrorCode::InvalidGrant);
+		assert_eq!(
+			response.to_string(),
+			"invalid_grant: The authorization code has expired."
+		);
+	}
+
+	#[tokio::test]
+	async fn test_malformed_responses() {
+		let (_server, config) = token_server(200, r#"{"token_type":"Bearer"}"#).await;
+		assert!(matches!(
+			exchange(&config).await,
+			Err(Error::MalformedResponse(_))
+		));
+
+		let (_server, config) = token_server(400, "Bad Request").await;
+		assert!(matches!(
+			exchange(&config).await,
+			Err(Error::UnexpectedResponse {
+				status: StatusCode::BAD_REQUEST,
+				..
+			})
+		));
+
+		let (_server, config) = token_server(502, "Bad Gateway").await;
+		assert!(matches!(
+			exchange(&config).await,
+			Err(Error::UnexpectedResponse {
+				status: StatusCode::BAD_GATEWAY,
+				..
+			})
+		));
+	}
+}
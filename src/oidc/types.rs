@@ -0,0 +1,166 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::session::VerificationLevel;
+
+/// The tokens returned by the World ID token endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct TokenResponse {
+	/// The token to call the UserInfo endpoint with.
+	pub access_token: String,
+	/// The ID token, holding the claims of the user.
+	pub id_token: String,
+	/// How long the access token is valid for, in seconds.
+	#[serde(default)]
+	pub expires_in: Option<u64>,
+	/// The type of the access token, usually `Bearer`.
+	pub token_type: String,
+	/// The scopes granted, if they differ from the requested ones.
+	#[serde(default)]
+	pub scope: Option<String>,
+}
+
+/// The standard OAuth 2.0 error body, returned by World ID when it rejects a request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct OAuthErrorResponse {
+	pub error: OAuthErrorCode,
+	/// A human-readable description of the error.
+	#[serde(default)]
+	pub error_description: Option<String>,
+}
+
+impl Display for OAuthErrorResponse {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.error)?;
+
+		if let Some(description) = &self.error_description {
+			write!(f, ": {description}")?;
+		}
+
+		Ok(())
+	}
+}
+
+impl std::error::Error for OAuthErrorResponse {}
+
+/// The error codes defined by [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.2).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OAuthErrorCode {
+	/// The request is missing a parameter, or is otherwise malformed.
+	InvalidRequest,
+	/// The client id or secret is invalid.
+	InvalidClient,
+	/// The authorization code or PKCE verifier is invalid, expired, already used, or was issued to another client or redirect URI.
+	InvalidGrant,
+	/// The client isn't allowed to use this grant type.
+	UnauthorizedClient,
+	/// The grant type isn't supported.
+	UnsupportedGrantType,
+	/// The requested scope is invalid.
+	InvalidScope,
+	/// An error code not known to this version of the crate.
+	Other(String),
+}
+
+impl OAuthErrorCode {
+	/// The error code as returned by World ID.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::InvalidRequest => "invalid_request",
+			Self::InvalidClient => "invalid_client",
+			Self::InvalidGrant => "invalid_grant",
+			Self::UnauthorizedClient => "unauthorized_client",
+			Self::UnsupportedGrantType => "unsupported_grant_type",
+			Self::InvalidScope => "invalid_scope",
+			Self::Other(code) => code,
+		}
+	}
+}
+
+impl From<String> for OAuthErrorCode {
+	fn from(code: String) -> Self {
+		match code.as_str() {
+			"invalid_request" => Self::InvalidRequest,
+			"invalid_client" => Self::InvalidClient,
+			"invalid_grant" => Self::InvalidGrant,
+			"unauthorized_client" => Self::UnauthorizedClient,
+			"unsupported_grant_type" => Self::UnsupportedGrantType,
+			"invalid_scope" => Self::InvalidScope,
+			_ => Self::Other(code),
+		}
+	}
+}
+
+impl FromStr for OAuthErrorCode {
+	type Err = std::convert::Infallible;
+
+	fn from_str(code: &str) -> Result<Self, Self::Err> {
+		Ok(Self::from(code.to_string()))
+	}
+}
+
+impl Display for OAuthErrorCode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for OAuthErrorCode {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		<String as serde::Deserialize>::deserialize(deserializer).map(Self::from)
+	}
+}
+
+/// The claims World ID makes about a signed in user, either from a validated ID token or from the UserInfo endpoint.
+///
+/// The profile claims are only set if the matching scope was requested.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(from = "RawWorldIdClaims")]
+pub struct WorldIdClaims {
+	/// The identifier of the user, which is their nullifier hash for the app: stable across sign ins, and unique per app.
+	pub sub: String,
+	/// How the user was verified.
+	pub verification_level: VerificationLevel,
+	/// The full name of the user, with the `profile` scope.
+	pub name: Option<String>,
+	/// The given name of the user, with the `profile` scope.
+	pub given_name: Option<String>,
+	/// The family name of the user, with the `profile` scope.
+	pub family_name: Option<String>,
+	/// The email of the user, with the `email` scope.
+	pub email: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawWorldIdClaims {
+	sub: String,
+	/// The World ID claims, namespaced as `https://id.worldcoin.org/v1`.
+	#[serde(rename = "https://id.worldcoin.org/v1")]
+	world_id: WorldIdNamespace,
+	#[serde(default)]
+	name: Option<String>,
+	#[serde(default)]
+	given_name: Option<String>,
+	#[serde(default)]
+	family_name: Option<String>,
+	#[serde(default)]
+	email: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct WorldIdNamespace {
+	verification_level: VerificationLevel,
+}
+
+impl From<RawWorldIdClaims> for WorldIdClaims {
+	fn from(claims: RawWorldIdClaims) -> Self {
+		Self {
+			sub: claims.sub,
+			verification_level: claims.world_id.verification_level,
+			name: claims.name,
+			given_name: claims.given_name,
+			family_name: claims.family_name,
+			email: claims.email,
+		}
+	}
+}
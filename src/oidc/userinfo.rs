@@ -0,0 +1,135 @@
+use reqwest::{Method, StatusCode};
+
+use super::{Error, OidcConfig, WorldIdClaims};
+use crate::verify::truncate_body;
+
+/// Fetches the claims about the user from the World ID UserInfo endpoint, with the access token returned by `exchange_code`.
+///
+/// This is an alternative to validating the ID token, for services that only hold the access token.
+///
+/// # Errors
+///
+/// Returns `Error::InvalidToken` if the access token is invalid or expired, or an error if the request fails or the response is malformed.
+pub async fn userinfo(config: &OidcConfig, access_token: &str) -> Result<WorldIdClaims, Error> {
+	let response = config
+		.request(Method::GET, "userinfo")
+		.bearer_auth(access_token)
+		.send()
+		.await?;
+	let status = response.status();
+	let body = response.text().await?;
+
+	match status {
+		status if status.is_success() => Ok(serde_json::from_str(&body)?),
+		StatusCode::UNAUTHORIZED => Err(Error::InvalidToken),
+		status => Err(Error::UnexpectedResponse {
+			status,
+			body: truncate_body(body),
+		}),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::{AppId, VerificationLevel};
+	use serde_json::{json, Value};
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{bearer_token, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	const SUB: &str = "0x2ae86d6d747702b3b2c81811cd2b39875e8fa6b780ee4a207bdc203a7860b535";
+
+	async fn userinfo_server(status: u16, body: Value) -> (MockServer, OidcConfig) {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/userinfo"))
+			.and(bearer_token("access_123"))
+			.respond_with(ResponseTemplate::new(status).set_body_json(body))
+			.mount(&server)
+			.await;
+
+		let mut config = OidcConfig::new(
+			AppId::from_str("app_123").unwrap(),
+			"https://example.com/callback",
+		);
+		config.issuer = Url::parse(&server.uri()).unwrap();
+
+		(server, config)
+	}
+
+	#[tokio::test]
+	async fn test_userinfo() {
+		let (_server, config) = userinfo_server(
+			200,
+			json!({
+				"sub": SUB,
+				"https://id.worldcoin.org/v1": { "verification_level": "orb" },
+				"https://id.worldcoin.org/beta": { "likely_human": "strong", "credential_type": "orb" },
+			}),
+		)
+		.await;
+
+		assert_eq!(
+			userinfo(&config, "access_123").await.unwrap(),
+			WorldIdClaims {
+				sub: SUB.to_string(),
+				verification_level: VerificationLevel::Orb,
+				name: None,
+				given_name: None,
+				family_name: None,
+				email: None,
+			}
+		);
+	}
+
+	#[tokio::test]
+	async fn test_userinfo_with_profile() {
+		let (_server, config) = userinfo_server(
+			200,
+			json!({
+				"sub": SUB,
+				"https://id.worldcoin.org/v1": { "verification_level": "device" },
+				"name": "World ID User",
+				"given_name": "World ID",
+				"family_name": "User",
+				"email": format!("{SUB}@id.worldcoin.org"),
+			}),
+		)
+		.await;
+
+		let claims = userinfo(&config, "access_123").await.unwrap();
+		assert_eq!(claims.verification_level, VerificationLevel::Device);
+		assert_eq!(claims.name.as_deref(), Some("World ID User"));
+		assert_eq!(claims.given_name.as_deref(), Some("World ID"));
+		assert_eq!(claims.family_name.as_deref(), Some("User"));
+		assert_eq!(claims.email, Some(format!("{SUB}@id.worldcoin.org")));
+	}
+
+	#[tokio::test]
+	async fn test_invalid_token() {
+		let (_server, config) = userinfo_server(
+			401,
+			json!({ "error": "invalid_token", "error_description": "The access token expired" }),
+		)
+		.await;
+
+		assert!(matches!(
+			userinfo(&config, "access_123").await,
+			Err(Error::InvalidToken)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_malformed_userinfo() {
+		let (_server, config) = userinfo_server(200, json!({ "sub": SUB })).await;
+
+		assert!(matches!(
+			userinfo(&config, "access_123").await,
+			Err(Error::MalformedResponse(_))
+		));
+	}
+}
@@ -0,0 +1,150 @@
+//! Addresses, chain ids and RPC URLs of the World ID deployments.
+
+use alloy_primitives::{address, Address};
+use std::fmt::Display;
+
+pub use crate::contracts::ORB_GROUP_ID;
+
+/// The chain id of World Chain.
+pub const WORLD_CHAIN_ID: u64 = 480;
+/// The chain id of World Chain Sepolia.
+pub const WORLD_CHAIN_SEPOLIA_ID: u64 = 4801;
+/// The chain id of Ethereum mainnet.
+pub const ETHEREUM_ID: u64 = 1;
+/// The chain id of Ethereum Sepolia.
+pub const ETHEREUM_SEPOLIA_ID: u64 = 11_155_111;
+
+/// The World ID router on World Chain.
+pub const WORLD_CHAIN_ROUTER: Address = address!("17B354dD2595411ff79041f930e491A4Df39A278");
+/// The World ID router on World Chain Sepolia.
+pub const WORLD_CHAIN_SEPOLIA_ROUTER: Address =
+	address!("57f928158C3EE7CDad1e4D8642503c4D0201f611");
+/// The World ID router on Ethereum mainnet.
+pub const ETHEREUM_ROUTER: Address = address!("163b09b4fE21177c455D850BD815B6D583732432");
+/// The World ID router on Ethereum Sepolia.
+pub const ETHEREUM_SEPOLIA_ROUTER: Address = address!("469449f251692E0779667583026b5A1E99512157");
+
+/// The World ID identity manager on Ethereum mainnet, which keeps the root history of the identity tree.
+pub const ETHEREUM_IDENTITY_MANAGER: Address = address!("f7134CE138832c1456F2a91D64621eE90c2bddEa");
+/// The World ID identity manager on Ethereum Sepolia.
+pub const ETHEREUM_SEPOLIA_IDENTITY_MANAGER: Address =
+	address!("b2EaD588f14e69266d1b87936b75325181377076");
+
+/// The public RPC endpoint of World Chain.
+pub const WORLD_CHAIN_RPC_URL: &str = "https://worldchain-mainnet.g.alchemy.com/public";
+/// The public RPC endpoint of World Chain Sepolia.
+pub const WORLD_CHAIN_SEPOLIA_RPC_URL: &str = "https://worldchain-sepolia.g.alchemy.com/public";
+/// A public RPC endpoint of Ethereum mainnet.
+pub const ETHEREUM_RPC_URL: &str = "https://ethereum-rpc.publicnode.com";
+/// A public RPC endpoint of Ethereum Sepolia.
+pub const ETHEREUM_SEPOLIA_RPC_URL: &str = "https://ethereum-sepolia-rpc.publicnode.com";
+
+/// A network World ID is deployed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+	WorldChain,
+	WorldChainSepolia,
+	Ethereum,
+	EthereumSepolia,
+}
+
+impl Network {
+	/// The chain id of the network.
+	#[must_use]
+	pub const fn chain_id(self) -> u64 {
+		match self {
+			Self::WorldChain => WORLD_CHAIN_ID,
+			Self::WorldChainSepolia => WORLD_CHAIN_SEPOLIA_ID,
+			Self::Ethereum => ETHEREUM_ID,
+			Self::EthereumSepolia => ETHEREUM_SEPOLIA_ID,
+		}
+	}
+
+	/// The address of the World ID router, which verifies proofs.
+	#[must_use]
+	pub const fn world_id_router(self) -> Address {
+		match self {
+			Self::WorldChain => WORLD_CHAIN_ROUTER,
+			Self::WorldChainSepolia => WORLD_CHAIN_SEPOLIA_ROUTER,
+			Self::Ethereum => ETHEREUM_ROUTER,
+			Self::EthereumSepolia => ETHEREUM_SEPOLIA_ROUTER,
+		}
+	}
+
+	/// The address of the World ID identity manager, which is only deployed on Ethereum.
+	#[must_use]
+	pub const fn identity_manager(self) -> Option<Address> {
+		match self {
+			Self::Ethereum => Some(ETHEREUM_IDENTITY_MANAGER),
+			Self::EthereumSepolia => Some(ETHEREUM_SEPOLIA_IDENTITY_MANAGER),
+			Self::WorldChain | Self::WorldChainSepolia => None,
+		}
+	}
+
+	/// A public RPC endpoint of the network. These are rate-limited, so production deployments should use their own.
+	#[must_use]
+	pub const fn rpc_url(self) -> &'static str {
+		match self {
+			Self::WorldChain => WORLD_CHAIN_RPC_URL,
+			Self::WorldChainSepolia => WORLD_CHAIN_SEPOLIA_RPC_URL,
+			Self::Ethereum => ETHEREUM_RPC_URL,
+			Self::EthereumSepolia => ETHEREUM_SEPOLIA_RPC_URL,
+		}
+	}
+}
+
+impl Display for Network {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Self::WorldChain => "World Chain",
+			Self::WorldChainSepolia => "World Chain Sepolia",
+			Self::Ethereum => "Ethereum",
+			Self::EthereumSepolia => "Ethereum Sepolia",
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_checksummed_addresses() {
+		assert_eq!(
+			WORLD_CHAIN_ROUTER.to_string(),
+			"0x17B354dD2595411ff79041f930e491A4Df39A278"
+		);
+		assert_eq!(
+			WORLD_CHAIN_SEPOLIA_ROUTER.to_string(),
+			"0x57f928158C3EE7CDad1e4D8642503c4D0201f611"
+		);
+		assert_eq!(
+			ETHEREUM_ROUTER.to_string(),
+			"0x163b09b4fE21177c455D850BD815B6D583732432"
+		);
+		assert_eq!(
+			ETHEREUM_SEPOLIA_ROUTER.to_string(),
+			"0x469449f251692E0779667583026b5A1E99512157"
+		);
+		assert_eq!(
+			ETHEREUM_IDENTITY_MANAGER.to_string(),
+			"0xf7134CE138832c1456F2a91D64621eE90c2bddEa"
+		);
+		assert_eq!(
+			ETHEREUM_SEPOLIA_IDENTITY_MANAGER.to_string(),
+			"0xb2EaD588f14e69266d1b87936b75325181377076"
+		);
+	}
+
+	#[test]
+	fn test_network() {
+		assert_eq!(Network::WorldChain.chain_id(), 480);
+		assert_eq!(Network::WorldChain.world_id_router(), WORLD_CHAIN_ROUTER);
+		assert_eq!(Network::WorldChain.identity_manager(), None);
+		assert_eq!(
+			Network::Ethereum.identity_manager(),
+			Some(ETHEREUM_IDENTITY_MANAGER)
+		);
+		assert_eq!(Network::EthereumSepolia.chain_id(), 11_155_111);
+	}
+}
@@ -0,0 +1,594 @@
+//! Verify World ID proofs against the `WorldIdRouter` contract instead of the Developer Portal.
+
+use alloy_primitives::{Address, Bytes};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_sol_types::{SolCall, SolInterface};
+use alloy_transport::{Transport, TransportError};
+use ruint::aliases::U256;
+use std::{
+	convert::Infallible,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+pub mod constants;
+
+use crate::{
+	contracts::IWorldID,
+	session::{Action, ActionError, AppId, VerificationLevel},
+	MerkleRoot, Proof, Signal,
+};
+pub use constants::Network;
+
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+mod bindings {
+	alloy_sol_types::sol! {
+		interface IAddressBook {
+			function addressVerifiedUntil(address account) external view returns (uint256);
+		}
+
+		interface IWorldIDIdentityManager {
+			function latestRoot() external view returns (uint256);
+			function rootHistory(uint256 root) external view returns (uint128);
+		}
+	}
+}
+
+use bindings::{IAddressBook, IWorldIDIdentityManager};
+
+/// An error when verifying a proof on-chain.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("malformed proof: {0}")]
+	MalformedProof(String),
+
+	#[error("only orb proofs can be verified on-chain, got {0}")]
+	UnsupportedVerificationLevel(VerificationLevel),
+
+	#[error("the proof is invalid")]
+	ProofInvalid,
+
+	#[error("the merkle root has expired")]
+	ExpiredRoot,
+
+	#[error("the merkle root does not exist")]
+	NonExistentRoot,
+
+	#[error("the group {0} does not exist")]
+	NoSuchGroup(U256),
+
+	#[error("the group {0} is deprecated")]
+	GroupIsDeprecated(U256),
+
+	#[error("the merkle root was superseded {age:?} ago, more than the allowed {max_age:?}")]
+	RootTooOld { age: Duration, max_age: Duration },
+
+	#[error("failed to decode the contract's response: {0}")]
+	Decode(#[from] alloy_sol_types::Error),
+
+	#[error("World ID has no identity manager on {0}")]
+	UnsupportedNetwork(Network),
+
+	#[error("the verification reverted: {0}")]
+	Reverted(Bytes),
+
+	#[error("failed to call the contract: {0}")]
+	Transport(TransportError),
+
+	#[error("invalid action: {0}")]
+	InvalidAction(#[from] ActionError),
+}
+
+impl From<Infallible> for Error {
+	fn from(error: Infallible) -> Self {
+		match error {}
+	}
+}
+
+impl From<TransportError> for Error {
+	// `ErrorPayload` isn't re-exported by `alloy_transport`, so its methods can't be named here.
+	#[allow(clippy::redundant_closure_for_method_calls)]
+	fn from(error: TransportError) -> Self {
+		error
+			.as_error_resp()
+			.and_then(|payload| payload.as_revert_data())
+			.map_or_else(|| Self::Transport(error), Self::from_revert)
+	}
+}
+
+impl Error {
+	fn from_revert(data: Bytes) -> Self {
+		match IWorldID::IWorldIDErrors::abi_decode(&data, true) {
+			Ok(IWorldID::IWorldIDErrors::ProofInvalid(_)) => Self::ProofInvalid,
+			Ok(IWorldID::IWorldIDErrors::ExpiredRoot(_)) => Self::ExpiredRoot,
+			Ok(IWorldID::IWorldIDErrors::NonExistentRoot(_)) => Self::NonExistentRoot,
+			Ok(IWorldID::IWorldIDErrors::NoSuchGroup(error)) => Self::NoSuchGroup(error.groupId),
+			Ok(IWorldID::IWorldIDErrors::GroupIsDeprecated(error)) => {
+				Self::GroupIsDeprecated(error.groupId)
+			},
+			Err(_) => Self::Reverted(data),
+		}
+	}
+}
+
+/// Verify a World ID proof by calling `verifyProof` on the World ID router contract deployed on `network`.
+///
+/// The call is a read-only `eth_call`, so no transaction is sent.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is malformed, if the contract rejects it (`Error::ProofInvalid`, `Error::ExpiredRoot`, ...), or if the call fails.
+pub async fn verify_proof_onchain<P, T, A, V>(
+	provider: &P,
+	network: Network,
+	proof: &Proof,
+	app_id: &AppId,
+	action: A,
+	signal: V,
+) -> Result<(), Error>
+where
+	P: Provider<T>,
+	T: Transport + Clone,
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+	V: Signal + Send,
+{
+	let action: Action = action.try_into()?;
+	let calldata = verify_proof_calldata(proof, app_id, &action, &signal)?;
+
+	provider
+		.call(
+			&TransactionRequest::default()
+				.to(network.world_id_router())
+				.input(calldata.into()),
+		)
+		.await?;
+
+	Ok(())
+}
+
+/// The state of a merkle root in the identity manager's root history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootInfo {
+	/// The merkle root.
+	pub root: MerkleRoot,
+	/// When the root was replaced by a newer one, or `None` if it's the latest root.
+	pub superseded_at: Option<SystemTime>,
+	/// Whether the root is the latest root of the identity tree.
+	pub is_latest: bool,
+}
+
+impl RootInfo {
+	/// How long ago the root was superseded, zero for the latest root.
+	#[must_use]
+	pub fn age(&self) -> Duration {
+		self.superseded_at
+			.and_then(|superseded_at| SystemTime::now().duration_since(superseded_at).ok())
+			.unwrap_or_default()
+	}
+}
+
+/// A policy rejecting proofs whose merkle root was superseded more than the given duration ago.
+///
+/// The Developer Portal and the World ID router accept roots for a while after they're superseded, so this can be used on top of either to narrow that window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxRootAge(pub Duration);
+
+impl MaxRootAge {
+	/// Checks the age of an already fetched root against the policy.
+	///
+	/// # Errors
+	///
+	/// Errors with `Error::RootTooOld` if the root was superseded too long ago.
+	pub fn check(&self, root: &RootInfo) -> Result<(), Error> {
+		let age = root.age();
+
+		if age > self.0 {
+			return Err(Error::RootTooOld {
+				age,
+				max_age: self.0,
+			});
+		}
+
+		Ok(())
+	}
+
+	/// Fetches the root from the identity manager deployed on `network` and checks its age against the policy.
+	///
+	/// # Errors
+	///
+	/// Errors if `network` has no identity manager (`Error::UnsupportedNetwork`), if the root doesn't exist (`Error::NonExistentRoot`), if it was superseded too long ago (`Error::RootTooOld`), or if the calls fail.
+	pub async fn enforce<P, T>(
+		&self,
+		provider: &P,
+		network: Network,
+		merkle_root: MerkleRoot,
+	) -> Result<RootInfo, Error>
+	where
+		P: Provider<T>,
+		T: Transport + Clone,
+	{
+		let root = check_root_age(provider, network, merkle_root).await?;
+		self.check(&root)?;
+
+		Ok(root)
+	}
+}
+
+/// Looks up a merkle root in the root history of the World ID identity manager contract deployed on `network`.
+///
+/// # Errors
+///
+/// Errors if `network` has no identity manager (`Error::UnsupportedNetwork`), if the root was never a root of the identity tree (`Error::NonExistentRoot`), or if the calls fail.
+pub async fn check_root_age<P, T>(
+	provider: &P,
+	network: Network,
+	merkle_root: MerkleRoot,
+) -> Result<RootInfo, Error>
+where
+	P: Provider<T>,
+	T: Transport + Clone,
+{
+	let identity_manager = network
+		.identity_manager()
+		.ok_or(Error::UnsupportedNetwork(network))?;
+
+	let latest_root = call(
+		provider,
+		identity_manager,
+		&IWorldIDIdentityManager::latestRootCall {},
+	)
+	.await?
+	._0;
+
+	if latest_root == merkle_root.0 {
+		return Ok(RootInfo {
+			root: merkle_root,
+			superseded_at: None,
+			is_latest: true,
+		});
+	}
+
+	let superseded_at = call(
+		provider,
+		identity_manager,
+		&IWorldIDIdentityManager::rootHistoryCall {
+			root: merkle_root.0,
+		},
+	)
+	.await?
+	._0;
+
+	// Roots that were never part of the history are reported as superseded at timestamp zero.
+	if superseded_at == 0 {
+		return Err(Error::NonExistentRoot);
+	}
+
+	Ok(RootInfo {
+		root: merkle_root,
+		superseded_at: Some(
+			UNIX_EPOCH + Duration::from_secs(superseded_at.try_into().unwrap_or(u64::MAX)),
+		),
+		is_latest: false,
+	})
+}
+
+/// When the Orb verification of a wallet address recorded in the address book expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VerifiedUntil(pub SystemTime);
+
+/// Checks whether `wallet` is Orb-verified according to the World ID address book contract deployed at `address_book`.
+///
+/// This only proves that the wallet was verified at some point, so it's a lower-assurance check than verifying a fresh proof.
+///
+/// Returns `None` if the wallet was never verified, or if its verification has expired.
+///
+/// # Errors
+///
+/// Errors if the call fails.
+pub async fn is_address_verified<P, T>(
+	provider: &P,
+	address_book: Address,
+	wallet: Address,
+) -> Result<Option<VerifiedUntil>, Error>
+where
+	P: Provider<T>,
+	T: Transport + Clone,
+{
+	let verified_until = call(
+		provider,
+		address_book,
+		&IAddressBook::addressVerifiedUntilCall { account: wallet },
+	)
+	.await?
+	._0;
+
+	let verified_until =
+		UNIX_EPOCH + Duration::from_secs(verified_until.try_into().unwrap_or(u64::MAX));
+
+	Ok((verified_until > SystemTime::now()).then_some(VerifiedUntil(verified_until)))
+}
+
+/// Performs a read-only call to the contract deployed at `to`, decoding its return value.
+async fn call<P, T, C>(provider: &P, to: Address, call: &C) -> Result<C::Return, Error>
+where
+	P: Provider<T>,
+	T: Transport + Clone,
+	C: SolCall + Sync,
+{
+	let data = provider
+		.call(
+			&TransactionRequest::default()
+				.to(to)
+				.input(call.abi_encode().into()),
+		)
+		.await?;
+
+	Ok(C::abi_decode_returns(&data, true)?)
+}
+
+/// Builds the calldata for `IWorldID.verifyProof`, rejecting proofs that can't be verified on-chain.
+fn verify_proof_calldata<V: Signal>(
+	proof: &Proof,
+	app_id: &AppId,
+	action: &Action,
+	signal: &V,
+) -> Result<Vec<u8>, Error> {
+	if proof.verification_level != VerificationLevel::Orb {
+		return Err(Error::UnsupportedVerificationLevel(
+			proof.verification_level,
+		));
+	}
+
+	Ok(proof
+		.to_verify_proof_call(app_id, action, signal)
+		.map_err(|error| Error::MalformedProof(error.to_string()))?
+		.abi_encode())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixtures, hashing::encode_signal};
+	use alloy_provider::ProviderBuilder;
+	use alloy_sol_types::SolError;
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{matchers::method, Mock, MockServer, Request, ResponseTemplate};
+
+	const ADDRESS_BOOK: Address = Address::repeat_byte(0x22);
+	/// A root of the identity tree that was superseded at 2024-09-01T12:00:00Z.
+	const SUPERSEDED_AT: u64 = 1_725_192_000;
+
+	/// Answers `eth_call`s with `respond`, called with the function selector and the encoded arguments of each call.
+	async fn mock_rpc(respond: fn(&str, &str) -> String) -> MockServer {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.respond_with(move |request: &Request| {
+				let body: serde_json::Value = request.body_json().unwrap();
+				let tx = &body["params"][0];
+				let input = tx["input"]
+					.as_str()
+					.or_else(|| tx["data"].as_str())
+					.unwrap();
+				let (selector, arguments) = input.split_at(10);
+
+				ResponseTemplate::new(200).set_body_json(serde_json::json!({
+					"jsonrpc": "2.0",
+					"id": body["id"],
+					"result": respond(selector, arguments),
+				}))
+			})
+			.mount(&server)
+			.await;
+
+		server
+	}
+
+	/// Responses recorded from the identity manager, where the latest root is `0xabcd` and `0x1234` was superseded at `SUPERSEDED_AT`.
+	async fn identity_manager() -> MockServer {
+		mock_rpc(|selector, arguments| match selector {
+			// latestRoot()
+			"0xd7b0fef1" => format!("0x{:064x}", 0xabcd),
+			// rootHistory(uint256)
+			"0xf1c621ee" if arguments == format!("{:064x}", 0x1234) => {
+				format!("0x{SUPERSEDED_AT:064x}")
+			},
+			_ => format!("0x{:064x}", 0),
+		})
+		.await
+	}
+
+	#[test]
+	fn test_verify_proof_calldata() {
+		let proof = fixtures::proof();
+		let decoded = proof.decode().unwrap();
+
+		let calldata = verify_proof_calldata(
+			&proof,
+			&AppId::from_str("app_123").unwrap(),
+			&Action::from_str("vote_1").unwrap(),
+			&"test",
+		)
+		.unwrap();
+
+		let call = IWorldID::verifyProofCall::abi_decode(&calldata, true).unwrap();
+		assert_eq!(call.root, decoded.merkle_root);
+		assert_eq!(call.groupId, U256::from(1));
+		assert_eq!(call.signalHash, encode_signal(&"test"));
+		assert_eq!(call.nullifierHash, decoded.nullifier_hash);
+		assert_eq!(
+			format!("{:#066x}", call.externalNullifierHash),
+			"0x00feadf361cdc46fb064c4ffa1dc77a7675e6cdc5d934ff65e5722e8bfbcadd3"
+		);
+		assert_eq!(call.proof, decoded.proof);
+	}
+
+	#[test]
+	fn test_verify_proof_calldata_rejects_device() {
+		let error = verify_proof_calldata(
+			&Proof {
+				verification_level: VerificationLevel::Device,
+				..fixtures::proof()
+			},
+			&AppId::from_str("app_123").unwrap(),
+			&Action::from_str("vote_1").unwrap(),
+			&"",
+		)
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::UnsupportedVerificationLevel(VerificationLevel::Device)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_verify_proof_onchain_rejects_invalid_action() {
+		let server = MockServer::start().await;
+		let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+
+		let error = verify_proof_onchain(
+			&provider,
+			Network::Ethereum,
+			&fixtures::proof(),
+			&AppId::from_str("app_123").unwrap(),
+			"vote 1",
+			"",
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::InvalidAction(ActionError::InvalidCharacter(' '))
+		));
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_check_root_age() {
+		let server = identity_manager().await;
+		let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+
+		assert_eq!(
+			check_root_age(&provider, Network::Ethereum, MerkleRoot(U256::from(0xabcd)))
+				.await
+				.unwrap(),
+			RootInfo {
+				root: MerkleRoot(U256::from(0xabcd)),
+				superseded_at: None,
+				is_latest: true,
+			}
+		);
+		assert_eq!(
+			check_root_age(&provider, Network::Ethereum, MerkleRoot(U256::from(0x1234)))
+				.await
+				.unwrap(),
+			RootInfo {
+				root: MerkleRoot(U256::from(0x1234)),
+				superseded_at: Some(UNIX_EPOCH + Duration::from_secs(SUPERSEDED_AT)),
+				is_latest: false,
+			}
+		);
+		assert!(matches!(
+			check_root_age(&provider, Network::Ethereum, MerkleRoot(U256::from(0x5678))).await,
+			Err(Error::NonExistentRoot)
+		));
+		assert!(matches!(
+			check_root_age(
+				&provider,
+				Network::WorldChain,
+				MerkleRoot(U256::from(0xabcd))
+			)
+			.await,
+			Err(Error::UnsupportedNetwork(Network::WorldChain))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_max_root_age() {
+		let server = identity_manager().await;
+		let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+		let policy = MaxRootAge(Duration::from_secs(3600));
+
+		policy
+			.enforce(&provider, Network::Ethereum, MerkleRoot(U256::from(0xabcd)))
+			.await
+			.unwrap();
+		assert!(matches!(
+			policy
+				.enforce(&provider, Network::Ethereum, MerkleRoot(U256::from(0x1234)))
+				.await,
+			Err(Error::RootTooOld { max_age, .. }) if max_age == Duration::from_secs(3600)
+		));
+
+		let recent = RootInfo {
+			root: MerkleRoot(U256::from(0x1234)),
+			superseded_at: Some(SystemTime::now() - Duration::from_secs(60)),
+			is_latest: false,
+		};
+		policy.check(&recent).unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_is_address_verified() {
+		// addressVerifiedUntil(address), where 0x33.. is verified until 2100, 0x44.. expired in 2024 and any other wallet was never verified.
+		let server = mock_rpc(|selector, arguments| {
+			assert_eq!(selector, "0x2bc91fed");
+
+			match &arguments[24..] {
+				account if account == "33".repeat(20) => format!("0x{:064x}", 4_102_444_800_u64),
+				account if account == "44".repeat(20) => format!("0x{SUPERSEDED_AT:064x}"),
+				_ => format!("0x{:064x}", 0),
+			}
+		})
+		.await;
+		let provider = ProviderBuilder::new().on_http(Url::parse(&server.uri()).unwrap());
+
+		assert_eq!(
+			is_address_verified(&provider, ADDRESS_BOOK, Address::repeat_byte(0x33))
+				.await
+				.unwrap(),
+			Some(VerifiedUntil(
+				UNIX_EPOCH + Duration::from_secs(4_102_444_800)
+			))
+		);
+		assert_eq!(
+			is_address_verified(&provider, ADDRESS_BOOK, Address::repeat_byte(0x44))
+				.await
+				.unwrap(),
+			None
+		);
+		assert_eq!(
+			is_address_verified(&provider, ADDRESS_BOOK, Address::repeat_byte(0x55))
+				.await
+				.unwrap(),
+			None
+		);
+	}
+
+	#[test]
+	fn test_revert_mapping() {
+		assert!(matches!(
+			Error::from_revert(IWorldID::ProofInvalid {}.abi_encode().into()),
+			Error::ProofInvalid
+		));
+		assert!(matches!(
+			Error::from_revert(IWorldID::ExpiredRoot {}.abi_encode().into()),
+			Error::ExpiredRoot
+		));
+		assert!(matches!(
+			Error::from_revert(IWorldID::NonExistentRoot {}.abi_encode().into()),
+			Error::NonExistentRoot
+		));
+		assert!(matches!(
+			Error::from_revert(IWorldID::NoSuchGroup { groupId: U256::from(2) }.abi_encode().into()),
+			Error::NoSuchGroup(group) if group == U256::from(2)
+		));
+		assert!(matches!(
+			Error::from_revert(Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef])),
+			Error::Reverted(_)
+		));
+	}
+}
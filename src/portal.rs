@@ -0,0 +1,316 @@
+//! Manage apps in the Developer Portal, authenticated with an API key.
+
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	session::AppId,
+	verify::{self, send_request, ActionMetadata, ApiKey, ErrorResponse, VerifyConfig},
+};
+
+/// An error when managing an app in the Developer Portal.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("an action named {0} already exists")]
+	DuplicateAction(String),
+
+	#[error("the API key is invalid or not allowed to manage this app")]
+	InvalidApiKey,
+
+	#[error("app not found: {0}")]
+	AppNotFound(String),
+
+	#[error("the request was rejected: {0}")]
+	Rejected(#[source] ErrorResponse),
+
+	#[error(transparent)]
+	Request(verify::Error),
+}
+
+impl From<verify::Error> for Error {
+	fn from(error: verify::Error) -> Self {
+		match error {
+			verify::Error::Verification(response) => Self::Rejected(response),
+			verify::Error::UnexpectedResponse {
+				status: StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN,
+				..
+			} => Self::InvalidApiKey,
+			error => Self::Request(error),
+		}
+	}
+}
+
+/// A new Incognito Action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreateAction {
+	/// The action identifier, as passed to `Session::new` and `verify_proof`.
+	pub action: String,
+	/// The human-readable name of the action.
+	pub name: String,
+	/// The description of the action, shown to users in the World App.
+	pub description: String,
+	/// The maximum number of times a user can verify for this action. `0` means unlimited.
+	pub max_verifications: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActionList {
+	actions: Vec<ActionMetadata>,
+}
+
+/// Create an Incognito Action for the app.
+///
+/// # Errors
+///
+/// Errors if the action already exists (`Error::DuplicateAction`), if the API key is invalid (`Error::InvalidApiKey`), or if the request fails.
+pub async fn create_action(
+	api_key: ApiKey,
+	app_id: &AppId,
+	action: &CreateAction,
+) -> Result<ActionMetadata, Error> {
+	create_action_with_config(app_id, action, &authenticated(api_key)).await
+}
+
+/// Create an Incognito Action for the app, with a custom configuration. The configuration must have an API key.
+///
+/// # Errors
+///
+/// Errors if the action already exists (`Error::DuplicateAction`), if the API key is missing or invalid, or if the request fails.
+pub async fn create_action_with_config(
+	app_id: &AppId,
+	action: &CreateAction,
+	config: &VerifyConfig,
+) -> Result<ActionMetadata, Error> {
+	require_api_key(config)?;
+
+	let response = send_request(
+		config,
+		Method::POST,
+		&format!("/api/v2/apps/{}/actions", app_id.0),
+		Some(action),
+	)
+	.await
+	.map_err(|error| match error {
+		verify::Error::UnexpectedResponse {
+			status: StatusCode::CONFLICT,
+			..
+		} => Error::DuplicateAction(action.action.clone()),
+		verify::Error::Verification(response)
+			if matches!(
+				response.code.as_str(),
+				"already_exists" | "duplicate_action"
+			) =>
+		{
+			Error::DuplicateAction(action.action.clone())
+		},
+		error => map_app_error(error, app_id),
+	})?;
+
+	Ok(response.json().await.map_err(verify::Error::from)?)
+}
+
+/// List the Incognito Actions of the app.
+///
+/// # Errors
+///
+/// Errors if the app doesn't exist (`Error::AppNotFound`), if the API key is invalid (`Error::InvalidApiKey`), or if the request fails.
+pub async fn list_actions(api_key: ApiKey, app_id: &AppId) -> Result<Vec<ActionMetadata>, Error> {
+	list_actions_with_config(app_id, &authenticated(api_key)).await
+}
+
+/// List the Incognito Actions of the app, with a custom configuration. The configuration must have an API key.
+///
+/// # Errors
+///
+/// Errors if the app doesn't exist (`Error::AppNotFound`), if the API key is missing or invalid, or if the request fails.
+pub async fn list_actions_with_config(
+	app_id: &AppId,
+	config: &VerifyConfig,
+) -> Result<Vec<ActionMetadata>, Error> {
+	require_api_key(config)?;
+
+	let response = send_request::<()>(
+		config,
+		Method::GET,
+		&format!("/api/v2/apps/{}/actions", app_id.0),
+		None,
+	)
+	.await
+	.map_err(|error| map_app_error(error, app_id))?;
+
+	let list: ActionList = response.json().await.map_err(verify::Error::from)?;
+
+	Ok(list.actions)
+}
+
+pub(crate) fn authenticated(api_key: ApiKey) -> VerifyConfig {
+	VerifyConfig {
+		api_key: Some(api_key),
+		..VerifyConfig::default()
+	}
+}
+
+pub(crate) const fn require_api_key(config: &VerifyConfig) -> Result<(), Error> {
+	if config.api_key.is_none() {
+		return Err(Error::Request(verify::Error::MissingField("api_key")));
+	}
+
+	Ok(())
+}
+
+fn map_app_error(error: verify::Error, app_id: &AppId) -> Error {
+	match error {
+		verify::Error::UnexpectedResponse {
+			status: StatusCode::NOT_FOUND,
+			..
+		} => Error::AppNotFound(app_id.0.clone()),
+		error => error.into(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::verify::ActionStatus;
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{body_json, header, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn test_config(server: &MockServer) -> VerifyConfig {
+		VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			api_key: Some(ApiKey::from_str("api_secret").unwrap()),
+			..VerifyConfig::default()
+		}
+	}
+
+	fn test_action() -> CreateAction {
+		CreateAction {
+			action: "vote_1".to_string(),
+			name: "Vote".to_string(),
+			description: "Cast your vote".to_string(),
+			max_verifications: 1,
+		}
+	}
+
+	fn action_json() -> serde_json::Value {
+		serde_json::json!({
+			"action": "vote_1",
+			"name": "Vote",
+			"description": "Cast your vote",
+			"max_verifications": 1,
+			"status": "active",
+		})
+	}
+
+	#[tokio::test]
+	async fn test_create_action() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/apps/app_123/actions"))
+			.and(header("authorization", "Bearer api_secret"))
+			.and(body_json(serde_json::json!({
+				"action": "vote_1",
+				"name": "Vote",
+				"description": "Cast your vote",
+				"max_verifications": 1,
+			})))
+			.respond_with(ResponseTemplate::new(201).set_body_json(action_json()))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let action = create_action_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			&test_action(),
+			&test_config(&server),
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(action.action, "vote_1");
+		assert_eq!(action.status, ActionStatus::Active);
+	}
+
+	#[tokio::test]
+	async fn test_create_duplicate_action() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/apps/app_123/actions"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "already_exists",
+				"detail": "This action already exists.",
+				"attribute": "action",
+			})))
+			.mount(&server)
+			.await;
+
+		let error = create_action_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			&test_action(),
+			&test_config(&server),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(error, Error::DuplicateAction(action) if action == "vote_1"));
+	}
+
+	#[tokio::test]
+	async fn test_invalid_api_key() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/api/v2/apps/app_123/actions"))
+			.respond_with(ResponseTemplate::new(401))
+			.mount(&server)
+			.await;
+
+		let error =
+			list_actions_with_config(&AppId::from_str("app_123").unwrap(), &test_config(&server))
+				.await
+				.unwrap_err();
+
+		assert!(matches!(error, Error::InvalidApiKey));
+	}
+
+	#[tokio::test]
+	async fn test_missing_api_key() {
+		let error = list_actions_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			&VerifyConfig::default(),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::Request(verify::Error::MissingField("api_key"))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_list_actions() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/api/v2/apps/app_123/actions"))
+			.and(header("authorization", "Bearer api_secret"))
+			.respond_with(
+				ResponseTemplate::new(200)
+					.set_body_json(serde_json::json!({ "actions": [action_json()] })),
+			)
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let actions =
+			list_actions_with_config(&AppId::from_str("app_123").unwrap(), &test_config(&server))
+				.await
+				.unwrap();
+
+		assert_eq!(actions.len(), 1);
+		assert_eq!(actions[0].name, "Vote");
+	}
+}
@@ -0,0 +1,542 @@
+use alloc::{
+	format,
+	string::{String, ToString},
+	vec::Vec,
+};
+use core::{fmt::Display, str::FromStr};
+use ruint::aliases::U256;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+	hex::{self, HexError},
+	protocol::PROOF_WORDS,
+	session::{CredentialType, VerificationLevel},
+	Proof,
+};
+
+/// The length of the proof when ABI-encoded as `uint256[8]`.
+const PROOF_LEN: usize = PROOF_WORDS * 32;
+
+/// A proof with its fields decoded into the values expected by the World ID contracts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedProof {
+	/// The Groth16 proof, in the layout expected by `IWorldID.verifyProof`.
+	pub proof: [U256; PROOF_WORDS],
+	pub merkle_root: U256,
+	pub nullifier_hash: U256,
+	pub verification_level: VerificationLevel,
+}
+
+/// An error when decoding the fields of a `Proof`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+	#[error("{field} is not a 0x-prefixed hex string")]
+	InvalidHex { field: &'static str },
+
+	#[error("{field} should be {expected} bytes long, got {actual}")]
+	InvalidLength {
+		field: &'static str,
+		expected: usize,
+		actual: usize,
+	},
+}
+
+impl DecodeError {
+	const fn from_hex(field: &'static str, error: HexError) -> Self {
+		match error {
+			HexError::InvalidLength { expected, actual } => Self::InvalidLength {
+				field,
+				expected,
+				actual,
+			},
+			HexError::MissingPrefix
+			| HexError::InvalidCharacter { .. }
+			| HexError::OddLength(_) => Self::InvalidHex { field },
+		}
+	}
+}
+
+/// A nullifier hash, compared by value regardless of the case or padding of its hex representation.
+///
+/// Displays and serializes as a lowercase, 0x-prefixed, zero-padded hex string, and parses from any 0x-prefixed hex string that fits in 32 bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NullifierHash(pub U256);
+
+impl FromStr for NullifierHash {
+	type Err = DecodeError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let field = "nullifier_hash";
+		let hex = s
+			.strip_prefix("0x")
+			.or_else(|| s.strip_prefix("0X"))
+			.filter(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()))
+			.ok_or(DecodeError::InvalidHex { field })?
+			.trim_start_matches('0');
+
+		if hex.len() > 64 {
+			return Err(DecodeError::InvalidLength {
+				field,
+				expected: 32,
+				actual: hex.len().div_ceil(2),
+			});
+		}
+
+		if hex.is_empty() {
+			return Ok(Self(U256::ZERO));
+		}
+
+		Ok(Self(
+			U256::from_str_radix(hex, 16).map_err(|_| DecodeError::InvalidHex { field })?,
+		))
+	}
+}
+
+impl Display for NullifierHash {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(f, "0x{:064x}", self.0)
+	}
+}
+
+impl From<U256> for NullifierHash {
+	fn from(value: U256) -> Self {
+		Self(value)
+	}
+}
+
+impl From<NullifierHash> for U256 {
+	fn from(value: NullifierHash) -> Self {
+		value.0
+	}
+}
+
+impl Serialize for NullifierHash {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for NullifierHash {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Self::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+	}
+}
+
+/// The root of the World ID identity tree a proof was generated against.
+///
+/// Parses strictly from a 0x-prefixed, 32-byte hex string, and displays and serializes as lowercase hex.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MerkleRoot(pub U256);
+
+impl FromStr for MerkleRoot {
+	type Err = DecodeError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		hex::decode_fixed::<32>(s)
+			.map(|bytes| Self(U256::from_be_bytes(bytes)))
+			.map_err(|error| DecodeError::from_hex("merkle_root", error))
+	}
+}
+
+impl Display for MerkleRoot {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(f, "0x{:064x}", self.0)
+	}
+}
+
+impl From<U256> for MerkleRoot {
+	fn from(value: U256) -> Self {
+		Self(value)
+	}
+}
+
+impl From<MerkleRoot> for U256 {
+	fn from(value: MerkleRoot) -> Self {
+		value.0
+	}
+}
+
+impl Serialize for MerkleRoot {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for MerkleRoot {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Self::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+	}
+}
+
+/// The wire format of a `Proof`, as sent by IDKit JS (`verification_level`) or by older widgets and the bridge (`credential_type`).
+#[derive(Deserialize)]
+struct RawProof {
+	proof: String,
+	merkle_root: String,
+	nullifier_hash: String,
+	verification_level: Option<VerificationLevel>,
+	credential_type: Option<CredentialType>,
+}
+
+impl<'de> Deserialize<'de> for Proof {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let raw = RawProof::deserialize(deserializer)?;
+
+		Ok(Self {
+			proof: raw.proof,
+			merkle_root: raw.merkle_root,
+			nullifier_hash: raw.nullifier_hash,
+			verification_level: raw
+				.verification_level
+				.or_else(|| raw.credential_type.map(Into::into))
+				.ok_or_else(|| D::Error::missing_field("verification_level"))?,
+		})
+	}
+}
+
+impl Proof {
+	/// The merkle root of the proof.
+	///
+	/// # Errors
+	///
+	/// Errors if the merkle root isn't a 0x-prefixed, 32-byte hex string.
+	pub fn merkle_root(&self) -> Result<MerkleRoot, DecodeError> {
+		self.merkle_root.parse()
+	}
+
+	/// The nullifier hash of the proof, parsed into a value that can be compared regardless of its hex representation.
+	///
+	/// # Errors
+	///
+	/// Errors if the nullifier hash isn't a 0x-prefixed hex string that fits in 32 bytes.
+	pub fn nullifier_hash(&self) -> Result<NullifierHash, DecodeError> {
+		self.nullifier_hash.parse()
+	}
+
+	/// Decodes the hex-encoded fields of the proof, so they can be passed to a contract.
+	///
+	/// The proof must be ABI-encoded as a `uint256[8]`, the only encoding the Developer Portal and `Proof::validate_format` accept.
+	///
+	/// # Errors
+	///
+	/// Errors if a field isn't valid hex (`DecodeError::InvalidHex`) or has the wrong length (`DecodeError::InvalidLength`).
+	pub fn decode(&self) -> Result<DecodedProof, DecodeError> {
+		let words = decode_words("proof", &self.proof)?;
+		let proof: [U256; PROOF_WORDS] =
+			words
+				.try_into()
+				.map_err(|words: Vec<U256>| DecodeError::InvalidLength {
+					field: "proof",
+					expected: PROOF_LEN,
+					actual: words.len() * 32,
+				})?;
+
+		Ok(DecodedProof {
+			proof,
+			merkle_root: decode_word("merkle_root", &self.merkle_root)?,
+			nullifier_hash: decode_word("nullifier_hash", &self.nullifier_hash)?,
+			verification_level: self.verification_level,
+		})
+	}
+}
+
+impl DecodedProof {
+	/// Encodes the proof back into its hex representation, with the proof ABI-encoded as a `uint256[8]`.
+	#[must_use]
+	pub fn encode(&self) -> Proof {
+		Proof {
+			proof: self
+				.proof
+				.iter()
+				.fold("0x".to_string(), |acc, word| acc + &format!("{word:064x}")),
+			merkle_root: format!("0x{:064x}", self.merkle_root),
+			nullifier_hash: format!("0x{:064x}", self.nullifier_hash),
+			verification_level: self.verification_level,
+		}
+	}
+}
+
+fn decode_word(field: &'static str, value: &str) -> Result<U256, DecodeError> {
+	match decode_words(field, value)?[..] {
+		[word] => Ok(word),
+		ref words => Err(DecodeError::InvalidLength {
+			field,
+			expected: 32,
+			actual: words.len() * 32,
+		}),
+	}
+}
+
+/// Splits a 0x-prefixed hex string into 32-byte words.
+fn decode_words(field: &'static str, value: &str) -> Result<Vec<U256>, DecodeError> {
+	let bytes = hex::decode_prefixed(value).map_err(|error| DecodeError::from_hex(field, error))?;
+
+	if bytes.len() % 32 != 0 {
+		return Err(DecodeError::InvalidLength {
+			field,
+			expected: if field == "proof" { PROOF_LEN } else { 32 },
+			actual: bytes.len(),
+		});
+	}
+
+	Ok(bytes.chunks(32).map(U256::from_be_slice).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashSet;
+
+	/// A proof payload in the format returned by World App, with the curve generators as proof points.
+	fn fixture() -> Proof {
+		Proof {
+			proof: concat!(
+				"0x",
+				"0000000000000000000000000000000000000000000000000000000000000001",
+				"0000000000000000000000000000000000000000000000000000000000000002",
+				"198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+				"1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+				"090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+				"12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+				"0000000000000000000000000000000000000000000000000000000000000001",
+				"30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+			)
+			.to_string(),
+			merkle_root: "0x2264a66d162d7893e12ea8e3c072c51e785bc085ad655f64c10c1a61e00f0bc2"
+				.to_string(),
+			nullifier_hash: "0x2bf8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8"
+				.to_string(),
+			verification_level: VerificationLevel::Orb,
+		}
+	}
+
+	#[test]
+	fn test_decode() {
+		let decoded = fixture().decode().unwrap();
+
+		assert_eq!(decoded.proof[0], U256::from(1));
+		assert_eq!(decoded.proof[1], U256::from(2));
+		assert_eq!(
+			format!("{:x}", decoded.proof[7]),
+			"30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45"
+		);
+		assert_eq!(
+			format!("0x{:064x}", decoded.nullifier_hash),
+			fixture().nullifier_hash
+		);
+		assert_eq!(decoded.encode(), fixture());
+	}
+
+	#[test]
+	fn test_decode_rejects_dynamic_array() {
+		let proof = fixture();
+		let dynamic = Proof {
+			proof: format!("0x{:064x}{:064x}{}", 32, 8, &proof.proof[2..]),
+			..proof
+		};
+
+		assert_eq!(
+			dynamic.decode(),
+			Err(DecodeError::InvalidLength {
+				field: "proof",
+				expected: 256,
+				actual: 320
+			})
+		);
+	}
+
+	#[test]
+	fn test_nullifier_hash() {
+		let canonical = NullifierHash::from_str(
+			"0x00f8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8",
+		)
+		.unwrap();
+
+		let equivalents = [
+			"0x00F8406809DCEFB1486DADC96C0A897DB9BAB002053054CF64272DB512C6FBD8",
+			"0xf8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8",
+			"0X0000f8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8",
+		]
+		.map(|hex| NullifierHash::from_str(hex).unwrap());
+
+		assert_eq!(HashSet::from(equivalents), HashSet::from([canonical]));
+
+		assert_eq!(
+			canonical.to_string(),
+			"0x00f8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8"
+		);
+		assert_eq!(
+			NullifierHash::from_str("0x0").unwrap(),
+			NullifierHash(U256::ZERO)
+		);
+
+		for invalid in ["", "0x", "f840", "0xzz", &format!("0x1{}", "0".repeat(64))] {
+			assert!(NullifierHash::from_str(invalid).is_err(), "{invalid}");
+		}
+	}
+
+	#[test]
+	fn test_nullifier_hash_serde() {
+		let nullifier_hash: NullifierHash = serde_json::from_str("\"0xABC\"").unwrap();
+
+		assert_eq!(nullifier_hash, NullifierHash(U256::from(0xabc)));
+		assert_eq!(
+			serde_json::to_string(&nullifier_hash).unwrap(),
+			format!("\"0x{:064x}\"", 0xabc)
+		);
+		assert_eq!(
+			fixture().nullifier_hash().unwrap().to_string(),
+			fixture().nullifier_hash
+		);
+	}
+
+	#[test]
+	fn test_merkle_root() {
+		let root = fixture().merkle_root().unwrap();
+		assert_eq!(root.to_string(), fixture().merkle_root);
+
+		assert_eq!(
+			MerkleRoot::from_str(
+				"0x2264A66D162D7893E12EA8E3C072C51E785BC085AD655F64C10C1A61E00F0BC2"
+			),
+			Ok(root)
+		);
+		assert_eq!(
+			MerkleRoot::from_str(
+				"2264a66d162d7893e12ea8e3c072c51e785bc085ad655f64c10c1a61e00f0bc2"
+			),
+			Err(DecodeError::InvalidHex {
+				field: "merkle_root"
+			})
+		);
+		assert_eq!(
+			MerkleRoot::from_str("0x2264a66d"),
+			Err(DecodeError::InvalidLength {
+				field: "merkle_root",
+				expected: 32,
+				actual: 4
+			})
+		);
+		assert_eq!(
+			MerkleRoot::from_str(&format!("{}00", fixture().merkle_root)),
+			Err(DecodeError::InvalidLength {
+				field: "merkle_root",
+				expected: 32,
+				actual: 33
+			})
+		);
+	}
+
+	#[test]
+	fn test_merkle_root_serde() {
+		let root = fixture().merkle_root().unwrap();
+		let json = serde_json::to_string(&root).unwrap();
+
+		assert_eq!(json, format!("\"{}\"", fixture().merkle_root));
+		assert_eq!(serde_json::from_str::<MerkleRoot>(&json).unwrap(), root);
+		assert!(serde_json::from_str::<MerkleRoot>("\"0xabc\"").is_err());
+	}
+
+	#[test]
+	fn test_deserialize_verification_level_or_credential_type() {
+		let proof = |fields: serde_json::Value| {
+			let mut json = serde_json::json!({
+				"proof": "0x1",
+				"merkle_root": "0x2",
+				"nullifier_hash": "0x3",
+			});
+			json.as_object_mut()
+				.unwrap()
+				.extend(fields.as_object().unwrap().clone());
+
+			serde_json::from_value::<Proof>(json).map(|proof| proof.verification_level)
+		};
+
+		for level in ["orb", "device"] {
+			let expected: VerificationLevel = serde_json::from_value(level.into()).unwrap();
+
+			assert_eq!(
+				proof(serde_json::json!({ "verification_level": level })).unwrap(),
+				expected
+			);
+			assert_eq!(
+				proof(serde_json::json!({ "credential_type": level })).unwrap(),
+				expected
+			);
+		}
+
+		assert_eq!(
+			proof(serde_json::json!({
+				"verification_level": "device",
+				"credential_type": "orb",
+			}))
+			.unwrap(),
+			VerificationLevel::Device
+		);
+		assert!(proof(serde_json::json!({})).is_err());
+		assert_eq!(
+			proof(serde_json::json!({ "credential_type": "phone" })).unwrap(),
+			VerificationLevel::Device
+		);
+		assert!(proof(serde_json::json!({ "verification_level": "phone" })).is_err());
+	}
+
+	#[test]
+	fn test_serialize_verification_level_only() {
+		let json = serde_json::to_value(fixture()).unwrap();
+
+		assert_eq!(json["verification_level"], "orb");
+		assert!(json.get("credential_type").is_none());
+		assert_eq!(serde_json::from_value::<Proof>(json).unwrap(), fixture());
+	}
+
+	#[test]
+	fn test_decode_errors() {
+		let proof = fixture();
+
+		assert_eq!(
+			Proof {
+				merkle_root: proof.merkle_root.replace("0x", "0X"),
+				..proof.clone()
+			}
+			.decode(),
+			Err(DecodeError::InvalidHex {
+				field: "merkle_root"
+			})
+		);
+		assert_eq!(
+			Proof {
+				nullifier_hash: proof.nullifier_hash.replace('b', "g"),
+				..proof.clone()
+			}
+			.decode(),
+			Err(DecodeError::InvalidHex {
+				field: "nullifier_hash"
+			})
+		);
+		assert_eq!(
+			Proof {
+				proof: proof.proof[..proof.proof.len() - 64].to_string(),
+				..proof.clone()
+			}
+			.decode(),
+			Err(DecodeError::InvalidLength {
+				field: "proof",
+				expected: 256,
+				actual: 224
+			})
+		);
+		assert_eq!(
+			Proof {
+				merkle_root: format!("{}00", proof.merkle_root),
+				..proof
+			}
+			.decode(),
+			Err(DecodeError::InvalidLength {
+				field: "merkle_root",
+				expected: 32,
+				actual: 33
+			})
+		);
+	}
+}
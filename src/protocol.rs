@@ -0,0 +1,67 @@
+//! Constants of the World ID protocol, for code that builds circuit inputs or contract calls itself.
+
+use ruint::aliases::U256;
+
+use crate::session::CredentialType;
+
+/// The group id of Orb-verified identities, the only group that can be verified on-chain.
+pub const ORB_GROUP_ID: U256 = U256::from_limbs([1, 0, 0, 0]);
+
+/// The group id of device-verified identities. Device proofs are only verifiable through the Developer Portal, so no World ID contract accepts this group.
+pub const DEVICE_GROUP_ID: U256 = U256::ZERO;
+
+/// The depth of the Merkle tree of identities proofs are generated against.
+pub const TREE_DEPTH: usize = 30;
+
+/// The number of `uint256` words in a Groth16 proof, as passed to `IWorldID.verifyProof`.
+pub const PROOF_WORDS: usize = 8;
+
+/// The order of the BN254 scalar field. Every public input of the circuit, including signal hashes and external nullifiers, is below it.
+pub const SNARK_SCALAR_FIELD: U256 = U256::from_limbs([
+	0x43e1_f593_f000_0001,
+	0x2833_e848_79b9_7091,
+	0xb850_45b6_8181_585d,
+	0x3064_4e72_e131_a029,
+]);
+
+/// The group id identities with the given credential are inserted into.
+#[must_use]
+pub const fn group_id(credential_type: CredentialType) -> U256 {
+	// Kept exhaustive on purpose, so a new credential type can't compile without a group id.
+	match credential_type {
+		CredentialType::Orb => ORB_GROUP_ID,
+		CredentialType::Device => DEVICE_GROUP_ID,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::hashing::hash_to_field;
+	use core::str::FromStr;
+
+	#[test]
+	fn test_group_id() {
+		assert_eq!(group_id(CredentialType::Orb), U256::from(1));
+		assert_eq!(group_id(CredentialType::Device), U256::ZERO);
+	}
+
+	#[test]
+	fn test_constants() {
+		assert_eq!(TREE_DEPTH, 30);
+		assert_eq!(PROOF_WORDS, 8);
+		assert_eq!(
+			SNARK_SCALAR_FIELD,
+			U256::from_str(
+				"21888242871839275222246405745257275088548364400416034343698204186575808495617"
+			)
+			.unwrap()
+		);
+	}
+
+	#[test]
+	fn test_hash_to_field_is_in_field() {
+		assert!(hash_to_field(&[0xff; 64]) < SNARK_SCALAR_FIELD);
+		assert!(U256::MAX >> 8 < SNARK_SCALAR_FIELD);
+	}
+}
@@ -0,0 +1,164 @@
+use std::{borrow::Cow, str::FromStr};
+use url::form_urlencoded;
+
+use crate::{
+	session::{CredentialType, VerificationLevel},
+	Proof,
+};
+
+/// An error when reading a proof from URL query parameters.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum QueryError {
+	#[error("missing query parameter {0}")]
+	MissingParameter(&'static str),
+
+	#[error("query parameter {0} was provided more than once")]
+	DuplicateParameter(&'static str),
+
+	#[error("invalid verification level {0:?}")]
+	InvalidVerificationLevel(String),
+}
+
+impl Proof {
+	/// Encodes the proof as URL query parameters, the way IDKit JS appends the success result to a redirect URL.
+	#[must_use]
+	pub fn to_query_string(&self) -> String {
+		form_urlencoded::Serializer::new(String::new())
+			.append_pair("proof", &self.proof)
+			.append_pair("merkle_root", &self.merkle_root)
+			.append_pair("nullifier_hash", &self.nullifier_hash)
+			.append_pair("verification_level", &self.verification_level.to_string())
+			.finish()
+	}
+
+	/// Reads a proof from already percent-decoded URL query parameters, such as the ones returned by `Url::query_pairs`.
+	///
+	/// Parameters can be in any order, and unknown parameters are ignored, so the callback URL can carry its own state. As with JSON, the verification level can also be provided as the legacy `credential_type`.
+	///
+	/// # Errors
+	///
+	/// Errors if a parameter is missing (`QueryError::MissingParameter`) or repeated (`QueryError::DuplicateParameter`), or if the verification level is unknown (`QueryError::InvalidVerificationLevel`).
+	pub fn from_query_pairs<'a>(
+		pairs: impl Iterator<Item = (Cow<'a, str>, Cow<'a, str>)>,
+	) -> Result<Self, QueryError> {
+		let mut proof = None;
+		let mut merkle_root = None;
+		let mut nullifier_hash = None;
+		let mut verification_level = None;
+		let mut credential_type = None;
+
+		for (key, value) in pairs {
+			let (name, slot) = match key.as_ref() {
+				"proof" => ("proof", &mut proof),
+				"merkle_root" => ("merkle_root", &mut merkle_root),
+				"nullifier_hash" => ("nullifier_hash", &mut nullifier_hash),
+				"verification_level" => ("verification_level", &mut verification_level),
+				"credential_type" => ("credential_type", &mut credential_type),
+				_ => continue,
+			};
+
+			if slot.replace(value.into_owned()).is_some() {
+				return Err(QueryError::DuplicateParameter(name));
+			}
+		}
+
+		let verification_level = match (verification_level, credential_type) {
+			(Some(level), _) => parse_level::<VerificationLevel>(level)?,
+			(None, Some(credential_type)) => CredentialType::from_str(&credential_type)
+				.map_err(|_| QueryError::InvalidVerificationLevel(credential_type))?
+				.into(),
+			(None, None) => return Err(QueryError::MissingParameter("verification_level")),
+		};
+
+		Ok(Self {
+			proof: proof.ok_or(QueryError::MissingParameter("proof"))?,
+			merkle_root: merkle_root.ok_or(QueryError::MissingParameter("merkle_root"))?,
+			nullifier_hash: nullifier_hash.ok_or(QueryError::MissingParameter("nullifier_hash"))?,
+			verification_level,
+		})
+	}
+}
+
+fn parse_level<T: serde::de::DeserializeOwned>(value: String) -> Result<T, QueryError> {
+	serde_json::from_value(serde_json::Value::String(value.clone()))
+		.map_err(|_| QueryError::InvalidVerificationLevel(value))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use url::Url;
+
+	#[test]
+	fn test_url_roundtrip() {
+		let mut url = Url::parse("https://example.com/callback?state=abc").unwrap();
+		url.query_pairs_mut().extend_pairs(form_urlencoded::parse(
+			fixtures::proof().to_query_string().as_bytes(),
+		));
+
+		assert_eq!(
+			Proof::from_query_pairs(url.query_pairs()).unwrap(),
+			fixtures::proof()
+		);
+	}
+
+	#[test]
+	fn test_percent_encoding() {
+		let proof = Proof {
+			proof: "0x12 34&=+".to_string(),
+			..fixtures::proof()
+		};
+		let query = proof.to_query_string();
+
+		assert!(query.starts_with("proof=0x12+34%26%3D%2B&"));
+		assert_eq!(
+			Proof::from_query_pairs(form_urlencoded::parse(query.as_bytes())).unwrap(),
+			proof
+		);
+	}
+
+	#[test]
+	fn test_callback_url() {
+		let url = Url::parse(&format!(
+			"https://example.com/callback?nullifier_hash={}&verification_level=orb&state=xyz&merkle_root={}&proof={}",
+			fixtures::proof().nullifier_hash,
+			fixtures::proof().merkle_root,
+			fixtures::proof().proof,
+		))
+		.unwrap();
+
+		assert_eq!(
+			Proof::from_query_pairs(url.query_pairs()).unwrap(),
+			fixtures::proof()
+		);
+	}
+
+	#[test]
+	fn test_errors() {
+		let parse = |query: &str| Proof::from_query_pairs(form_urlencoded::parse(query.as_bytes()));
+
+		assert_eq!(
+			parse("proof=0x1&merkle_root=0x2&verification_level=orb"),
+			Err(QueryError::MissingParameter("nullifier_hash"))
+		);
+		assert_eq!(
+			parse("proof=0x1&merkle_root=0x2&nullifier_hash=0x3"),
+			Err(QueryError::MissingParameter("verification_level"))
+		);
+		assert_eq!(
+			parse("proof=0x1&proof=0x2&merkle_root=0x2&nullifier_hash=0x3&verification_level=orb"),
+			Err(QueryError::DuplicateParameter("proof"))
+		);
+		assert_eq!(
+			parse("proof=0x1&merkle_root=0x2&nullifier_hash=0x3&verification_level=phone"),
+			Err(QueryError::InvalidVerificationLevel("phone".to_string()))
+		);
+		assert_eq!(
+			parse("proof=0x1&merkle_root=0x2&nullifier_hash=0x3&credential_type=device")
+				.unwrap()
+				.verification_level,
+			VerificationLevel::Device
+		);
+	}
+}
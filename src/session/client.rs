@@ -0,0 +1,1016 @@
+use reqwest::StatusCode;
+use ring::rand::{SecureRandom, SystemRandom};
+#[cfg(feature = "verify")]
+use std::sync::Arc;
+use std::{convert::Infallible, fmt::Display};
+use url::Url;
+use uuid::Uuid;
+
+use super::{
+	connect::{ConnectInfo, ConnectUrlOptions, DEFAULT_CONNECT_HOST},
+	crypto::{self, EncryptedPayload},
+	Action, ActionError, AppError, AppId, BridgeProof, BridgeUrl, CredentialType,
+	VerificationLevel,
+};
+#[cfg(feature = "verify")]
+use crate::metrics::{Metrics, SessionMetrics};
+use crate::{
+	hashing::{base64_encode, FieldElement},
+	network::Failure,
+	trace::TraceContext,
+	ErrorKind, Proof, Signal, USER_AGENT,
+};
+
+/// The status of a verification request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum Status {
+	/// Waiting for the World App to retrieve the request
+	WaitingForConnection,
+	/// Waiting for the user to confirm the request
+	AwaitingConfirmation,
+	/// The user has confirmed the request. Contains the proof of verification.
+	Confirmed(Proof),
+	/// The request has failed. Contains details about the failure.
+	Failed(AppError),
+}
+
+impl Status {
+	/// What the user should be told about the request, for UIs that provide their own copy instead of the `Display` one.
+	#[must_use]
+	pub const fn progress_hint(&self) -> ProgressHint {
+		match self {
+			Self::WaitingForConnection => ProgressHint::ScanQrCode,
+			Self::AwaitingConfirmation => ProgressHint::ConfirmInWorldApp,
+			Self::Confirmed(_) => ProgressHint::Verified,
+			Self::Failed(error) => ProgressHint::Failed(*error),
+		}
+	}
+
+	/// The snake case name of the status, like `awaiting_confirmation`.
+	#[cfg(any(feature = "sse", feature = "wasm-bindgen"))]
+	pub(crate) const fn name(&self) -> &'static str {
+		match self {
+			Self::WaitingForConnection => "waiting_for_connection",
+			Self::AwaitingConfirmation => "awaiting_confirmation",
+			Self::Confirmed(_) => "confirmed",
+			Self::Failed(_) => "failed",
+		}
+	}
+}
+
+/// Displays a short message meant for the end user, like "Confirm the request in World App".
+impl Display for Status {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.progress_hint().fmt(f)
+	}
+}
+
+/// What the user should be told about a verification request, without the proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressHint {
+	/// The user should scan the QR code with the World App.
+	ScanQrCode,
+	/// The user should confirm the request in the World App.
+	ConfirmInWorldApp,
+	/// The user has been verified.
+	Verified,
+	/// The request has failed, for the given reason.
+	Failed(AppError),
+}
+
+impl Display for ProgressHint {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ScanQrCode => f.write_str("Waiting for you to scan the QR code"),
+			Self::ConfirmInWorldApp => f.write_str("Confirm the request in World App"),
+			Self::Verified => f.write_str("Verified"),
+			Self::Failed(error) => error.fmt(f),
+		}
+	}
+}
+
+/// The decrypted response of the World App, as relayed by the Wallet Bridge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeResponseOutcome {
+	/// The user has confirmed the request. Contains the proof of verification.
+	Proof(Proof),
+	/// The request has failed. Contains details about the failure.
+	Failed(AppError),
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct BridgeCreateResponse {
+	request_id: Uuid,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct BridgePollResponse {
+	status: String,
+	response: Option<EncryptedPayload>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum BridgeResponse {
+	Error { error_code: AppError },
+	Success(BridgeProof),
+}
+
+/// The verification request a session sends to the World App, before it's encrypted for the Wallet Bridge.
+///
+/// Fields are declared in alphabetical order, which is the order they've always been sent in.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BridgeRequest<'a> {
+	/// The action the user is verifying for.
+	pub action: &'a Action,
+	/// A description of the action, shown to the user.
+	pub action_description: Option<&'a str>,
+	/// The app requesting the verification.
+	pub app_id: &'a AppId,
+	/// The credentials that satisfy `verification_level`.
+	pub credential_types: Vec<CredentialType>,
+	/// The signal hash, as computed by `FieldElement::signal`.
+	pub signal: FieldElement,
+	/// The minimum verification level accepted.
+	pub verification_level: VerificationLevel,
+}
+
+impl<'a> BridgeRequest<'a> {
+	/// Builds the request for a verification of `action`, committing to `signal`.
+	#[must_use]
+	pub fn new<V: Signal + ?Sized>(
+		app_id: &'a AppId,
+		action: &'a Action,
+		action_description: Option<&'a str>,
+		signal: &V,
+		verification_level: VerificationLevel,
+	) -> Self {
+		Self {
+			action,
+			action_description,
+			app_id,
+			credential_types: verification_level.to_credential_types(),
+			signal: FieldElement::signal(signal),
+			verification_level,
+		}
+	}
+}
+
+/// A session with the Wallet Bridge.
+#[derive(Debug)]
+pub struct Session {
+	key: [u8; 32],
+	request_id: Uuid,
+	app_id: AppId,
+	action: Action,
+	bridge_url: BridgeUrl,
+	client: reqwest::Client,
+	trace_context: Option<TraceContext>,
+	#[cfg(feature = "verify")]
+	metrics: Option<SessionMetrics>,
+}
+
+/// An error when interacting with the Wallet Bridge.
+///
+/// New variants may be added in minor releases, match on `kind()` to handle errors by category.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+	#[error("An error occurred when communicating with the Wallet Bridge: {0}")]
+	Bridge(#[source] reqwest::Error),
+
+	#[error("The request to the Wallet Bridge timed out")]
+	Timeout { source: reqwest::Error },
+
+	#[error("Failed to connect to the Wallet Bridge: {source}")]
+	Connect { source: reqwest::Error },
+
+	#[error("Failed to resolve the address of the Wallet Bridge: {source}")]
+	Dns { source: reqwest::Error },
+
+	#[error("The Wallet Bridge responded with an error ({code})")]
+	Status {
+		code: StatusCode,
+		source: reqwest::Error,
+	},
+
+	#[error("An error occurred when encoding or decoding a request or response: {0}")]
+	Json(#[from] serde_json::Error),
+
+	#[error("An error occurred when generating a key, encrypting or decrypting a request or response: {0}")]
+	Encryption(&'static str),
+
+	#[error("An error occurred when base64 encoding or decoding a request or response: {0}")]
+	Base64(#[from] base64::DecodeError),
+
+	#[error("Invalid action provided: {0}")]
+	InvalidAction(#[from] ActionError),
+}
+
+impl Error {
+	/// The category this error belongs to.
+	#[must_use]
+	pub const fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Bridge(_)
+			| Self::Timeout { .. }
+			| Self::Connect { .. }
+			| Self::Dns { .. }
+			| Self::Status { .. } => ErrorKind::Transport,
+			Self::Json(_) | Self::Base64(_) => ErrorKind::Protocol,
+			Self::Encryption(_) => ErrorKind::Crypto,
+			Self::InvalidAction(_) => ErrorKind::Configuration,
+		}
+	}
+}
+
+impl From<Infallible> for Error {
+	fn from(error: Infallible) -> Self {
+		match error {}
+	}
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(error: reqwest::Error) -> Self {
+		match Failure::of(&error) {
+			Failure::Timeout => Self::Timeout { source: error },
+			Failure::Dns => Self::Dns { source: error },
+			Failure::Connect => Self::Connect { source: error },
+			Failure::Status(code) => Self::Status {
+				code,
+				source: error,
+			},
+			Failure::Other => Self::Bridge(error),
+		}
+	}
+}
+
+impl Session {
+	/// Create a new session with the Wallet Bridge.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the action is invalid, if the request to the bridge fails, or if the response from the bridge is malformed.
+	pub async fn new<A, V>(
+		app_id: &AppId,
+		action: A,
+		verification_level: VerificationLevel,
+		bridge_url: BridgeUrl,
+		signal: V,
+		action_description: Option<&str>,
+	) -> Result<Self, Error>
+	where
+		A: TryInto<Action> + Send,
+		Error: From<A::Error>,
+		V: Signal + Send,
+	{
+		Self::with_user_agent(
+			app_id,
+			action,
+			verification_level,
+			bridge_url,
+			signal,
+			action_description,
+			USER_AGENT,
+		)
+		.await
+	}
+
+	/// Create a new session with the Wallet Bridge, identifying every request made by the session with a custom `User-Agent`.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the action is invalid, if the request to the bridge fails, or if the response from the bridge is malformed.
+	pub async fn with_user_agent<A, V>(
+		app_id: &AppId,
+		action: A,
+		verification_level: VerificationLevel,
+		bridge_url: BridgeUrl,
+		signal: V,
+		action_description: Option<&str>,
+		user_agent: &str,
+	) -> Result<Self, Error>
+	where
+		A: TryInto<Action> + Send,
+		Error: From<A::Error>,
+		V: Signal + Send,
+	{
+		Self::create(
+			app_id,
+			action,
+			verification_level,
+			bridge_url,
+			signal,
+			action_description,
+			user_agent,
+			None,
+		)
+		.await
+	}
+
+	/// Create a new session with the Wallet Bridge, propagating the trace context of the caller with every request made by the session. Use `set_trace_context` to change it for later polls.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the action is invalid, if the request to the bridge fails, or if the response from the bridge is malformed.
+	pub async fn with_trace_context<A, V>(
+		app_id: &AppId,
+		action: A,
+		verification_level: VerificationLevel,
+		bridge_url: BridgeUrl,
+		signal: V,
+		action_description: Option<&str>,
+		trace_context: TraceContext,
+	) -> Result<Self, Error>
+	where
+		A: TryInto<Action> + Send,
+		Error: From<A::Error>,
+		V: Signal + Send,
+	{
+		Self::create(
+			app_id,
+			action,
+			verification_level,
+			bridge_url,
+			signal,
+			action_description,
+			USER_AGENT,
+			Some(trace_context),
+		)
+		.await
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	async fn create<A, V>(
+		app_id: &AppId,
+		action: A,
+		verification_level: VerificationLevel,
+		bridge_url: BridgeUrl,
+		signal: V,
+		action_description: Option<&str>,
+		user_agent: &str,
+		trace_context: Option<TraceContext>,
+	) -> Result<Self, Error>
+	where
+		A: TryInto<Action> + Send,
+		Error: From<A::Error>,
+		V: Signal + Send,
+	{
+		let action: Action = action.try_into()?;
+		let client = reqwest::Client::builder().user_agent(user_agent).build()?;
+
+		let (key, iv) = Self::generate_key()?;
+
+		let mut request = client.post(
+			bridge_url
+				.join("/request")
+				.unwrap_or_else(|_| unreachable!()),
+		);
+		for (name, value) in trace_context.iter().flat_map(TraceContext::headers) {
+			request = request.header(name, value);
+		}
+
+		let response = request
+			.json(&Self::encrypt_request(
+				&key,
+				&iv,
+				&BridgeRequest::new(
+					app_id,
+					&action,
+					action_description,
+					&signal,
+					verification_level,
+				),
+			)?)
+			.send()
+			.await?
+			.error_for_status()?
+			.json::<BridgeCreateResponse>()
+			.await?;
+
+		Ok(Self {
+			key,
+			action,
+			client,
+			bridge_url,
+			trace_context,
+			app_id: app_id.clone(),
+			request_id: response.request_id,
+			#[cfg(feature = "verify")]
+			metrics: None,
+		})
+	}
+
+	/// The app the session is verifying the user for.
+	#[must_use]
+	pub const fn app_id(&self) -> &AppId {
+		&self.app_id
+	}
+
+	/// The action the session is verifying the user for.
+	#[must_use]
+	pub const fn action(&self) -> &Action {
+		&self.action
+	}
+
+	/// Reports the session and its polls to `metrics`, labelled with the app id and action. The session counts as started when this is called, and as abandoned if it's dropped before being confirmed or failing.
+	///
+	/// Replacing the metrics of a session ends it as abandoned for the previous ones.
+	#[cfg(feature = "verify")]
+	pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+		self.metrics = Some(SessionMetrics::start(
+			metrics,
+			&self.app_id.0,
+			self.action.as_str(),
+		));
+	}
+
+	/// Propagates the trace context of the caller with the next requests made by the session, e.g. the one of the incoming request that triggered the next poll.
+	pub fn set_trace_context(&mut self, trace_context: Option<TraceContext>) {
+		self.trace_context = trace_context;
+	}
+
+	/// Returns the URL that the user should be directed to in order to connect their World App to the client.
+	#[must_use]
+	pub fn connect_url(&self) -> Url {
+		self.connect_url_with(&ConnectUrlOptions::default())
+	}
+
+	/// Returns the URL that the user should be directed to, built with custom `options`.
+	#[must_use]
+	pub fn connect_url_with(&self, options: &ConnectUrlOptions) -> Url {
+		self.connect_info().to_url_with(
+			&Url::parse(DEFAULT_CONNECT_HOST).unwrap_or_else(|_| unreachable!()),
+			options,
+		)
+	}
+
+	/// Returns the pieces of the connect URL, to build a custom QR payload or native intent from.
+	#[must_use]
+	pub fn connect_info(&self) -> ConnectInfo {
+		ConnectInfo {
+			request_id: self.request_id,
+			key_b64: base64_encode(self.key),
+			bridge: (self.bridge_url != BridgeUrl::default()).then(|| self.bridge_url.clone()),
+		}
+	}
+
+	/// Polls the bridge for the status of the request, and returns the current status.
+	/// You should call this method repeatedly until it returns `Status::Confirmed` or `Status::Failed`. Calling it again after leads to undefined behaviour.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the request to the bridge fails, or if the response from the bridge is malformed.
+	pub async fn poll_for_status(&self) -> Result<Status, Error> {
+		let result = self.poll().await;
+
+		#[cfg(feature = "verify")]
+		if let Some(metrics) = &self.metrics {
+			metrics.polled(&result);
+		}
+
+		result
+	}
+
+	async fn poll(&self) -> Result<Status, Error> {
+		let mut request = self.client.get(
+			self.bridge_url
+				.join(&format!("/response/{}", self.request_id))
+				.unwrap_or_else(|_| unreachable!()),
+		);
+		for (name, value) in self.trace_context.iter().flat_map(TraceContext::headers) {
+			request = request.header(name, value);
+		}
+
+		let response = request.send().await?;
+
+		if !response.status().is_success() {
+			return Ok(Status::Failed(AppError::ConnectionFailed));
+		}
+
+		let response = response.json::<BridgePollResponse>().await?;
+
+		if response.status != "completed" {
+			return Ok(match response.status.as_str() {
+				"retrieved" => Status::AwaitingConfirmation,
+				"initialized" => Status::WaitingForConnection,
+				_ => unreachable!("Invalid status returned from bridge"),
+			});
+		}
+
+		match decrypt_bridge_payload(
+			&self.key,
+			&response.response.unwrap_or_else(|| unreachable!()),
+		)? {
+			BridgeResponseOutcome::Proof(proof) => Ok(Status::Confirmed(proof)),
+			BridgeResponseOutcome::Failed(error) => Ok(Status::Failed(error)),
+		}
+	}
+
+	fn generate_key() -> Result<([u8; 32], [u8; 12]), Error> {
+		let rand = SystemRandom::new();
+
+		let mut iv = [0; 12];
+		rand.fill(&mut iv)
+			.map_err(|_| Error::Encryption("Failed to generate IV"))?;
+
+		let mut key = [0; 32];
+		rand.fill(&mut key)
+			.map_err(|_| Error::Encryption("Failed to generate key"))?;
+
+		Ok((key, iv))
+	}
+
+	fn encrypt_request<T: serde::Serialize + ?Sized>(
+		key: &[u8; 32],
+		nonce: &[u8; 12],
+		payload: &T,
+	) -> Result<EncryptedPayload, Error> {
+		crypto::encrypt(key, nonce, &serde_json::to_vec(payload)?)
+	}
+}
+
+/// Decrypts a response relayed by the Wallet Bridge with the session's key, for services that hold the key but don't poll the bridge themselves.
+///
+/// # Errors
+///
+/// Returns an error if the payload isn't valid base64, can't be decrypted with `key`, or doesn't contain a bridge response.
+pub fn decrypt_bridge_payload(
+	key: &[u8; 32],
+	payload: &EncryptedPayload,
+) -> Result<BridgeResponseOutcome, Error> {
+	Ok(
+		match serde_json::from_slice(&crypto::decrypt(key, payload)?)? {
+			BridgeResponse::Error { error_code } => BridgeResponseOutcome::Failed(error_code),
+			BridgeResponse::Success(proof) => BridgeResponseOutcome::Proof(proof.into()),
+		},
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PreHashedSignal;
+	use ruint::aliases::U256;
+	use serde_json::json;
+	use std::str::FromStr;
+	use wiremock::{
+		matchers::{header, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	async fn bridge_server(user_agent: &str) -> MockServer {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/request"))
+			.and(header("user-agent", user_agent))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"request_id": Uuid::new_v4(),
+			})))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		server
+	}
+
+	fn encrypt_response(key: &[u8; 32], response: &serde_json::Value) -> EncryptedPayload {
+		Session::encrypt_request(key, &[9; 12], response).unwrap()
+	}
+
+	#[test]
+	fn test_status_display() {
+		let proof = Proof {
+			proof: "0x1".to_string(),
+			merkle_root: "0x2".to_string(),
+			nullifier_hash: "0x3".to_string(),
+			verification_level: VerificationLevel::Orb,
+		};
+
+		for (status, hint, message) in [
+			(
+				Status::WaitingForConnection,
+				ProgressHint::ScanQrCode,
+				"Waiting for you to scan the QR code",
+			),
+			(
+				Status::AwaitingConfirmation,
+				ProgressHint::ConfirmInWorldApp,
+				"Confirm the request in World App",
+			),
+			(Status::Confirmed(proof), ProgressHint::Verified, "Verified"),
+			(
+				Status::Failed(AppError::VerificationRejected),
+				ProgressHint::Failed(AppError::VerificationRejected),
+				"The user rejected the verification request in the World App.",
+			),
+			(
+				Status::Failed(AppError::MaxVerificationsReached),
+				ProgressHint::Failed(AppError::MaxVerificationsReached),
+				"The user already verified the maximum number of times for this action.",
+			),
+		] {
+			assert_eq!(status.progress_hint(), hint);
+			assert_eq!(status.to_string(), message);
+			assert_eq!(hint.to_string(), message);
+		}
+	}
+
+	#[test]
+	fn test_decrypt_bridge_payload() {
+		let key = [7; 32];
+		let payload = encrypt_response(
+			&key,
+			&json!({
+				"proof": "0x1",
+				"merkle_root": "0x2",
+				"nullifier_hash": "0x3",
+				"credential_type": "device",
+			}),
+		);
+
+		assert_eq!(
+			decrypt_bridge_payload(&key, &payload).unwrap(),
+			BridgeResponseOutcome::Proof(Proof {
+				proof: "0x1".to_string(),
+				merkle_root: "0x2".to_string(),
+				nullifier_hash: "0x3".to_string(),
+				verification_level: VerificationLevel::Device,
+			})
+		);
+	}
+
+	#[test]
+	fn test_decrypt_bridge_payload_error() {
+		let key = [7; 32];
+		let payload = encrypt_response(&key, &json!({ "error_code": "verification_rejected" }));
+
+		assert_eq!(
+			decrypt_bridge_payload(&key, &payload).unwrap(),
+			BridgeResponseOutcome::Failed(AppError::VerificationRejected)
+		);
+		assert_eq!(
+			decrypt_bridge_payload(&[8; 32], &payload)
+				.unwrap_err()
+				.kind(),
+			ErrorKind::Crypto
+		);
+		assert_eq!(
+			decrypt_bridge_payload(
+				&key,
+				&EncryptedPayload {
+					iv: "!".to_string(),
+					..payload
+				}
+			)
+			.unwrap_err()
+			.kind(),
+			ErrorKind::Protocol
+		);
+	}
+
+	#[test]
+	fn test_encrypted_payload_serde() {
+		let payload = encrypt_response(&[7; 32], &json!({ "error_code": "generic_error" }));
+		let json = serde_json::to_value(&payload).unwrap();
+
+		assert_eq!(json["iv"], "CQkJCQkJCQkJCQkJ");
+		assert_eq!(
+			serde_json::from_value::<EncryptedPayload>(json).unwrap(),
+			payload
+		);
+	}
+
+	fn bridge_url(server: &MockServer) -> BridgeUrl {
+		BridgeUrl::try_from(Url::parse(&server.uri()).unwrap()).unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_default_user_agent() {
+		let server = bridge_server(&format!("idkit-rs/{}", env!("CARGO_PKG_VERSION"))).await;
+
+		Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			bridge_url(&server),
+			"",
+			None,
+		)
+		.await
+		.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_connect_info() {
+		let server = bridge_server(&format!("idkit-rs/{}", env!("CARGO_PKG_VERSION"))).await;
+
+		let session = Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			bridge_url(&server),
+			"",
+			None,
+		)
+		.await
+		.unwrap();
+
+		let info = session.connect_info();
+		assert_eq!(info.request_id, session.request_id);
+		assert_eq!(info.key_bytes().unwrap(), session.key);
+		assert_eq!(info.bridge, Some(bridge_url(&server)));
+		assert_eq!(ConnectInfo::from_url(&session.connect_url()).unwrap(), info);
+
+		let url_safe = session.connect_url_with(&ConnectUrlOptions {
+			key_encoding: crate::session::KeyEncoding::UrlSafe,
+		});
+		assert_eq!(ConnectInfo::from_url(&url_safe).unwrap(), info);
+	}
+
+	/// Creates a session with `signal`, returning the signal of the decrypted request sent to the bridge.
+	async fn requested_signal<V: Signal + Send>(signal: V) -> serde_json::Value {
+		let server = bridge_server(USER_AGENT).await;
+
+		let session = Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			bridge_url(&server),
+			signal,
+			None,
+		)
+		.await
+		.unwrap();
+
+		let requests = server.received_requests().await.unwrap();
+		let payload: EncryptedPayload = serde_json::from_slice(&requests[0].body).unwrap();
+		let request: serde_json::Value =
+			serde_json::from_slice(&crypto::decrypt(&session.key, &payload).unwrap()).unwrap();
+
+		request["signal"].clone()
+	}
+
+	#[tokio::test]
+	async fn test_u256_and_prehashed_signal_requests() {
+		// A `U256` is hashed as `hash_to_field(abi.encodePacked(uint256(1)))`
+		assert_eq!(
+			requested_signal(U256::from(1)).await,
+			"0x00b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0c"
+		);
+		// A `PreHashedSignal` is sent as-is
+		assert_eq!(
+			requested_signal(PreHashedSignal(U256::from(1))).await,
+			"0x0000000000000000000000000000000000000000000000000000000000000001"
+		);
+	}
+
+	#[test]
+	fn test_bridge_request_wire_format() {
+		let app_id = AppId::from_str("app_123").unwrap();
+		let action = Action::from_str("vote_1").unwrap();
+
+		// The body as it was built with `json!` before `BridgeRequest` existed.
+		let legacy = |action_description: Option<&str>, verification_level: VerificationLevel| {
+			serde_json::to_vec(&json!({
+				"app_id": app_id,
+				"action": action,
+				"action_description": action_description,
+				"signal": FieldElement::signal("test"),
+				"verification_level": verification_level.to_string(),
+				"credential_types": verification_level.to_credential_types(),
+			}))
+			.unwrap()
+		};
+
+		for action_description in [None, Some("Vote on proposal #1")] {
+			for verification_level in VerificationLevel::ALL {
+				let request = BridgeRequest::new(
+					&app_id,
+					&action,
+					action_description,
+					"test",
+					verification_level,
+				);
+
+				assert_eq!(
+					serde_json::to_vec(&request).unwrap(),
+					legacy(action_description, verification_level)
+				);
+			}
+		}
+
+		assert_eq!(
+			serde_json::to_string(&BridgeRequest::new(
+				&app_id,
+				&action,
+				None,
+				"test",
+				VerificationLevel::Device,
+			))
+			.unwrap(),
+			r#"{"action":"vote_1","action_description":null,"app_id":"app_123","credential_types":["orb","device"],"signal":"0x009c22ff5f21f0b81b113e63f7db6da94fedef11b2119b4088b89664fb9a3cb6","verification_level":"device"}"#
+		);
+	}
+
+	#[tokio::test]
+	async fn test_custom_user_agent() {
+		let server = bridge_server("my-service/abc123").await;
+
+		Session::with_user_agent(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			bridge_url(&server),
+			"",
+			None,
+			"my-service/abc123",
+		)
+		.await
+		.unwrap();
+	}
+
+	#[test]
+	fn test_error_kinds() {
+		let reqwest_error = || {
+			reqwest::Client::new()
+				.get("http://[::1")
+				.build()
+				.unwrap_err()
+		};
+
+		for (error, kind) in [
+			(Error::Bridge(reqwest_error()), ErrorKind::Transport),
+			(
+				Error::Timeout {
+					source: reqwest_error(),
+				},
+				ErrorKind::Transport,
+			),
+			(
+				Error::Connect {
+					source: reqwest_error(),
+				},
+				ErrorKind::Transport,
+			),
+			(
+				Error::Dns {
+					source: reqwest_error(),
+				},
+				ErrorKind::Transport,
+			),
+			(
+				Error::Status {
+					code: StatusCode::BAD_GATEWAY,
+					source: reqwest_error(),
+				},
+				ErrorKind::Transport,
+			),
+			(
+				Error::Json(serde_json::from_str::<()>("invalid").unwrap_err()),
+				ErrorKind::Protocol,
+			),
+			(
+				Error::Base64(crate::hashing::base64_decode("!").unwrap_err()),
+				ErrorKind::Protocol,
+			),
+			(Error::Encryption("Invalid IV"), ErrorKind::Crypto),
+			(
+				Error::InvalidAction(ActionError::TooLong(300)),
+				ErrorKind::Configuration,
+			),
+		] {
+			assert_eq!(error.kind(), kind, "{error:?}");
+		}
+	}
+
+	async fn create_session(bridge_url: BridgeUrl) -> Result<Session, Error> {
+		Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			bridge_url,
+			"",
+			None,
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_network_errors() {
+		use std::error::Error as _;
+
+		let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		drop(listener);
+		let error = create_session(
+			BridgeUrl::try_from(Url::parse(&format!("http://{address}")).unwrap()).unwrap(),
+		)
+		.await
+		.unwrap_err();
+		assert!(matches!(error, Error::Connect { .. }), "{error:?}");
+		assert_eq!(error.kind(), ErrorKind::Transport);
+		assert!(error
+			.source()
+			.is_some_and(|source| source.is::<reqwest::Error>()));
+
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/request"))
+			.respond_with(ResponseTemplate::new(500))
+			.mount(&server)
+			.await;
+		let error = create_session(bridge_url(&server)).await.unwrap_err();
+		assert!(
+			matches!(
+				error,
+				Error::Status {
+					code: StatusCode::INTERNAL_SERVER_ERROR,
+					..
+				}
+			),
+			"{error:?}"
+		);
+	}
+
+	#[tokio::test]
+	async fn test_slow_bridge() {
+		let request_id = Uuid::new_v4();
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path(format!("/response/{request_id}")))
+			.respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(5)))
+			.mount(&server)
+			.await;
+
+		let error = Session {
+			key: [7; 32],
+			request_id,
+			app_id: AppId::from_str("app_123").unwrap(),
+			action: Action::from_str("vote_1").unwrap(),
+			bridge_url: bridge_url(&server),
+			client: reqwest::Client::builder()
+				.timeout(std::time::Duration::from_millis(100))
+				.build()
+				.unwrap(),
+			trace_context: None,
+			#[cfg(feature = "verify")]
+			metrics: None,
+		}
+		.poll_for_status()
+		.await
+		.unwrap_err();
+		assert!(matches!(error, Error::Timeout { .. }), "{error:?}");
+	}
+
+	#[tokio::test]
+	async fn test_trace_context() {
+		const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+		let request_id = Uuid::new_v4();
+
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/request"))
+			.and(header("traceparent", TRACEPARENT))
+			.and(header("tracestate", "rojo=00f067aa0ba902b7"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"request_id": request_id,
+			})))
+			.expect(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path(format!("/response/{request_id}")))
+			.respond_with(
+				ResponseTemplate::new(200)
+					.set_body_json(json!({ "status": "initialized", "response": null })),
+			)
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let mut session = Session::with_trace_context(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			bridge_url(&server),
+			"",
+			None,
+			TraceContext::new(TRACEPARENT, Some("rojo=00f067aa0ba902b7".to_string())).unwrap(),
+		)
+		.await
+		.unwrap();
+		session.poll_for_status().await.unwrap();
+
+		let next = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+		session.set_trace_context(Some(TraceContext::from_str(next).unwrap()));
+		session.poll_for_status().await.unwrap();
+
+		let requests = server.received_requests().await.unwrap();
+		assert_eq!(requests[1].headers["traceparent"], TRACEPARENT);
+		assert_eq!(requests[1].headers["tracestate"], "rojo=00f067aa0ba902b7");
+		assert_eq!(requests[2].headers["traceparent"], next);
+		assert!(!requests[2].headers.contains_key("tracestate"));
+	}
+}
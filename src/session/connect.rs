@@ -0,0 +1,304 @@
+//! The connect URL the World App opens to pick up a verification request from the bridge.
+
+use base64::{
+	engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+	Engine,
+};
+use std::{borrow::Cow, str::FromStr};
+use url::Url;
+use uuid::Uuid;
+
+use super::BridgeUrl;
+use crate::hashing::base64_decode;
+
+/// The page the World App is sent to when scanning a connect URL.
+pub const DEFAULT_CONNECT_HOST: &str = "https://worldcoin.org/verify";
+
+/// How the key is base64 encoded in the `k` parameter of a connect URL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyEncoding {
+	/// Standard base64 with padding, percent-encoded. Understood by every World App version.
+	#[default]
+	Standard,
+	/// URL-safe base64 without padding, which needs no percent-encoding and survives QR scanners that mangle `%2B` and `%2F`.
+	UrlSafe,
+}
+
+/// Options for building a connect URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectUrlOptions {
+	/// How to encode the key. Defaults to `KeyEncoding::Standard`.
+	pub key_encoding: KeyEncoding,
+}
+
+/// The pieces of a connect URL, for building custom QR payloads or native intents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectInfo {
+	/// The id of the request on the bridge.
+	pub request_id: Uuid,
+	/// The key the request and its response are encrypted with, as standard base64.
+	pub key_b64: String,
+	/// The bridge the request was sent to, if not the default one.
+	pub bridge: Option<BridgeUrl>,
+}
+
+/// Error returned when parsing an invalid connect URL.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ConnectInfoError {
+	#[error("Invalid connect URL: {0}")]
+	InvalidUrl(#[from] url::ParseError),
+
+	#[error("Connect URL is missing the {0} parameter")]
+	MissingParameter(&'static str),
+
+	#[error("Connect URL is for {0:?} requests, expected wld")]
+	UnsupportedType(String),
+
+	#[error("Connect URL has an invalid request id: {0:?}")]
+	InvalidRequestId(String),
+
+	#[error("Connect URL has an invalid bridge URL: {0:?}")]
+	InvalidBridgeUrl(String),
+
+	#[error("Connect URL has a key that isn't standard or URL-safe base64: {0:?}")]
+	InvalidKey(String),
+}
+
+impl ConnectInfo {
+	/// The connect URL pointing to `host`, with the request id, key and bridge as query parameters.
+	#[must_use]
+	pub fn to_url(&self, host: &Url) -> Url {
+		self.to_url_with(host, &ConnectUrlOptions::default())
+	}
+
+	/// The connect URL pointing to `host`, built with custom `options`.
+	#[must_use]
+	pub fn to_url_with(&self, host: &Url, options: &ConnectUrlOptions) -> Url {
+		let bridge = self
+			.bridge
+			.as_ref()
+			.map_or_else(String::new, |bridge| format!("&b={}", bridge.as_str()));
+
+		let mut url = host.clone();
+		url.set_query(Some(&format!(
+			"t=wld&i={}&k={}{bridge}",
+			self.request_id,
+			self.encoded_key(options.key_encoding),
+		)));
+
+		url
+	}
+
+	/// The raw bytes of the key, accepting both standard and URL-safe base64.
+	///
+	/// # Errors
+	///
+	/// Errors if the key isn't valid base64 in either alphabet.
+	pub fn key_bytes(&self) -> Result<Vec<u8>, base64::DecodeError> {
+		base64_decode(&self.key_b64)
+	}
+
+	fn encoded_key(&self, encoding: KeyEncoding) -> Cow<'_, str> {
+		match encoding {
+			KeyEncoding::Standard => urlencoding::encode(&self.key_b64),
+			KeyEncoding::UrlSafe => self.key_bytes().map_or_else(
+				|_| Cow::Borrowed(self.key_b64.as_str()),
+				|key| Cow::Owned(URL_SAFE_NO_PAD.encode(key)),
+			),
+		}
+	}
+
+	/// Reads the request id, key and bridge back out of a connect URL, such as one scanned from a QR code.
+	///
+	/// # Errors
+	///
+	/// Errors if a parameter is missing or malformed.
+	pub fn from_url(url: &Url) -> Result<Self, ConnectInfoError> {
+		let param = |name: &'static str| {
+			url.query_pairs()
+				.find(|(key, _)| key == name)
+				.map(|(_, value)| value.into_owned())
+		};
+
+		let kind = param("t").ok_or(ConnectInfoError::MissingParameter("t"))?;
+		if kind != "wld" {
+			return Err(ConnectInfoError::UnsupportedType(kind));
+		}
+
+		let request_id = param("i").ok_or(ConnectInfoError::MissingParameter("i"))?;
+		let request_id = Uuid::from_str(&request_id)
+			.map_err(|_| ConnectInfoError::InvalidRequestId(request_id))?;
+
+		let bridge = param("b")
+			.map(|bridge| {
+				Url::parse(&bridge)
+					.ok()
+					.and_then(|url| BridgeUrl::try_from(url).ok())
+					.ok_or(ConnectInfoError::InvalidBridgeUrl(bridge))
+			})
+			.transpose()?;
+
+		let key = param("k").ok_or(ConnectInfoError::MissingParameter("k"))?;
+		let key = base64_decode(&key).map_err(|_| ConnectInfoError::InvalidKey(key))?;
+
+		Ok(Self {
+			request_id,
+			key_b64: STANDARD.encode(key),
+			bridge,
+		})
+	}
+}
+
+impl FromStr for ConnectInfo {
+	type Err = ConnectInfoError;
+
+	fn from_str(url: &str) -> Result<Self, Self::Err> {
+		Self::from_url(&Url::parse(url)?)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const REQUEST_ID: &str = "0f1e2d3c-4b5a-4978-8796-a5b4c3d2e1f0";
+	const KEY: &str = "q+v/7mEJYt3Hz0c4Z1bKq2p3o8VnW0Q7m6d5B9zX+aA=";
+
+	fn host() -> Url {
+		Url::parse(DEFAULT_CONNECT_HOST).unwrap()
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let url = format!(
+			"https://worldcoin.org/verify?t=wld&i={REQUEST_ID}&k=q%2Bv%2F7mEJYt3Hz0c4Z1bKq2p3o8VnW0Q7m6d5B9zX%2BaA%3D"
+		);
+
+		let info = ConnectInfo::from_str(&url).unwrap();
+		assert_eq!(info.request_id, Uuid::from_str(REQUEST_ID).unwrap());
+		assert_eq!(info.key_b64, KEY);
+		assert_eq!(info.bridge, None);
+		assert_eq!(info.to_url(&host()).as_str(), url);
+	}
+
+	#[test]
+	fn test_round_trip_with_bridge() {
+		let url = format!(
+			"https://worldcoin.org/verify?t=wld&i={REQUEST_ID}&k=q%2Bv%2F7mEJYt3Hz0c4Z1bKq2p3o8VnW0Q7m6d5B9zX%2BaA%3D&b=https://bridge.example.com/"
+		);
+
+		let info = ConnectInfo::from_str(&url).unwrap();
+		assert_eq!(
+			info.bridge.as_ref().map(|bridge| bridge.as_str()),
+			Some("https://bridge.example.com/")
+		);
+		assert_eq!(info.to_url(&host()).as_str(), url);
+	}
+
+	#[test]
+	fn test_custom_host() {
+		let info = ConnectInfo {
+			request_id: Uuid::from_str(REQUEST_ID).unwrap(),
+			key_b64: KEY.to_string(),
+			bridge: None,
+		};
+		let url = info.to_url(&Url::parse("https://example.com/connect?ignored=1").unwrap());
+
+		assert!(url
+			.as_str()
+			.starts_with("https://example.com/connect?t=wld&i="));
+		assert_eq!(ConnectInfo::from_url(&url).unwrap(), info);
+	}
+
+	#[test]
+	fn test_url_safe_key() {
+		let info = ConnectInfo {
+			request_id: Uuid::from_str(REQUEST_ID).unwrap(),
+			key_b64: KEY.to_string(),
+			bridge: None,
+		};
+		let url = info.to_url_with(
+			&host(),
+			&ConnectUrlOptions {
+				key_encoding: KeyEncoding::UrlSafe,
+			},
+		);
+
+		assert_eq!(
+			url.as_str(),
+			format!(
+				"https://worldcoin.org/verify?t=wld&i={REQUEST_ID}&k=q-v_7mEJYt3Hz0c4Z1bKq2p3o8VnW0Q7m6d5B9zX-aA"
+			)
+		);
+
+		let parsed = ConnectInfo::from_url(&url).unwrap();
+		assert_eq!(parsed, info);
+		assert_eq!(
+			parsed.key_bytes().unwrap(),
+			ConnectInfo::from_url(&info.to_url(&host()))
+				.unwrap()
+				.key_bytes()
+				.unwrap()
+		);
+		assert_eq!(parsed.key_bytes().unwrap().len(), 32);
+	}
+
+	#[test]
+	fn test_key_bytes_accepts_both_alphabets() {
+		let info = |key_b64: &str| ConnectInfo {
+			request_id: Uuid::from_str(REQUEST_ID).unwrap(),
+			key_b64: key_b64.to_string(),
+			bridge: None,
+		};
+
+		assert_eq!(
+			info(KEY).key_bytes().unwrap(),
+			info("q-v_7mEJYt3Hz0c4Z1bKq2p3o8VnW0Q7m6d5B9zX-aA")
+				.key_bytes()
+				.unwrap()
+		);
+		assert!(info("not base64!").key_bytes().is_err());
+	}
+
+	#[test]
+	fn test_invalid_urls() {
+		for (url, error) in [
+			(
+				"https://worldcoin.org/verify?i=x&k=y".to_string(),
+				ConnectInfoError::MissingParameter("t"),
+			),
+			(
+				"https://worldcoin.org/verify?t=other&i=x&k=y".to_string(),
+				ConnectInfoError::UnsupportedType("other".to_string()),
+			),
+			(
+				"https://worldcoin.org/verify?t=wld&k=y".to_string(),
+				ConnectInfoError::MissingParameter("i"),
+			),
+			(
+				"https://worldcoin.org/verify?t=wld&i=x&k=y".to_string(),
+				ConnectInfoError::InvalidRequestId("x".to_string()),
+			),
+			(
+				format!("https://worldcoin.org/verify?t=wld&i={REQUEST_ID}"),
+				ConnectInfoError::MissingParameter("k"),
+			),
+			(
+				format!(
+					"https://worldcoin.org/verify?t=wld&i={REQUEST_ID}&k=y&b=http://example.com"
+				),
+				ConnectInfoError::InvalidBridgeUrl("http://example.com".to_string()),
+			),
+			(
+				format!("https://worldcoin.org/verify?t=wld&i={REQUEST_ID}&k=%24%24"),
+				ConnectInfoError::InvalidKey("$$".to_string()),
+			),
+			(
+				"not a url".to_string(),
+				ConnectInfoError::InvalidUrl(url::ParseError::RelativeUrlWithoutBase),
+			),
+		] {
+			assert_eq!(ConnectInfo::from_str(&url), Err(error), "{url}");
+		}
+	}
+}
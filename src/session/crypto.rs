@@ -0,0 +1,144 @@
+//! The encryption of requests and responses exchanged with the Wallet Bridge.
+//!
+//! Payloads are encrypted with AES-256-GCM, without associated data, under the 32-byte key shared with the World App through the connect URL. The 12-byte nonce and the ciphertext, followed by its 16-byte tag, are sent as standard base64 in the `iv` and `payload` fields of an `EncryptedPayload`.
+//!
+//! Test vectors for other implementations are published in `tests/vectors/bridge-encryption.json`.
+
+use ring::aead::{self, LessSafeKey, Nonce, UnboundKey};
+
+use super::Error;
+use crate::hashing::{base64_decode, base64_encode};
+
+/// An encrypted request or response, as exchanged with the Wallet Bridge.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EncryptedPayload {
+	/// The AES-GCM nonce, base64 encoded.
+	pub iv: String,
+	/// The ciphertext followed by its authentication tag, base64 encoded.
+	pub payload: String,
+}
+
+/// Encrypts `plaintext` under `key`, using `nonce` as the IV.
+///
+/// A nonce must never be reused with the same key.
+///
+/// # Errors
+///
+/// Returns an error if the plaintext is too large to be encrypted.
+pub fn encrypt(
+	key: &[u8; 32],
+	nonce: &[u8; aead::NONCE_LEN],
+	plaintext: &[u8],
+) -> Result<EncryptedPayload, Error> {
+	let mut payload = plaintext.to_vec();
+
+	cipher(key)?
+		.seal_in_place_append_tag(
+			Nonce::assume_unique_for_key(*nonce),
+			aead::Aad::empty(),
+			&mut payload,
+		)
+		.map_err(|_| Error::Encryption("Failed to encrypt bridge request"))?;
+
+	Ok(EncryptedPayload {
+		iv: base64_encode(nonce),
+		payload: base64_encode(payload),
+	})
+}
+
+/// Decrypts `payload` with `key`, returning the plaintext.
+///
+/// # Errors
+///
+/// Returns an error if the payload isn't valid base64, or can't be decrypted with `key`.
+pub fn decrypt(key: &[u8; 32], payload: &EncryptedPayload) -> Result<Vec<u8>, Error> {
+	let nonce = Nonce::try_assume_unique_for_key(&base64_decode(&payload.iv)?)
+		.map_err(|_| Error::Encryption("Invalid IV"))?;
+
+	let mut ciphertext = base64_decode(&payload.payload)?;
+	let plaintext = cipher(key)?
+		.open_in_place(nonce, aead::Aad::empty(), &mut ciphertext)
+		.map_err(|_| Error::Encryption("Failed to decrypt bridge response"))?;
+
+	Ok(plaintext.to_vec())
+}
+
+fn cipher(key: &[u8; 32]) -> Result<LessSafeKey, Error> {
+	let key = UnboundKey::new(&aead::AES_256_GCM, key)
+		.map_err(|_| Error::Encryption("AES-256-GCM is a supported algorithm"))?;
+
+	Ok(LessSafeKey::new(key))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(serde::Deserialize)]
+	struct Vector {
+		description: String,
+		key: String,
+		iv: String,
+		plaintext: String,
+		payload: String,
+	}
+
+	fn vectors() -> Vec<Vector> {
+		serde_json::from_str(include_str!("../../tests/vectors/bridge-encryption.json")).unwrap()
+	}
+
+	#[test]
+	fn test_vectors() {
+		for vector in vectors() {
+			let key: [u8; 32] = base64_decode(&vector.key).unwrap().try_into().unwrap();
+			let nonce: [u8; 12] = base64_decode(&vector.iv).unwrap().try_into().unwrap();
+			let expected = EncryptedPayload {
+				iv: vector.iv,
+				payload: vector.payload,
+			};
+
+			assert_eq!(
+				encrypt(&key, &nonce, vector.plaintext.as_bytes()).unwrap(),
+				expected,
+				"{}",
+				vector.description
+			);
+			assert_eq!(
+				decrypt(&key, &expected).unwrap(),
+				vector.plaintext.as_bytes(),
+				"{}",
+				vector.description
+			);
+		}
+	}
+
+	#[test]
+	fn test_decrypt_errors() {
+		let payload = encrypt(&[1; 32], &[2; 12], b"hello").unwrap();
+
+		assert!(matches!(
+			decrypt(&[3; 32], &payload),
+			Err(Error::Encryption(_))
+		));
+		assert!(matches!(
+			decrypt(
+				&[1; 32],
+				&EncryptedPayload {
+					iv: base64_encode([2; 8]),
+					..payload.clone()
+				}
+			),
+			Err(Error::Encryption("Invalid IV"))
+		));
+		assert!(matches!(
+			decrypt(
+				&[1; 32],
+				&EncryptedPayload {
+					payload: "!".to_string(),
+					..payload
+				}
+			),
+			Err(Error::Base64(_))
+		));
+	}
+}
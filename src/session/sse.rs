@@ -0,0 +1,273 @@
+//! Stream the status of a `Session` to browsers as [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+//!
+//! The stream is framework-agnostic: every `Event` displays as its wire format, and the `axum` feature provides an adapter to `axum::response::Sse`.
+
+use futures_util::{stream, Stream};
+use serde_json::json;
+use std::{fmt::Display, time::Duration};
+use tokio::time::{sleep, Instant};
+
+use super::{Session, Status};
+
+/// An event of the stream returned by `status_events`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+	/// A change of the status of the request, named after it (`waiting_for_connection`, `awaiting_confirmation`, `confirmed` or `failed`), or an `error` when the bridge couldn't be polled.
+	///
+	/// The data is a JSON object with the `status` and the `message` to display to the user. Confirmed requests also contain the `proof`, and failed ones the `error_code`.
+	Message {
+		/// The name of the event.
+		event: &'static str,
+		/// The JSON data of the event.
+		data: String,
+	},
+	/// A comment sent when the status doesn't change, to keep proxies from closing the connection.
+	Heartbeat,
+}
+
+impl Event {
+	/// The name of the event, if it isn't a heartbeat.
+	#[must_use]
+	pub const fn name(&self) -> Option<&'static str> {
+		match self {
+			Self::Message { event, .. } => Some(*event),
+			Self::Heartbeat => None,
+		}
+	}
+
+	/// Whether the stream ends after this event.
+	#[must_use]
+	pub fn is_terminal(&self) -> bool {
+		matches!(self.name(), Some("confirmed" | "failed" | "error"))
+	}
+
+	fn status(status: &Status) -> Self {
+		let event = status.name();
+		let mut data = json!({ "status": event, "message": status.to_string() });
+		match status {
+			Status::Confirmed(proof) => data["proof"] = json!(proof),
+			Status::Failed(error) => data["error_code"] = json!(error),
+			Status::WaitingForConnection | Status::AwaitingConfirmation => {},
+		}
+
+		Self::Message {
+			event,
+			data: data.to_string(),
+		}
+	}
+
+	fn error(error: &super::Error) -> Self {
+		Self::Message {
+			event: "error",
+			data: json!({ "status": "error", "message": error.to_string() }).to_string(),
+		}
+	}
+}
+
+/// Displays the event in the `text/event-stream` format, including the blank line ending it.
+impl Display for Event {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Message { event, data } => write!(f, "event: {event}\ndata: {data}\n\n"),
+			Self::Heartbeat => f.write_str(": heartbeat\n\n"),
+		}
+	}
+}
+
+/// How often `status_events` polls the bridge and sends heartbeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamConfig {
+	/// The time between polls of the bridge. Defaults to 1 second.
+	pub poll_interval: Duration,
+	/// The time without events after which a heartbeat is sent. Defaults to 15 seconds.
+	pub heartbeat_interval: Duration,
+}
+
+impl Default for StreamConfig {
+	fn default() -> Self {
+		Self {
+			poll_interval: Duration::from_secs(1),
+			heartbeat_interval: Duration::from_secs(15),
+		}
+	}
+}
+
+struct State {
+	session: Session,
+	config: StreamConfig,
+	last_status: Option<&'static str>,
+	last_event: Instant,
+}
+
+/// Polls `session` until the request is confirmed or fails, sending an event every time its status changes.
+///
+/// The stream ends after a terminal event: `confirmed`, `failed`, or `error` if the bridge couldn't be polled.
+pub fn status_events(session: Session, config: StreamConfig) -> impl Stream<Item = Event> + Send {
+	let state = State {
+		session,
+		config,
+		last_status: None,
+		last_event: Instant::now(),
+	};
+
+	stream::unfold(Some(state), |state| async move {
+		let mut state = state?;
+
+		loop {
+			if state.last_status.is_some() {
+				sleep(state.config.poll_interval).await;
+			}
+
+			let event = match state.session.poll_for_status().await {
+				Ok(status) if state.last_status == Some(status.name()) => {
+					if state.last_event.elapsed() < state.config.heartbeat_interval {
+						continue;
+					}
+					Event::Heartbeat
+				},
+				Ok(status) => {
+					state.last_status = Some(status.name());
+					Event::status(&status)
+				},
+				Err(error) => Event::error(&error),
+			};
+			state.last_event = Instant::now();
+
+			let next = (!event.is_terminal()).then_some(state);
+			return Some((event, next));
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		session::{crypto, AppError, AppId, BridgeProof, BridgeUrl, VerificationLevel},
+		Proof,
+	};
+	use futures_util::StreamExt;
+	use std::str::FromStr;
+	use url::Url;
+	use uuid::Uuid;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	/// Starts a bridge answering polls with each of `responses` in order, as a pending status or the decrypted payload of a completed request.
+	async fn scripted_bridge(responses: Vec<serde_json::Value>) -> (MockServer, Session) {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/request"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_json(json!({ "request_id": Uuid::nil() })),
+			)
+			.mount(&server)
+			.await;
+
+		let session = Session::new(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			VerificationLevel::Orb,
+			BridgeUrl::try_from(Url::parse(&server.uri()).unwrap()).unwrap(),
+			"",
+			None,
+		)
+		.await
+		.unwrap();
+		let key: [u8; 32] = session
+			.connect_info()
+			.key_bytes()
+			.unwrap()
+			.try_into()
+			.unwrap();
+
+		for response in responses {
+			let body = match response.get("status") {
+				Some(status) => json!({ "status": status, "response": null }),
+				None => json!({
+					"status": "completed",
+					"response": crypto::encrypt(&key, &[9; 12], response.to_string().as_bytes()).unwrap(),
+				}),
+			};
+
+			Mock::given(method("GET"))
+				.and(path(format!("/response/{}", Uuid::nil())))
+				.respond_with(ResponseTemplate::new(200).set_body_json(body))
+				.up_to_n_times(1)
+				.mount(&server)
+				.await;
+		}
+
+		(server, session)
+	}
+
+	fn config() -> StreamConfig {
+		StreamConfig {
+			poll_interval: Duration::from_millis(1),
+			heartbeat_interval: Duration::ZERO,
+		}
+	}
+
+	#[tokio::test]
+	async fn test_confirmed_events() {
+		let proof = Proof {
+			proof: "0x1".to_string(),
+			merkle_root: "0x2".to_string(),
+			nullifier_hash: "0x3".to_string(),
+			verification_level: VerificationLevel::Orb,
+		};
+		let (_server, session) = scripted_bridge(vec![
+			json!({ "status": "initialized" }),
+			json!({ "status": "initialized" }),
+			json!({ "status": "retrieved" }),
+			json!(BridgeProof::try_from(proof).unwrap()),
+		])
+		.await;
+
+		let events = status_events(session, config()).collect::<Vec<_>>().await;
+
+		assert_eq!(
+			events.iter().map(ToString::to_string).collect::<String>(),
+			concat!(
+				"event: waiting_for_connection\n",
+				"data: {\"message\":\"Waiting for you to scan the QR code\",\"status\":\"waiting_for_connection\"}\n\n",
+				": heartbeat\n\n",
+				"event: awaiting_confirmation\n",
+				"data: {\"message\":\"Confirm the request in World App\",\"status\":\"awaiting_confirmation\"}\n\n",
+				"event: confirmed\n",
+				"data: {\"message\":\"Verified\",\"proof\":{\"merkle_root\":\"0x2\",\"nullifier_hash\":\"0x3\",\"proof\":\"0x1\",\"verification_level\":\"orb\"},\"status\":\"confirmed\"}\n\n",
+			)
+		);
+		assert!(events.last().unwrap().is_terminal());
+	}
+
+	#[tokio::test]
+	async fn test_failed_events() {
+		let (_server, session) = scripted_bridge(vec![
+			json!({ "status": "retrieved" }),
+			json!({ "error_code": "verification_rejected" }),
+		])
+		.await;
+
+		let events = status_events(session, config()).collect::<Vec<_>>().await;
+
+		assert_eq!(
+			events.iter().map(Event::name).collect::<Vec<_>>(),
+			[Some("awaiting_confirmation"), Some("failed")]
+		);
+		assert_eq!(
+			events[1],
+			Event::Message {
+				event: "failed",
+				data: json!({
+					"status": "failed",
+					"message": AppError::VerificationRejected.to_string(),
+					"error_code": "verification_rejected",
+				})
+				.to_string(),
+			}
+		);
+	}
+}
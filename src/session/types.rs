@@ -1,18 +1,88 @@
-use std::{fmt::Display, ops::Deref, str::FromStr};
+use alloc::{
+	string::{String, ToString},
+	vec,
+	vec::Vec,
+};
+use core::{fmt::Display, ops::Deref, str::FromStr};
+#[cfg(feature = "session")]
 use url::Url;
 
-use crate::Proof;
+#[cfg(feature = "session")]
+use crate::{ErrorKind, Proof};
 
+#[cfg(feature = "session")]
 const DEFAULT_BRIDGE_URL: &str = "https://bridge.worldcoin.org";
 
+/// The wire representation of orb credentials and verification.
+const ORB: &str = "orb";
+/// The wire representation of device credentials and verification.
+const DEVICE: &str = "device";
+/// The wire representation of phone credentials, which device credentials replaced.
+const LEGACY_PHONE: &str = "phone";
+
 /// The strongest credential with which a user has been verified.
+///
+/// When deserializing, the legacy `phone` credential type is read as `Device`, so proofs and bridge payloads from before device credentials existed can still be replayed. It's never serialized back, and `FromStr` rejects it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(try_from = "String", into = "&'static str")]
 pub enum CredentialType {
 	Orb,
 	Device,
 }
 
+/// Error returned when an unknown credential type is provided.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown credential type {0:?}, expected orb or device")]
+pub struct CredentialTypeError(String);
+
+impl CredentialType {
+	/// The lowercase name of the credential type, as sent over the wire.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Orb => ORB,
+			Self::Device => DEVICE,
+		}
+	}
+}
+
+impl Display for CredentialType {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl FromStr for CredentialType {
+	type Err = CredentialTypeError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			ORB => Ok(Self::Orb),
+			DEVICE => Ok(Self::Device),
+			_ => Err(CredentialTypeError(s.to_string())),
+		}
+	}
+}
+
+impl TryFrom<String> for CredentialType {
+	type Error = CredentialTypeError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		// Phone credentials were superseded by device ones, which grant the same verification level
+		if value == LEGACY_PHONE {
+			return Ok(Self::Device);
+		}
+
+		value.parse()
+	}
+}
+
+impl From<CredentialType> for &'static str {
+	fn from(value: CredentialType) -> Self {
+		value.as_str()
+	}
+}
+
 impl From<CredentialType> for VerificationLevel {
 	fn from(val: CredentialType) -> Self {
 		match val {
@@ -22,14 +92,31 @@ impl From<CredentialType> for VerificationLevel {
 	}
 }
 
+impl From<VerificationLevel> for CredentialType {
+	fn from(val: VerificationLevel) -> Self {
+		match val {
+			VerificationLevel::Orb => Self::Orb,
+			VerificationLevel::Device => Self::Device,
+		}
+	}
+}
+
 /// The minimum verification level accepted.
+///
+/// Parsing, either through `FromStr` or when deserializing, ignores case. Levels are always displayed and serialized in lowercase.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[serde(try_from = "String", into = "&'static str")]
 pub enum VerificationLevel {
 	Orb,
 	Device,
 }
 
+/// Error returned when an unknown verification level is provided.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown verification level {0:?}, expected one of: {levels}", levels = VerificationLevel::names())]
+pub struct VerificationLevelError(String);
+
 impl Default for VerificationLevel {
 	fn default() -> Self {
 		Self::Orb
@@ -37,15 +124,54 @@ impl Default for VerificationLevel {
 }
 
 impl Display for VerificationLevel {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		match self {
-			Self::Orb => write!(f, "orb"),
-			Self::Device => write!(f, "device"),
-		}
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl FromStr for VerificationLevel {
+	type Err = VerificationLevelError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|level| level.as_str().eq_ignore_ascii_case(s))
+			.ok_or_else(|| VerificationLevelError(s.to_string()))
+	}
+}
+
+impl TryFrom<String> for VerificationLevel {
+	type Error = VerificationLevelError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<VerificationLevel> for &'static str {
+	fn from(value: VerificationLevel) -> Self {
+		value.as_str()
 	}
 }
 
 impl VerificationLevel {
+	/// Every verification level, from strongest to weakest.
+	pub const ALL: [Self; 2] = [Self::Orb, Self::Device];
+
+	/// The lowercase name of the verification level, as sent over the wire.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Orb => ORB,
+			Self::Device => DEVICE,
+		}
+	}
+
+	/// The names of every verification level, comma-separated.
+	fn names() -> String {
+		Self::ALL.map(Self::as_str).join(", ")
+	}
+
 	#[must_use]
 	pub fn to_credential_types(&self) -> Vec<CredentialType> {
 		match self {
@@ -53,11 +179,29 @@ impl VerificationLevel {
 			Self::Device => vec![CredentialType::Orb, CredentialType::Device],
 		}
 	}
+
+	/// Whether this level is at least as strong as the `required` one. Orb verification satisfies every level, while device verification only satisfies itself.
+	#[must_use]
+	pub const fn satisfies(self, required: Self) -> bool {
+		self.strength() >= required.strength()
+	}
+
+	const fn strength(self) -> u8 {
+		match self {
+			Self::Device => 0,
+			Self::Orb => 1,
+		}
+	}
 }
 
 /// The error returned by the World App.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, thiserror::Error)]
-#[serde(rename_all = "snake_case")]
+///
+/// Serialized as its snake case `code`, which `FromStr` parses back.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize, thiserror::Error,
+)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[serde(try_from = "String", into = "&'static str")]
 pub enum AppError {
 	/// Failed to connect to the World App. Please create a new session and try again.
 	#[error("Failed to connect to the World App. Please create a new session and try again.")]
@@ -98,21 +242,132 @@ pub enum AppError {
 	GenericError,
 }
 
+/// Error returned when an unknown World App error code is provided.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown World App error code {0:?}")]
+pub struct AppErrorCodeError(String);
+
+impl AppError {
+	/// Every error the World App can return.
+	pub const ALL: [Self; 11] = [
+		Self::ConnectionFailed,
+		Self::VerificationRejected,
+		Self::MaxVerificationsReached,
+		Self::CredentialUnavailable,
+		Self::MalformedRequest,
+		Self::InvalidNetwork,
+		Self::InclusionProofFailed,
+		Self::InclusionProofPending,
+		Self::UnexpectedResponse,
+		Self::FailedByHostApp,
+		Self::GenericError,
+	];
+
+	/// The machine-readable code of the error, as sent over the wire.
+	#[must_use]
+	pub const fn code(self) -> &'static str {
+		match self {
+			Self::ConnectionFailed => "connection_failed",
+			Self::VerificationRejected => "verification_rejected",
+			Self::MaxVerificationsReached => "max_verifications_reached",
+			Self::CredentialUnavailable => "credential_unavailable",
+			Self::MalformedRequest => "malformed_request",
+			Self::InvalidNetwork => "invalid_network",
+			Self::InclusionProofFailed => "inclusion_proof_failed",
+			Self::InclusionProofPending => "inclusion_proof_pending",
+			Self::UnexpectedResponse => "unexpected_response",
+			Self::FailedByHostApp => "failed_by_host_app",
+			Self::GenericError => "generic_error",
+		}
+	}
+}
+
+impl FromStr for AppError {
+	type Err = AppErrorCodeError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|error| error.code() == s)
+			.ok_or_else(|| AppErrorCodeError(s.to_string()))
+	}
+}
+
+impl TryFrom<String> for AppError {
+	type Error = AppErrorCodeError;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<AppError> for &'static str {
+	fn from(value: AppError) -> Self {
+		value.code()
+	}
+}
+
+/// The environment an app or a proof belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Environment {
+	/// Real users, verifying with the World App.
+	Production,
+	/// Test users, verifying with the [World ID Simulator](https://simulator.worldcoin.org).
+	Staging,
+}
+
+impl Display for Environment {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Production => write!(f, "production"),
+			Self::Staging => write!(f, "staging"),
+		}
+	}
+}
+
 /// Unique identifier for the app verifying the action. This should be the App ID obtained from the [Developer Portal](https://developer.worldcoin.org).
 #[repr(transparent)]
-#[derive(Debug, Clone, serde::Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String")]
+// Deserializing goes through `TryFrom<String>`, so unlike `new_unchecked` it always validates the app id.
+#[allow(clippy::unsafe_derive_deserialize)]
 pub struct AppId(pub(crate) String);
 
+/// The prefix of production app ids.
+const APP_ID_PREFIX: &str = "app_";
+/// The prefix of staging app ids.
+const STAGING_APP_ID_PREFIX: &str = "app_staging_";
+/// The maximum length of the hex identifier following the prefix of an app id.
+const MAX_APP_ID_LEN: usize = 32;
+
 /// Error returned when an invalid app id is provided.
 #[derive(Debug, thiserror::Error, PartialEq, Eq)]
-#[error("Invalid app id provided, expected app_*, got {0}")]
-pub struct AppIdError(String);
+pub enum AppIdError {
+	#[error("Invalid app id {0:?}, expected it to start with app_ or app_staging_")]
+	InvalidPrefix(String),
+
+	#[error("Invalid app id {0:?}, expected a lowercase hex identifier after the prefix")]
+	InvalidCharacter(String),
+
+	#[error("Invalid app id {0:?}, expected an identifier of 1 to {MAX_APP_ID_LEN} characters after the prefix")]
+	InvalidLength(String),
+}
 
 impl AppId {
 	/// Whether this app id represents a staging app.
 	#[must_use]
 	pub fn is_staging(&self) -> bool {
-		self.0.contains("staging")
+		self.0.starts_with(STAGING_APP_ID_PREFIX)
+	}
+
+	/// The environment of this app, which must match the environment of the proofs it verifies.
+	#[must_use]
+	pub fn environment(&self) -> Environment {
+		if self.is_staging() {
+			Environment::Staging
+		} else {
+			Environment::Production
+		}
 	}
 
 	/// Create a new app id from a string, bypasing the validation.
@@ -130,11 +385,45 @@ impl FromStr for AppId {
 	type Err = AppIdError;
 
 	fn from_str(app_id: &str) -> Result<Self, Self::Err> {
-		if app_id.starts_with("app_") {
-			Ok(Self(app_id.to_string()))
-		} else {
-			Err(AppIdError(app_id.to_string()))
+		let Some(identifier) = app_id
+			.strip_prefix(STAGING_APP_ID_PREFIX)
+			.or_else(|| app_id.strip_prefix(APP_ID_PREFIX))
+		else {
+			return Err(AppIdError::InvalidPrefix(app_id.to_string()));
+		};
+
+		if !identifier
+			.chars()
+			.all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+		{
+			return Err(AppIdError::InvalidCharacter(app_id.to_string()));
+		}
+
+		if identifier.is_empty() || identifier.len() > MAX_APP_ID_LEN {
+			return Err(AppIdError::InvalidLength(app_id.to_string()));
 		}
+
+		Ok(Self(app_id.to_string()))
+	}
+}
+
+impl TryFrom<String> for AppId {
+	type Error = AppIdError;
+
+	fn try_from(app_id: String) -> Result<Self, Self::Error> {
+		app_id.parse()
+	}
+}
+
+impl Display for AppId {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl AsRef<str> for AppId {
+	fn as_ref(&self) -> &str {
+		&self.0
 	}
 }
 
@@ -146,13 +435,106 @@ impl Deref for AppId {
 	}
 }
 
+/// The maximum length of an action identifier, in bytes.
+const MAX_ACTION_LEN: usize = 256;
+
+/// The identifier of an action, as configured in the [Developer Portal](https://developer.worldcoin.org). Actions are case-sensitive, and may be empty to verify a user once for the whole app.
+#[repr(transparent)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct Action(String);
+
+/// Error returned when an invalid action is provided.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ActionError {
+	#[error("Action must be at most {MAX_ACTION_LEN} bytes long, got {0}")]
+	TooLong(usize),
+
+	#[error("Action must only contain printable ASCII characters without spaces, got {0:?}")]
+	InvalidCharacter(char),
+}
+
+impl Action {
+	/// Returns the action identifier as a string slice.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for Action {
+	type Err = ActionError;
+
+	fn from_str(action: &str) -> Result<Self, Self::Err> {
+		if let Some(c) = action.chars().find(|c| !c.is_ascii_graphic()) {
+			return Err(ActionError::InvalidCharacter(c));
+		}
+
+		if action.len() > MAX_ACTION_LEN {
+			return Err(ActionError::TooLong(action.len()));
+		}
+
+		Ok(Self(action.to_string()))
+	}
+}
+
+impl TryFrom<&str> for Action {
+	type Error = ActionError;
+
+	fn try_from(action: &str) -> Result<Self, Self::Error> {
+		action.parse()
+	}
+}
+
+impl TryFrom<&String> for Action {
+	type Error = ActionError;
+
+	fn try_from(action: &String) -> Result<Self, Self::Error> {
+		action.parse()
+	}
+}
+
+impl TryFrom<String> for Action {
+	type Error = ActionError;
+
+	fn try_from(action: String) -> Result<Self, Self::Error> {
+		action.parse()
+	}
+}
+
+impl From<Action> for String {
+	fn from(action: Action) -> Self {
+		action.0
+	}
+}
+
+impl Display for Action {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+impl Deref for Action {
+	type Target = str;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
 /// The URL of the Wallet Bridge to use for establishing a connection with the user's World App. Defaults to the bridge service hosted by Worldcoin. Only change this if you are running your own bridge service.
+#[cfg(feature = "session")]
 #[repr(transparent)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BridgeUrl(pub(crate) url::Url);
 
+#[cfg(feature = "session")]
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum BridgeUrlError {
+	#[error("Bridge URL must have a host.")]
+	MissingHost,
+
 	#[error("Bridge URL must use HTTPS.")]
 	NotHttps,
 
@@ -169,12 +551,30 @@ pub enum BridgeUrlError {
 	ContainsFragment,
 }
 
+#[cfg(feature = "session")]
+impl BridgeUrlError {
+	/// The category this error belongs to, which is always `ErrorKind::Configuration` as the URL is provided by the caller.
+	#[must_use]
+	pub const fn kind(&self) -> ErrorKind {
+		match self {
+			Self::MissingHost
+			| Self::NotHttps
+			| Self::NotDefaultPort
+			| Self::ContainsPath
+			| Self::ContainsQuery
+			| Self::ContainsFragment => ErrorKind::Configuration,
+		}
+	}
+}
+
+#[cfg(feature = "session")]
 impl Default for BridgeUrl {
 	fn default() -> Self {
 		Self(Url::parse(DEFAULT_BRIDGE_URL).unwrap())
 	}
 }
 
+#[cfg(feature = "session")]
 impl Deref for BridgeUrl {
 	type Target = Url;
 
@@ -183,11 +583,13 @@ impl Deref for BridgeUrl {
 	}
 }
 
+#[cfg(feature = "session")]
 impl TryFrom<Url> for BridgeUrl {
 	type Error = BridgeUrlError;
 
 	fn try_from(url: Url) -> Result<Self, Self::Error> {
-		if ["localhost", "127.0.0.1"].contains(&url.host_str().unwrap()) {
+		let host = url.host_str().ok_or(BridgeUrlError::MissingHost)?;
+		if ["localhost", "127.0.0.1"].contains(&host) {
 			return Ok(Self(url));
 		};
 
@@ -215,7 +617,10 @@ impl TryFrom<Url> for BridgeUrl {
 	}
 }
 
-/// The proof of verification returned by the World ID Bridge.
+/// The proof of verification returned by the World ID Bridge, as decrypted from its payload.
+///
+/// It carries the same fields as `Proof`, but names the verification with the credential type the user proved rather than the verification level it grants. Convert it into a `Proof` to verify it.
+#[cfg(feature = "session")]
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct BridgeProof {
 	/// The Zero-knowledge proof of the verification. A hex string, ABI encoded.
@@ -228,6 +633,7 @@ pub struct BridgeProof {
 	pub credential_type: CredentialType,
 }
 
+#[cfg(feature = "session")]
 impl From<BridgeProof> for Proof {
 	fn from(val: BridgeProof) -> Self {
 		Self {
@@ -239,19 +645,404 @@ impl From<BridgeProof> for Proof {
 	}
 }
 
+/// Error returned when a proof's verification level can't be expressed as a single credential type.
+#[cfg(feature = "session")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Verification level {0} has no matching credential type")]
+pub struct BridgeProofError(pub VerificationLevel);
+
+#[cfg(feature = "session")]
+impl TryFrom<Proof> for BridgeProof {
+	type Error = BridgeProofError;
+
+	fn try_from(proof: Proof) -> Result<Self, Self::Error> {
+		// Not a `From<VerificationLevel>` conversion: levels that don't correspond to exactly one credential type must be rejected here.
+		let credential_type = match proof.verification_level {
+			VerificationLevel::Orb => CredentialType::Orb,
+			VerificationLevel::Device => CredentialType::Device,
+		};
+
+		Ok(Self {
+			proof: proof.proof,
+			merkle_root: proof.merkle_root,
+			nullifier_hash: proof.nullifier_hash,
+			credential_type,
+		})
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use proptest::prelude::*;
+
+	#[cfg(feature = "session")]
+	#[test]
+	fn test_bridge_url_errors() {
+		for (url, error) in [
+			("data:text/plain,bridge", "Bridge URL must have a host."),
+			("http://bridge.example", "Bridge URL must use HTTPS."),
+			(
+				"https://bridge.example:8443",
+				"Bridge URL must use the default port.",
+			),
+			(
+				"https://bridge.example/path",
+				"Bridge URL must not contain a path.",
+			),
+			(
+				"https://bridge.example/?query",
+				"Bridge URL must not contain a query.",
+			),
+			(
+				"https://bridge.example/#fragment",
+				"Bridge URL must not contain a fragment.",
+			),
+		] {
+			let result = BridgeUrl::try_from(Url::parse(url).unwrap()).unwrap_err();
+			assert_eq!(result.to_string(), error);
+			assert_eq!(result.kind(), ErrorKind::Configuration);
+		}
+
+		BridgeUrl::try_from(Url::parse("https://bridge.example").unwrap()).unwrap();
+	}
 
 	#[test]
 	fn test_app_id() {
 		assert_eq!(AppId::from_str("app_123").unwrap().0, "app_123");
 		assert_eq!(
 			AppId::from_str("test").unwrap_err(),
-			AppIdError("test".to_string())
+			AppIdError::InvalidPrefix("test".to_string())
 		);
 
 		assert!(!AppId::from_str("app_123").unwrap().is_staging());
 		assert!(AppId::from_str("app_staging_123").unwrap().is_staging());
+		assert_eq!(
+			AppId::from_str("app_123").unwrap().environment(),
+			Environment::Production
+		);
+		assert_eq!(
+			AppId::from_str("app_staging_123").unwrap().environment(),
+			Environment::Staging
+		);
+	}
+
+	#[test]
+	fn test_app_id_validation() {
+		for (app_id, expected) in [
+			("app_123", Ok(Environment::Production)),
+			(
+				"app_ce4cb73cb75fc3b73b71ffb4de178410",
+				Ok(Environment::Production),
+			),
+			(
+				"app_staging_45068dca85829d2fd90e2dd6f0bff997",
+				Ok(Environment::Staging),
+			),
+			("app_staging_1", Ok(Environment::Staging)),
+			("test", Err(AppIdError::InvalidPrefix("test".to_string()))),
+			(
+				"App_123",
+				Err(AppIdError::InvalidPrefix("App_123".to_string())),
+			),
+			(
+				"staging_app_123",
+				Err(AppIdError::InvalidPrefix("staging_app_123".to_string())),
+			),
+			(
+				"app_mystagingthing",
+				Err(AppIdError::InvalidCharacter(
+					"app_mystagingthing".to_string(),
+				)),
+			),
+			(
+				"app_12 34",
+				Err(AppIdError::InvalidCharacter("app_12 34".to_string())),
+			),
+			(
+				"app_CE4CB73C",
+				Err(AppIdError::InvalidCharacter("app_CE4CB73C".to_string())),
+			),
+			("app_", Err(AppIdError::InvalidLength("app_".to_string()))),
+			(
+				"app_staging_",
+				Err(AppIdError::InvalidLength("app_staging_".to_string())),
+			),
+			(
+				"app_ce4cb73cb75fc3b73b71ffb4de1784100",
+				Err(AppIdError::InvalidLength(
+					"app_ce4cb73cb75fc3b73b71ffb4de1784100".to_string(),
+				)),
+			),
+		] {
+			assert_eq!(
+				AppId::from_str(app_id).map(|app_id| app_id.environment()),
+				expected,
+				"{app_id}"
+			);
+		}
+	}
+
+	#[test]
+	fn test_app_id_serde() {
+		for app_id in ["app_123", "app_staging_45068dca85829d2fd90e2dd6f0bff997"] {
+			let parsed: AppId = serde_json::from_value(app_id.into()).unwrap();
+
+			assert_eq!(parsed.to_string(), app_id);
+			assert_eq!(parsed.as_ref(), app_id);
+			assert_eq!(serde_json::to_value(&parsed).unwrap(), app_id);
+		}
+
+		let error = serde_json::from_str::<AppId>("\"my_app\"").unwrap_err();
+		assert!(error.to_string().contains("my_app"), "{error}");
+		assert!(serde_json::from_str::<AppId>("123").is_err());
+	}
+
+	#[test]
+	fn test_action() {
+		assert_eq!(Action::from_str("vote_1").unwrap().as_str(), "vote_1");
+		assert_eq!(Action::from_str("").unwrap(), Action::default());
+		assert_ne!(
+			Action::from_str("Vote_1").unwrap(),
+			Action::from_str("vote_1").unwrap()
+		);
+		assert_eq!(
+			Action::from_str("cast vote").unwrap_err(),
+			ActionError::InvalidCharacter(' ')
+		);
+		assert_eq!(
+			Action::from_str(&"a".repeat(257)).unwrap_err(),
+			ActionError::TooLong(257)
+		);
+
+		assert_eq!(
+			serde_json::to_string(&Action::from_str("vote_1").unwrap()).unwrap(),
+			"\"vote_1\""
+		);
+		assert!(serde_json::from_str::<Action>("\"cast vote\"").is_err());
+	}
+
+	proptest! {
+		#[test]
+		fn test_valid_actions_roundtrip(action in "[!-~]{0,256}") {
+			let parsed = Action::from_str(&action).unwrap();
+
+			prop_assert_eq!(parsed.to_string(), action.clone());
+			prop_assert_eq!(
+				serde_json::from_str::<Action>(&serde_json::to_string(&parsed).unwrap()).unwrap(),
+				parsed
+			);
+		}
+
+		#[test]
+		fn test_actions_with_invalid_characters(
+			prefix in "[!-~]{0,100}",
+			invalid in "[^!-~]",
+			suffix in "[!-~]{0,100}",
+		) {
+			let action = format!("{prefix}{invalid}{suffix}");
+			prop_assert!(Action::from_str(&action).is_err());
+		}
+
+		#[test]
+		fn test_long_actions(action in "[!-~]{257,512}") {
+			prop_assert_eq!(
+				Action::from_str(&action).unwrap_err(),
+				ActionError::TooLong(action.len())
+			);
+		}
+	}
+
+	#[test]
+	fn test_credential_type() {
+		for credential_type in [CredentialType::Orb, CredentialType::Device] {
+			assert_eq!(
+				credential_type.to_string().parse::<CredentialType>(),
+				Ok(credential_type)
+			);
+			assert_eq!(
+				serde_json::to_value(credential_type).unwrap(),
+				credential_type.to_string()
+			);
+			assert_eq!(
+				serde_json::from_value::<CredentialType>(credential_type.to_string().into())
+					.unwrap(),
+				credential_type
+			);
+			assert_eq!(
+				CredentialType::from(VerificationLevel::from(credential_type)),
+				credential_type
+			);
+		}
+
+		assert_eq!(
+			CredentialType::from_str("Orb"),
+			Err(CredentialTypeError("Orb".to_string()))
+		);
+		assert_eq!(
+			CredentialType::from_str("phone").unwrap_err().to_string(),
+			"Unknown credential type \"phone\", expected orb or device"
+		);
+	}
+
+	#[test]
+	fn test_legacy_phone_credential_type() {
+		assert_eq!(
+			serde_json::from_str::<CredentialType>("\"phone\"").unwrap(),
+			CredentialType::Device
+		);
+		assert_eq!(
+			serde_json::to_string(&CredentialType::try_from("phone".to_string()).unwrap()).unwrap(),
+			"\"device\""
+		);
+		assert!(serde_json::from_str::<CredentialType>("\"Phone\"").is_err());
+	}
+
+	#[cfg(feature = "session")]
+	#[test]
+	fn test_legacy_phone_bridge_proof() {
+		let bridge_proof: BridgeProof = serde_json::from_value(serde_json::json!({
+			"proof": "0x1",
+			"merkle_root": "0x2",
+			"nullifier_hash": "0x3",
+			"credential_type": "phone",
+		}))
+		.unwrap();
+		assert_eq!(bridge_proof.credential_type, CredentialType::Device);
+
+		let json = serde_json::to_value(&bridge_proof).unwrap();
+		assert_eq!(json["credential_type"], "device");
+		assert_eq!(
+			Proof::from(bridge_proof).verification_level,
+			VerificationLevel::Device
+		);
+	}
+
+	#[test]
+	fn test_verification_level() {
+		for (level, names) in [
+			(VerificationLevel::Orb, ["orb", "ORB", "Orb", "oRb"]),
+			(
+				VerificationLevel::Device,
+				["device", "DEVICE", "Device", "dEvIcE"],
+			),
+		] {
+			assert_eq!(level.to_string(), names[0]);
+			assert_eq!(serde_json::to_value(level).unwrap(), names[0]);
+
+			for name in names {
+				assert_eq!(VerificationLevel::from_str(name), Ok(level));
+				assert_eq!(
+					serde_json::from_value::<VerificationLevel>(name.into()).unwrap(),
+					level
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn test_invalid_verification_level() {
+		assert_eq!(
+			VerificationLevel::from_str("phone")
+				.unwrap_err()
+				.to_string(),
+			"Unknown verification level \"phone\", expected one of: orb, device"
+		);
+		assert!(VerificationLevel::from_str("").is_err());
+		assert!(VerificationLevel::from_str(" orb").is_err());
+		assert!(serde_json::from_str::<VerificationLevel>("\"Phone\"").is_err());
+	}
+
+	#[test]
+	fn test_app_error_code() {
+		assert_eq!(
+			AppError::MaxVerificationsReached.code(),
+			"max_verifications_reached"
+		);
+		assert_eq!(AppError::FailedByHostApp.code(), "failed_by_host_app");
+		assert_eq!(
+			AppError::from_str("MaxVerificationsReached")
+				.unwrap_err()
+				.to_string(),
+			"Unknown World App error code \"MaxVerificationsReached\""
+		);
+	}
+
+	#[cfg(feature = "session")]
+	const CAPTURED_BRIDGE_PROOF: &str = r#"{"proof":"0x1aa8b8f3b2d2de5ff452c0e1a83e29d6bf46fb83ef35dc5957121ff3d3698a11","merkle_root":"0x2264a66d162d7893e12ea8e3c072c51e785bc085ad655f64c10c1a61e00f0bc2","nullifier_hash":"0x2bf8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8","credential_type":"orb"}"#;
+
+	#[cfg(feature = "session")]
+	#[test]
+	fn test_bridge_proof_round_trip() {
+		let bridge_proof: BridgeProof = serde_json::from_str(CAPTURED_BRIDGE_PROOF).unwrap();
+		assert_eq!(bridge_proof.credential_type, CredentialType::Orb);
+		assert_eq!(
+			serde_json::to_string(&bridge_proof).unwrap(),
+			CAPTURED_BRIDGE_PROOF
+		);
+
+		let proof = Proof::from(bridge_proof.clone());
+		assert_eq!(proof.verification_level, VerificationLevel::Orb);
+		assert_eq!(
+			serde_json::to_string(&proof).unwrap(),
+			CAPTURED_BRIDGE_PROOF.replace(r#""credential_type""#, r#""verification_level""#)
+		);
+		assert_eq!(
+			serde_json::from_str::<Proof>(&serde_json::to_string(&proof).unwrap()).unwrap(),
+			proof
+		);
+
+		assert_eq!(BridgeProof::try_from(proof), Ok(bridge_proof));
+	}
+
+	proptest! {
+		#[test]
+		fn test_app_error_roundtrip(error in any::<AppError>()) {
+			prop_assert_eq!(AppError::from_str(error.code()), Ok(error));
+			prop_assert_eq!(serde_json::to_value(error).unwrap(), error.code());
+			prop_assert_eq!(
+				serde_json::from_value::<AppError>(error.code().into()).unwrap(),
+				error
+			);
+		}
+
+		#[test]
+		fn test_app_id_roundtrip(app_id in any::<AppId>()) {
+			prop_assert_eq!(AppId::from_str(&app_id.to_string()), Ok(app_id.clone()));
+			prop_assert_eq!(
+				serde_json::from_str::<AppId>(&serde_json::to_string(&app_id).unwrap()).unwrap(),
+				app_id
+			);
+		}
+
+		#[test]
+		fn test_verification_level_roundtrip(level in any::<VerificationLevel>()) {
+			prop_assert_eq!(VerificationLevel::from_str(&level.to_string()), Ok(level));
+			prop_assert_eq!(
+				serde_json::from_value::<VerificationLevel>(serde_json::to_value(level).unwrap())
+					.unwrap(),
+				level
+			);
+		}
+
+		#[cfg(feature = "session")]
+		#[test]
+		fn test_bridge_proof_try_from_proof(proof in any::<Proof>()) {
+			let bridge_proof = BridgeProof::try_from(proof.clone()).unwrap();
+			prop_assert_eq!(
+				serde_json::from_str::<BridgeProof>(&serde_json::to_string(&bridge_proof).unwrap())
+					.unwrap(),
+				bridge_proof.clone()
+			);
+			prop_assert_eq!(Proof::from(bridge_proof), proof);
+		}
+	}
+
+	#[test]
+	fn test_verification_level_satisfies() {
+		assert!(VerificationLevel::Orb.satisfies(VerificationLevel::Orb));
+		assert!(VerificationLevel::Orb.satisfies(VerificationLevel::Device));
+		assert!(VerificationLevel::Device.satisfies(VerificationLevel::Device));
+		assert!(!VerificationLevel::Device.satisfies(VerificationLevel::Orb));
 	}
 }
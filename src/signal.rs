@@ -0,0 +1,379 @@
+//! The values a proof can commit to as its signal.
+
+use alloc::{string::String, vec::Vec};
+use alloy_primitives::{Address, Bytes};
+use ruint::aliases::U256;
+
+mod sealed {
+	pub trait Sealed {}
+}
+
+/// A value that can be used as the signal of a proof, such as a wallet address or a message.
+///
+/// Signals are encoded as Solidity's `abi.encodePacked` would, so a tuple commits to the concatenation of its elements. This trait is sealed, see `SolSignal` to use any other Solidity value.
+pub trait Signal: sealed::Sealed {
+	/// Appends the packed encoding of the signal to `out`.
+	#[doc(hidden)]
+	fn encode_packed_to(&self, out: &mut Vec<u8>);
+
+	/// The packed encoding of the signal, as Solidity's `abi.encodePacked`.
+	#[doc(hidden)]
+	fn encode_packed(&self) -> Vec<u8> {
+		let mut out = Vec::new();
+		self.encode_packed_to(&mut out);
+		out
+	}
+
+	/// The packed encoding of the signal, if it can be borrowed without encoding it first.
+	#[doc(hidden)]
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		None
+	}
+
+	/// The hash of the signal, if it was already computed.
+	#[doc(hidden)]
+	fn prehashed(&self) -> Option<U256> {
+		None
+	}
+}
+
+impl<T: Signal + ?Sized> sealed::Sealed for &T {}
+impl<T: Signal + ?Sized> Signal for &T {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		(**self).encode_packed_to(out);
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		(**self).packed_bytes()
+	}
+
+	fn prehashed(&self) -> Option<U256> {
+		(**self).prehashed()
+	}
+}
+
+/// A signal hash computed elsewhere, for example by IDKit JS in the frontend, used as-is instead of hashing a signal.
+///
+/// Use it when the value is already a field element that the proof commits to directly, like the output of another hash. A bare `U256` is a value to be hashed, so `PreHashedSignal(x)` and `x` produce different signal hashes. Inside a tuple, it's encoded like any other `U256`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PreHashedSignal(pub U256);
+
+impl sealed::Sealed for PreHashedSignal {}
+impl Signal for PreHashedSignal {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		self.0.encode_packed_to(out);
+	}
+
+	fn prehashed(&self) -> Option<U256> {
+		Some(self.0)
+	}
+}
+
+impl sealed::Sealed for str {}
+impl Signal for str {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(self.as_bytes());
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(self.as_bytes())
+	}
+}
+
+impl sealed::Sealed for String {}
+impl Signal for String {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(self.as_bytes());
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(self.as_bytes())
+	}
+}
+
+/// Byte slices are encoded as Solidity `bytes`, without padding.
+impl sealed::Sealed for [u8] {}
+impl Signal for [u8] {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(self);
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(self)
+	}
+}
+
+impl sealed::Sealed for Vec<u8> {}
+impl Signal for Vec<u8> {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(self);
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(self)
+	}
+}
+
+impl sealed::Sealed for Bytes {}
+impl Signal for Bytes {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(self);
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(self)
+	}
+}
+
+/// Integers are encoded as Solidity `uint256`, and hashed like any other value. Wrap a value in `PreHashedSignal` to use it as the signal hash instead.
+impl sealed::Sealed for U256 {}
+impl Signal for U256 {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(&self.to_be_bytes::<32>());
+	}
+}
+
+impl sealed::Sealed for Address {}
+impl Signal for Address {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(self.as_slice());
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(self.as_slice())
+	}
+}
+
+/// The empty signal, for proofs that don't commit to anything.
+impl sealed::Sealed for () {}
+impl Signal for () {
+	fn encode_packed_to(&self, _: &mut Vec<u8>) {}
+}
+
+macro_rules! impl_signal_for_tuple {
+	($($name:ident),+) => {
+		impl<$($name: Signal),+> sealed::Sealed for ($($name,)+) {}
+		impl<$($name: Signal),+> Signal for ($($name,)+) {
+			#[allow(non_snake_case)]
+			fn encode_packed_to(&self, out: &mut Vec<u8>) {
+				let ($($name,)+) = self;
+				$($name.encode_packed_to(out);)+
+			}
+		}
+	};
+}
+
+impl_signal_for_tuple!(A);
+impl_signal_for_tuple!(A, B);
+impl_signal_for_tuple!(A, B, C);
+impl_signal_for_tuple!(A, B, C, D);
+impl_signal_for_tuple!(A, B, C, D, E);
+impl_signal_for_tuple!(A, B, C, D, E, F);
+
+/// Builds a composite signal one value at a time, packed as Solidity's `abi.encodePacked` would.
+///
+/// This matches `solidityEncode(types, values)` in IDKit JS, so a signal built here hashes to the same value as the one the frontend requested a proof for. Values are packed in the order they're pushed, with no padding or length prefixes between them.
+///
+/// ```
+/// # use idkit::{exports::{Address, U256}, SignalBuilder};
+/// let signal = SignalBuilder::new()
+///     .push_address(Address::ZERO)
+///     .push_uint(U256::from(42))
+///     .push_string("vote_1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignalBuilder(Vec<u8>);
+
+impl SignalBuilder {
+	/// Create a builder for the empty signal.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self(Vec::new())
+	}
+
+	/// Appends an `address`, as its 20 bytes.
+	#[must_use]
+	pub fn push_address(mut self, address: Address) -> Self {
+		address.encode_packed_to(&mut self.0);
+		self
+	}
+
+	/// Appends a `uint256`, as its 32 big-endian bytes.
+	#[must_use]
+	pub fn push_uint(mut self, value: U256) -> Self {
+		value.encode_packed_to(&mut self.0);
+		self
+	}
+
+	/// Appends a `string`, as its raw UTF-8 bytes.
+	#[must_use]
+	pub fn push_string(mut self, value: &str) -> Self {
+		value.encode_packed_to(&mut self.0);
+		self
+	}
+
+	/// Appends `bytes`, as-is.
+	#[must_use]
+	pub fn push_bytes(mut self, value: &[u8]) -> Self {
+		value.encode_packed_to(&mut self.0);
+		self
+	}
+
+	/// The signal hash of the values pushed so far.
+	#[must_use]
+	pub fn hash(&self) -> U256 {
+		crate::hashing::encode_signal(self)
+	}
+
+	/// The packed encoding of the values pushed so far, which hashes to the same signal hash.
+	#[must_use]
+	pub fn into_signal(self) -> Bytes {
+		self.0.into()
+	}
+}
+
+impl sealed::Sealed for SignalBuilder {}
+impl Signal for SignalBuilder {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(&self.0);
+	}
+
+	fn packed_bytes(&self) -> Option<&[u8]> {
+		Some(&self.0)
+	}
+}
+
+/// Any Solidity value used as a signal, encoded with `SolValue::abi_encode_packed`.
+#[cfg(feature = "sol-value")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolSignal<T>(pub T);
+
+#[cfg(feature = "sol-value")]
+impl<T: alloy_sol_types::SolValue> sealed::Sealed for SolSignal<T> {}
+#[cfg(feature = "sol-value")]
+impl<T: alloy_sol_types::SolValue> Signal for SolSignal<T> {
+	fn encode_packed_to(&self, out: &mut Vec<u8>) {
+		out.extend_from_slice(&self.0.abi_encode_packed());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::hashing::{encode_signal, hash_to_field};
+	use alloy_primitives::address;
+	use alloy_sol_types::SolValue;
+
+	fn assert_same_hash<S: Signal, V: SolValue>(signal: &S, value: &V) {
+		assert_eq!(signal.encode_packed(), value.abi_encode_packed());
+		assert_eq!(
+			hash_to_field(&signal.encode_packed()),
+			hash_to_field(&value.abi_encode_packed())
+		);
+	}
+
+	#[test]
+	fn test_matches_sol_value_encoding() {
+		let address = Address::repeat_byte(0x42);
+		let bytes = vec![1_u8, 2, 3];
+
+		assert_same_hash(&"test", &"test");
+		assert_same_hash(&String::from("test"), &String::from("test"));
+		assert_same_hash(&"", &"");
+		assert_same_hash(&U256::from(1), &U256::from(1));
+		assert_same_hash(&address, &address);
+		assert_same_hash(&(), &());
+		assert_same_hash(&bytes.as_slice(), &Bytes::from(bytes.clone()));
+		assert_same_hash(&bytes, &Bytes::from(bytes.clone()));
+		assert_same_hash(&(U256::from(1), "test"), &(U256::from(1), "test"));
+		assert_same_hash(
+			&(address, U256::from(7), "vote"),
+			&(address, U256::from(7), "vote"),
+		);
+	}
+
+	#[test]
+	fn test_known_hashes() {
+		assert_eq!(
+			format!("0x{:064x}", hash_to_field(&"test".encode_packed())),
+			"0x009c22ff5f21f0b81b113e63f7db6da94fedef11b2119b4088b89664fb9a3cb6"
+		);
+		assert_eq!(
+			format!(
+				"0x{:064x}",
+				hash_to_field(&(U256::from(1), "test").encode_packed())
+			),
+			"0x0088c8c90482320f18b0c0842feaeab88065fd7ef3ef7b06066af823d8eef6f9"
+		);
+	}
+
+	#[test]
+	fn test_prehashed_signal() {
+		let hash = crate::hashing::encode_signal("test");
+
+		assert_eq!(PreHashedSignal(hash).prehashed(), Some(hash));
+		assert_eq!(crate::hashing::encode_signal(&PreHashedSignal(hash)), hash);
+		assert_eq!("test".prehashed(), None);
+	}
+
+	#[cfg(feature = "sol-value")]
+	#[test]
+	fn test_sol_signal() {
+		let value = (U256::from(1), true, "test");
+
+		assert_same_hash(&SolSignal(value), &value);
+	}
+
+	#[test]
+	fn test_signal_builder() {
+		let wallet = address!("11e5b5e7a3e5c9f1a1f12c1d1c1b1a1918171615");
+
+		// Reference hashes of `solidityEncode(types, values)`, as computed by IDKit JS
+		for (signal, expected) in [
+			(
+				SignalBuilder::new()
+					.push_address(wallet)
+					.push_uint(U256::from(42))
+					.push_string("vote_1"),
+				"0x00ac6be944af02bebd82c994e76eb1e1c76cbe932da30c89f8d44d6f56113bd9",
+			),
+			(
+				SignalBuilder::new()
+					.push_string("hello")
+					.push_bytes(&[0xde, 0xad, 0xbe, 0xef]),
+				"0x00759672963d7495d8aa1eee43abb632b741dceccaccd4a9fb7ed79666b4efe7",
+			),
+			(
+				SignalBuilder::new()
+					.push_uint(U256::from(1))
+					.push_uint(U256::from(2)),
+				"0x00e90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2",
+			),
+			(
+				SignalBuilder::new().push_address(wallet),
+				"0x00d78401c1c280691d502de23a972612871634abee599509fe021d284d314a60",
+			),
+			(
+				SignalBuilder::new(),
+				"0x00c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a4",
+			),
+		] {
+			assert_eq!(format!("0x{:064x}", signal.hash()), expected);
+			assert_eq!(encode_signal(&signal.clone().into_signal()), signal.hash());
+		}
+	}
+
+	#[test]
+	fn test_signal_builder_matches_tuples() {
+		let wallet = address!("11e5b5e7a3e5c9f1a1f12c1d1c1b1a1918171615");
+
+		assert_eq!(
+			SignalBuilder::new()
+				.push_address(wallet)
+				.push_uint(U256::from(42))
+				.push_string("vote_1")
+				.hash(),
+			encode_signal(&(wallet, U256::from(42), "vote_1"))
+		);
+	}
+}
@@ -0,0 +1,93 @@
+//! `proptest` strategies for the public data types, for fuzzing code built on top of this crate.
+//!
+//! Generated values are structurally valid: hex fields have the right length, and app ids have a valid prefix and identifier. Every type also implements `Arbitrary` with its strategy, so `any::<Proof>()` works as well.
+
+use alloc::format;
+use proptest::{collection::vec, prelude::*, sample::select};
+
+#[cfg(feature = "session")]
+use crate::session::Status;
+use crate::{
+	hex::encode_prefixed,
+	protocol::PROOF_WORDS,
+	session::{AppError, AppId, CredentialType, VerificationLevel},
+	Proof,
+};
+
+/// Any verification level.
+pub fn verification_level() -> impl Strategy<Value = VerificationLevel> {
+	select(VerificationLevel::ALL.to_vec())
+}
+
+/// Any credential type.
+pub fn credential_type() -> impl Strategy<Value = CredentialType> {
+	select(vec![CredentialType::Orb, CredentialType::Device])
+}
+
+/// Any error the World App can return.
+pub fn app_error() -> impl Strategy<Value = AppError> {
+	select(AppError::ALL.to_vec())
+}
+
+/// A production or staging app id, with an identifier of 1 to 32 lowercase hex characters.
+pub fn app_id() -> impl Strategy<Value = AppId> {
+	(select(vec!["app_", "app_staging_"]), "[0-9a-f]{1,32}")
+		.prop_map(|(prefix, identifier)| AppId(format!("{prefix}{identifier}")))
+}
+
+/// A well-formed proof, with lowercase hex fields of the expected lengths. The proof itself isn't valid.
+pub fn proof() -> impl Strategy<Value = Proof> {
+	(
+		vec(any::<u8>(), PROOF_WORDS * 32),
+		any::<[u8; 32]>(),
+		any::<[u8; 32]>(),
+		verification_level(),
+	)
+		.prop_map(
+			|(proof, merkle_root, nullifier_hash, verification_level)| Proof {
+				proof: encode_prefixed(&proof),
+				merkle_root: encode_prefixed(&merkle_root),
+				nullifier_hash: encode_prefixed(&nullifier_hash),
+				verification_level,
+			},
+		)
+}
+
+/// Any status of a verification request, with a well-formed proof once confirmed.
+#[cfg(feature = "session")]
+pub fn status() -> impl Strategy<Value = Status> {
+	prop_oneof![
+		Just(Status::WaitingForConnection),
+		Just(Status::AwaitingConfirmation),
+		proof().prop_map(Status::Confirmed),
+		app_error().prop_map(Status::Failed),
+	]
+}
+
+macro_rules! impl_arbitrary {
+	($($ty:ty => $strategy:ident),* $(,)?) => {
+		$(
+			impl Arbitrary for $ty {
+				type Parameters = ();
+				type Strategy = BoxedStrategy<Self>;
+
+				fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+					$strategy().boxed()
+				}
+			}
+		)*
+	};
+}
+
+impl_arbitrary! {
+	VerificationLevel => verification_level,
+	CredentialType => credential_type,
+	AppError => app_error,
+	AppId => app_id,
+	Proof => proof,
+}
+
+#[cfg(feature = "session")]
+impl_arbitrary! {
+	Status => status,
+}
@@ -0,0 +1,366 @@
+//! A [tower](https://docs.rs/tower) layer that verifies proofs before requests reach the inner service, for hyper, axum or any other `http`-based stack.
+//!
+//! Requests with a valid proof are forwarded with their `VerifiedProof` in the request extensions, and the others are answered with a JSON `ErrorReply`.
+//!
+//! ```no_run
+//! use idkit::{
+//!     session::{Action, AppId},
+//!     tower::{header_extractor, VerifyLayer},
+//! };
+//! use tower::ServiceBuilder;
+//!
+//! # fn example<S>(app_id: AppId, action: Action, service: S) {
+//! let service = ServiceBuilder::new()
+//!     .layer(VerifyLayer::new(app_id, action).extractor(header_extractor("x-world-id-proof")))
+//!     .service(service);
+//! # }
+//! ```
+
+use ::tower::{Layer, Service};
+use alloy_primitives::Bytes;
+use http::{header, request::Parts, Request, Response};
+use http_body::Body;
+use http_body_util::{BodyExt, Full, Limited};
+use std::{
+	future::Future,
+	pin::Pin,
+	sync::Arc,
+	task::{Context, Poll},
+};
+
+pub use crate::verify::VerifiedProof;
+use crate::{
+	session::{Action, AppId},
+	verify::{
+		extract::{body_signal, MAX_BODY_LEN},
+		ErrorReply, VerifyConfig,
+	},
+	Proof, SuccessResult,
+};
+
+/// Takes the proof and the packed encoding of its signal (see `Signal`) from the request, with its body buffered.
+pub type ProofExtractor =
+	Arc<dyn Fn(&Parts, &[u8]) -> Result<(Proof, Bytes), ErrorReply> + Send + Sync>;
+
+/// Takes the proof from the JSON body (in the format of IDKit JS' success result), with the signal sent alongside it, if any.
+#[must_use]
+pub fn body_extractor() -> ProofExtractor {
+	Arc::new(|_, body| parse_success_result(body))
+}
+
+/// Takes the proof from the `name` header, holding the same JSON as `body_extractor`.
+#[must_use]
+pub fn header_extractor(name: impl Into<String>) -> ProofExtractor {
+	let name = name.into();
+
+	Arc::new(move |parts, _| {
+		let value = parts
+			.headers
+			.get(name.as_str())
+			.ok_or_else(|| ErrorReply::invalid_request(format!("missing {name} header")))?;
+
+		parse_success_result(value.as_bytes())
+	})
+}
+
+fn parse_success_result(value: &[u8]) -> Result<(Proof, Bytes), ErrorReply> {
+	let result: SuccessResult = serde_json::from_slice(value)
+		.map_err(|error| ErrorReply::invalid_request(error.to_string()))?;
+	let signal = body_signal(&result);
+
+	Ok((result.proof, signal))
+}
+
+/// Wraps services with a `VerifyService`, verifying proofs for `action` of `app_id`.
+#[derive(Clone)]
+pub struct VerifyLayer {
+	app_id: AppId,
+	action: Action,
+	extractor: ProofExtractor,
+	config: VerifyConfig,
+}
+
+impl VerifyLayer {
+	/// Verifies proofs for `action` of `app_id`, taken from the body with `body_extractor`, with the default configuration.
+	#[must_use]
+	pub fn new(app_id: AppId, action: Action) -> Self {
+		Self {
+			app_id,
+			action,
+			extractor: body_extractor(),
+			config: VerifyConfig::default(),
+		}
+	}
+
+	/// Takes the proof and its signal from the request with `extractor` instead, for example with `header_extractor`.
+	#[must_use]
+	pub fn extractor(mut self, extractor: ProofExtractor) -> Self {
+		self.extractor = extractor;
+		self
+	}
+
+	/// Sends requests to the Developer Portal with `config`.
+	#[must_use]
+	pub fn config(mut self, config: VerifyConfig) -> Self {
+		self.config = config;
+		self
+	}
+}
+
+impl std::fmt::Debug for VerifyLayer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("VerifyLayer")
+			.field("app_id", &self.app_id)
+			.field("action", &self.action)
+			.field("config", &self.config)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<S> Layer<S> for VerifyLayer {
+	type Service = VerifyService<S>;
+
+	fn layer(&self, inner: S) -> Self::Service {
+		VerifyService {
+			inner,
+			layer: self.clone(),
+		}
+	}
+}
+
+/// Verifies the proof of every request before calling the inner service.
+///
+/// The body of the request is buffered, and passed on to the inner service as `Full<Bytes>`.
+#[derive(Clone, Debug)]
+pub struct VerifyService<S> {
+	inner: S,
+	layer: VerifyLayer,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for VerifyService<S>
+where
+	S: Service<Request<Full<bytes::Bytes>>, Response = Response<ResBody>> + Clone + Send + 'static,
+	S::Future: Send,
+	B: Body + Send + 'static,
+	B::Data: Send,
+	B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+	ResBody: From<String>,
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+	fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, request: Request<B>) -> Self::Future {
+		// The clone might not be ready, so call the instance that was polled and keep the clone.
+		let clone = self.inner.clone();
+		let mut inner = std::mem::replace(&mut self.inner, clone);
+		let layer = self.layer.clone();
+
+		Box::pin(async move {
+			let (mut parts, body) = request.into_parts();
+			let body = match Limited::new(body, MAX_BODY_LEN).collect().await {
+				Ok(body) => body.to_bytes(),
+				Err(error) => return Ok(reply(&ErrorReply::invalid_request(error.to_string()))),
+			};
+
+			let verified = match (layer.extractor)(&parts, &body) {
+				Ok((proof, signal)) => {
+					VerifiedProof::verify(proof, signal, layer.app_id, &layer.action, layer.config)
+						.await
+				},
+				Err(error) => Err(error),
+			};
+
+			match verified {
+				Ok(verified) => {
+					parts.extensions.insert(verified);
+					inner
+						.call(Request::from_parts(parts, Full::new(body)))
+						.await
+				},
+				Err(error) => Ok(reply(&error)),
+			}
+		})
+	}
+}
+
+fn reply<ResBody: From<String>>(error: &ErrorReply) -> Response<ResBody> {
+	let mut response = Response::new(ResBody::from(
+		serde_json::to_string(error).unwrap_or_default(),
+	));
+	*response.status_mut() = error.status;
+	response.headers_mut().insert(
+		header::CONTENT_TYPE,
+		header::HeaderValue::from_static("application/json"),
+	);
+
+	response
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use ::tower::{service_fn, ServiceExt};
+	use http::StatusCode;
+	use std::{convert::Infallible, str::FromStr};
+	use url::Url;
+	use wiremock::{
+		matchers::{body_partial_json, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn layer(server: &MockServer) -> VerifyLayer {
+		VerifyLayer::new(
+			AppId::from_str("app_123").unwrap(),
+			Action::from_str("vote_1").unwrap(),
+		)
+		.config(VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		})
+	}
+
+	async fn call(
+		layer: VerifyLayer,
+		request: Request<Full<bytes::Bytes>>,
+	) -> (StatusCode, String) {
+		let service = layer.layer(service_fn(
+			|request: Request<Full<bytes::Bytes>>| async move {
+				let verified = request.extensions().get::<VerifiedProof>().unwrap();
+				let signal = String::from_utf8_lossy(&verified.signal).into_owned();
+
+				Ok::<_, Infallible>(Response::new(format!(
+					"{} voted {signal}",
+					verified.proof.nullifier_hash
+				)))
+			},
+		));
+
+		let response = service.oneshot(request).await.unwrap();
+
+		(response.status(), response.into_body())
+	}
+
+	async fn mock_portal(server: &MockServer, signal: &str) {
+		let mut body = serde_json::json!({
+			"action": "vote_1",
+			"nullifier_hash": fixtures::proof().nullifier_hash,
+		});
+		// An empty signal is sent without a signal hash.
+		if !signal.is_empty() {
+			body["signal_hash"] = serde_json::json!(crate::hashing::FieldElement::signal(signal));
+		}
+
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(body))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"success": true,
+				"action": "vote_1",
+				"nullifier_hash": fixtures::proof().nullifier_hash,
+			})))
+			.expect(1)
+			.mount(server)
+			.await;
+	}
+
+	#[tokio::test]
+	async fn test_body_proof() {
+		let server = MockServer::start().await;
+		mock_portal(&server, "yes").await;
+
+		let body = serde_json::to_string(&SuccessResult {
+			proof: fixtures::proof(),
+			signal: Some("yes".to_string()),
+		})
+		.unwrap();
+
+		assert_eq!(
+			call(layer(&server), Request::new(Full::from(body))).await,
+			(
+				StatusCode::OK,
+				format!("{} voted yes", fixtures::proof().nullifier_hash)
+			)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_header_proof() {
+		let server = MockServer::start().await;
+		mock_portal(&server, "").await;
+
+		let request = Request::builder()
+			.header("x-world-id-proof", fixtures::proof().to_idkit_js())
+			.body(Full::default())
+			.unwrap();
+
+		assert_eq!(
+			call(
+				layer(&server).extractor(header_extractor("x-world-id-proof")),
+				request
+			)
+			.await,
+			(
+				StatusCode::OK,
+				format!("{} voted ", fixtures::proof().nullifier_hash)
+			)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_rejected_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let (status, body) = call(
+			layer(&server),
+			Request::new(Full::from(fixtures::proof().to_idkit_js())),
+		)
+		.await;
+
+		assert_eq!(status, StatusCode::BAD_REQUEST);
+		assert_eq!(
+			serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+			serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+			})
+		);
+	}
+
+	#[tokio::test]
+	async fn test_missing_proof() {
+		let server = MockServer::start().await;
+
+		for (layer, body) in [
+			(layer(&server), "{}"),
+			(
+				layer(&server).extractor(header_extractor("x-world-id-proof")),
+				"",
+			),
+		] {
+			let (status, body) = call(layer, Request::new(Full::from(body))).await;
+
+			assert_eq!(status, StatusCode::BAD_REQUEST);
+			assert_eq!(
+				serde_json::from_str::<serde_json::Value>(&body).unwrap()["code"],
+				"invalid_request"
+			);
+		}
+
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+}
@@ -0,0 +1,204 @@
+//! [W3C Trace Context](https://www.w3.org/TR/trace-context/) propagation, so requests to the Wallet Bridge and the Developer Portal show up in the caller's distributed traces.
+
+use std::{fmt::Display, str::FromStr};
+
+/// The `traceparent` header.
+const TRACEPARENT: &str = "traceparent";
+/// The `tracestate` header.
+const TRACESTATE: &str = "tracestate";
+/// The length of a version `00` `traceparent`, which later versions may extend.
+const TRACEPARENT_LEN: usize = 55;
+/// The maximum length of a `tracestate` the crate propagates, as recommended by the specification.
+const MAX_TRACESTATE_LEN: usize = 512;
+
+/// An error when parsing a trace context.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TraceContextError {
+	#[error(
+		"invalid traceparent {0:?}, expected `00-<32 hex digits>-<16 hex digits>-<2 hex digits>`"
+	)]
+	InvalidTraceparent(String),
+
+	#[error("invalid tracestate, expected at most 512 printable ASCII characters")]
+	InvalidTracestate,
+}
+
+/// The trace context of the caller, propagated as the `traceparent` and `tracestate` headers of outbound requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+	traceparent: String,
+	tracestate: Option<String>,
+}
+
+impl TraceContext {
+	/// Creates a trace context from the `traceparent` and optional `tracestate` headers, e.g. those of the incoming request being served.
+	///
+	/// # Errors
+	///
+	/// Errors if `traceparent` isn't a valid `traceparent` header, or `tracestate` can't be sent as a header.
+	pub fn new(
+		traceparent: impl Into<String>,
+		tracestate: Option<String>,
+	) -> Result<Self, TraceContextError> {
+		let traceparent = traceparent.into();
+		if !is_valid_traceparent(&traceparent) {
+			return Err(TraceContextError::InvalidTraceparent(traceparent));
+		}
+
+		let tracestate = tracestate.filter(|tracestate| !tracestate.is_empty());
+		if tracestate.as_ref().is_some_and(|tracestate| {
+			tracestate.len() > MAX_TRACESTATE_LEN
+				|| !tracestate
+					.bytes()
+					.all(|byte| byte == b' ' || byte.is_ascii_graphic())
+		}) {
+			return Err(TraceContextError::InvalidTracestate);
+		}
+
+		Ok(Self {
+			traceparent,
+			tracestate,
+		})
+	}
+
+	/// The `traceparent` header.
+	#[must_use]
+	pub fn traceparent(&self) -> &str {
+		&self.traceparent
+	}
+
+	/// The `tracestate` header, if any.
+	#[must_use]
+	pub fn tracestate(&self) -> Option<&str> {
+		self.tracestate.as_deref()
+	}
+
+	/// The id of the trace, shared by every request of the trace.
+	#[must_use]
+	pub fn trace_id(&self) -> &str {
+		&self.traceparent[3..35]
+	}
+
+	/// The headers to add to outbound requests.
+	pub(crate) fn headers(&self) -> impl Iterator<Item = (&'static str, &str)> {
+		std::iter::once((TRACEPARENT, self.traceparent.as_str())).chain(
+			self.tracestate
+				.as_deref()
+				.map(|tracestate| (TRACESTATE, tracestate)),
+		)
+	}
+}
+
+impl FromStr for TraceContext {
+	type Err = TraceContextError;
+
+	/// Parses a `traceparent` header, without a `tracestate`.
+	fn from_str(traceparent: &str) -> Result<Self, Self::Err> {
+		Self::new(traceparent, None)
+	}
+}
+
+impl Display for TraceContext {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.traceparent)
+	}
+}
+
+/// Checks the `version-trace_id-parent_id-flags` format of a `traceparent`, where the ids can't be all zeros and version `ff` is forbidden. Versions after `00` may append fields.
+fn is_valid_traceparent(traceparent: &str) -> bool {
+	let Some(fields) = traceparent.get(..TRACEPARENT_LEN) else {
+		return false;
+	};
+	let rest = &traceparent[TRACEPARENT_LEN..];
+
+	let is_hex = |field: &str, len: usize| {
+		field.len() == len
+			&& field
+				.bytes()
+				.all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+	};
+	let is_zero = |field: &str| field.bytes().all(|byte| byte == b'0');
+
+	match fields.split('-').collect::<Vec<_>>()[..] {
+		[version, trace_id, parent_id, flags] => {
+			is_hex(version, 2)
+				&& version != "ff"
+				&& is_hex(trace_id, 32)
+				&& !is_zero(trace_id)
+				&& is_hex(parent_id, 16)
+				&& !is_zero(parent_id)
+				&& is_hex(flags, 2)
+				&& if version == "00" {
+					rest.is_empty()
+				} else {
+					rest.is_empty() || rest.starts_with('-')
+				}
+		},
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+	#[test]
+	fn test_valid_trace_contexts() {
+		let context = TraceContext::new(
+			TRACEPARENT,
+			Some("congo=t61rcWkgMzE,rojo=00f067aa0ba902b7".to_string()),
+		)
+		.unwrap();
+		assert_eq!(context.traceparent(), TRACEPARENT);
+		assert_eq!(
+			context.tracestate(),
+			Some("congo=t61rcWkgMzE,rojo=00f067aa0ba902b7")
+		);
+		assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+
+		assert_eq!(
+			TraceContext::new(TRACEPARENT, Some(String::new()))
+				.unwrap()
+				.tracestate(),
+			None
+		);
+		// Later versions may add fields.
+		TraceContext::from_str(
+			"cc-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-what-the-future-will-be-like",
+		)
+		.unwrap();
+	}
+
+	#[test]
+	fn test_invalid_trace_contexts() {
+		for traceparent in [
+			"",
+			"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+			"00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+			"00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+			"00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+			"ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+			"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+			"00_4bf92f3577b34da6a3ce929d0e0e4736_00f067aa0ba902b7_01",
+		] {
+			assert_eq!(
+				TraceContext::from_str(traceparent),
+				Err(TraceContextError::InvalidTraceparent(
+					traceparent.to_string()
+				)),
+				"{traceparent}"
+			);
+		}
+
+		assert_eq!(
+			TraceContext::new(TRACEPARENT, Some("rojo=00f067aa\n".to_string())),
+			Err(TraceContextError::InvalidTracestate)
+		);
+		assert_eq!(
+			TraceContext::new(TRACEPARENT, Some("a".repeat(513))),
+			Err(TraceContextError::InvalidTracestate)
+		);
+	}
+}
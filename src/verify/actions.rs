@@ -0,0 +1,148 @@
+use reqwest::{Method, StatusCode};
+
+use super::{send_request, ActionMetadata, Error, VerifyConfig};
+use crate::session::{Action, AppId};
+
+/// Fetch the metadata of an action as configured in the Developer Portal.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if it doesn't exist (`Error::ActionNotFound`), or if the response can't be retrieved or decoded.
+pub async fn get_action<A>(app_id: &AppId, action: A) -> Result<ActionMetadata, Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+{
+	get_action_with_config(app_id, action, &VerifyConfig::try_default()?).await
+}
+
+/// Fetch the metadata of an action as configured in the Developer Portal, with a custom configuration.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if it doesn't exist (`Error::ActionNotFound`), or if the response can't be retrieved or decoded.
+pub async fn get_action_with_config<A>(
+	app_id: &AppId,
+	action: A,
+	config: &VerifyConfig,
+) -> Result<ActionMetadata, Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+{
+	let action: Action = action.try_into()?;
+	let response = send_request::<()>(
+		config,
+		Method::GET,
+		&format!(
+			"/api/v2/apps/{}/actions/{}",
+			app_id.0,
+			urlencoding::encode(&action)
+		),
+		None,
+	)
+	.await
+	.map_err(|error| match error {
+		Error::UnexpectedResponse {
+			status: StatusCode::NOT_FOUND,
+			..
+		} => Error::ActionNotFound(action.to_string()),
+		error => error,
+	})?;
+
+	Ok(response.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{session::ActionError, verify::ActionStatus};
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn test_config(server: &MockServer) -> VerifyConfig {
+		VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		}
+	}
+
+	#[tokio::test]
+	async fn test_get_action() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/api/v2/apps/app_123/actions/vote_1"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"action": "vote_1",
+				"name": "Vote",
+				"description": "Cast your vote",
+				"max_verifications": 1,
+				"status": "active",
+				"created_at": "2024-01-01T00:00:00Z",
+			})))
+			.mount(&server)
+			.await;
+
+		let action = get_action_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			&test_config(&server),
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(
+			action,
+			ActionMetadata {
+				action: "vote_1".to_string(),
+				name: "Vote".to_string(),
+				description: "Cast your vote".to_string(),
+				max_verifications: 1,
+				status: ActionStatus::Active,
+			}
+		);
+	}
+
+	#[tokio::test]
+	async fn test_get_action_not_found() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/api/v2/apps/app_123/actions/missing"))
+			.respond_with(ResponseTemplate::new(404))
+			.mount(&server)
+			.await;
+
+		let error = get_action_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			"missing",
+			&test_config(&server),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(error, Error::ActionNotFound(action) if action == "missing"));
+	}
+
+	#[tokio::test]
+	async fn test_get_action_rejects_invalid_action() {
+		let server = MockServer::start().await;
+
+		let error = get_action_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			"../vote 1",
+			&test_config(&server),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::InvalidAction(ActionError::InvalidCharacter(' '))
+		));
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+}
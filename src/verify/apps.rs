@@ -0,0 +1,157 @@
+use reqwest::{Method, StatusCode};
+
+use super::{send_request, AppMetadata, Error, VerifyConfig};
+use crate::session::AppId;
+
+/// Fetch the metadata of an app as registered in the Developer Portal, to check that it exists and whether it's verified or a staging app.
+///
+/// # Errors
+///
+/// Errors if the app doesn't exist (`Error::AppNotFound`), or if the response can't be retrieved or decoded.
+pub async fn get_app_metadata(app_id: &AppId) -> Result<AppMetadata, Error> {
+	get_app_metadata_with_config(app_id, &VerifyConfig::try_default()?).await
+}
+
+/// Fetch the metadata of an app as registered in the Developer Portal, with a custom configuration.
+///
+/// # Errors
+///
+/// Errors if the app doesn't exist (`Error::AppNotFound`), or if the response can't be retrieved or decoded.
+pub async fn get_app_metadata_with_config(
+	app_id: &AppId,
+	config: &VerifyConfig,
+) -> Result<AppMetadata, Error> {
+	let response = send_request::<()>(
+		config,
+		Method::GET,
+		&format!("/api/v2/apps/{}", app_id.0),
+		None,
+	)
+	.await
+	.map_err(|error| match error {
+		Error::UnexpectedResponse {
+			status: StatusCode::NOT_FOUND,
+			..
+		} => Error::AppNotFound(app_id.0.clone()),
+		error => error,
+	})?;
+
+	Ok(response.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn test_config(server: &MockServer) -> VerifyConfig {
+		VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		}
+	}
+
+	#[test]
+	fn test_deserialize_verified_app() {
+		let app: AppMetadata = serde_json::from_str(
+			r#"{
+				"id": "app_ce4cb73cb75fc3b73b71ffb4de178410",
+				"name": "Worldcoin Docs",
+				"verified_app_logo": "https://world-id-public.s3.amazonaws.com/app_ce4cb73cb75fc3b73b71ffb4de178410/logo.png",
+				"is_verified": true,
+				"is_staging": false,
+				"engine": "cloud"
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			app,
+			AppMetadata {
+				id: "app_ce4cb73cb75fc3b73b71ffb4de178410".to_string(),
+				name: "Worldcoin Docs".to_string(),
+				is_verified: true,
+				is_staging: false,
+				logo_url: Some(
+					"https://world-id-public.s3.amazonaws.com/app_ce4cb73cb75fc3b73b71ffb4de178410/logo.png"
+						.to_string()
+				),
+			}
+		);
+	}
+
+	#[test]
+	fn test_deserialize_staging_app() {
+		let app: AppMetadata = serde_json::from_str(
+			r#"{
+				"app_id": "app_staging_45068dca85829d2fd90e2dd6f0bff997",
+				"name": "My Staging App",
+				"verified_app_logo": null,
+				"is_verified": false,
+				"is_staging": true
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			app,
+			AppMetadata {
+				id: "app_staging_45068dca85829d2fd90e2dd6f0bff997".to_string(),
+				name: "My Staging App".to_string(),
+				is_verified: false,
+				is_staging: true,
+				logo_url: None,
+			}
+		);
+	}
+
+	#[tokio::test]
+	async fn test_get_app_metadata() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/api/v2/apps/app_123"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"id": "app_123",
+				"name": "Test App",
+				"is_verified": false,
+				"is_staging": true,
+			})))
+			.mount(&server)
+			.await;
+
+		let app = get_app_metadata_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			&test_config(&server),
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(app.name, "Test App");
+		assert!(app.is_staging);
+		assert!(!app.is_verified);
+	}
+
+	#[tokio::test]
+	async fn test_get_app_metadata_not_found() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/api/v2/apps/app_123"))
+			.respond_with(ResponseTemplate::new(404))
+			.mount(&server)
+			.await;
+
+		let error = get_app_metadata_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			&test_config(&server),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(error, Error::AppNotFound(app_id) if app_id == "app_123"));
+	}
+}
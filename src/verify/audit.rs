@@ -0,0 +1,217 @@
+use std::{fmt, time::SystemTime};
+use time::OffsetDateTime;
+
+use super::{time::now, Error, VerificationRequest, VerifyErrorCode};
+use crate::session::{AppId, VerificationLevel};
+
+/// The outcome of a verification attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+	/// The Developer Portal accepted the proof.
+	Verified,
+	/// The Developer Portal rejected the proof.
+	Rejected,
+	/// The Developer Portal couldn't be reached, or responded unexpectedly.
+	Failed,
+}
+
+impl AuditOutcome {
+	/// The snake case name of the outcome, like `verified`.
+	#[must_use]
+	pub const fn as_str(self) -> &'static str {
+		match self {
+			Self::Verified => "verified",
+			Self::Rejected => "rejected",
+			Self::Failed => "failed",
+		}
+	}
+
+	/// The outcome of a verification that ended with `result`.
+	pub(super) const fn of<T>(result: &Result<T, Error>) -> Self {
+		match result {
+			Ok(_) => Self::Verified,
+			Err(Error::Verification(_)) => Self::Rejected,
+			Err(_) => Self::Failed,
+		}
+	}
+}
+
+/// A record of a verification attempt, for compliance and auditing purposes. The proof itself is never included.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AuditRecord {
+	pub app_id: String,
+	pub action: String,
+	pub nullifier_hash: String,
+	pub verification_level: Option<VerificationLevel>,
+	pub outcome: AuditOutcome,
+	/// The error code returned by the Developer Portal, if the proof was rejected.
+	pub error_code: Option<VerifyErrorCode>,
+	/// A description of the error, if the verification didn't succeed.
+	pub error: Option<String>,
+	/// When the request to the Developer Portal was sent.
+	#[serde(with = "time::serde::rfc3339")]
+	pub started_at: OffsetDateTime,
+	/// When the outcome of the verification was known.
+	#[serde(with = "time::serde::rfc3339")]
+	pub completed_at: OffsetDateTime,
+}
+
+/// A destination for the audit records of verification attempts, invoked once per attempt when configured in `VerifyConfig::audit_sink`.
+pub trait AuditSink: Send + Sync {
+	/// Record a verification attempt. This is called inline, so slow sinks should hand the record off instead of blocking.
+	fn record(&self, record: AuditRecord);
+}
+
+impl fmt::Debug for dyn AuditSink {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("AuditSink")
+	}
+}
+
+impl AuditRecord {
+	pub(super) fn new<T>(
+		request: &VerificationRequest<'_>,
+		app_id: &AppId,
+		started_at: SystemTime,
+		result: &Result<T, Error>,
+	) -> Self {
+		let (outcome, error_code, error) = match result {
+			Ok(_) => (AuditOutcome::Verified, None, None),
+			Err(Error::Verification(response)) => (
+				AuditOutcome::Rejected,
+				Some(response.code.clone()),
+				Some(response.detail.clone()),
+			),
+			Err(error) => (AuditOutcome::Failed, None, Some(error.to_string())),
+		};
+
+		Self {
+			outcome,
+			error,
+			error_code,
+			started_at: OffsetDateTime::from(started_at),
+			app_id: app_id.0.clone(),
+			action: request.action.to_string(),
+			completed_at: OffsetDateTime::from(now()),
+			nullifier_hash: request.nullifier_hash.to_string(),
+			verification_level: request.verification_level,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		fixtures,
+		verify::{verify_proof_with_config, VerifyConfig},
+	};
+	use std::{
+		str::FromStr,
+		sync::{Arc, Mutex},
+	};
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	#[derive(Default)]
+	struct CollectingSink(Mutex<Vec<AuditRecord>>);
+
+	impl AuditSink for CollectingSink {
+		fn record(&self, record: AuditRecord) {
+			self.0.lock().unwrap().push(record);
+		}
+	}
+
+	async fn verify(base_url: &str, sink: &Arc<CollectingSink>) -> AuditRecord {
+		let _ = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			"",
+			&VerifyConfig {
+				base_url: Url::parse(base_url).unwrap(),
+				audit_sink: Some(sink.clone()),
+				..VerifyConfig::default()
+			},
+		)
+		.await;
+
+		let mut records = sink.0.lock().unwrap();
+		assert_eq!(records.len(), 1);
+		records.pop().unwrap()
+	}
+
+	#[tokio::test]
+	async fn test_audit_success() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.mount(&server)
+			.await;
+
+		let record = verify(&server.uri(), &Arc::default()).await;
+
+		assert_eq!(record.app_id, "app_123");
+		assert_eq!(record.action, "vote_1");
+		assert_eq!(record.nullifier_hash, fixtures::proof().nullifier_hash);
+		assert_eq!(record.verification_level, Some(VerificationLevel::Orb));
+		assert_eq!(record.outcome, AuditOutcome::Verified);
+		assert_eq!(record.error_code, None);
+		assert_eq!(record.error, None);
+		assert!(record.completed_at >= record.started_at);
+	}
+
+	#[tokio::test]
+	async fn test_audit_invalid_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let record = verify(&server.uri(), &Arc::default()).await;
+
+		assert_eq!(record.outcome, AuditOutcome::Rejected);
+		assert_eq!(record.error_code, Some(VerifyErrorCode::InvalidProof));
+		assert_eq!(
+			record.error.as_deref(),
+			Some("The provided proof is invalid.")
+		);
+
+		let json = serde_json::to_value(&record).unwrap();
+		assert_eq!(json["outcome"], "rejected");
+		assert_eq!(json["error_code"], "invalid_proof");
+		assert!(OffsetDateTime::parse(
+			json["started_at"].as_str().unwrap(),
+			&time::format_description::well_known::Rfc3339
+		)
+		.is_ok());
+		assert!(json.get("proof").is_none());
+		assert!(json.get("merkle_root").is_none());
+		assert_eq!(serde_json::from_value::<AuditRecord>(json).unwrap(), record);
+	}
+
+	#[tokio::test]
+	async fn test_audit_transport_failure() {
+		let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		drop(listener);
+
+		let record = verify(&format!("http://{address}"), &Arc::default()).await;
+
+		assert_eq!(record.app_id, "app_123");
+		assert_eq!(record.outcome, AuditOutcome::Failed);
+		assert_eq!(record.error_code, None);
+		assert!(record.error.is_some());
+	}
+}
@@ -0,0 +1,307 @@
+use futures_util::future::join_all;
+use ruint::aliases::U256;
+use std::sync::{Mutex, PoisonError};
+use tokio::sync::Semaphore;
+
+use super::{
+	hash_signal, send_verification,
+	time::{sleep, Instant},
+	Error, VerificationRequest, VerifyConfig, VerifyResponse,
+};
+use crate::{
+	session::{Action, AppId},
+	Proof, Signal,
+};
+
+/// A single proof to verify as part of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationItem {
+	pub proof: Proof,
+	pub app_id: AppId,
+	pub action: Action,
+	/// The hashed signal, as returned by `hash_to_field`, or `None` for an empty signal.
+	pub signal_hash: Option<U256>,
+}
+
+impl VerificationItem {
+	/// Create a new verification item, hashing the provided signal.
+	///
+	/// # Errors
+	///
+	/// Errors if the action is invalid (`Error::InvalidAction`).
+	pub fn new<A, V>(proof: Proof, app_id: AppId, action: A, signal: V) -> Result<Self, Error>
+	where
+		A: TryInto<Action>,
+		Error: From<A::Error>,
+		V: Signal,
+	{
+		Ok(Self {
+			proof,
+			app_id,
+			action: action.try_into()?,
+			signal_hash: hash_signal(&signal),
+		})
+	}
+}
+
+/// Verify many World ID proofs using the Developer Portal API, sending at most `concurrency` requests at a time.
+///
+/// The returned results are in the same order as the provided items. A failing item doesn't stop the rest of the batch, and a rate limit response pauses the whole batch.
+pub async fn verify_proofs(
+	items: Vec<VerificationItem>,
+	concurrency: usize,
+) -> Vec<Result<VerifyResponse, Error>> {
+	verify_proofs_with_config(items, concurrency, &VerifyConfig::default()).await
+}
+
+/// Verify many World ID proofs using the Developer Portal API, with a custom configuration.
+///
+/// The returned results are in the same order as the provided items. A failing item doesn't stop the rest of the batch, and a rate limit response pauses the whole batch.
+pub async fn verify_proofs_with_config(
+	items: Vec<VerificationItem>,
+	concurrency: usize,
+	config: &VerifyConfig,
+) -> Vec<Result<VerifyResponse, Error>> {
+	let batch = Batch {
+		config,
+		paused_until: Mutex::new(None),
+		semaphore: Semaphore::new(concurrency.max(1)),
+		request_config: VerifyConfig {
+			max_retries: 0,
+			..config.clone()
+		},
+	};
+
+	join_all(items.iter().map(|item| batch.verify(item))).await
+}
+
+struct Batch<'a> {
+	config: &'a VerifyConfig,
+	semaphore: Semaphore,
+	/// Retries are handled by the batch, so they can pause every request instead of a single one.
+	request_config: VerifyConfig,
+	paused_until: Mutex<Option<Instant>>,
+}
+
+impl Batch<'_> {
+	async fn verify(&self, item: &VerificationItem) -> Result<VerifyResponse, Error> {
+		item.proof.validate_format()?;
+		let request = VerificationRequest::new(&item.proof, &item.action, item.signal_hash);
+		let mut attempt = 0;
+
+		loop {
+			let result = {
+				let _permit = self
+					.semaphore
+					.acquire()
+					.await
+					.unwrap_or_else(|_| unreachable!("the semaphore is never closed"));
+
+				self.wait_if_paused().await;
+				send_verification(&request, &item.app_id, &self.request_config).await
+			};
+
+			let error = match result {
+				Ok(response) => return Ok(response),
+				Err(error) => error,
+			};
+
+			match self.config.retry_delay(&error, attempt) {
+				Some(delay) if matches!(error, Error::RateLimited { .. }) => self.pause(delay),
+				Some(delay) => sleep(delay).await,
+				None => return Err(error),
+			}
+
+			attempt += 1;
+		}
+	}
+
+	async fn wait_if_paused(&self) {
+		let paused_until = *self
+			.paused_until
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+
+		if let Some(paused_until) = paused_until {
+			sleep(paused_until.saturating_duration_since(Instant::now())).await;
+		}
+	}
+
+	fn pause(&self, delay: std::time::Duration) {
+		let mut paused_until = self
+			.paused_until
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner);
+
+		let until = Instant::now() + delay;
+		*paused_until = Some(paused_until.map_or(until, |current| current.max(until)));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixtures, session::ActionError};
+	use std::{
+		str::FromStr,
+		sync::Arc,
+		time::{Duration, Instant as StdInstant},
+	};
+	use url::Url;
+	use wiremock::{
+		matchers::{body_partial_json, method, path},
+		Mock, MockServer, Request, Respond, ResponseTemplate,
+	};
+
+	const RESPONSE_DELAY: Duration = Duration::from_millis(200);
+
+	/// Records when each request arrived, responding after a fixed delay.
+	#[derive(Clone, Default)]
+	struct Recorder(Arc<Mutex<Vec<StdInstant>>>);
+
+	impl Respond for Recorder {
+		fn respond(&self, _: &Request) -> ResponseTemplate {
+			self.0.lock().unwrap().push(StdInstant::now());
+
+			ResponseTemplate::new(200).set_delay(RESPONSE_DELAY)
+		}
+	}
+
+	impl Recorder {
+		/// The maximum number of requests that were in flight at the same time.
+		fn max_in_flight(&self) -> usize {
+			let arrivals = self.0.lock().unwrap();
+
+			arrivals
+				.iter()
+				.map(|start| {
+					arrivals
+						.iter()
+						.filter(|other| **other >= *start && **other < *start + RESPONSE_DELAY)
+						.count()
+				})
+				.max()
+				.unwrap_or_default()
+		}
+	}
+
+	fn test_item(action: &str) -> VerificationItem {
+		VerificationItem::new(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			action,
+			"",
+		)
+		.unwrap()
+	}
+
+	#[test]
+	fn test_verification_item_rejects_invalid_action() {
+		assert!(matches!(
+			VerificationItem::new(
+				fixtures::proof(),
+				AppId::from_str("app_123").unwrap(),
+				"a".repeat(300),
+				"",
+			),
+			Err(Error::InvalidAction(ActionError::TooLong(300)))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_verify_proofs_respects_concurrency() {
+		let recorder = Recorder::default();
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(recorder.clone())
+			.expect(6)
+			.mount(&server)
+			.await;
+
+		let results = verify_proofs_with_config(
+			(0..6).map(|i| test_item(&format!("action_{i}"))).collect(),
+			2,
+			&VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await;
+
+		assert_eq!(results.len(), 6);
+		assert!(results.iter().all(Result::is_ok));
+		assert_eq!(recorder.max_in_flight(), 2);
+	}
+
+	#[tokio::test]
+	async fn test_verify_proofs_preserves_order_and_continues_on_failure() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(
+				serde_json::json!({ "action": "invalid" }),
+			))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.with_priority(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_json(serde_json::json!({ "uses": 1 })),
+			)
+			.mount(&server)
+			.await;
+
+		let results = verify_proofs_with_config(
+			vec![test_item("valid"), test_item("invalid"), test_item("valid")],
+			4,
+			&VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await;
+
+		assert_eq!(results[0].as_ref().unwrap().uses, Some(1));
+		assert!(matches!(results[1], Err(Error::Verification(_))));
+		assert_eq!(results[2].as_ref().unwrap().uses, Some(1));
+	}
+
+	#[tokio::test]
+	async fn test_verify_proofs_backs_off_on_rate_limit() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+			.up_to_n_times(1)
+			.with_priority(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.mount(&server)
+			.await;
+
+		let started = StdInstant::now();
+		let results = verify_proofs_with_config(
+			vec![test_item("first"), test_item("second")],
+			1,
+			&VerifyConfig {
+				max_retries: 1,
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await;
+
+		assert!(results.iter().all(Result::is_ok));
+		assert!(started.elapsed() >= Duration::from_secs(1));
+	}
+}
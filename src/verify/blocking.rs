@@ -0,0 +1,280 @@
+//! Verify World ID proofs without an async runtime, sharing the request format of the async API.
+
+use reqwest::{
+	blocking::{Client, ClientBuilder, Response},
+	header, redirect, tls,
+};
+use std::{io::Read, sync::OnceLock};
+use url::Url;
+
+use super::{
+	check_status, decode_error_body, decode_verify_response, hash_signal, parse_rate_limit,
+	record_metrics, time, AuditRecord, Error, ErrorResponse, ResponseStatus, VerificationRequest,
+	VerifyConfig, VerifyResponse, DEFAULT_CONNECT_TIMEOUT, MAX_ERROR_BODY_READ, POOL_IDLE_TIMEOUT,
+	TCP_KEEPALIVE,
+};
+use crate::{
+	session::{Action, AppId},
+	trace::TraceContext,
+	Proof, Signal,
+};
+
+/// The settings shared by every blocking client, matching those of the default async client.
+fn client_builder() -> ClientBuilder {
+	Client::builder()
+		.redirect(redirect::Policy::none())
+		.min_tls_version(tls::Version::TLS_1_2)
+		.connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+		.pool_idle_timeout(POOL_IDLE_TIMEOUT)
+		.tcp_keepalive(TCP_KEEPALIVE)
+		.timeout(None)
+}
+
+/// The process-wide blocking client, built on first use. A failure to build it is reported rather than falling back to a client without the shared settings.
+fn shared_client() -> Result<&'static Client, Error> {
+	static CLIENT: OnceLock<Result<Client, String>> = OnceLock::new();
+
+	CLIENT
+		.get_or_init(|| client_builder().build().map_err(|error| error.to_string()))
+		.as_ref()
+		.map_err(|error| Error::Tls(error.clone()))
+}
+
+/// The blocking client for `config`, built with its TLS settings if it has any.
+fn client(config: &VerifyConfig) -> Result<Client, Error> {
+	match &config.tls {
+		Some(tls) => client_builder()
+			.use_preconfigured_tls(tls.client_config()?)
+			.build()
+			.map_err(|error| Error::Tls(error.to_string())),
+		None => shared_client().cloned(),
+	}
+}
+
+/// Verify a World ID proof using the Developer Portal API, blocking the current thread.
+///
+/// Must not be called from within an async runtime.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+#[allow(clippy::module_name_repetitions)]
+pub fn verify_proof<A, V>(proof: Proof, app_id: AppId, action: A, signal: V) -> Result<(), Error>
+where
+	A: TryInto<Action>,
+	Error: From<A::Error>,
+	V: Signal,
+{
+	verify_proof_with_config(proof, app_id, action, signal, &VerifyConfig::try_default()?)
+}
+
+/// Verify a World ID proof using the Developer Portal API with a custom configuration, blocking the current thread.
+///
+/// The `client` of the configuration is async-only, so a blocking client is used instead, built with the TLS settings of `VerifyConfig::with_tls` if any. Every other setting is respected.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+// Takes the proof and app id by value, like the async `verify_proof_with_config`.
+#[allow(clippy::module_name_repetitions, clippy::needless_pass_by_value)]
+pub fn verify_proof_with_config<A, V>(
+	proof: Proof,
+	app_id: AppId,
+	action: A,
+	signal: V,
+	config: &VerifyConfig,
+) -> Result<(), Error>
+where
+	A: TryInto<Action>,
+	Error: From<A::Error>,
+	V: Signal,
+{
+	let action: Action = action.try_into()?;
+	proof.validate_format()?;
+
+	let request = VerificationRequest::new(&proof, &action, hash_signal(&signal));
+	send_verification(&request, &app_id, config)?;
+
+	Ok(())
+}
+
+fn send_verification(
+	request: &VerificationRequest<'_>,
+	app_id: &AppId,
+	config: &VerifyConfig,
+) -> Result<VerifyResponse, Error> {
+	let started_at = time::now();
+	let timer = time::Instant::now();
+	let result = send_verification_unaudited(request, app_id, config);
+
+	if let Some(sink) = &config.audit_sink {
+		sink.record(AuditRecord::new(request, app_id, started_at, &result));
+	}
+	record_metrics(request, app_id, config, timer, &result);
+
+	result
+}
+
+fn send_verification_unaudited(
+	request: &VerificationRequest<'_>,
+	app_id: &AppId,
+	config: &VerifyConfig,
+) -> Result<VerifyResponse, Error> {
+	let url = config.endpoint(&format!("/api/{}/verify/{}", config.api_version, app_id.0))?;
+	let body = request.for_version(config.api_version);
+	let client = client(config)?;
+	let mut attempt = 0;
+
+	let response = loop {
+		match send_request_once(&client, config, url.clone(), &body) {
+			Err(error) => match config.retry_delay(&error, attempt) {
+				Some(delay) => {
+					std::thread::sleep(delay);
+					attempt += 1;
+				},
+				None => return Err(error),
+			},
+			Ok(response) => break response,
+		}
+	};
+
+	let rate_limit = parse_rate_limit(response.headers());
+
+	decode_verify_response(&response.bytes()?, rate_limit)
+}
+
+fn send_request_once(
+	client: &Client,
+	config: &VerifyConfig,
+	url: Url,
+	body: &VerificationRequest<'_>,
+) -> Result<Response, Error> {
+	let mut builder = client
+		.post(url)
+		.header(header::USER_AGENT, &config.user_agent)
+		.json(body);
+
+	if let Some(timeout) = config.timeout {
+		builder = builder.timeout(timeout);
+	}
+	if let Some(api_key) = &config.api_key {
+		builder = builder.bearer_auth(api_key.expose());
+	}
+	for (name, value) in config.trace_context.iter().flat_map(TraceContext::headers) {
+		builder = builder.header(name, value);
+	}
+
+	let response = builder.send()?;
+
+	match check_status(response.status(), response.headers()) {
+		ResponseStatus::Success => Ok(response),
+		ResponseStatus::Failed(error) => Err(error),
+		ResponseStatus::Rejected => Err(Error::Verification(response.json::<ErrorResponse>()?)),
+		ResponseStatus::Unexpected(status) => Err(Error::UnexpectedResponse {
+			status,
+			body: read_error_body(response),
+		}),
+	}
+}
+
+/// Reads the body of an unexpected response, up to what `truncate_body` keeps. The status is the error, so failing to read the body only shortens it.
+fn read_error_body(response: Response) -> String {
+	let mut body = Vec::new();
+	let _ = response
+		.take(MAX_ERROR_BODY_READ as u64)
+		.read_to_end(&mut body);
+
+	decode_error_body(body)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		fixtures,
+		verify::{self, VerifyErrorCode},
+	};
+	use std::str::FromStr;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn test_config(server: &MockServer) -> VerifyConfig {
+		VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		}
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn test_blocking_matches_async_request() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let config = test_config(&server);
+		let app_id = AppId::from_str("app_123").unwrap();
+
+		verify::verify_proof_with_config(
+			fixtures::proof(),
+			app_id.clone(),
+			"vote_1",
+			"test",
+			&config,
+		)
+		.await
+		.unwrap();
+
+		tokio::task::spawn_blocking(move || {
+			verify_proof_with_config(fixtures::proof(), app_id, "vote_1", "test", &config)
+		})
+		.await
+		.unwrap()
+		.unwrap();
+
+		let requests = server.received_requests().await.unwrap();
+		assert_eq!(requests.len(), 2);
+		assert_eq!(requests[0].body, requests[1].body);
+		assert_eq!(
+			requests[0].headers.get("content-type"),
+			requests[1].headers.get("content-type")
+		);
+	}
+
+	#[tokio::test(flavor = "multi_thread")]
+	async fn test_blocking_returns_error_response() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let config = test_config(&server);
+		let error = tokio::task::spawn_blocking(move || {
+			verify_proof_with_config(
+				fixtures::proof(),
+				AppId::from_str("app_123").unwrap(),
+				"vote_1",
+				"",
+				&config,
+			)
+		})
+		.await
+		.unwrap()
+		.unwrap_err();
+
+		assert!(
+			matches!(error, Error::Verification(ErrorResponse { code, .. }) if code == VerifyErrorCode::InvalidProof)
+		);
+	}
+}
@@ -0,0 +1,195 @@
+use ruint::aliases::U256;
+use std::time::Duration;
+use url::Url;
+
+use super::{
+	hash_signal, send_verification, ApiKey, Error, VerificationRequest, VerifyConfig,
+	VerifyResponse,
+};
+use crate::{
+	session::{Action, AppId, Environment, VerificationLevel},
+	trace::TraceContext,
+	Proof, Signal,
+};
+
+/// A verification request to the Developer Portal API.
+///
+/// ```no_run
+/// # async fn example(proof: idkit::Proof, app_id: idkit::session::AppId) -> Result<(), idkit::verify::Error> {
+/// use idkit::verify::Verification;
+///
+/// Verification::new(app_id, "vote_1")?
+///     .proof(proof)
+///     .signal("my_signal")
+///     .send()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Verification {
+	app_id: AppId,
+	action: Action,
+	proof: Option<Proof>,
+	signal_hash: Option<U256>,
+	required_level: Option<VerificationLevel>,
+	proof_environment: Option<Environment>,
+	config: VerifyConfig,
+}
+
+impl Verification {
+	/// Start building a verification request for the given app and action, with the configuration of `VerifyConfig::try_default`.
+	///
+	/// # Errors
+	///
+	/// Errors if the action is invalid (`Error::InvalidAction`), or if the default client can't be built (`Error::Tls`).
+	pub fn new<A>(app_id: AppId, action: A) -> Result<Self, Error>
+	where
+		A: TryInto<Action>,
+		Error: From<A::Error>,
+	{
+		Self::with_config(app_id, action, VerifyConfig::try_default()?)
+	}
+
+	/// Start building a verification request for the given app and action, with a custom configuration.
+	///
+	/// # Errors
+	///
+	/// Errors if the action is invalid (`Error::InvalidAction`).
+	pub fn with_config<A>(app_id: AppId, action: A, config: VerifyConfig) -> Result<Self, Error>
+	where
+		A: TryInto<Action>,
+		Error: From<A::Error>,
+	{
+		Ok(Self {
+			app_id,
+			proof: None,
+			signal_hash: None,
+			required_level: None,
+			proof_environment: None,
+			action: action.try_into()?,
+			config,
+		})
+	}
+
+	/// The proof to verify. Required.
+	pub fn proof(mut self, proof: Proof) -> Self {
+		self.proof = Some(proof);
+		self
+	}
+
+	/// The signal the proof was generated with. Defaults to an empty signal.
+	pub fn signal<V: Signal>(mut self, signal: V) -> Self {
+		self.signal_hash = hash_signal(&signal);
+		self
+	}
+
+	/// An already computed signal hash (as returned by `hash_to_field`), used instead of `signal`.
+	pub const fn signal_hash(mut self, signal_hash: U256) -> Self {
+		self.signal_hash = Some(signal_hash);
+		self
+	}
+
+	/// Reject proofs with a weaker verification level than `required_level` locally, without contacting the Developer Portal. Disabled by default.
+	pub const fn required_level(mut self, required_level: VerificationLevel) -> Self {
+		self.required_level = Some(required_level);
+		self
+	}
+
+	/// The environment the proof was generated in, if known (e.g. whether the frontend used the World ID Simulator). Proofs from another environment than the app's are rejected locally, without contacting the Developer Portal.
+	pub const fn proof_environment(mut self, proof_environment: Environment) -> Self {
+		self.proof_environment = Some(proof_environment);
+		self
+	}
+
+	/// Replace the whole configuration used to reach the Developer Portal.
+	pub fn config(mut self, config: VerifyConfig) -> Self {
+		self.config = config;
+		self
+	}
+
+	/// The base URL of the Developer Portal.
+	pub fn base_url(mut self, base_url: Url) -> Self {
+		self.config.base_url = base_url;
+		self
+	}
+
+	/// The HTTP client used to send the request. Its own settings are respected, so no additional timeout is applied unless `timeout` is called afterwards.
+	pub fn client(mut self, client: reqwest::Client) -> Self {
+		self.config.client = client;
+		self.config.timeout = None;
+		self
+	}
+
+	/// The maximum time allowed for each request to the Developer Portal.
+	pub const fn timeout(mut self, timeout: Duration) -> Self {
+		self.config.timeout = Some(timeout);
+		self
+	}
+
+	/// How many times to retry a request that was rate limited or failed with a server error.
+	pub const fn max_retries(mut self, max_retries: u32) -> Self {
+		self.config.max_retries = max_retries;
+		self
+	}
+
+	/// The `User-Agent` header to send, replacing the default `idkit-rs/<version>`.
+	pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+		self.config.user_agent = user_agent.into();
+		self
+	}
+
+	/// The trace context to propagate with the request, e.g. the one of the incoming request being served.
+	pub fn trace_context(mut self, trace_context: TraceContext) -> Self {
+		self.config.trace_context = Some(trace_context);
+		self
+	}
+
+	/// The Developer Portal API key to authenticate the request with.
+	pub fn api_key(mut self, api_key: ApiKey) -> Self {
+		self.config.api_key = Some(api_key);
+		self
+	}
+
+	/// Send the verification request to the Developer Portal.
+	///
+	/// # Errors
+	///
+	/// Errors if a required field is missing (`Error::MissingField`), if the proof environment set with `proof_environment` isn't the app's (`Error::EnvironmentMismatch`), if the proof is malformed (`Error::MalformedProof`) or below the required level (`Error::InsufficientVerificationLevel`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+	pub async fn send(&self) -> Result<(), Error> {
+		self.send_with_response().await?;
+
+		Ok(())
+	}
+
+	/// Send the verification request to the Developer Portal, returning the details of the successful verification.
+	///
+	/// # Errors
+	///
+	/// Errors in the same cases as `send`.
+	pub async fn send_with_response(&self) -> Result<VerifyResponse, Error> {
+		let proof = self.proof.as_ref().ok_or(Error::MissingField("proof"))?;
+		proof.validate_format()?;
+
+		if let Some(proof) = self.proof_environment {
+			let app = self.app_id.environment();
+			if app != proof {
+				return Err(Error::EnvironmentMismatch { app, proof });
+			}
+		}
+
+		if let Some(required) = self.required_level {
+			if !proof.verification_level.satisfies(required) {
+				return Err(Error::InsufficientVerificationLevel {
+					got: proof.verification_level,
+					required,
+				});
+			}
+		}
+
+		let request = VerificationRequest::new(proof, &self.action, self.signal_hash);
+
+		send_verification(&request, &self.app_id, &self.config).await
+	}
+}
@@ -0,0 +1,382 @@
+use ring::digest::{digest, SHA256};
+use std::{
+	collections::HashMap,
+	convert::Infallible,
+	future::{ready, Future},
+	sync::{Mutex, PoisonError},
+	time::Duration,
+};
+
+use super::{
+	hash_signal, send_verification, time::Instant, Error, ErrorResponse, VerificationRequest,
+	VerifyConfig,
+};
+use crate::{
+	session::{Action, AppId},
+	Proof, Signal,
+};
+
+/// Identifies a verification in a `VerificationCache`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+	pub app_id: String,
+	pub action: String,
+	/// The nullifier hash in the canonical form displayed by `NullifierHash`, so variants of the same nullifier hash share an entry.
+	pub nullifier_hash: String,
+}
+
+/// The outcome of a verification, as stored in a `VerificationCache`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedVerification {
+	/// The SHA-256 digest of the verification request, so a cached result is only reused for the exact same proof and signal.
+	pub request_digest: [u8; 32],
+	/// `Ok` if the Developer Portal accepted the proof, or the error it was rejected with.
+	pub outcome: Result<(), ErrorResponse>,
+}
+
+/// Storage for the outcome of recent verifications.
+pub trait VerificationCache {
+	type Error: std::error::Error + Send + Sync + 'static;
+
+	/// Returns the cached verification for the key, if any and not expired.
+	fn get(
+		&self,
+		key: &CacheKey,
+	) -> impl Future<Output = Result<Option<CachedVerification>, Self::Error>> + Send;
+
+	/// Stores the outcome of a verification. Implementations decide how long to keep it, usually less for rejections than for successes.
+	fn put(
+		&self,
+		key: CacheKey,
+		value: CachedVerification,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// A `VerificationCache` keeping entries in memory, with separate lifetimes for successful and rejected verifications.
+#[derive(Debug)]
+pub struct MemoryVerificationCache {
+	success_ttl: Duration,
+	failure_ttl: Option<Duration>,
+	entries: Mutex<HashMap<CacheKey, (CachedVerification, Instant)>>,
+}
+
+impl MemoryVerificationCache {
+	/// Create an empty cache, keeping successful verifications for `success_ttl` and rejections for `failure_ttl` (or not at all if `None`).
+	#[must_use]
+	pub fn new(success_ttl: Duration, failure_ttl: Option<Duration>) -> Self {
+		Self {
+			success_ttl,
+			failure_ttl,
+			entries: Mutex::new(HashMap::new()),
+		}
+	}
+}
+
+impl VerificationCache for MemoryVerificationCache {
+	type Error = Infallible;
+
+	fn get(
+		&self,
+		key: &CacheKey,
+	) -> impl Future<Output = Result<Option<CachedVerification>, Self::Error>> + Send {
+		let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+		let now = Instant::now();
+		entries.retain(|_, (_, expires_at)| *expires_at > now);
+
+		ready(Ok(entries.get(key).map(|(value, _)| value.clone())))
+	}
+
+	fn put(
+		&self,
+		key: CacheKey,
+		value: CachedVerification,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send {
+		let ttl = if value.outcome.is_ok() {
+			Some(self.success_ttl)
+		} else {
+			self.failure_ttl
+		};
+
+		if let Some(ttl) = ttl {
+			self.entries
+				.lock()
+				.unwrap_or_else(PoisonError::into_inner)
+				.insert(key, (value, Instant::now() + ttl));
+		}
+
+		ready(Ok(()))
+	}
+}
+
+/// Verify a World ID proof using the Developer Portal API, reusing the outcome of a previous identical verification if it's still cached.
+///
+/// Only successes and rejections by the Developer Portal are cached. Errors from the cache itself are ignored, falling back to contacting the Developer Portal.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+pub async fn verify_proof_cached<C, A, V>(
+	cache: &C,
+	proof: &Proof,
+	app_id: &AppId,
+	action: A,
+	signal: V,
+) -> Result<(), Error>
+where
+	C: VerificationCache + Sync,
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+	V: Signal + Send,
+{
+	verify_proof_cached_with_config(
+		cache,
+		proof,
+		app_id,
+		action,
+		signal,
+		&VerifyConfig::try_default()?,
+	)
+	.await
+}
+
+/// Verify a World ID proof using the Developer Portal API, reusing the outcome of a previous identical verification if it's still cached, with a custom configuration.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+pub async fn verify_proof_cached_with_config<C, A, V>(
+	cache: &C,
+	proof: &Proof,
+	app_id: &AppId,
+	action: A,
+	signal: V,
+	config: &VerifyConfig,
+) -> Result<(), Error>
+where
+	C: VerificationCache + Sync,
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+	V: Signal + Send,
+{
+	let action: Action = action.try_into()?;
+	proof.validate_format()?;
+	let nullifier_hash = proof
+		.nullifier_hash()
+		.map_err(|error| Error::MalformedProof {
+			field: "nullifier_hash",
+			reason: error.to_string(),
+		})?
+		.to_string();
+
+	let request = VerificationRequest::new(proof, &action, hash_signal(&signal));
+	// The digest is of the canonical nullifier hash too, so variants of it are the same verification.
+	let canonical = VerificationRequest {
+		nullifier_hash: &nullifier_hash,
+		..request.clone()
+	};
+	let request_digest = digest(&SHA256, &serde_json::to_vec(&canonical)?)
+		.as_ref()
+		.try_into()
+		.unwrap_or_else(|_| unreachable!("SHA-256 digests are 32 bytes long"));
+
+	let key = CacheKey {
+		app_id: app_id.to_string(),
+		action: action.to_string(),
+		nullifier_hash,
+	};
+
+	if let Ok(Some(cached)) = cache.get(&key).await {
+		if cached.request_digest == request_digest {
+			return cached.outcome.map_err(Error::Verification);
+		}
+	}
+
+	let outcome = match send_verification(&request, app_id, config).await {
+		Ok(_) => Ok(()),
+		Err(Error::Verification(response)) => Err(response),
+		Err(error) => return Err(error),
+	};
+
+	let _ = cache
+		.put(
+			key,
+			CachedVerification {
+				request_digest,
+				outcome: outcome.clone(),
+			},
+		)
+		.await;
+
+	outcome.map_err(Error::Verification)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fixtures;
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	async fn verify(
+		cache: &MemoryVerificationCache,
+		server: &MockServer,
+		signal: &str,
+	) -> Result<(), Error> {
+		verify_proof_cached_with_config(
+			cache,
+			&fixtures::proof(),
+			&AppId::from_str("app_123").unwrap(),
+			"test-action",
+			signal,
+			&VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_cached_success() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let cache = MemoryVerificationCache::new(Duration::from_secs(60), None);
+
+		verify(&cache, &server, "test").await.unwrap();
+		verify(&cache, &server, "test").await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_nullifier_hash_variants_are_cached() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let cache = MemoryVerificationCache::new(Duration::from_secs(60), None);
+		let config = VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		};
+		let app_id = AppId::from_str("app_123").unwrap();
+
+		for nullifier_hash in [
+			format!("0x{}", "ef".repeat(32)),
+			format!("0x{}", "EF".repeat(32)),
+		] {
+			let proof = Proof {
+				nullifier_hash,
+				..fixtures::proof()
+			};
+			verify_proof_cached_with_config(
+				&cache,
+				&proof,
+				&app_id,
+				"test-action",
+				"test",
+				&config,
+			)
+			.await
+			.unwrap();
+		}
+	}
+
+	#[tokio::test]
+	async fn test_cache_miss_on_different_signal() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let cache = MemoryVerificationCache::new(Duration::from_secs(60), None);
+
+		verify(&cache, &server, "test").await.unwrap();
+		verify(&cache, &server, "other").await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_cached_rejection() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let cache =
+			MemoryVerificationCache::new(Duration::from_secs(60), Some(Duration::from_secs(5)));
+
+		assert!(matches!(
+			verify(&cache, &server, "test").await,
+			Err(Error::Verification(_))
+		));
+		assert!(matches!(
+			verify(&cache, &server, "test").await,
+			Err(Error::Verification(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_rejections_not_cached_by_default() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let cache = MemoryVerificationCache::new(Duration::from_secs(60), None);
+
+		assert!(verify(&cache, &server, "test").await.is_err());
+		assert!(verify(&cache, &server, "test").await.is_err());
+	}
+
+	#[tokio::test]
+	async fn test_expired_entries() {
+		let cache = MemoryVerificationCache::new(Duration::ZERO, None);
+		let key = CacheKey {
+			app_id: "app_123".to_string(),
+			action: "test-action".to_string(),
+			nullifier_hash: "0x1".to_string(),
+		};
+
+		cache
+			.put(
+				key.clone(),
+				CachedVerification {
+					request_digest: [0; 32],
+					outcome: Ok(()),
+				},
+			)
+			.await
+			.unwrap();
+
+		assert_eq!(cache.get(&key).await, Ok(None));
+	}
+}
@@ -0,0 +1,71 @@
+use alloy_primitives::Bytes;
+
+use super::{ErrorReply, Verification, VerifyConfig, VerifyResponse};
+use crate::{
+	session::{Action, AppId},
+	Proof, SuccessResult,
+};
+
+/// The largest request body read when extracting a proof. Proofs are around 1 KB.
+pub const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// A proof taken from the JSON body of a request (in the format of IDKit JS' success result), and verified with the Developer Portal.
+///
+/// The web framework integrations extract it before the handler runs, and reject the request with an `ErrorReply` if the body doesn't contain a proof, or if it can't be verified.
+#[derive(Debug, Clone)]
+pub struct VerifiedProof {
+	/// The verified proof.
+	pub proof: Proof,
+	/// The packed encoding of the signal the proof was verified with.
+	pub signal: Bytes,
+	/// The details of the verification returned by the Developer Portal.
+	pub response: VerifyResponse,
+}
+
+impl VerifiedProof {
+	/// Parses the body of a request and verifies its proof, with the signal computed by `signal`.
+	pub(crate) async fn from_body(
+		body: &[u8],
+		app_id: AppId,
+		action: &Action,
+		config: VerifyConfig,
+		signal: impl FnOnce(&SuccessResult) -> Bytes,
+	) -> Result<Self, ErrorReply> {
+		let result: SuccessResult = serde_json::from_slice(body)
+			.map_err(|error| ErrorReply::invalid_request(error.to_string()))?;
+
+		let signal = signal(&result);
+
+		Self::verify(result.proof, signal, app_id, action, config).await
+	}
+
+	/// Verifies `proof` with the packed encoding of its `signal`.
+	pub(crate) async fn verify(
+		proof: Proof,
+		signal: Bytes,
+		app_id: AppId,
+		action: &Action,
+		config: VerifyConfig,
+	) -> Result<Self, ErrorReply> {
+		let response = Verification::with_config(app_id, action.clone(), config)?
+			.proof(proof.clone())
+			.signal(signal.clone())
+			.send_with_response()
+			.await?;
+
+		Ok(Self {
+			proof,
+			signal,
+			response,
+		})
+	}
+}
+
+/// The signal sent alongside the proof in the body, if any, which the integrations verify proofs with by default.
+pub fn body_signal(result: &SuccessResult) -> Bytes {
+	result
+		.signal
+		.as_deref()
+		.map(|signal| Bytes::copy_from_slice(signal.as_bytes()))
+		.unwrap_or_default()
+}
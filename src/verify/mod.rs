@@ -0,0 +1,1983 @@
+use reqwest::{header, Method, StatusCode};
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::{redirect, tls as reqwest_tls};
+use ruint::aliases::U256;
+use serde::Serialize;
+use std::{
+	convert::Infallible,
+	sync::{Arc, OnceLock},
+	time::{Duration, UNIX_EPOCH},
+};
+use url::Url;
+
+use crate::{
+	hashing::{hash_to_field, FieldElement},
+	metrics::{Labels, Metrics},
+	network::Failure,
+	session::{
+		Action, ActionError, AppError, AppId, CredentialType, Environment, VerificationLevel,
+	},
+	trace::TraceContext,
+	ErrorKind, Proof, Signal, USER_AGENT,
+};
+
+mod actions;
+mod apps;
+mod audit;
+mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+mod cache;
+#[cfg(any(
+	feature = "axum",
+	feature = "actix",
+	feature = "tower",
+	feature = "warp"
+))]
+pub(crate) mod extract;
+mod precheck;
+mod record;
+mod reply;
+pub(crate) mod time;
+#[cfg(not(target_arch = "wasm32"))]
+mod tls;
+mod types;
+
+pub use actions::{get_action, get_action_with_config};
+pub use apps::{get_app_metadata, get_app_metadata_with_config};
+pub use audit::{AuditOutcome, AuditRecord, AuditSink};
+pub use batch::{verify_proofs, verify_proofs_with_config, VerificationItem};
+pub use builder::Verification;
+pub use cache::{
+	verify_proof_cached, verify_proof_cached_with_config, CacheKey, CachedVerification,
+	MemoryVerificationCache, VerificationCache,
+};
+#[cfg(any(
+	feature = "axum",
+	feature = "actix",
+	feature = "tower",
+	feature = "warp"
+))]
+pub use extract::VerifiedProof;
+pub use precheck::{precheck, precheck_with_config};
+pub use record::ProofRecord;
+pub use reply::ErrorReply;
+#[cfg(not(target_arch = "wasm32"))]
+pub use tls::TlsConfig;
+pub use types::{
+	ActionMetadata, ActionStatus, ApiKey, ApiKeyError, ApiVersion, AppMetadata, ErrorResponse,
+	NullifierUses, PrecheckResponse, RateLimitInfo, VerifyErrorCode, VerifyResponse,
+};
+
+const DEFAULT_PORTAL_URL: &str = "https://developer.worldcoin.org";
+/// The maximum number of bytes of an unexpected response body kept in `Error::UnexpectedResponse`.
+const MAX_ERROR_BODY_LEN: usize = 1024;
+/// The maximum number of bytes read from an unexpected response body, a character more than is kept so `truncate_body` notices it was cut.
+const MAX_ERROR_BODY_READ: usize = MAX_ERROR_BODY_LEN + 4;
+/// The base delay between retries, doubled on every attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// The maximum time to wait between retries, regardless of the `Retry-After` header.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+/// The default time allowed to establish a connection to the Developer Portal.
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The default time allowed for a whole request to the Developer Portal.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+/// How long idle connections to the Developer Portal are kept around for reuse.
+#[cfg(not(target_arch = "wasm32"))]
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// The interval of TCP keep-alive probes on connections to the Developer Portal.
+#[cfg(not(target_arch = "wasm32"))]
+const TCP_KEEPALIVE: Duration = Duration::from_mins(1);
+/// Rate limit reset values above this are Unix timestamps rather than a number of seconds.
+const RESET_TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+
+/// An error when verifying a proof with the Developer Portal.
+///
+/// New variants may be added in minor releases, match on `kind()` to handle errors by how they should be handled.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+	#[error("verification failed: {0}")]
+	Verification(#[source] ErrorResponse),
+	#[error("fail to send request: {0}")]
+	Reqwest(reqwest::Error),
+	#[error("request to the developer portal timed out")]
+	Timeout { source: reqwest::Error },
+	#[error("failed to connect to the developer portal: {source}")]
+	Connect { source: reqwest::Error },
+	#[error("failed to resolve the address of the developer portal: {source}")]
+	Dns { source: reqwest::Error },
+	#[error("failed to decode response: {0}")]
+	Serde(#[from] serde_json::Error),
+	#[error("invalid TLS configuration: {0}")]
+	Tls(String),
+	#[error("invalid developer portal url: {0}")]
+	Url(#[from] url::ParseError),
+	#[error("refusing to send a request over plaintext HTTP to {0}, only localhost may use HTTP")]
+	InsecureUrl(Url),
+	#[error("the developer portal redirected the request ({status}) to {}, which is not followed", location.as_deref().unwrap_or("an unknown location"))]
+	Redirected {
+		status: StatusCode,
+		location: Option<String>,
+	},
+	#[error("invalid action: {0}")]
+	InvalidAction(#[from] ActionError),
+	#[error("action not found: {0}")]
+	ActionNotFound(String),
+	#[error("app not found: {0}")]
+	AppNotFound(String),
+	#[error("malformed proof, invalid {field}: {reason}")]
+	MalformedProof { field: &'static str, reason: String },
+	#[error("insufficient verification level: got {got}, required {required}")]
+	InsufficientVerificationLevel {
+		got: VerificationLevel,
+		required: VerificationLevel,
+	},
+	#[error("{}", environment_mismatch_message(*app, *proof))]
+	EnvironmentMismatch {
+		app: Environment,
+		proof: Environment,
+	},
+	#[error("missing required field: {0}")]
+	MissingField(&'static str),
+	#[error("rate limited by the developer portal")]
+	RateLimited { retry_after: Option<Duration> },
+	#[error("unexpected response ({status}): {body}")]
+	UnexpectedResponse { status: StatusCode, body: String },
+}
+
+impl Error {
+	/// Classifies the error by how it should be handled: `ErrorKind::User` errors should be reported to the client, and `ErrorKind::Transport` ones can be retried.
+	#[must_use]
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Verification(response) => match response.code {
+				VerifyErrorCode::NotFound => ErrorKind::Configuration,
+				_ => ErrorKind::User,
+			},
+			Self::MalformedProof { .. } | Self::InsufficientVerificationLevel { .. } => {
+				ErrorKind::User
+			},
+			Self::Reqwest(error) if error.is_builder() => ErrorKind::Configuration,
+			Self::Reqwest(error) if error.is_decode() => ErrorKind::Protocol,
+			Self::Timeout { .. }
+			| Self::Connect { .. }
+			| Self::Dns { .. }
+			| Self::RateLimited { .. }
+			| Self::Reqwest(_) => ErrorKind::Transport,
+			Self::UnexpectedResponse { status, .. } if status.is_server_error() => {
+				ErrorKind::Transport
+			},
+			Self::UnexpectedResponse { .. } | Self::Redirected { .. } | Self::Serde(_) => {
+				ErrorKind::Protocol
+			},
+			Self::Tls(_)
+			| Self::Url(_)
+			| Self::InsecureUrl(_)
+			| Self::InvalidAction(_)
+			| Self::ActionNotFound(_)
+			| Self::AppNotFound(_)
+			| Self::EnvironmentMismatch { .. }
+			| Self::MissingField(_) => ErrorKind::Configuration,
+		}
+	}
+
+	/// Whether the request failed for a transient reason, and can be retried.
+	#[must_use]
+	pub fn is_retryable(&self) -> bool {
+		self.kind() == ErrorKind::Transport
+	}
+
+	/// Whether the proof itself is invalid, either malformed or rejected by the Developer Portal.
+	#[must_use]
+	pub const fn is_invalid_proof(&self) -> bool {
+		match self {
+			Self::MalformedProof { .. } => true,
+			Self::Verification(response) => matches!(
+				response.code,
+				VerifyErrorCode::InvalidProof | VerifyErrorCode::InvalidMerkleRoot
+			),
+			_ => false,
+		}
+	}
+
+	/// The `AppError` equivalent to the error code returned by the Developer Portal, if any. See `VerifyErrorCode::as_app_error` for the mapping.
+	#[must_use]
+	pub const fn as_app_error(&self) -> Option<AppError> {
+		match self {
+			Self::Verification(response) => response.code.as_app_error(),
+			Self::EnvironmentMismatch { .. } => Some(AppError::InvalidNetwork),
+			_ => None,
+		}
+	}
+}
+
+const fn environment_mismatch_message(app: Environment, proof: Environment) -> &'static str {
+	match (app, proof) {
+		(Environment::Staging, Environment::Production) => "the app is a staging app, which only accepts proofs from the World ID Simulator, but the proof comes from production: use a production app id, or generate the proof with the Simulator",
+		(Environment::Production, Environment::Staging) => "the app is a production app, which only accepts proofs from the World App, but the proof comes from the World ID Simulator: use a staging app id (app_staging_*), or generate the proof with the World App",
+		_ => "the proof doesn't belong to the same environment as the app",
+	}
+}
+
+impl From<Infallible> for Error {
+	fn from(error: Infallible) -> Self {
+		match error {}
+	}
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(error: reqwest::Error) -> Self {
+		match Failure::of(&error) {
+			Failure::Timeout => Self::Timeout { source: error },
+			Failure::Dns => Self::Dns { source: error },
+			Failure::Connect => Self::Connect { source: error },
+			Failure::Status(_) | Failure::Other => Self::Reqwest(error),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct VerificationRequest<'a> {
+	action: &'a str,
+	proof: &'a str,
+	merkle_root: &'a str,
+	nullifier_hash: &'a str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	verification_level: Option<VerificationLevel>,
+	/// Replaces `verification_level` in the v1 API.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	credential_type: Option<CredentialType>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	signal_hash: Option<FieldElement>,
+}
+
+impl<'a> VerificationRequest<'a> {
+	fn new(proof: &'a Proof, action: &'a str, signal_hash: Option<U256>) -> Self {
+		Self {
+			action,
+			proof: &proof.proof,
+			merkle_root: &proof.merkle_root,
+			nullifier_hash: &proof.nullifier_hash,
+			credential_type: None,
+			verification_level: Some(proof.verification_level),
+			signal_hash: signal_hash.map(FieldElement),
+		}
+	}
+
+	/// Adapts the request body to the given API version.
+	fn for_version(&self, api_version: ApiVersion) -> Self {
+		match api_version {
+			ApiVersion::V2 => self.clone(),
+			ApiVersion::V1 => Self {
+				verification_level: None,
+				credential_type: self.verification_level.map(CredentialType::from),
+				..self.clone()
+			},
+		}
+	}
+}
+
+/// Configuration for requests made to the Developer Portal API.
+#[derive(Debug, Clone)]
+pub struct VerifyConfig {
+	/// The base URL of the Developer Portal. Defaults to `https://developer.worldcoin.org`. Override it to target a staging portal, a mock server or an internal proxy.
+	pub base_url: Url,
+	/// The HTTP client used to send requests to the Developer Portal.
+	pub client: reqwest::Client,
+	/// The maximum time allowed for each request to the Developer Portal, applied on top of the client's own settings.
+	pub timeout: Option<Duration>,
+	/// How many times to retry a request that was rate limited or failed with a server error. Defaults to 0 (no retries).
+	pub max_retries: u32,
+	/// The Developer Portal API key, sent as a bearer token when provided.
+	pub api_key: Option<ApiKey>,
+	/// The version of the verification API to use. Defaults to v2.
+	pub api_version: ApiVersion,
+	/// Where to record every verification attempt, if anywhere.
+	pub audit_sink: Option<Arc<dyn AuditSink>>,
+	/// Where to report the outcome and latency of every verification, if anywhere.
+	pub metrics: Option<Arc<dyn Metrics>>,
+	/// The `User-Agent` header sent with every request. Defaults to `idkit-rs/<version>`.
+	pub user_agent: String,
+	/// The trace context propagated with every request, if any.
+	pub trace_context: Option<TraceContext>,
+	/// The TLS settings `client` was built with by `VerifyConfig::with_tls`, so the blocking API can build its own client with them.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub tls: Option<TlsConfig>,
+}
+
+impl Default for VerifyConfig {
+	/// Creates a configuration with a client that times out after 5 seconds when connecting, and 15 seconds for the whole request.
+	///
+	/// The client is shared by every default configuration in the process, so connections to the Developer Portal are reused across calls.
+	///
+	/// # Panics
+	///
+	/// Panics if the client can't be built because the TLS backend can't be initialized, like `reqwest::Client::new`. Use `VerifyConfig::try_default` to handle it instead.
+	fn default() -> Self {
+		Self::try_default().unwrap_or_else(|error| panic!("{error}"))
+	}
+}
+
+/// The process-wide client used by default configurations, built on first use. Its settings matter for security, so a failure to build it is reported rather than falling back to a client without them.
+fn shared_client() -> Result<&'static reqwest::Client, Error> {
+	static CLIENT: OnceLock<Result<reqwest::Client, String>> = OnceLock::new();
+
+	let client = CLIENT.get_or_init(|| {
+		#[cfg(not(target_arch = "wasm32"))]
+		let builder = reqwest::Client::builder()
+			.redirect(redirect::Policy::none())
+			.min_tls_version(reqwest_tls::Version::TLS_1_2)
+			.connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+			.pool_idle_timeout(POOL_IDLE_TIMEOUT)
+			.tcp_keepalive(TCP_KEEPALIVE);
+		// Connections are managed by `fetch` on wasm.
+		#[cfg(target_arch = "wasm32")]
+		let builder = reqwest::Client::builder();
+
+		builder.build().map_err(|error| error.to_string())
+	});
+
+	client.as_ref().map_err(|error| Error::Tls(error.clone()))
+}
+
+impl VerifyConfig {
+	/// Creates the configuration of `VerifyConfig::default`, without panicking if its client can't be built.
+	///
+	/// # Errors
+	///
+	/// Returns `Error::Tls` if the client can't be built because the TLS backend can't be initialized.
+	pub fn try_default() -> Result<Self, Error> {
+		Ok(Self {
+			timeout: Some(DEFAULT_TIMEOUT),
+			..Self::new(shared_client()?.clone())
+		})
+	}
+
+	/// Creates a configuration using the provided client. No additional timeouts are applied, so the client's own settings are respected.
+	#[must_use]
+	pub fn new(client: reqwest::Client) -> Self {
+		Self {
+			client,
+			timeout: None,
+			api_key: None,
+			audit_sink: None,
+			metrics: None,
+			user_agent: USER_AGENT.to_string(),
+			trace_context: None,
+			#[cfg(not(target_arch = "wasm32"))]
+			tls: None,
+			api_version: ApiVersion::default(),
+			max_retries: 0,
+			base_url: Url::parse(DEFAULT_PORTAL_URL).unwrap_or_else(|_| unreachable!()),
+		}
+	}
+
+	/// Resolves a path relative to the base URL, preserving any path prefix the base URL might have. Only localhost may be reached over plaintext HTTP.
+	fn endpoint(&self, path: &str) -> Result<Url, Error> {
+		let mut base = self.base_url.clone();
+		if !base.path().ends_with('/') {
+			base.set_path(&format!("{}/", base.path()));
+		}
+
+		let url = base.join(path.trim_start_matches('/'))?;
+		if url.scheme() != "https" && !is_localhost(&url) {
+			return Err(Error::InsecureUrl(url));
+		}
+
+		Ok(url)
+	}
+
+	/// Propagates the trace context of the caller with every request, e.g. the one of the incoming request being served.
+	#[must_use]
+	pub fn with_trace_context(mut self, trace_context: TraceContext) -> Self {
+		self.trace_context = Some(trace_context);
+		self
+	}
+
+	/// Appends a product (e.g. `my-service/1a2b3c`) to the `User-Agent` header, keeping the crate's own.
+	#[must_use]
+	pub fn append_user_agent(mut self, product: &str) -> Self {
+		self.user_agent = format!("{} {product}", self.user_agent);
+		self
+	}
+
+	/// Returns how long to wait before retrying after the given error, or `None` if the request shouldn't be retried.
+	fn retry_delay(&self, error: &Error, attempt: u32) -> Option<Duration> {
+		if attempt >= self.max_retries {
+			return None;
+		}
+
+		let backoff = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt));
+		let delay = match error {
+			Error::RateLimited { retry_after } => retry_after.unwrap_or(backoff),
+			Error::UnexpectedResponse { status, .. } if status.is_server_error() => backoff,
+			_ => return None,
+		};
+
+		Some(delay.min(MAX_RETRY_DELAY))
+	}
+}
+
+/// Verify a World ID proof using the Developer Portal API.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+#[allow(clippy::module_name_repetitions)]
+pub async fn verify_proof<A, V>(
+	proof: Proof,
+	app_id: AppId,
+	action: A,
+	signal: V,
+) -> Result<(), Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+	V: Signal + Send,
+{
+	Verification::new(app_id, action)?
+		.proof(proof)
+		.signal(signal)
+		.send()
+		.await
+}
+
+/// Verify a World ID proof using the Developer Portal API, with a custom configuration.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+#[allow(clippy::module_name_repetitions)]
+pub async fn verify_proof_with_config<A, V>(
+	proof: Proof,
+	app_id: AppId,
+	action: A,
+	signal: V,
+	config: &VerifyConfig,
+) -> Result<(), Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+	V: Signal + Send,
+{
+	Verification::with_config(app_id, action, config.clone())?
+		.proof(proof)
+		.signal(signal)
+		.send()
+		.await
+}
+
+/// Verify a borrowed World ID proof using the Developer Portal API.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+#[allow(clippy::module_name_repetitions)]
+pub async fn verify_proof_ref<A, V>(
+	proof: &Proof,
+	app_id: &AppId,
+	action: A,
+	signal: V,
+) -> Result<(), Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+	V: Signal + Send,
+{
+	let action: Action = action.try_into()?;
+	proof.validate_format()?;
+	let request = VerificationRequest::new(proof, &action, hash_signal(&signal));
+
+	send_verification(&request, app_id, &VerifyConfig::try_default()?).await?;
+
+	Ok(())
+}
+
+/// Verify a World ID proof using the Developer Portal API, with an already computed signal hash (as returned by `hash_to_field`).
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the proof is invalid (`Error::Verification`), or if there's an error validating the proof.
+#[allow(clippy::module_name_repetitions)]
+pub async fn verify_proof_prehashed<A>(
+	proof: &Proof,
+	app_id: &AppId,
+	action: A,
+	signal_hash: U256,
+) -> Result<(), Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+{
+	let action: Action = action.try_into()?;
+	proof.validate_format()?;
+	let request = VerificationRequest::new(proof, &action, Some(signal_hash));
+
+	send_verification(&request, app_id, &VerifyConfig::try_default()?).await?;
+
+	Ok(())
+}
+
+/// Hashes the signal as expected by the Developer Portal, omitting it entirely when empty.
+fn hash_signal<V: Signal + ?Sized>(signal: &V) -> Option<U256> {
+	if let Some(hash) = signal.prehashed() {
+		return Some(hash);
+	}
+
+	let signal = signal.encode_packed();
+
+	if signal.is_empty() {
+		None
+	} else {
+		Some(hash_to_field(&signal))
+	}
+}
+
+async fn send_verification(
+	request: &VerificationRequest<'_>,
+	app_id: &AppId,
+	config: &VerifyConfig,
+) -> Result<VerifyResponse, Error> {
+	let started_at = time::now();
+	let timer = time::Instant::now();
+	let result = send_verification_unaudited(request, app_id, config).await;
+
+	if let Some(sink) = &config.audit_sink {
+		sink.record(AuditRecord::new(request, app_id, started_at, &result));
+	}
+	record_metrics(request, app_id, config, timer, &result);
+
+	result
+}
+
+/// Reports the outcome and latency of a verification to the configured metrics, if any.
+fn record_metrics<T>(
+	request: &VerificationRequest<'_>,
+	app_id: &AppId,
+	config: &VerifyConfig,
+	started_at: time::Instant,
+	result: &Result<T, Error>,
+) {
+	if let Some(metrics) = &config.metrics {
+		metrics.verification_completed(
+			Labels {
+				app_id: &app_id.0,
+				action: request.action,
+			},
+			AuditOutcome::of(result),
+			started_at.elapsed(),
+		);
+	}
+}
+
+async fn send_verification_unaudited(
+	request: &VerificationRequest<'_>,
+	app_id: &AppId,
+	config: &VerifyConfig,
+) -> Result<VerifyResponse, Error> {
+	let response = send_request(
+		config,
+		Method::POST,
+		&format!("/api/{}/verify/{}", config.api_version, app_id.0),
+		Some(&request.for_version(config.api_version)),
+	)
+	.await?;
+
+	let rate_limit = parse_rate_limit(response.headers());
+
+	decode_verify_response(&response.bytes().await?, rate_limit)
+}
+
+/// Decodes the body of a successful verification, which may be empty.
+fn decode_verify_response(
+	body: &[u8],
+	rate_limit: Option<RateLimitInfo>,
+) -> Result<VerifyResponse, Error> {
+	if body.is_empty() {
+		return Ok(VerifyResponse {
+			rate_limit,
+			..VerifyResponse::default()
+		});
+	}
+
+	Ok(VerifyResponse {
+		rate_limit,
+		..serde_json::from_slice(body)?
+	})
+}
+
+/// Sends a request to the Developer Portal, retrying as configured, and returns the successful response.
+pub(crate) async fn send_request<B: Serialize + Sync + ?Sized>(
+	config: &VerifyConfig,
+	method: Method,
+	path: &str,
+	body: Option<&B>,
+) -> Result<reqwest::Response, Error> {
+	let url = config.endpoint(path)?;
+	let mut attempt = 0;
+
+	loop {
+		match send_request_once(config, method.clone(), url.clone(), body).await {
+			Err(error) => match config.retry_delay(&error, attempt) {
+				Some(delay) => {
+					time::sleep(delay).await;
+					attempt += 1;
+				},
+				None => return Err(error),
+			},
+			Ok(response) => return Ok(response),
+		}
+	}
+}
+
+async fn send_request_once<B: Serialize + Sync + ?Sized>(
+	config: &VerifyConfig,
+	method: Method,
+	url: Url,
+	body: Option<&B>,
+) -> Result<reqwest::Response, Error> {
+	let mut builder = config
+		.client
+		.request(method, url)
+		.header(header::USER_AGENT, &config.user_agent);
+
+	if let Some(body) = body {
+		builder = builder.json(body);
+	}
+	if let Some(timeout) = config.timeout {
+		builder = builder.timeout(timeout);
+	}
+	if let Some(api_key) = &config.api_key {
+		builder = builder.bearer_auth(api_key.expose());
+	}
+	for (name, value) in config.trace_context.iter().flat_map(TraceContext::headers) {
+		builder = builder.header(name, value);
+	}
+
+	let response = builder.send().await?;
+
+	match check_status(response.status(), response.headers()) {
+		ResponseStatus::Success => Ok(response),
+		ResponseStatus::Failed(error) => Err(error),
+		ResponseStatus::Rejected => {
+			Err(Error::Verification(response.json::<ErrorResponse>().await?))
+		},
+		ResponseStatus::Unexpected(status) => Err(Error::UnexpectedResponse {
+			status,
+			body: read_error_body(response).await,
+		}),
+	}
+}
+
+/// Reads the body of an unexpected response, stopping once there's more than `truncate_body` keeps, so huge bodies aren't buffered. The status is the error, so failing to read the body only shortens it.
+async fn read_error_body(response: reqwest::Response) -> String {
+	// `fetch` buffers the whole body on wasm anyway, and doesn't expose it in chunks.
+	#[cfg(target_arch = "wasm32")]
+	let body = response
+		.bytes()
+		.await
+		.map(|bytes| bytes.to_vec())
+		.unwrap_or_default();
+
+	#[cfg(not(target_arch = "wasm32"))]
+	let body = {
+		let mut response = response;
+		let mut body = Vec::new();
+		while body.len() < MAX_ERROR_BODY_READ {
+			let Ok(Some(chunk)) = response.chunk().await else {
+				break;
+			};
+			body.extend_from_slice(&chunk);
+		}
+
+		body
+	};
+
+	decode_error_body(body)
+}
+
+/// Decodes the start of an error body, read by the async or blocking client.
+fn decode_error_body(mut body: Vec<u8>) -> String {
+	body.truncate(MAX_ERROR_BODY_READ);
+
+	truncate_body(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// What the status of a response from the Developer Portal means, before its body is read.
+enum ResponseStatus {
+	Success,
+	/// The request failed, and the body isn't needed to tell why.
+	Failed(Error),
+	/// The request was rejected, with an `ErrorResponse` body.
+	Rejected,
+	/// The status wasn't expected, and the body should be kept to tell why.
+	Unexpected(StatusCode),
+}
+
+/// Maps the status of a response to an error, shared by the async and blocking clients.
+fn check_status(status: StatusCode, headers: &header::HeaderMap) -> ResponseStatus {
+	match status {
+		status if status.is_success() => ResponseStatus::Success,
+		status if status.is_redirection() => ResponseStatus::Failed(Error::Redirected {
+			status,
+			location: parse_location(headers),
+		}),
+		StatusCode::BAD_REQUEST => ResponseStatus::Rejected,
+		StatusCode::TOO_MANY_REQUESTS => ResponseStatus::Failed(Error::RateLimited {
+			retry_after: parse_retry_after(headers),
+		}),
+		status => ResponseStatus::Unexpected(status),
+	}
+}
+
+fn is_localhost(url: &Url) -> bool {
+	match url.host() {
+		Some(url::Host::Domain(domain)) => domain == "localhost",
+		Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+		Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+		None => false,
+	}
+}
+
+fn parse_location(headers: &header::HeaderMap) -> Option<String> {
+	Some(headers.get(header::LOCATION)?.to_str().ok()?.to_string())
+}
+
+/// Parses the `Retry-After` header, expressed in seconds.
+fn parse_retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+	headers
+		.get(header::RETRY_AFTER)?
+		.to_str()
+		.ok()?
+		.trim()
+		.parse()
+		.ok()
+		.map(Duration::from_secs)
+}
+
+/// Parses the `X-RateLimit-*` headers, ignoring any that are missing or malformed.
+fn parse_rate_limit(headers: &header::HeaderMap) -> Option<RateLimitInfo> {
+	let parse =
+		|name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+
+	let info = RateLimitInfo {
+		limit: parse("x-ratelimit-limit"),
+		remaining: parse("x-ratelimit-remaining"),
+		reset: parse("x-ratelimit-reset").map(reset_delay),
+	};
+
+	(info != RateLimitInfo::default()).then_some(info)
+}
+
+/// Converts a rate limit reset value, either a number of seconds or a Unix timestamp, to the time left until the reset.
+fn reset_delay(reset: u64) -> Duration {
+	if reset < RESET_TIMESTAMP_THRESHOLD {
+		return Duration::from_secs(reset);
+	}
+
+	let now = time::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+
+	Duration::from_secs(reset).saturating_sub(now)
+}
+
+pub(crate) fn truncate_body(mut body: String) -> String {
+	if body.len() > MAX_ERROR_BODY_LEN {
+		let mut end = MAX_ERROR_BODY_LEN;
+		while !body.is_char_boundary(end) {
+			end -= 1;
+		}
+
+		body.truncate(end);
+		body.push('…');
+	}
+
+	body
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixtures, hashing::encode_signal, PreHashedSignal};
+	use std::{str::FromStr, time::SystemTime};
+	use wiremock::{
+		matchers::{body_json, header, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn test_config(server: &MockServer, prefix: &str) -> VerifyConfig {
+		VerifyConfig {
+			base_url: Url::parse(&format!("{}{prefix}", server.uri())).unwrap(),
+			..VerifyConfig::default()
+		}
+	}
+
+	#[test]
+	fn test_endpoint_joining() {
+		for base in [
+			"https://portal.example",
+			"https://portal.example/",
+			"https://proxy.example/portal",
+			"https://proxy.example/portal/",
+		] {
+			let config = VerifyConfig {
+				base_url: Url::parse(base).unwrap(),
+				..VerifyConfig::default()
+			};
+
+			assert_eq!(
+				config.endpoint("/api/v2/verify/app_123").unwrap().as_str(),
+				format!("{}/api/v2/verify/app_123", base.trim_end_matches('/'))
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_verify_does_not_follow_redirects() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(
+				ResponseTemplate::new(302)
+					.insert_header("location", format!("{}/attacker", server.uri()).as_str()),
+			)
+			.mount(&server)
+			.await;
+		Mock::given(path("/attacker"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(0)
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::Redirected { status: StatusCode::FOUND, location: Some(location) } if location.ends_with("/attacker")
+		));
+	}
+
+	#[tokio::test]
+	async fn test_verify_rejects_plaintext_urls() {
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&VerifyConfig {
+				base_url: Url::parse("http://portal.example").unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await
+		.unwrap_err();
+
+		assert!(
+			matches!(error, Error::InsecureUrl(url) if url.host_str() == Some("portal.example"))
+		);
+	}
+
+	#[test]
+	fn test_is_localhost() {
+		for url in [
+			"http://localhost:8080",
+			"http://127.0.0.1",
+			"http://[::1]:3000",
+		] {
+			assert!(is_localhost(&Url::parse(url).unwrap()), "{url}");
+		}
+		for url in [
+			"http://portal.example",
+			"http://10.0.0.1",
+			"http://localhost.example",
+		] {
+			assert!(!is_localhost(&Url::parse(url).unwrap()), "{url}");
+		}
+	}
+
+	#[tokio::test]
+	async fn test_invalid_network_is_verification_error() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_network",
+				"detail": "Invalid network.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		// The environment of the proof isn't known, so the portal's error is kept as is.
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+		assert!(matches!(
+			&error,
+			Error::Verification(response) if response.code == VerifyErrorCode::InvalidNetwork
+		));
+		assert_eq!(error.as_app_error(), Some(AppError::InvalidNetwork));
+	}
+
+	#[tokio::test]
+	async fn test_proof_environment_preflight() {
+		for (app_id, proof_environment, app) in [
+			(
+				"app_staging_123",
+				Environment::Production,
+				Environment::Staging,
+			),
+			("app_123", Environment::Staging, Environment::Production),
+		] {
+			let error = Verification::new(AppId::from_str(app_id).unwrap(), "test-action")
+				.unwrap()
+				.proof(fixtures::proof())
+				.proof_environment(proof_environment)
+				.base_url(Url::parse("https://portal.invalid").unwrap())
+				.send()
+				.await
+				.unwrap_err();
+
+			assert!(matches!(
+				error,
+				Error::EnvironmentMismatch { app: a, proof: p } if a == app && p == proof_environment
+			));
+			assert_eq!(
+				error.to_string().contains("app_staging_*"),
+				app == Environment::Production
+			);
+			assert_eq!(error.as_app_error(), Some(AppError::InvalidNetwork));
+		}
+	}
+
+	#[tokio::test]
+	async fn test_trace_context() {
+		const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(header("traceparent", TRACEPARENT))
+			.and(header("tracestate", "congo=t61rcWkgMzE"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_456"))
+			.and(header("traceparent", TRACEPARENT))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, "").with_trace_context(
+				TraceContext::new(TRACEPARENT, Some("congo=t61rcWkgMzE".to_string())).unwrap(),
+			),
+		)
+		.await
+		.unwrap();
+
+		Verification::with_config(
+			AppId::from_str("app_456").unwrap(),
+			"test-action",
+			test_config(&server, ""),
+		)
+		.unwrap()
+		.proof(fixtures::proof())
+		.trace_context(TraceContext::from_str(TRACEPARENT).unwrap())
+		.send()
+		.await
+		.unwrap();
+
+		let requests = server.received_requests().await.unwrap();
+		assert!(!requests[1].headers.contains_key("tracestate"));
+	}
+
+	#[tokio::test]
+	async fn test_user_agent() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(header(
+				"user-agent",
+				format!("idkit-rs/{}", env!("CARGO_PKG_VERSION")).as_str(),
+			))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_456"))
+			.and(header(
+				"user-agent",
+				format!("idkit-rs/{} my-service/abc123", env!("CARGO_PKG_VERSION")).as_str(),
+			))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_789"))
+			.and(header("user-agent", "my-service/abc123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let config = test_config(&server, "");
+		for (app_id, config) in [
+			("app_123", config.clone()),
+			(
+				"app_456",
+				config.clone().append_user_agent("my-service/abc123"),
+			),
+			(
+				"app_789",
+				VerifyConfig {
+					user_agent: "my-service/abc123".to_string(),
+					..config
+				},
+			),
+		] {
+			verify_proof_with_config(
+				fixtures::proof(),
+				AppId::from_str(app_id).unwrap(),
+				"test-action",
+				"",
+				&config,
+			)
+			.await
+			.unwrap();
+		}
+	}
+
+	#[tokio::test]
+	async fn test_verify_with_custom_base_url() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/portal/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, "/portal/"),
+		)
+		.await
+		.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_verify_returns_error_response() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(
+			matches!(error, Error::Verification(ErrorResponse { code, .. }) if code == VerifyErrorCode::InvalidProof)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_verify_rate_limit_headers() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(
+				ResponseTemplate::new(200)
+					.insert_header("x-ratelimit-limit", "100")
+					.insert_header("x-ratelimit-remaining", "99")
+					.insert_header("x-ratelimit-reset", "invalid"),
+			)
+			.mount(&server)
+			.await;
+
+		let response = Verification::with_config(
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			test_config(&server, ""),
+		)
+		.unwrap()
+		.proof(fixtures::proof())
+		.send_with_response()
+		.await
+		.unwrap();
+
+		assert_eq!(
+			response.rate_limit,
+			Some(RateLimitInfo {
+				limit: Some(100),
+				remaining: Some(99),
+				reset: None,
+			})
+		);
+	}
+
+	#[tokio::test]
+	async fn test_verify_without_rate_limit_headers() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.mount(&server)
+			.await;
+
+		let response = Verification::with_config(
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			test_config(&server, ""),
+		)
+		.unwrap()
+		.proof(fixtures::proof())
+		.send_with_response()
+		.await
+		.unwrap();
+
+		assert_eq!(response.rate_limit, None);
+	}
+
+	fn error_response(code: VerifyErrorCode) -> Error {
+		Error::Verification(ErrorResponse {
+			code,
+			detail: String::new(),
+			attribute: None,
+		})
+	}
+
+	#[tokio::test]
+	async fn test_error_kind() {
+		let builder_error = reqwest::Client::new()
+			.get("http://[::1")
+			.build()
+			.unwrap_err();
+		let connect_error = reqwest::Client::new()
+			.get("http://127.0.0.1:1")
+			.send()
+			.await
+			.unwrap_err();
+
+		for (error, kind, is_invalid_proof) in [
+			(
+				error_response(VerifyErrorCode::InvalidProof),
+				ErrorKind::User,
+				true,
+			),
+			(
+				error_response(VerifyErrorCode::InvalidMerkleRoot),
+				ErrorKind::User,
+				true,
+			),
+			(
+				error_response(VerifyErrorCode::MaxVerificationsReached),
+				ErrorKind::User,
+				false,
+			),
+			(
+				error_response(VerifyErrorCode::NotFound),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::MalformedProof {
+					field: "proof",
+					reason: String::new(),
+				},
+				ErrorKind::User,
+				true,
+			),
+			(
+				Error::InsufficientVerificationLevel {
+					got: VerificationLevel::Device,
+					required: VerificationLevel::Orb,
+				},
+				ErrorKind::User,
+				false,
+			),
+			(
+				Error::Timeout {
+					source: reqwest::Client::new()
+						.get("http://[::1")
+						.build()
+						.unwrap_err(),
+				},
+				ErrorKind::Transport,
+				false,
+			),
+			(
+				Error::RateLimited { retry_after: None },
+				ErrorKind::Transport,
+				false,
+			),
+			(Error::from(connect_error), ErrorKind::Transport, false),
+			(
+				Error::Dns {
+					source: reqwest::Client::new()
+						.get("http://[::1")
+						.build()
+						.unwrap_err(),
+				},
+				ErrorKind::Transport,
+				false,
+			),
+			(
+				Error::Reqwest(builder_error),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::UnexpectedResponse {
+					status: StatusCode::BAD_GATEWAY,
+					body: String::new(),
+				},
+				ErrorKind::Transport,
+				false,
+			),
+			(
+				Error::UnexpectedResponse {
+					status: StatusCode::UNAUTHORIZED,
+					body: String::new(),
+				},
+				ErrorKind::Protocol,
+				false,
+			),
+			(
+				Error::Serde(serde_json::from_str::<()>("invalid").unwrap_err()),
+				ErrorKind::Protocol,
+				false,
+			),
+			(Error::Tls(String::new()), ErrorKind::Configuration, false),
+			(
+				Error::Url(url::ParseError::EmptyHost),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::InsecureUrl(Url::parse("http://portal.example").unwrap()),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::Redirected {
+					status: StatusCode::FOUND,
+					location: None,
+				},
+				ErrorKind::Protocol,
+				false,
+			),
+			(
+				Error::InvalidAction(ActionError::TooLong(300)),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::ActionNotFound(String::new()),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::AppNotFound(String::new()),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::MissingField("proof"),
+				ErrorKind::Configuration,
+				false,
+			),
+			(
+				Error::EnvironmentMismatch {
+					app: Environment::Staging,
+					proof: Environment::Production,
+				},
+				ErrorKind::Configuration,
+				false,
+			),
+		] {
+			assert_eq!(error.kind(), kind, "{error:?}");
+			assert_eq!(
+				error.is_retryable(),
+				kind == ErrorKind::Transport,
+				"{error:?}"
+			);
+			assert_eq!(error.is_invalid_proof(), is_invalid_proof, "{error:?}");
+		}
+	}
+
+	#[test]
+	fn test_reset_delay() {
+		assert_eq!(reset_delay(30), Duration::from_secs(30));
+		assert_eq!(reset_delay(RESET_TIMESTAMP_THRESHOLD), Duration::ZERO);
+
+		let in_a_minute = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_secs()
+			+ 60;
+		assert!(reset_delay(in_a_minute) <= Duration::from_secs(60));
+		assert!(reset_delay(in_a_minute) > Duration::from_secs(50));
+	}
+
+	#[tokio::test]
+	async fn test_verify_unexpected_response() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+
+		assert_eq!(
+			error.to_string(),
+			"unexpected response (500 Internal Server Error): Internal Server Error"
+		);
+		assert!(matches!(
+			error,
+			Error::UnexpectedResponse { status: StatusCode::INTERNAL_SERVER_ERROR, body } if body == "Internal Server Error"
+		));
+	}
+
+	#[test]
+	fn test_prehashed_request_matches() {
+		let proof = fixtures::proof();
+
+		for signal in ["test", "0x1234"] {
+			assert_eq!(
+				serde_json::to_vec(&VerificationRequest::new(
+					&proof,
+					"test-action",
+					hash_signal(&signal)
+				))
+				.unwrap(),
+				serde_json::to_vec(&VerificationRequest::new(
+					&proof,
+					"test-action",
+					Some(encode_signal(&signal))
+				))
+				.unwrap()
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn test_verify_ref_and_prehashed_send_same_body() {
+		let proof = fixtures::proof();
+		let app_id = AppId::from_str("app_123").unwrap();
+
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_json(serde_json::json!({
+				"action": "test-action",
+				"proof": proof.proof,
+				"merkle_root": proof.merkle_root,
+				"nullifier_hash": proof.nullifier_hash,
+				"verification_level": "orb",
+				"signal_hash": "0x009c22ff5f21f0b81b113e63f7db6da94fedef11b2119b4088b89664fb9a3cb6",
+			})))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(4)
+			.mount(&server)
+			.await;
+
+		let config = test_config(&server, "");
+		let request = VerificationRequest::new(&proof, "test-action", hash_signal(&"test"));
+		send_verification(&request, &app_id, &config).await.unwrap();
+
+		let request = VerificationRequest::new(&proof, "test-action", Some(encode_signal(&"test")));
+		send_verification(&request, &app_id, &config).await.unwrap();
+
+		verify_proof_with_config(
+			proof.clone(),
+			app_id.clone(),
+			"test-action",
+			"test",
+			&config,
+		)
+		.await
+		.unwrap();
+
+		let prehashed = PreHashedSignal(encode_signal("test"));
+		verify_proof_with_config(proof, app_id, "test-action", prehashed, &config)
+			.await
+			.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_verify_ref_and_prehashed_validate_action() {
+		let proof = fixtures::proof();
+		let app_id = AppId::from_str("app_123").unwrap();
+		let action = "a".repeat(300);
+
+		assert!(matches!(
+			verify_proof_ref(&proof, &app_id, action.as_str(), "").await,
+			Err(Error::InvalidAction(ActionError::TooLong(300)))
+		));
+		assert!(matches!(
+			verify_proof_prehashed(&proof, &app_id, &action, U256::ZERO).await,
+			Err(Error::InvalidAction(ActionError::TooLong(300)))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_verify_u256_and_prehashed_signal_bodies() {
+		let proof = fixtures::proof();
+		let app_id = AppId::from_str("app_123").unwrap();
+
+		let server = MockServer::start().await;
+		for signal_hash in [
+			// `U256::from(1)`, hashed as `hash_to_field(abi.encodePacked(uint256(1)))`
+			"0x00b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0c",
+			// `PreHashedSignal(U256::from(1))`, sent as-is
+			"0x0000000000000000000000000000000000000000000000000000000000000001",
+		] {
+			Mock::given(method("POST"))
+				.and(path("/api/v2/verify/app_123"))
+				.and(body_json(serde_json::json!({
+					"action": "test-action",
+					"proof": proof.proof,
+					"merkle_root": proof.merkle_root,
+					"nullifier_hash": proof.nullifier_hash,
+					"verification_level": "orb",
+					"signal_hash": signal_hash,
+				})))
+				.respond_with(ResponseTemplate::new(200))
+				.expect(1)
+				.mount(&server)
+				.await;
+		}
+
+		let config = test_config(&server, "");
+		verify_proof_with_config(
+			proof.clone(),
+			app_id.clone(),
+			"test-action",
+			U256::from(1),
+			&config,
+		)
+		.await
+		.unwrap();
+		verify_proof_with_config(
+			proof,
+			app_id,
+			"test-action",
+			PreHashedSignal(U256::from(1)),
+			&config,
+		)
+		.await
+		.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_rate_limited_with_retry_after() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "120"))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::RateLimited { retry_after: Some(retry_after) } if retry_after == Duration::from_secs(120)
+		));
+	}
+
+	#[tokio::test]
+	async fn test_rate_limited_without_retry_after() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(429))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(error, Error::RateLimited { retry_after: None }));
+	}
+
+	#[tokio::test]
+	async fn test_retries_after_rate_limit_and_server_error() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+			.up_to_n_times(1)
+			.with_priority(1)
+			.expect(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(503))
+			.up_to_n_times(1)
+			.with_priority(2)
+			.expect(1)
+			.mount(&server)
+			.await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&VerifyConfig {
+				max_retries: 2,
+				..test_config(&server, "")
+			},
+		)
+		.await
+		.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_verify_timeout() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&VerifyConfig {
+				timeout: Some(Duration::from_millis(100)),
+				..test_config(&server, "")
+			},
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(&error, Error::Timeout { source } if source.is_timeout()));
+		assert!(std::error::Error::source(&error).is_some());
+	}
+
+	#[tokio::test]
+	async fn test_verify_connection_refused() {
+		let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		drop(listener);
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&VerifyConfig {
+				base_url: Url::parse(&format!("http://{address}")).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(error, Error::Connect { .. }), "{error:?}");
+		assert_eq!(error.kind(), ErrorKind::Transport);
+	}
+
+	#[tokio::test]
+	async fn test_builder_matches_legacy_request() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(2)
+			.mount(&server)
+			.await;
+
+		let config = test_config(&server, "");
+		let app_id = AppId::from_str("app_123").unwrap();
+
+		verify_proof_with_config(
+			fixtures::proof(),
+			app_id.clone(),
+			"test-action",
+			"test",
+			&config,
+		)
+		.await
+		.unwrap();
+		Verification::with_config(app_id, "test-action", config)
+			.unwrap()
+			.proof(fixtures::proof())
+			.signal("test")
+			.send()
+			.await
+			.unwrap();
+
+		let requests = server.received_requests().await.unwrap();
+		assert_eq!(requests[0].body, requests[1].body);
+	}
+
+	#[tokio::test]
+	async fn test_builder_requires_proof() {
+		let error = Verification::new(AppId::from_str("app_123").unwrap(), "test-action")
+			.unwrap()
+			.send()
+			.await
+			.unwrap_err();
+
+		assert!(matches!(error, Error::MissingField("proof")));
+	}
+
+	#[tokio::test]
+	async fn test_verify_sends_api_key() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(header("Authorization", "Bearer api_c2VjcmV0"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(1)
+			.mount(&server)
+			.await;
+
+		let config = VerifyConfig {
+			api_key: Some(ApiKey::from_str("api_c2VjcmV0").unwrap()),
+			..test_config(&server, "")
+		};
+		assert!(!format!("{config:?}").contains("c2VjcmV0"));
+
+		verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&config,
+		)
+		.await
+		.unwrap();
+	}
+
+	#[tokio::test]
+	async fn test_verify_rejects_invalid_action() {
+		let error = verify_proof(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"cast vote",
+			"",
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::InvalidAction(ActionError::InvalidCharacter(' '))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_verify_rejects_malformed_proof() {
+		let error = verify_proof(
+			Proof {
+				proof: "0x1234".to_string(),
+				..fixtures::proof()
+			},
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::MalformedProof { field: "proof", .. }
+		));
+	}
+
+	#[tokio::test]
+	async fn test_builder_required_level() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(200))
+			.expect(3)
+			.mount(&server)
+			.await;
+
+		for (got, required) in [
+			(VerificationLevel::Orb, VerificationLevel::Orb),
+			(VerificationLevel::Orb, VerificationLevel::Device),
+			(VerificationLevel::Device, VerificationLevel::Device),
+			(VerificationLevel::Device, VerificationLevel::Orb),
+		] {
+			let result = Verification::with_config(
+				AppId::from_str("app_123").unwrap(),
+				"test-action",
+				test_config(&server, ""),
+			)
+			.unwrap()
+			.proof(Proof {
+				verification_level: got,
+				..fixtures::proof()
+			})
+			.required_level(required)
+			.send()
+			.await;
+
+			if got.satisfies(required) {
+				result.unwrap();
+			} else {
+				assert!(matches!(
+					result.unwrap_err(),
+					Error::InsufficientVerificationLevel { got: g, required: r } if g == got && r == required
+				));
+			}
+		}
+	}
+
+	#[test]
+	fn test_request_body_versions() {
+		let proof = Proof {
+			proof: "0x01".to_string(),
+			merkle_root: "0x02".to_string(),
+			nullifier_hash: "0x03".to_string(),
+			verification_level: VerificationLevel::Device,
+		};
+		let request = VerificationRequest::new(&proof, "test-action", None);
+
+		assert_eq!(
+			serde_json::to_string(&request.for_version(ApiVersion::V2)).unwrap(),
+			r#"{"action":"test-action","proof":"0x01","merkle_root":"0x02","nullifier_hash":"0x03","verification_level":"device"}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&request.for_version(ApiVersion::V1)).unwrap(),
+			r#"{"action":"test-action","proof":"0x01","merkle_root":"0x02","nullifier_hash":"0x03","credential_type":"device"}"#
+		);
+	}
+
+	#[tokio::test]
+	async fn test_verify_v1() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v1/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_merkle_root",
+				"message": "The provided merkle root is invalid.",
+			})))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&VerifyConfig {
+				api_version: ApiVersion::V1,
+				..test_config(&server, "")
+			},
+		)
+		.await
+		.unwrap_err();
+
+		assert!(matches!(
+			error,
+			Error::Verification(ErrorResponse {
+				code: VerifyErrorCode::InvalidMerkleRoot,
+				attribute: None,
+				..
+			})
+		));
+	}
+
+	/// The length of the first HTTP request in `data`, if it has been fully received.
+	fn request_len(data: &[u8]) -> Option<usize> {
+		let headers_end = data.windows(4).position(|window| window == b"\r\n\r\n")? + 4;
+		let content_length = String::from_utf8_lossy(&data[..headers_end])
+			.lines()
+			.find_map(|line| {
+				let (name, value) = line.split_once(':')?;
+				name.eq_ignore_ascii_case("content-length")
+					.then(|| value.trim().parse::<usize>().ok())?
+			})
+			.unwrap_or(0);
+
+		(data.len() >= headers_end + content_length).then_some(headers_end + content_length)
+	}
+
+	#[test]
+	fn test_try_default_config() {
+		let config = VerifyConfig::try_default().unwrap();
+
+		assert_eq!(config.timeout, Some(DEFAULT_TIMEOUT));
+	}
+
+	#[tokio::test]
+	async fn test_default_config_reuses_connections() {
+		use std::sync::{
+			atomic::{AtomicUsize, Ordering},
+			Arc,
+		};
+		use tokio::{
+			io::{AsyncReadExt, AsyncWriteExt},
+			net::TcpListener,
+		};
+
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let base_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+		let connections = Arc::new(AtomicUsize::new(0));
+
+		let accepted = connections.clone();
+		tokio::spawn(async move {
+			while let Ok((mut stream, _)) = listener.accept().await {
+				accepted.fetch_add(1, Ordering::SeqCst);
+
+				tokio::spawn(async move {
+					let mut pending = Vec::new();
+					let mut buf = [0; 4096];
+
+					while let Ok(n @ 1..) = stream.read(&mut buf).await {
+						pending.extend_from_slice(&buf[..n]);
+
+						// Answer every complete request, keeping the connection open.
+						while let Some(len) = request_len(&pending) {
+							pending.drain(..len);
+							let _ = stream
+								.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+								.await;
+						}
+					}
+				});
+			}
+		});
+
+		for _ in 0..3 {
+			verify_proof_with_config(
+				fixtures::proof(),
+				AppId::from_str("app_123").unwrap(),
+				"test-action",
+				"",
+				&VerifyConfig {
+					base_url: base_url.clone(),
+					..VerifyConfig::default()
+				},
+			)
+			.await
+			.unwrap();
+		}
+
+		assert_eq!(connections.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test]
+	async fn test_large_error_body() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(500).set_body_string("x".repeat(1 << 20)))
+			.mount(&server)
+			.await;
+
+		let error = verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&test_config(&server, ""),
+		)
+		.await
+		.unwrap_err();
+
+		assert!(
+			matches!(
+				&error,
+				Error::UnexpectedResponse { status, body }
+					if *status == StatusCode::INTERNAL_SERVER_ERROR
+						&& *body == format!("{}…", "x".repeat(MAX_ERROR_BODY_LEN))
+			),
+			"{error:?}"
+		);
+	}
+
+	#[test]
+	fn test_truncate_body() {
+		assert_eq!(truncate_body("short".to_string()), "short");
+
+		let truncated = truncate_body("é".repeat(MAX_ERROR_BODY_LEN));
+		assert!(truncated.len() <= MAX_ERROR_BODY_LEN + '…'.len_utf8());
+		assert!(truncated.ends_with('…'));
+	}
+}
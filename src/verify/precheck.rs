@@ -0,0 +1,188 @@
+use reqwest::Method;
+use serde::Serialize;
+
+use super::{parse_rate_limit, send_request, Error, PrecheckResponse, VerifyConfig};
+use crate::session::{Action, AppId};
+
+#[derive(Debug, Serialize)]
+struct PrecheckRequest<'a> {
+	action: &'a str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	nullifier_hash: Option<&'a str>,
+}
+
+/// Query the Developer Portal for the state of an action and, optionally, how many times a nullifier has been used for it.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the request is rejected by the Developer Portal (`Error::Verification`), or if the response can't be retrieved or decoded.
+pub async fn precheck<A>(
+	app_id: &AppId,
+	action: A,
+	nullifier_hash: Option<&str>,
+) -> Result<PrecheckResponse, Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+{
+	precheck_with_config(
+		app_id,
+		action,
+		nullifier_hash,
+		&VerifyConfig::try_default()?,
+	)
+	.await
+}
+
+/// Query the Developer Portal for the state of an action, with a custom configuration.
+///
+/// # Errors
+///
+/// Errors if the action is invalid (`Error::InvalidAction`), if the request is rejected by the Developer Portal (`Error::Verification`), or if the response can't be retrieved or decoded.
+pub async fn precheck_with_config<A>(
+	app_id: &AppId,
+	action: A,
+	nullifier_hash: Option<&str>,
+	config: &VerifyConfig,
+) -> Result<PrecheckResponse, Error>
+where
+	A: TryInto<Action> + Send,
+	Error: From<A::Error>,
+{
+	let action: Action = action.try_into()?;
+	let response = send_request(
+		config,
+		Method::POST,
+		&format!("/api/v2/precheck/{}", app_id.0),
+		Some(&PrecheckRequest {
+			action: &action,
+			nullifier_hash,
+		}),
+	)
+	.await?;
+
+	let rate_limit = parse_rate_limit(response.headers());
+
+	Ok(PrecheckResponse {
+		rate_limit,
+		..response.json().await?
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::verify::{ActionStatus, RateLimitInfo, VerifyErrorCode};
+	use std::{str::FromStr, time::Duration};
+	use url::Url;
+	use wiremock::{
+		matchers::{body_json, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	#[tokio::test]
+	async fn test_precheck() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/precheck/app_123"))
+			.and(body_json(serde_json::json!({
+				"action": "vote_1",
+				"nullifier_hash": "0x1234",
+			})))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"action": "vote_1",
+				"status": "active",
+				"max_verifications": 1,
+				"nullifiers": [{ "nullifier_hash": "0x1234", "uses": 1 }],
+			})))
+			.mount(&server)
+			.await;
+
+		let response = precheck_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			Some("0x1234"),
+			&VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(response.status, ActionStatus::Active);
+		assert_eq!(response.remaining_verifications(), Some(0));
+		assert_eq!(response.rate_limit, None);
+	}
+
+	#[tokio::test]
+	async fn test_precheck_rate_limit_headers() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/precheck/app_123"))
+			.respond_with(
+				ResponseTemplate::new(200)
+					.insert_header("x-ratelimit-limit", "100")
+					.insert_header("x-ratelimit-remaining", "42")
+					.insert_header("x-ratelimit-reset", "30")
+					.set_body_json(serde_json::json!({
+						"action": "vote_1",
+						"status": "active",
+						"max_verifications": 0,
+					})),
+			)
+			.mount(&server)
+			.await;
+
+		let response = precheck_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			None,
+			&VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await
+		.unwrap();
+
+		assert_eq!(
+			response.rate_limit,
+			Some(RateLimitInfo {
+				limit: Some(100),
+				remaining: Some(42),
+				reset: Some(Duration::from_secs(30)),
+			})
+		);
+	}
+
+	#[tokio::test]
+	async fn test_precheck_error() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/precheck/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "not_found",
+				"detail": "Action not found.",
+				"attribute": "action",
+			})))
+			.mount(&server)
+			.await;
+
+		let error = precheck_with_config(
+			&AppId::from_str("app_123").unwrap(),
+			"vote_1",
+			None,
+			&VerifyConfig {
+				base_url: Url::parse(&server.uri()).unwrap(),
+				..VerifyConfig::default()
+			},
+		)
+		.await
+		.unwrap_err();
+
+		assert!(
+			matches!(error, Error::Verification(response) if response.code == VerifyErrorCode::NotFound)
+		);
+	}
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{time::now, VerifyResponse};
+use crate::{
+	session::{Action, AppId},
+	Proof,
+};
+
+/// The current version of the `ProofRecord` schema.
+const SCHEMA_VERSION: u32 = 1;
+
+/// A successfully verified proof, bundled with what it was verified for, to be persisted.
+///
+/// The JSON representation is stable, and any breaking change to it will bump `schema_version`:
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "app_id": "app_123",
+///   "action": "vote_1",
+///   "proof": {
+///     "proof": "0x...",
+///     "merkle_root": "0x...",
+///     "nullifier_hash": "0x...",
+///     "verification_level": "orb"
+///   },
+///   "uses": 1,
+///   "verified_at": "2024-09-01T12:00:00Z",
+///   "first_verified_at": "2024-09-01T12:00:00Z"
+/// }
+/// ```
+///
+/// `uses` and `first_verified_at` are `null` when the Developer Portal doesn't report them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofRecord {
+	/// The version of the schema this record was written with.
+	pub schema_version: u32,
+	pub app_id: AppId,
+	pub action: Action,
+	pub proof: Proof,
+	/// How many times the nullifier has been used for the action, including this verification.
+	pub uses: Option<u64>,
+	/// When the proof was verified.
+	#[serde(with = "time::serde::rfc3339")]
+	pub verified_at: OffsetDateTime,
+	/// When the nullifier was first verified for the action.
+	#[serde(with = "time::serde::rfc3339::option")]
+	pub first_verified_at: Option<OffsetDateTime>,
+}
+
+impl ProofRecord {
+	/// Create a record of a proof that was just verified, from the response returned by `Verification::send_with_response`.
+	#[must_use]
+	pub fn new(proof: Proof, app_id: AppId, action: Action, response: &VerifyResponse) -> Self {
+		Self {
+			proof,
+			app_id,
+			action,
+			uses: response.uses,
+			schema_version: SCHEMA_VERSION,
+			verified_at: OffsetDateTime::from(now()),
+			first_verified_at: response
+				.created_at
+				.as_deref()
+				.and_then(|created_at| OffsetDateTime::parse(created_at, &Rfc3339).ok()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::session::VerificationLevel;
+	use std::str::FromStr;
+
+	fn timestamp(unix: i64) -> OffsetDateTime {
+		OffsetDateTime::from_unix_timestamp(unix).unwrap()
+	}
+
+	fn test_record() -> ProofRecord {
+		ProofRecord {
+			schema_version: 1,
+			app_id: AppId::from_str("app_123").unwrap(),
+			action: Action::from_str("vote_1").unwrap(),
+			proof: Proof {
+				proof: "0x1234".to_string(),
+				merkle_root: "0x5678".to_string(),
+				nullifier_hash: "0x9abc".to_string(),
+				verification_level: VerificationLevel::Orb,
+			},
+			uses: Some(1),
+			verified_at: timestamp(1_725_192_000),
+			first_verified_at: Some(timestamp(1_722_501_000)),
+		}
+	}
+
+	#[test]
+	fn test_schema() {
+		assert_eq!(
+			serde_json::to_value(test_record()).unwrap(),
+			serde_json::json!({
+				"schema_version": 1,
+				"app_id": "app_123",
+				"action": "vote_1",
+				"proof": {
+					"proof": "0x1234",
+					"merkle_root": "0x5678",
+					"nullifier_hash": "0x9abc",
+					"verification_level": "orb",
+				},
+				"uses": 1,
+				"verified_at": "2024-09-01T12:00:00Z",
+				"first_verified_at": "2024-08-01T08:30:00Z",
+			})
+		);
+	}
+
+	#[test]
+	fn test_json_roundtrip() {
+		let record = test_record();
+		let json = serde_json::to_string(&record).unwrap();
+
+		assert_eq!(serde_json::from_str::<ProofRecord>(&json).unwrap(), record);
+
+		let record = ProofRecord {
+			uses: None,
+			first_verified_at: None,
+			..test_record()
+		};
+		let json = serde_json::to_string(&record).unwrap();
+
+		assert_eq!(serde_json::from_str::<ProofRecord>(&json).unwrap(), record);
+	}
+
+	#[test]
+	fn test_rejects_invalid_app_id() {
+		let mut json = serde_json::to_value(test_record()).unwrap();
+		json["app_id"] = "123".into();
+
+		assert!(serde_json::from_value::<ProofRecord>(json).is_err());
+	}
+
+	#[test]
+	fn test_new_from_response() {
+		let before = OffsetDateTime::now_utc();
+		let record = ProofRecord::new(
+			test_record().proof,
+			AppId::from_str("app_123").unwrap(),
+			Action::from_str("vote_1").unwrap(),
+			&VerifyResponse {
+				uses: Some(2),
+				created_at: Some("2024-08-01T08:30:00.000Z".to_string()),
+				..VerifyResponse::default()
+			},
+		);
+
+		assert_eq!(record.schema_version, 1);
+		assert_eq!(record.uses, Some(2));
+		assert_eq!(record.first_verified_at, Some(timestamp(1_722_501_000)));
+		assert!(record.verified_at >= before);
+	}
+}
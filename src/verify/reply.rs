@@ -0,0 +1,202 @@
+use reqwest::StatusCode;
+
+use super::Error;
+use crate::ErrorKind;
+
+/// The HTTP response a server should send when it can't verify a proof it received, shared by the web framework integrations.
+///
+/// Serializes as `{"code": ..., "detail": ...}`, the shape of the Developer Portal's own errors. Only rejections of the proof are detailed, other failures get a generic description so the server's configuration isn't leaked to clients.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ErrorReply {
+	/// The HTTP status of the response.
+	#[serde(skip)]
+	pub status: StatusCode,
+	/// A machine-readable code: the Developer Portal's when it rejected the proof, or one of `invalid_request`, `malformed_proof`, `insufficient_verification_level`, `invalid_network`, `rate_limited`, `portal_timeout`, `portal_unavailable` or `internal_error`.
+	pub code: String,
+	/// A human-readable description of the error.
+	pub detail: String,
+}
+
+impl ErrorReply {
+	/// The reply to a request that doesn't contain a proof, or not in the expected format.
+	#[must_use]
+	pub fn invalid_request(detail: impl Into<String>) -> Self {
+		Self::new(StatusCode::BAD_REQUEST, "invalid_request", detail)
+	}
+
+	/// The reply when the server isn't configured to verify proofs.
+	pub(crate) fn internal_error() -> Self {
+		Self::new(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			"internal_error",
+			"The server isn't able to verify proofs",
+		)
+	}
+
+	fn new(status: StatusCode, code: &str, detail: impl Into<String>) -> Self {
+		Self {
+			status,
+			code: code.to_string(),
+			detail: detail.into(),
+		}
+	}
+}
+
+impl std::fmt::Display for ErrorReply {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}: {}", self.code, self.detail)
+	}
+}
+
+impl From<&Error> for ErrorReply {
+	fn from(error: &Error) -> Self {
+		let rejected = |code: &str| Self::new(StatusCode::BAD_REQUEST, code, error.to_string());
+
+		match error {
+			Error::Verification(response) if error.kind() == ErrorKind::User => Self::new(
+				StatusCode::BAD_REQUEST,
+				response.code.as_str(),
+				&response.detail,
+			),
+			Error::MalformedProof { .. } => rejected("malformed_proof"),
+			Error::InsufficientVerificationLevel { .. } => {
+				rejected("insufficient_verification_level")
+			},
+			Error::EnvironmentMismatch { .. } => rejected("invalid_network"),
+			Error::RateLimited { .. } => Self::new(
+				StatusCode::SERVICE_UNAVAILABLE,
+				"rate_limited",
+				"Too many verifications, try again later",
+			),
+			Error::Timeout { .. } => Self::new(
+				StatusCode::GATEWAY_TIMEOUT,
+				"portal_timeout",
+				"The Developer Portal took too long to respond, try again later",
+			),
+			_ => match error.kind() {
+				ErrorKind::Transport | ErrorKind::Protocol => Self::new(
+					StatusCode::BAD_GATEWAY,
+					"portal_unavailable",
+					"The proof couldn't be verified with the Developer Portal, try again later",
+				),
+				ErrorKind::User | ErrorKind::Configuration | ErrorKind::Crypto => {
+					Self::internal_error()
+				},
+			},
+		}
+	}
+}
+
+impl From<Error> for ErrorReply {
+	fn from(error: Error) -> Self {
+		Self::from(&error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		session::{Environment, VerificationLevel},
+		verify::{ErrorResponse, VerifyErrorCode},
+	};
+
+	fn portal_error(code: VerifyErrorCode) -> Error {
+		Error::Verification(ErrorResponse {
+			code,
+			detail: "Rejected by the portal.".to_string(),
+			attribute: None,
+		})
+	}
+
+	#[test]
+	fn test_error_reply() {
+		for (error, status, code) in [
+			(
+				portal_error(VerifyErrorCode::InvalidProof),
+				StatusCode::BAD_REQUEST,
+				"invalid_proof",
+			),
+			(
+				portal_error(VerifyErrorCode::MaxVerificationsReached),
+				StatusCode::BAD_REQUEST,
+				"max_verifications_reached",
+			),
+			(
+				portal_error(VerifyErrorCode::NotFound),
+				StatusCode::INTERNAL_SERVER_ERROR,
+				"internal_error",
+			),
+			(
+				Error::MalformedProof {
+					field: "proof",
+					reason: "missing 0x prefix".to_string(),
+				},
+				StatusCode::BAD_REQUEST,
+				"malformed_proof",
+			),
+			(
+				Error::InsufficientVerificationLevel {
+					got: VerificationLevel::Device,
+					required: VerificationLevel::Orb,
+				},
+				StatusCode::BAD_REQUEST,
+				"insufficient_verification_level",
+			),
+			(
+				Error::EnvironmentMismatch {
+					app: Environment::Production,
+					proof: Environment::Staging,
+				},
+				StatusCode::BAD_REQUEST,
+				"invalid_network",
+			),
+			(
+				Error::RateLimited { retry_after: None },
+				StatusCode::SERVICE_UNAVAILABLE,
+				"rate_limited",
+			),
+			(
+				Error::Timeout {
+					source: reqwest::Client::new()
+						.get("http://[::1")
+						.build()
+						.unwrap_err(),
+				},
+				StatusCode::GATEWAY_TIMEOUT,
+				"portal_timeout",
+			),
+			(
+				Error::UnexpectedResponse {
+					status: StatusCode::INTERNAL_SERVER_ERROR,
+					body: "oops".to_string(),
+				},
+				StatusCode::BAD_GATEWAY,
+				"portal_unavailable",
+			),
+			(
+				Error::MissingField("proof"),
+				StatusCode::INTERNAL_SERVER_ERROR,
+				"internal_error",
+			),
+		] {
+			let reply = ErrorReply::from(&error);
+
+			assert_eq!(reply.status, status, "{error}");
+			assert_eq!(reply.code, code, "{error}");
+		}
+	}
+
+	#[test]
+	fn test_error_reply_body() {
+		let reply = ErrorReply::from(portal_error(VerifyErrorCode::InvalidProof));
+
+		assert_eq!(
+			serde_json::to_value(&reply).unwrap(),
+			serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "Rejected by the portal.",
+			})
+		);
+	}
+}
@@ -0,0 +1,30 @@
+//! Clocks and timers that also work on `wasm32-unknown-unknown`, where the `std::time` clocks panic and there's no tokio timer.
+
+use std::time::{Duration, SystemTime};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
+/// The current wall-clock time.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> SystemTime {
+	SystemTime::now()
+}
+
+/// The current wall-clock time.
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> SystemTime {
+	std::time::UNIX_EPOCH + Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+	tokio::time::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+	gloo_timers::future::sleep(duration).await;
+}
@@ -0,0 +1,337 @@
+use ring::digest::{digest, SHA256};
+use rustls::{
+	client::{
+		danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+		WebPkiServerVerifier,
+	},
+	crypto::ring::default_provider,
+	pki_types::{CertificateDer, ServerName, UnixTime},
+	ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use std::sync::Arc;
+
+use super::{Error, VerifyConfig, DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT};
+
+/// TLS settings for the connection to the Developer Portal, e.g. to go through an egress proxy with its own certificate authority, or to pin the portal's certificate.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+	/// DER-encoded root certificates to trust.
+	pub root_certificates: Vec<Vec<u8>>,
+	/// Whether to also trust the Mozilla root certificates bundled with the crate. Defaults to `true`.
+	pub built_in_roots: bool,
+	/// SHA-256 fingerprints of the DER-encoded leaf certificates the Developer Portal is allowed to present. When not empty, connections presenting any other certificate are rejected, even if it chains to a trusted root.
+	pub pinned_certificates: Vec<[u8; 32]>,
+}
+
+impl Default for TlsConfig {
+	fn default() -> Self {
+		Self {
+			built_in_roots: true,
+			root_certificates: Vec::new(),
+			pinned_certificates: Vec::new(),
+		}
+	}
+}
+
+impl TlsConfig {
+	/// The SHA-256 fingerprint of a DER-encoded certificate, as expected by `pinned_certificates`.
+	#[must_use]
+	pub fn fingerprint(certificate: &[u8]) -> [u8; 32] {
+		digest(&SHA256, certificate)
+			.as_ref()
+			.try_into()
+			.unwrap_or_else(|_| unreachable!("SHA-256 digests are 32 bytes long"))
+	}
+
+	pub(super) fn client_config(&self) -> Result<ClientConfig, Error> {
+		let provider = Arc::new(default_provider());
+
+		let mut roots = RootCertStore::empty();
+		if self.built_in_roots {
+			roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+		}
+		for certificate in &self.root_certificates {
+			roots
+				.add(CertificateDer::from(certificate.clone()))
+				.map_err(|error| Error::Tls(error.to_string()))?;
+		}
+
+		let builder = ClientConfig::builder_with_provider(provider.clone())
+			.with_safe_default_protocol_versions()
+			.map_err(|error| Error::Tls(error.to_string()))?;
+
+		if self.pinned_certificates.is_empty() {
+			return Ok(builder.with_root_certificates(roots).with_no_client_auth());
+		}
+
+		let verifier = PinnedVerifier {
+			pins: self.pinned_certificates.clone(),
+			inner: WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider)
+				.build()
+				.map_err(|error| Error::Tls(error.to_string()))?,
+		};
+
+		Ok(builder
+			.dangerous()
+			.with_custom_certificate_verifier(Arc::new(verifier))
+			.with_no_client_auth())
+	}
+}
+
+impl VerifyConfig {
+	/// Creates a configuration with the default timeouts, whose client uses the provided TLS settings.
+	///
+	/// # Errors
+	///
+	/// Errors if a root certificate is invalid, or the client can't be built (`Error::Tls`).
+	pub fn with_tls(tls: &TlsConfig) -> Result<Self, Error> {
+		let client = reqwest::Client::builder()
+			.use_preconfigured_tls(tls.client_config()?)
+			.redirect(reqwest::redirect::Policy::none())
+			.connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+			.build()
+			.map_err(|error| Error::Tls(error.to_string()))?;
+
+		Ok(Self {
+			timeout: Some(DEFAULT_TIMEOUT),
+			tls: Some(tls.clone()),
+			..Self::new(client)
+		})
+	}
+}
+
+/// Verifies certificates as usual, and then checks the leaf certificate against the pinned fingerprints.
+#[derive(Debug)]
+struct PinnedVerifier {
+	pins: Vec<[u8; 32]>,
+	inner: Arc<WebPkiServerVerifier>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+	fn verify_server_cert(
+		&self,
+		end_entity: &CertificateDer<'_>,
+		intermediates: &[CertificateDer<'_>],
+		server_name: &ServerName<'_>,
+		ocsp_response: &[u8],
+		now: UnixTime,
+	) -> Result<ServerCertVerified, rustls::Error> {
+		self.inner.verify_server_cert(
+			end_entity,
+			intermediates,
+			server_name,
+			ocsp_response,
+			now,
+		)?;
+
+		if self.pins.contains(&TlsConfig::fingerprint(end_entity)) {
+			Ok(ServerCertVerified::assertion())
+		} else {
+			Err(rustls::Error::General(
+				"certificate does not match any pinned fingerprint".to_string(),
+			))
+		}
+	}
+
+	fn verify_tls12_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		self.inner.verify_tls12_signature(message, cert, dss)
+	}
+
+	fn verify_tls13_signature(
+		&self,
+		message: &[u8],
+		cert: &CertificateDer<'_>,
+		dss: &DigitallySignedStruct,
+	) -> Result<HandshakeSignatureValid, rustls::Error> {
+		self.inner.verify_tls13_signature(message, cert, dss)
+	}
+
+	fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+		self.inner.supported_verify_schemes()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixtures, session::AppId, verify::verify_proof_with_config};
+	use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+	use rustls::{pki_types::PrivatePkcs8KeyDer, ServerConfig};
+	use std::str::FromStr;
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+	use tokio_rustls::TlsAcceptor;
+	use url::Url;
+
+	struct TlsStub {
+		url: Url,
+		ca: Vec<u8>,
+		leaf: Vec<u8>,
+	}
+
+	/// Starts an HTTPS server for `localhost`, with a certificate issued by a freshly generated certificate authority, answering every request with an empty 200 response.
+	async fn start_tls_stub() -> TlsStub {
+		let ca_key = KeyPair::generate().unwrap();
+		let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+		ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+		let ca = ca_params.self_signed(&ca_key).unwrap();
+
+		let leaf_key = KeyPair::generate().unwrap();
+		let leaf = CertificateParams::new(vec!["localhost".to_string()])
+			.unwrap()
+			.signed_by(&leaf_key, &ca, &ca_key)
+			.unwrap();
+
+		let server_config = ServerConfig::builder_with_provider(Arc::new(default_provider()))
+			.with_safe_default_protocol_versions()
+			.unwrap()
+			.with_no_client_auth()
+			.with_single_cert(
+				vec![leaf.der().clone()],
+				PrivatePkcs8KeyDer::from(leaf_key.serialize_der()).into(),
+			)
+			.unwrap();
+
+		let acceptor = TlsAcceptor::from(Arc::new(server_config));
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let port = listener.local_addr().unwrap().port();
+
+		tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				let acceptor = acceptor.clone();
+				tokio::spawn(async move {
+					let Ok(mut stream) = acceptor.accept(stream).await else {
+						return;
+					};
+
+					let mut buf = [0; 8192];
+					let _ = stream.read(&mut buf).await;
+					let _ = stream
+						.write_all(
+							b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+						)
+						.await;
+					let _ = stream.shutdown().await;
+				});
+			}
+		});
+
+		TlsStub {
+			url: Url::parse(&format!("https://localhost:{port}")).unwrap(),
+			ca: ca.der().to_vec(),
+			leaf: leaf.der().to_vec(),
+		}
+	}
+
+	async fn verify(stub: &TlsStub, tls: &TlsConfig) -> Result<(), Error> {
+		verify_proof_with_config(
+			fixtures::proof(),
+			AppId::from_str("app_123").unwrap(),
+			"test-action",
+			"",
+			&VerifyConfig {
+				base_url: stub.url.clone(),
+				..VerifyConfig::with_tls(tls)?
+			},
+		)
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_custom_root_certificate() {
+		let stub = start_tls_stub().await;
+
+		verify(
+			&stub,
+			&TlsConfig {
+				root_certificates: vec![stub.ca.clone()],
+				..TlsConfig::default()
+			},
+		)
+		.await
+		.unwrap();
+
+		assert!(matches!(
+			verify(&stub, &TlsConfig::default()).await,
+			Err(Error::Connect { .. })
+		));
+	}
+
+	#[tokio::test]
+	async fn test_pinned_certificate() {
+		let stub = start_tls_stub().await;
+
+		verify(
+			&stub,
+			&TlsConfig {
+				root_certificates: vec![stub.ca.clone()],
+				pinned_certificates: vec![TlsConfig::fingerprint(&stub.leaf)],
+				..TlsConfig::default()
+			},
+		)
+		.await
+		.unwrap();
+
+		assert!(matches!(
+			verify(
+				&stub,
+				&TlsConfig {
+					root_certificates: vec![stub.ca.clone()],
+					pinned_certificates: vec![TlsConfig::fingerprint(&stub.ca)],
+					..TlsConfig::default()
+				},
+			)
+			.await,
+			Err(Error::Connect { .. })
+		));
+	}
+
+	#[cfg(feature = "blocking")]
+	#[tokio::test(flavor = "multi_thread")]
+	async fn test_blocking_pinned_certificate() {
+		let stub = start_tls_stub().await;
+		let verify_blocking = |tls: TlsConfig| {
+			let url = stub.url.clone();
+
+			tokio::task::spawn_blocking(move || {
+				crate::verify::blocking::verify_proof_with_config(
+					fixtures::proof(),
+					AppId::from_str("app_123").unwrap(),
+					"test-action",
+					"",
+					&VerifyConfig {
+						base_url: url,
+						..VerifyConfig::with_tls(&tls)?
+					},
+				)
+			})
+		};
+
+		verify_blocking(TlsConfig {
+			root_certificates: vec![stub.ca.clone()],
+			pinned_certificates: vec![TlsConfig::fingerprint(&stub.leaf)],
+			..TlsConfig::default()
+		})
+		.await
+		.unwrap()
+		.unwrap();
+
+		assert!(matches!(
+			verify_blocking(TlsConfig {
+				root_certificates: vec![stub.ca.clone()],
+				pinned_certificates: vec![TlsConfig::fingerprint(&stub.ca)],
+				..TlsConfig::default()
+			})
+			.await
+			.unwrap(),
+			Err(Error::Connect { .. })
+		));
+	}
+}
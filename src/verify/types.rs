@@ -0,0 +1,472 @@
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use crate::session::AppError;
+
+/// The error returned by the Developer Portal when a proof fails verification.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct ErrorResponse {
+	pub code: VerifyErrorCode,
+	/// A human-readable description of the error. The v1 API names this field `message`.
+	#[serde(alias = "message")]
+	pub detail: String,
+	#[serde(default)]
+	pub attribute: Option<String>,
+}
+
+impl Display for ErrorResponse {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}: {}", self.code, self.detail)?;
+
+		if let Some(attribute) = &self.attribute {
+			write!(f, " (attribute: {attribute})")?;
+		}
+
+		Ok(())
+	}
+}
+
+impl std::error::Error for ErrorResponse {}
+
+/// The version of the Developer Portal verification API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApiVersion {
+	/// The legacy API, which expects a `credential_type` instead of a `verification_level`.
+	V1,
+	/// The current API.
+	#[default]
+	V2,
+}
+
+impl Display for ApiVersion {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::V1 => write!(f, "v1"),
+			Self::V2 => write!(f, "v2"),
+		}
+	}
+}
+
+/// The error codes documented by the Developer Portal verification API.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VerifyErrorCode {
+	/// The provided proof is invalid and cannot be verified.
+	InvalidProof,
+	/// The merkle root is not valid or has expired.
+	InvalidMerkleRoot,
+	/// The app ID is for a different network (staging or production) than the proof.
+	InvalidNetwork,
+	/// The credential type of the proof is not accepted by this action.
+	InvalidCredentialType,
+	/// The user already verified the maximum number of times for this action.
+	MaxVerificationsReached,
+	/// The user has exceeded the maximum number of verifications for this action.
+	ExceededMaxVerifications,
+	/// The user has already verified for this action.
+	AlreadyVerified,
+	/// There was an error verifying the proof.
+	VerificationError,
+	/// The app or action could not be found.
+	NotFound,
+	/// The request was rejected by the request validation.
+	ValidationError,
+	/// An error code not known to this version of the crate.
+	Other(String),
+}
+
+impl VerifyErrorCode {
+	/// The error code as returned by the Developer Portal.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		match self {
+			Self::InvalidProof => "invalid_proof",
+			Self::InvalidMerkleRoot => "invalid_merkle_root",
+			Self::InvalidNetwork => "invalid_network",
+			Self::InvalidCredentialType => "invalid_credential_type",
+			Self::MaxVerificationsReached => "max_verifications_reached",
+			Self::ExceededMaxVerifications => "exceeded_max_verifications",
+			Self::AlreadyVerified => "already_verified",
+			Self::VerificationError => "verification_error",
+			Self::NotFound => "not_found",
+			Self::ValidationError => "validation_error",
+			Self::Other(code) => code,
+		}
+	}
+
+	/// The `AppError` with the same meaning as this error code, so portal failures can share the messages shown for World App failures.
+	///
+	/// | Error code                                                                    | `AppError`                |
+	/// | ----------------------------------------------------------------------------- | ------------------------- |
+	/// | `invalid_merkle_root`                                                         | `InclusionProofFailed`    |
+	/// | `invalid_network`                                                             | `InvalidNetwork`          |
+	/// | `invalid_credential_type`                                                     | `CredentialUnavailable`   |
+	/// | `max_verifications_reached`, `exceeded_max_verifications`, `already_verified` | `MaxVerificationsReached` |
+	/// | `verification_error`                                                          | `GenericError`            |
+	/// | `not_found`, `validation_error`                                               | `MalformedRequest`        |
+	///
+	/// Returns `None` for `invalid_proof` and unknown codes, which have no equivalent.
+	#[must_use]
+	pub const fn as_app_error(&self) -> Option<AppError> {
+		match self {
+			Self::InvalidMerkleRoot => Some(AppError::InclusionProofFailed),
+			Self::InvalidNetwork => Some(AppError::InvalidNetwork),
+			Self::InvalidCredentialType => Some(AppError::CredentialUnavailable),
+			Self::MaxVerificationsReached
+			| Self::ExceededMaxVerifications
+			| Self::AlreadyVerified => Some(AppError::MaxVerificationsReached),
+			Self::VerificationError => Some(AppError::GenericError),
+			Self::NotFound | Self::ValidationError => Some(AppError::MalformedRequest),
+			Self::InvalidProof | Self::Other(_) => None,
+		}
+	}
+}
+
+impl From<String> for VerifyErrorCode {
+	fn from(code: String) -> Self {
+		match code.as_str() {
+			"invalid_proof" => Self::InvalidProof,
+			"invalid_merkle_root" => Self::InvalidMerkleRoot,
+			"invalid_network" => Self::InvalidNetwork,
+			"invalid_credential_type" => Self::InvalidCredentialType,
+			"max_verifications_reached" => Self::MaxVerificationsReached,
+			"exceeded_max_verifications" => Self::ExceededMaxVerifications,
+			"already_verified" => Self::AlreadyVerified,
+			"verification_error" => Self::VerificationError,
+			"not_found" => Self::NotFound,
+			"validation_error" => Self::ValidationError,
+			_ => Self::Other(code),
+		}
+	}
+}
+
+impl FromStr for VerifyErrorCode {
+	type Err = std::convert::Infallible;
+
+	fn from_str(code: &str) -> Result<Self, Self::Err> {
+		Ok(Self::from(code.to_string()))
+	}
+}
+
+impl Display for VerifyErrorCode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for VerifyErrorCode {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		<String as serde::Deserialize>::deserialize(deserializer).map(Self::from)
+	}
+}
+
+impl serde::Serialize for VerifyErrorCode {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.as_str())
+	}
+}
+
+/// The response returned by the Developer Portal for a successful verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+pub struct VerifyResponse {
+	/// The action the proof was verified for.
+	#[serde(default)]
+	pub action: Option<String>,
+	/// The nullifier hash of the verified proof.
+	#[serde(default)]
+	pub nullifier_hash: Option<String>,
+	/// How many times the nullifier has been used for this action, including this verification.
+	#[serde(default)]
+	pub uses: Option<u64>,
+	/// When the nullifier was first verified for this action.
+	#[serde(default)]
+	pub created_at: Option<String>,
+	/// The rate limit state reported by the Developer Portal, if any.
+	#[serde(skip)]
+	pub rate_limit: Option<RateLimitInfo>,
+}
+
+/// The rate limit state reported by the Developer Portal in the `X-RateLimit-*` response headers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitInfo {
+	/// The maximum number of requests allowed in the current window.
+	pub limit: Option<u64>,
+	/// How many requests are left in the current window.
+	pub remaining: Option<u64>,
+	/// How long until the current window resets.
+	pub reset: Option<Duration>,
+}
+
+/// Whether an action is currently accepting verifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionStatus {
+	Active,
+	Inactive,
+}
+
+/// An action as configured in the Developer Portal.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct ActionMetadata {
+	/// The action identifier.
+	pub action: String,
+	/// The human-readable name of the action.
+	pub name: String,
+	/// The description of the action, shown to users in the World App.
+	#[serde(default)]
+	pub description: String,
+	/// The maximum number of times a user can verify for this action. `0` means unlimited.
+	pub max_verifications: u64,
+	/// Whether the action is currently accepting verifications.
+	pub status: ActionStatus,
+}
+
+/// An app as registered in the Developer Portal.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct AppMetadata {
+	/// The app identifier.
+	#[serde(alias = "app_id")]
+	pub id: String,
+	/// The human-readable name of the app.
+	pub name: String,
+	/// Whether the app has been verified by Worldcoin.
+	#[serde(default)]
+	pub is_verified: bool,
+	/// Whether the app is a staging app, which only accepts proofs from the simulator.
+	#[serde(default)]
+	pub is_staging: bool,
+	/// The URL of the app logo, only available for verified apps.
+	#[serde(default, alias = "verified_app_logo")]
+	pub logo_url: Option<String>,
+}
+
+/// The number of times a nullifier has been used for an action.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct NullifierUses {
+	pub nullifier_hash: String,
+	pub uses: u64,
+}
+
+/// The state of an action as returned by the Developer Portal precheck endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct PrecheckResponse {
+	/// The action identifier.
+	pub action: String,
+	/// Whether the action is currently accepting verifications.
+	pub status: ActionStatus,
+	/// The maximum number of times a user can verify for this action. `0` means unlimited.
+	pub max_verifications: u64,
+	/// Usage of the nullifier hash provided in the request, if any.
+	#[serde(default)]
+	pub nullifiers: Vec<NullifierUses>,
+	/// The rate limit state reported by the Developer Portal, if any.
+	#[serde(skip)]
+	pub rate_limit: Option<RateLimitInfo>,
+}
+
+impl PrecheckResponse {
+	/// How many times the queried nullifier has already been used for this action.
+	#[must_use]
+	pub fn uses(&self) -> u64 {
+		self.nullifiers.iter().map(|nullifier| nullifier.uses).sum()
+	}
+
+	/// How many verifications the queried nullifier has left for this action, or `None` if unlimited.
+	#[must_use]
+	pub fn remaining_verifications(&self) -> Option<u64> {
+		if self.max_verifications == 0 {
+			return None;
+		}
+
+		Some(self.max_verifications.saturating_sub(self.uses()))
+	}
+}
+
+/// An API key for the [Developer Portal](https://developer.worldcoin.org), sent as a bearer token on requests that need it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+/// Error returned when an invalid API key is provided.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("Invalid API key provided, expected api_*")]
+pub struct ApiKeyError;
+
+impl ApiKey {
+	/// The raw API key, to be sent to the Developer Portal.
+	pub(crate) fn expose(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for ApiKey {
+	type Err = ApiKeyError;
+
+	fn from_str(api_key: &str) -> Result<Self, Self::Err> {
+		let is_valid = api_key
+			.strip_prefix("api_")
+			.is_some_and(|key| !key.is_empty() && key.chars().all(|c| c.is_ascii_graphic()));
+
+		if is_valid {
+			Ok(Self(api_key.to_string()))
+		} else {
+			Err(ApiKeyError)
+		}
+	}
+}
+
+impl std::fmt::Debug for ApiKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("ApiKey(<redacted>)")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_as_app_error() {
+		for (code, expected) in [
+			("invalid_proof", None),
+			("invalid_merkle_root", Some(AppError::InclusionProofFailed)),
+			("invalid_network", Some(AppError::InvalidNetwork)),
+			(
+				"invalid_credential_type",
+				Some(AppError::CredentialUnavailable),
+			),
+			(
+				"max_verifications_reached",
+				Some(AppError::MaxVerificationsReached),
+			),
+			(
+				"exceeded_max_verifications",
+				Some(AppError::MaxVerificationsReached),
+			),
+			("already_verified", Some(AppError::MaxVerificationsReached)),
+			("verification_error", Some(AppError::GenericError)),
+			("not_found", Some(AppError::MalformedRequest)),
+			("validation_error", Some(AppError::MalformedRequest)),
+			("something_new", None),
+		] {
+			assert_eq!(
+				VerifyErrorCode::from(code.to_string()).as_app_error(),
+				expected
+			);
+		}
+	}
+
+	#[test]
+	fn test_known_error_codes() {
+		for (code, expected) in [
+			("invalid_proof", VerifyErrorCode::InvalidProof),
+			("invalid_merkle_root", VerifyErrorCode::InvalidMerkleRoot),
+			("invalid_network", VerifyErrorCode::InvalidNetwork),
+			(
+				"invalid_credential_type",
+				VerifyErrorCode::InvalidCredentialType,
+			),
+			(
+				"max_verifications_reached",
+				VerifyErrorCode::MaxVerificationsReached,
+			),
+			(
+				"exceeded_max_verifications",
+				VerifyErrorCode::ExceededMaxVerifications,
+			),
+			("already_verified", VerifyErrorCode::AlreadyVerified),
+			("verification_error", VerifyErrorCode::VerificationError),
+			("not_found", VerifyErrorCode::NotFound),
+			("validation_error", VerifyErrorCode::ValidationError),
+		] {
+			let parsed: VerifyErrorCode = serde_json::from_str(&format!("\"{code}\"")).unwrap();
+
+			assert_eq!(parsed, expected);
+			assert_eq!(parsed.as_str(), code);
+			assert_eq!(
+				serde_json::to_string(&parsed).unwrap(),
+				format!("\"{code}\"")
+			);
+		}
+	}
+
+	#[test]
+	fn test_unknown_error_code() {
+		let response: ErrorResponse = serde_json::from_str(
+			r#"{"code":"something_new","detail":"Something new happened.","attribute":null}"#,
+		)
+		.unwrap();
+
+		assert_eq!(
+			response.code,
+			VerifyErrorCode::Other("something_new".to_string())
+		);
+		assert_eq!(response.code.as_str(), "something_new");
+		assert_eq!(response.code.to_string(), "something_new");
+	}
+
+	#[test]
+	fn test_api_key() {
+		let api_key = ApiKey::from_str("api_c2VjcmV0").unwrap();
+
+		assert_eq!(api_key.expose(), "api_c2VjcmV0");
+		assert_eq!(format!("{api_key:?}"), "ApiKey(<redacted>)");
+
+		assert_eq!(ApiKey::from_str("api_").unwrap_err(), ApiKeyError);
+		assert_eq!(ApiKey::from_str("c2VjcmV0").unwrap_err(), ApiKeyError);
+		assert_eq!(ApiKey::from_str("api_ c2VjcmV0").unwrap_err(), ApiKeyError);
+	}
+
+	#[test]
+	fn test_precheck_response() {
+		let response: PrecheckResponse = serde_json::from_str(
+			r#"{
+				"id": "action_4a2d1b0b8e5c7f3a",
+				"__typename": "action",
+				"action": "vote_1",
+				"status": "active",
+				"max_verifications": 2,
+				"max_accounts_per_user": 1,
+				"can_user_verify": "yes",
+				"nullifiers": [
+					{ "nullifier_hash": "0x2bf8406809dcefb1486dadc96c0a897db9bab002053054cf64272db512c6fbd8", "uses": 1 }
+				]
+			}"#,
+		)
+		.unwrap();
+
+		assert_eq!(response.action, "vote_1");
+		assert_eq!(response.status, ActionStatus::Active);
+		assert_eq!(response.uses(), 1);
+		assert_eq!(response.remaining_verifications(), Some(1));
+	}
+
+	#[test]
+	fn test_precheck_response_unlimited() {
+		let response: PrecheckResponse = serde_json::from_str(
+			r#"{"action":"vote_1","status":"inactive","max_verifications":0}"#,
+		)
+		.unwrap();
+
+		assert_eq!(response.status, ActionStatus::Inactive);
+		assert_eq!(response.uses(), 0);
+		assert_eq!(response.remaining_verifications(), None);
+	}
+
+	#[test]
+	fn test_error_response_display() {
+		let mut response = ErrorResponse {
+			code: VerifyErrorCode::InvalidProof,
+			detail: "The provided proof could not be verified".to_string(),
+			attribute: Some("proof".to_string()),
+		};
+
+		assert_eq!(
+			response.to_string(),
+			"invalid_proof: The provided proof could not be verified (attribute: proof)"
+		);
+
+		response.attribute = None;
+		assert_eq!(
+			response.to_string(),
+			"invalid_proof: The provided proof could not be verified"
+		);
+	}
+}
@@ -0,0 +1,314 @@
+//! A [warp](https://docs.rs/warp) filter that rejects requests without a valid proof.
+//!
+//! ```no_run
+//! use idkit::{
+//!     session::{Action, AppId},
+//!     verify::VerifyResponse,
+//!     warp::{recover, require_verified, IdKitConfig},
+//! };
+//! use warp::Filter;
+//!
+//! # async fn example(app_id: AppId, action: Action) {
+//! let vote = warp::post()
+//!     .and(warp::path("vote"))
+//!     .and(require_verified(IdKitConfig::new(app_id, action)))
+//!     .map(|response: VerifyResponse| format!("Verified {:?}", response.nullifier_hash))
+//!     .recover(recover);
+//!
+//! warp::serve(vote).run(([127, 0, 0, 1], 8080)).await;
+//! # }
+//! ```
+
+use ::warp::{
+	filters::BoxedFilter,
+	http::{HeaderMap, HeaderValue, StatusCode},
+	hyper::body::Bytes,
+	reject::{self, Reject},
+	reply, Filter, Rejection, Reply,
+};
+use std::sync::Arc;
+
+use crate::{
+	session::{Action, AppId},
+	verify::{
+		extract::{body_signal, MAX_BODY_LEN},
+		ErrorReply, VerifiedProof, VerifyConfig, VerifyResponse,
+	},
+};
+
+/// What `require_verified` verifies proofs against.
+#[derive(Debug, Clone)]
+pub struct IdKitConfig {
+	/// The app the proofs are for.
+	pub app_id: AppId,
+	/// The action the proofs are for.
+	pub action: Action,
+	/// The header the proof is taken from, instead of the body.
+	pub header: Option<String>,
+	/// The configuration of the requests to the Developer Portal.
+	pub config: VerifyConfig,
+}
+
+impl IdKitConfig {
+	/// Verifies proofs for `action` of `app_id`, taken from the body with the default configuration.
+	#[must_use]
+	pub fn new(app_id: AppId, action: Action) -> Self {
+		Self {
+			app_id,
+			action,
+			header: None,
+			config: VerifyConfig::default(),
+		}
+	}
+
+	/// Takes the proof from the `name` header instead, holding the same JSON as the body would.
+	#[must_use]
+	pub fn header(mut self, name: impl Into<String>) -> Self {
+		self.header = Some(name.into());
+		self
+	}
+
+	/// Sends requests to the Developer Portal with `config`.
+	#[must_use]
+	pub fn config(mut self, config: VerifyConfig) -> Self {
+		self.config = config;
+		self
+	}
+}
+
+impl Reject for ErrorReply {}
+
+/// Extracts the proof from the JSON body (in the format of IDKit JS' success result) or the configured header, and verifies it with the Developer Portal.
+///
+/// The proof is verified with the signal sent alongside it, if any. Failures are rejected with an `ErrorReply`, which `recover` turns into a JSON response. Bodies must have a `Content-Length` of at most `MAX_BODY_LEN`, so oversized ones are rejected before they're read.
+pub fn require_verified(
+	config: IdKitConfig,
+) -> impl Filter<Extract = (VerifyResponse,), Error = Rejection> + Clone {
+	let body = if config.header.is_some() {
+		::warp::any().map(Bytes::new).boxed()
+	} else {
+		limited_body()
+	};
+	let config = Arc::new(config);
+
+	::warp::header::headers_cloned()
+		.and(body)
+		.and_then(move |headers: HeaderMap, body: Bytes| {
+			let config = config.clone();
+
+			async move {
+				let proof = config
+					.header
+					.as_ref()
+					.map_or_else(
+						|| Ok(body.as_ref()),
+						|name| {
+							headers
+								.get(name.as_str())
+								.map(HeaderValue::as_bytes)
+								.ok_or_else(|| {
+									ErrorReply::invalid_request(format!("missing {name} header"))
+								})
+						},
+					)
+					.map_err(reject::custom)?;
+
+				VerifiedProof::from_body(
+					proof,
+					config.app_id.clone(),
+					&config.action,
+					config.config.clone(),
+					body_signal,
+				)
+				.await
+				.map(|verified| verified.response)
+				.map_err(reject::custom)
+			}
+		})
+}
+
+/// The body, rejected with an `ErrorReply` if its `Content-Length` is over `MAX_BODY_LEN`.
+fn limited_body() -> BoxedFilter<(Bytes,)> {
+	::warp::body::content_length_limit(MAX_BODY_LEN as u64)
+		.or_else(|rejection: Rejection| async move {
+			Err::<(), _>(if rejection.find::<reject::PayloadTooLarge>().is_some() {
+				reject::custom(ErrorReply::invalid_request("request body is too large"))
+			} else {
+				rejection
+			})
+		})
+		.and(::warp::body::bytes())
+		.boxed()
+}
+
+/// Turns the rejections of `require_verified` into JSON responses, with the status of the `ErrorReply`. Other rejections are passed on.
+///
+/// # Errors
+///
+/// Errors with the rejection if it wasn't caused by `require_verified`.
+#[allow(clippy::unused_async)] // warp expects recovery handlers to return a future
+pub async fn recover(rejection: Rejection) -> Result<impl Reply, Rejection> {
+	let Some(error) = rejection.find::<ErrorReply>() else {
+		return Err(rejection);
+	};
+
+	let status =
+		StatusCode::from_u16(error.status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+	Ok(reply::with_status(reply::json(error), status))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixtures, SuccessResult};
+	use std::str::FromStr;
+	use url::Url;
+	use wiremock::{
+		matchers::{body_partial_json, method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	fn config(server: &MockServer) -> IdKitConfig {
+		IdKitConfig::new(
+			AppId::from_str("app_123").unwrap(),
+			Action::from_str("vote_1").unwrap(),
+		)
+		.config(VerifyConfig {
+			base_url: Url::parse(&server.uri()).unwrap(),
+			..VerifyConfig::default()
+		})
+	}
+
+	fn filter(
+		config: IdKitConfig,
+	) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+		require_verified(config)
+			.map(|response: VerifyResponse| response.nullifier_hash.unwrap_or_default())
+			.recover(recover)
+	}
+
+	async fn mock_portal(server: &MockServer, signal: &str) {
+		let mut body = serde_json::json!({
+			"action": "vote_1",
+			"nullifier_hash": fixtures::proof().nullifier_hash,
+		});
+		// An empty signal is sent without a signal hash.
+		if !signal.is_empty() {
+			body["signal_hash"] = serde_json::json!(crate::hashing::FieldElement::signal(signal));
+		}
+
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.and(body_partial_json(body))
+			.respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+				"success": true,
+				"action": "vote_1",
+				"nullifier_hash": fixtures::proof().nullifier_hash,
+			})))
+			.expect(1)
+			.mount(server)
+			.await;
+	}
+
+	#[tokio::test]
+	async fn test_body_proof() {
+		let server = MockServer::start().await;
+		mock_portal(&server, "yes").await;
+
+		let response = ::warp::test::request()
+			.method("POST")
+			.json(&SuccessResult {
+				proof: fixtures::proof(),
+				signal: Some("yes".to_string()),
+			})
+			.reply(&filter(config(&server)))
+			.await;
+
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.body(), fixtures::proof().nullifier_hash.as_bytes());
+	}
+
+	#[tokio::test]
+	async fn test_header_proof() {
+		let server = MockServer::start().await;
+		mock_portal(&server, "").await;
+
+		let response = ::warp::test::request()
+			.method("POST")
+			.header("x-world-id-proof", fixtures::proof().to_idkit_js())
+			.reply(&filter(config(&server).header("x-world-id-proof")))
+			.await;
+
+		assert_eq!(response.status(), StatusCode::OK);
+		assert_eq!(response.body(), fixtures::proof().nullifier_hash.as_bytes());
+	}
+
+	#[tokio::test]
+	async fn test_rejected_proof() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/api/v2/verify/app_123"))
+			.respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+				"attribute": null,
+			})))
+			.mount(&server)
+			.await;
+
+		let response = ::warp::test::request()
+			.method("POST")
+			.body(fixtures::proof().to_idkit_js())
+			.reply(&filter(config(&server)))
+			.await;
+
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+		assert_eq!(
+			serde_json::from_slice::<serde_json::Value>(response.body()).unwrap(),
+			serde_json::json!({
+				"code": "invalid_proof",
+				"detail": "The provided proof is invalid and it cannot be verified.",
+			})
+		);
+	}
+
+	#[tokio::test]
+	async fn test_missing_proof() {
+		let server = MockServer::start().await;
+
+		for config in [config(&server), config(&server).header("x-world-id-proof")] {
+			let response = ::warp::test::request()
+				.method("POST")
+				.body("{}")
+				.reply(&filter(config))
+				.await;
+
+			assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+			assert_eq!(
+				serde_json::from_slice::<serde_json::Value>(response.body()).unwrap()["code"],
+				"invalid_request"
+			);
+		}
+
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_body_too_large() {
+		let server = MockServer::start().await;
+
+		let response = ::warp::test::request()
+			.method("POST")
+			.body(vec![b' '; MAX_BODY_LEN + 1])
+			.reply(&filter(config(&server)))
+			.await;
+
+		assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+		assert_eq!(
+			serde_json::from_slice::<serde_json::Value>(response.body()).unwrap()["detail"],
+			"request body is too large"
+		);
+		assert!(server.received_requests().await.unwrap().is_empty());
+	}
+}
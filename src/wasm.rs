@@ -0,0 +1,175 @@
+//! A [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/) wrapper over `Session`, for JavaScript apps.
+//!
+//! Build the library as a `cdylib` and generate the package with the `wasm-bindgen` CLI:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm-bindgen --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/idkit.wasm
+//! ```
+//!
+//! Then:
+//!
+//! ```js
+//! const session = await createSession({ appId: "app_123", action: "vote_1", signal: "yes" });
+//! showQrCode(session.connectUrl());
+//!
+//! const { status, proof } = await session.pollForStatus();
+//! ```
+//!
+//! Statuses and proofs are plain objects. Promises reject with an `Error` named after the `ErrorKind` of the failure, like `TransportError` or `ConfigurationError`.
+
+use serde_json::json;
+use std::{rc::Rc, str::FromStr};
+use url::Url;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::{
+	session::{AppId, BridgeUrl, Session, Status, VerificationLevel},
+	ErrorKind,
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &str = r#"
+export interface SessionOptions {
+	appId: string;
+	action: string;
+	/** The minimum verification level to accept, `orb` by default. */
+	verificationLevel?: "orb" | "device";
+	/** The signal the proof commits to, an empty string by default. */
+	signal?: string;
+	bridgeUrl?: string;
+	actionDescription?: string;
+}
+
+export interface Proof {
+	proof: string;
+	merkle_root: string;
+	nullifier_hash: string;
+	verification_level: "orb" | "device";
+}
+
+export interface Status {
+	status: "waiting_for_connection" | "awaiting_confirmation" | "confirmed" | "failed";
+	/** A short message to display to the user. */
+	message: string;
+	/** Only set on confirmed requests. */
+	proof?: Proof;
+	/** Only set on failed requests. */
+	errorCode?: string;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+	/// The options of `createSession`, see the `SessionOptions` TypeScript interface.
+	#[wasm_bindgen(typescript_type = "SessionOptions")]
+	pub type SessionOptions;
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Options {
+	app_id: String,
+	action: String,
+	verification_level: Option<VerificationLevel>,
+	#[serde(default)]
+	signal: String,
+	bridge_url: Option<String>,
+	action_description: Option<String>,
+}
+
+/// A session with the Wallet Bridge, see `Session`.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct JsSession {
+	session: Rc<Session>,
+}
+
+/// Creates a session with the Wallet Bridge. Returns a `Promise` of a `JsSession`, rejected if one of the options is invalid or if the request to the bridge fails.
+#[wasm_bindgen(js_name = createSession)]
+#[must_use]
+pub fn create_session(options: SessionOptions) -> js_sys::Promise {
+	let options = js_sys::JSON::stringify(&options)
+		.ok()
+		.and_then(|options| serde_json::from_str::<Options>(&String::from(options)).ok());
+
+	future_to_promise(async move {
+		let options = options
+			.ok_or_else(|| named_error(ErrorKind::Configuration, "Invalid session options"))?;
+
+		let app_id = AppId::from_str(&options.app_id).map_err(js_error)?;
+		let bridge_url = match options.bridge_url {
+			Some(url) => BridgeUrl::try_from(
+				Url::parse(&url)
+					.map_err(|error| named_error(ErrorKind::Configuration, &error.to_string()))?,
+			)
+			.map_err(js_error)?,
+			None => BridgeUrl::default(),
+		};
+
+		let session = Session::new(
+			&app_id,
+			options.action.as_str(),
+			options.verification_level.unwrap_or(VerificationLevel::Orb),
+			bridge_url,
+			options.signal,
+			options.action_description.as_deref(),
+		)
+		.await
+		.map_err(js_error)?;
+
+		Ok(JsSession {
+			session: Rc::new(session),
+		}
+		.into())
+	})
+}
+
+#[wasm_bindgen]
+impl JsSession {
+	/// The URL to open or encode in a QR code for the user to connect their World App, see `Session::connect_url`.
+	#[wasm_bindgen(js_name = connectUrl)]
+	#[must_use]
+	pub fn connect_url(&self) -> String {
+		self.session.connect_url().to_string()
+	}
+
+	/// Polls the bridge once for the status of the request, see `Session::poll_for_status`. Returns a `Promise` of a `Status` object.
+	#[wasm_bindgen(js_name = pollForStatus)]
+	#[must_use]
+	pub fn poll_for_status(&self) -> js_sys::Promise {
+		let session = Rc::clone(&self.session);
+
+		future_to_promise(async move {
+			let status = session.poll_for_status().await.map_err(js_error)?;
+
+			let mut object = json!({ "status": status.name(), "message": status.to_string() });
+			match status {
+				Status::Confirmed(proof) => object["proof"] = json!(proof),
+				Status::Failed(error) => object["errorCode"] = json!(error),
+				Status::WaitingForConnection | Status::AwaitingConfirmation => {},
+			}
+
+			js_sys::JSON::parse(&object.to_string())
+		})
+	}
+}
+
+fn js_error(error: impl Into<crate::Error>) -> JsValue {
+	let error = error.into();
+	named_error(error.kind(), &error.to_string())
+}
+
+fn named_error(kind: ErrorKind, message: &str) -> JsValue {
+	let error = js_sys::Error::new(message);
+	error.set_name(match kind {
+		ErrorKind::Transport => "TransportError",
+		ErrorKind::Protocol => "ProtocolError",
+		ErrorKind::Crypto => "CryptoError",
+		ErrorKind::User => "UserError",
+		ErrorKind::Configuration => "ConfigurationError",
+	});
+
+	error.into()
+}
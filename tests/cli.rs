@@ -0,0 +1,184 @@
+//! Drives the `idkit` binary against a mock Wallet Bridge and Developer Portal.
+
+mod common;
+
+use assert_cmd::Command;
+use common::proof;
+use idkit::{
+	session::{crypto, BridgeProof, ConnectInfo},
+	Proof,
+};
+use serde_json::{json, Value};
+use std::{
+	io::{BufRead, BufReader},
+	process::Stdio,
+};
+use url::Url;
+use wiremock::{
+	matchers::{method, path, path_regex},
+	Mock, MockServer, ResponseTemplate,
+};
+
+async fn bridge_server() -> MockServer {
+	let server = MockServer::start().await;
+	Mock::given(method("POST"))
+		.and(path("/request"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+			"request_id": "00000000-0000-0000-0000-000000000000",
+		})))
+		.mount(&server)
+		.await;
+	Mock::given(method("GET"))
+		.and(path_regex("^/response/"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+			"status": "initialized",
+			"response": null,
+		})))
+		.mount(&server)
+		.await;
+
+	server
+}
+
+fn verify_flow(server: &MockServer) -> std::process::Command {
+	let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("idkit"));
+	command.args([
+		"verify-flow",
+		"--json",
+		"--app-id",
+		"app_123",
+		"--action",
+		"vote_1",
+		"--poll-interval",
+		"10",
+		"--bridge-url",
+		&server.uri(),
+	]);
+
+	command
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_verify_flow() {
+	let server = bridge_server().await;
+
+	let mut child = verify_flow(&server).stdout(Stdio::piped()).spawn().unwrap();
+	let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+
+	let line: Value = serde_json::from_str(&stdout.next().unwrap().unwrap()).unwrap();
+	let connect_url = Url::parse(line["connect_url"].as_str().unwrap()).unwrap();
+	let key: [u8; 32] = ConnectInfo::from_url(&connect_url)
+		.unwrap()
+		.key_bytes()
+		.unwrap()
+		.try_into()
+		.unwrap();
+
+	let response = serde_json::to_vec(&BridgeProof::try_from(proof()).unwrap()).unwrap();
+	Mock::given(method("GET"))
+		.and(path_regex("^/response/"))
+		.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+			"status": "completed",
+			"response": crypto::encrypt(&key, &[9; 12], &response).unwrap(),
+		})))
+		.with_priority(1)
+		.mount(&server)
+		.await;
+
+	let line = stdout.next().unwrap().unwrap();
+	assert_eq!(Proof::from_json_str(&line).unwrap(), proof());
+	assert!(child.wait().unwrap().success());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_verify_flow_failure() {
+	let server = bridge_server().await;
+	Mock::given(method("GET"))
+		.and(path_regex("^/response/"))
+		.respond_with(ResponseTemplate::new(500))
+		.with_priority(1)
+		.mount(&server)
+		.await;
+
+	let output = verify_flow(&server).output().unwrap();
+	let stdout = String::from_utf8(output.stdout).unwrap();
+	let result: Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+
+	assert!(!output.status.success());
+	assert_eq!(result["success"], false);
+	assert_eq!(result["code"], "connection_failed");
+}
+
+async fn portal_server(status: u16, body: Value) -> MockServer {
+	let server = MockServer::start().await;
+	Mock::given(method("POST"))
+		.and(path("/api/v2/verify/app_123"))
+		.respond_with(ResponseTemplate::new(status).set_body_json(body))
+		.mount(&server)
+		.await;
+
+	server
+}
+
+fn verify(server: &MockServer, proof_file: &std::path::Path) -> Command {
+	let mut command = Command::cargo_bin("idkit").unwrap();
+	command
+		.args([
+			"verify", "--json", "--app-id", "app_123", "--action", "vote_1",
+		])
+		.arg("--proof-file")
+		.arg(proof_file)
+		.args(["--portal-url", &server.uri()]);
+
+	command
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_verify() {
+	let proof_file = std::env::temp_dir().join("idkit-cli-test-verify.json");
+	std::fs::write(&proof_file, proof().to_json_string()).unwrap();
+
+	let server = portal_server(
+		200,
+		json!({ "success": true, "nullifier_hash": proof().nullifier_hash, "uses": 1 }),
+	)
+	.await;
+	let output = verify(&server, &proof_file).assert().success();
+	assert_eq!(
+		serde_json::from_slice::<Value>(&output.get_output().stdout).unwrap(),
+		json!({ "success": true, "nullifier_hash": proof().nullifier_hash, "uses": 1 })
+	);
+
+	let server = portal_server(
+		400,
+		json!({
+			"code": "invalid_proof",
+			"detail": "The provided proof is invalid and it cannot be verified.",
+			"attribute": null,
+		}),
+	)
+	.await;
+	let output = verify(&server, &proof_file).assert().failure();
+	let result: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+	assert_eq!(result["success"], false);
+	assert_eq!(result["code"], "invalid_proof");
+}
+
+#[test]
+fn test_invalid_proof_file() {
+	let output = Command::cargo_bin("idkit")
+		.unwrap()
+		.args([
+			"verify",
+			"--proof-file",
+			"does-not-exist.json",
+			"--app-id",
+			"app_123",
+			"--action",
+			"vote_1",
+		])
+		.assert()
+		.failure();
+
+	assert!(String::from_utf8_lossy(&output.get_output().stderr).starts_with("Error: "));
+}
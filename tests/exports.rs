@@ -0,0 +1,41 @@
+//! Uses the public API through `idkit::exports` only, without naming `url`, `ruint` or `alloy` directly.
+
+use idkit::{
+	exports::{Address, Bytes, Signal, Url, Uuid, U256},
+	hashing::encode_signal,
+	session::BridgeUrl,
+	verify::{VerifyConfig, VerifyResponse},
+	PreHashedSignal,
+};
+
+fn signal_hash<S: Signal>(signal: &S) -> U256 {
+	encode_signal(signal)
+}
+
+#[test]
+fn test_exported_types_match_the_api() {
+	let bridge_url =
+		BridgeUrl::try_from(Url::parse("https://bridge.example.com").unwrap()).unwrap();
+	assert_eq!(bridge_url.host_str(), Some("bridge.example.com"));
+
+	let config = VerifyConfig {
+		base_url: Url::parse("https://developer.example.com").unwrap(),
+		..VerifyConfig::default()
+	};
+	assert_eq!(config.base_url.host_str(), Some("developer.example.com"));
+
+	let hash = signal_hash(&(Address::ZERO, Bytes::from_static(b"test"), U256::from(1)));
+	assert_eq!(signal_hash(&PreHashedSignal(hash)), hash);
+
+	assert_ne!(Uuid::new_v4(), Uuid::new_v4());
+}
+
+#[test]
+fn test_verify_response_is_exported() {
+	let response = VerifyResponse {
+		uses: Some(1),
+		..VerifyResponse::default()
+	};
+
+	assert_eq!(response.uses, Some(1));
+}
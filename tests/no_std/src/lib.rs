@@ -0,0 +1,48 @@
+//! Checks that the hashing and proof types of `idkit` build without the standard library.
+//!
+//! Build it for a target without `std` to make sure no dependency pulls it in:
+//!
+//! ```sh
+//! cargo build --manifest-path tests/no_std/Cargo.toml --target thumbv7em-none-eabihf
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use idkit::{
+	hashing::{encode_signal, external_nullifier, hash_to_field, keccak256, FieldElement},
+	session::{AppId, VerificationLevel},
+	DecodeError, NullifierHash, Proof,
+};
+use ruint::aliases::U256;
+
+#[must_use]
+pub fn signal_hash(signal: &[u8]) -> FieldElement {
+	FieldElement::signal(signal)
+}
+
+#[must_use]
+pub fn nullifier_inputs(app_id: &AppId, action: &str, signal: &str) -> (U256, U256) {
+	(external_nullifier(app_id, action), encode_signal(signal))
+}
+
+#[must_use]
+pub fn hashes(input: &[u8]) -> ([u8; 32], U256) {
+	(keccak256(input), hash_to_field(input))
+}
+
+/// # Errors
+///
+/// Errors if the proof is malformed.
+pub fn decode(proof: &Proof) -> Result<(NullifierHash, VerificationLevel), DecodeError> {
+	Ok((proof.nullifier_hash()?, proof.verification_level))
+}
+
+/// # Errors
+///
+/// Errors if the proof is malformed.
+pub fn to_bytes(proof: &Proof) -> Result<Vec<u8>, DecodeError> {
+	proof.to_bytes()
+}
@@ -0,0 +1,84 @@
+//! Generates the Kotlin bindings of the compiled library, and checks they expose the facade of `idkit::ffi`.
+
+use assert_cmd::Command;
+use std::{
+	env::consts::{DLL_PREFIX, DLL_SUFFIX},
+	fs,
+	path::{Path, PathBuf},
+};
+
+/// Builds the library as a `cdylib`, like apps embedding the bindings do. It's built in its own target directory, as the one of the tests is locked while they run.
+fn build_library() -> PathBuf {
+	let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("uniffi");
+
+	Command::new(env!("CARGO"))
+		.args([
+			"rustc",
+			"--lib",
+			"--features",
+			"uniffi",
+			"--crate-type",
+			"cdylib",
+		])
+		.current_dir(env!("CARGO_MANIFEST_DIR"))
+		.env("CARGO_TARGET_DIR", &target_dir)
+		.assert()
+		.success();
+
+	target_dir
+		.join("debug")
+		.join(format!("{DLL_PREFIX}idkit{DLL_SUFFIX}"))
+}
+
+fn read_sources(dir: &Path, extension: &str) -> String {
+	let mut sources = String::new();
+
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		if path.is_dir() {
+			sources.push_str(&read_sources(&path, extension));
+		} else if path.extension().is_some_and(|ext| ext == extension) {
+			sources.push_str(&fs::read_to_string(&path).unwrap());
+		}
+	}
+
+	sources
+}
+
+#[test]
+fn test_kotlin_bindings() {
+	let bindgen = assert_cmd::cargo::cargo_bin("uniffi-bindgen");
+	let library = build_library();
+	let out_dir = std::env::temp_dir().join("idkit-uniffi-kotlin");
+	let _ = fs::remove_dir_all(&out_dir);
+
+	Command::new(bindgen)
+		.arg("generate")
+		.arg("--library")
+		.arg(&library)
+		.args(["--language", "kotlin", "--no-format", "--out-dir"])
+		.arg(&out_dir)
+		.assert()
+		.success();
+
+	let bindings = read_sources(&out_dir, "kt");
+	for name in [
+		"createSession",
+		"class IdKitSession",
+		"connectUrl",
+		"pollForStatus",
+		"waitForCompletion",
+		"interface StatusListener",
+		"sealed class SignalInput",
+		"sealed class Status",
+		"data class Proof",
+		"enum class VerificationLevel",
+		"enum class AppError",
+		"sealed class IdKitException",
+	] {
+		assert!(
+			bindings.contains(name),
+			"missing {name} in the Kotlin bindings"
+		);
+	}
+}
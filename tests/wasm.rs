@@ -0,0 +1,96 @@
+//! Runs the verification flow on `wasm32-unknown-unknown` against a stubbed `fetch`, with `wasm-pack test --node -- --test wasm`.
+#![cfg(target_arch = "wasm32")]
+
+mod common;
+
+use common::proof;
+use idkit::{
+	hashing::hash_to_field,
+	session::AppId,
+	verify::{verify_proof_with_config, Error, VerifyConfig, VerifyErrorCode},
+};
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen(inline_js = r#"
+export function stub_fetch(status, body) {
+	globalThis.recordedRequests = [];
+	globalThis.fetch = async (request) => {
+		globalThis.recordedRequests.push({
+			url: request.url,
+			method: request.method,
+			body: await request.text(),
+		});
+
+		return new Response(body, { status, headers: { "content-type": "application/json" } });
+	};
+}
+
+export function recorded_requests() {
+	return JSON.stringify(globalThis.recordedRequests);
+}
+"#)]
+extern "C" {
+	fn stub_fetch(status: u16, body: Option<String>);
+	fn recorded_requests() -> String;
+}
+
+#[wasm_bindgen_test]
+async fn test_verify_proof() {
+	stub_fetch(200, None);
+
+	verify_proof_with_config(
+		proof(),
+		AppId::from_str("app_123").unwrap(),
+		"vote_1",
+		"test",
+		&VerifyConfig::default(),
+	)
+	.await
+	.unwrap();
+
+	let requests: serde_json::Value = serde_json::from_str(&recorded_requests()).unwrap();
+	assert_eq!(requests.as_array().unwrap().len(), 1);
+	assert_eq!(
+		requests[0]["url"],
+		"https://developer.worldcoin.org/api/v2/verify/app_123"
+	);
+	assert_eq!(requests[0]["method"], "POST");
+
+	let body: serde_json::Value =
+		serde_json::from_str(requests[0]["body"].as_str().unwrap()).unwrap();
+	assert_eq!(
+		body,
+		serde_json::json!({
+			"action": "vote_1",
+			"proof": proof().proof,
+			"merkle_root": proof().merkle_root,
+			"nullifier_hash": proof().nullifier_hash,
+			"verification_level": "orb",
+			"signal_hash": format!("0x{:x}", hash_to_field(b"test")),
+		})
+	);
+}
+
+#[wasm_bindgen_test]
+async fn test_verify_proof_rejected() {
+	stub_fetch(
+		400,
+		Some(r#"{"code":"invalid_proof","detail":"The provided proof is invalid.","attribute":null}"#.to_string()),
+	);
+
+	let error = verify_proof_with_config(
+		proof(),
+		AppId::from_str("app_123").unwrap(),
+		"vote_1",
+		"",
+		&VerifyConfig::default(),
+	)
+	.await
+	.unwrap_err();
+
+	assert!(
+		matches!(error, Error::Verification(response) if response.code == VerifyErrorCode::InvalidProof)
+	);
+}
@@ -0,0 +1,164 @@
+//! Drives the JavaScript API of `idkit::wasm` against a stubbed Wallet Bridge, with `wasm-pack test --headless --chrome -- --features wasm-bindgen --test wasm_bindgen`.
+#![cfg(target_arch = "wasm32")]
+
+use idkit::{
+	session::{crypto, BridgeProof, ConnectInfo, VerificationLevel},
+	wasm::{create_session, SessionOptions},
+	Proof,
+};
+use js_sys::{Function, Reflect, JSON};
+use serde_json::{json, Value};
+use url::Url;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen(inline_js = r#"
+export function stub_bridge(request_body) {
+	globalThis.bridgeResponses = [];
+	globalThis.fetch = async (request) => {
+		const json = (body) => new Response(body, { status: 200, headers: { "content-type": "application/json" } });
+		const url = new URL(request.url);
+
+		if (request.method === "POST" && url.pathname === "/request") {
+			return json(request_body);
+		}
+
+		return json(globalThis.bridgeResponses.shift() ?? '{"status":"initialized","response":null}');
+	};
+}
+
+export function push_response(body) {
+	globalThis.bridgeResponses.push(body);
+}
+"#)]
+extern "C" {
+	fn stub_bridge(request_body: &str);
+	fn push_response(body: &str);
+}
+
+const BRIDGE_URL: &str = "https://bridge.example.com";
+
+fn stub() {
+	stub_bridge(r#"{"request_id":"00000000-0000-0000-0000-000000000000"}"#);
+}
+
+fn options(value: &Value) -> SessionOptions {
+	JSON::parse(&value.to_string()).unwrap().unchecked_into()
+}
+
+async fn call(object: &JsValue, method: &str) -> Result<JsValue, JsValue> {
+	let function: Function = Reflect::get(object, &method.into())
+		.unwrap()
+		.unchecked_into();
+	let result = function.call0(object).unwrap();
+
+	match result.dyn_into::<js_sys::Promise>() {
+		Ok(promise) => JsFuture::from(promise).await,
+		Err(result) => Ok(result),
+	}
+}
+
+fn to_json(value: &JsValue) -> Value {
+	serde_json::from_str(&String::from(JSON::stringify(value).unwrap())).unwrap()
+}
+
+fn error_name(error: &JsValue) -> String {
+	error.unchecked_ref::<js_sys::Error>().name().into()
+}
+
+#[wasm_bindgen_test]
+async fn test_poll_for_status() {
+	stub();
+
+	let session = JsFuture::from(create_session(options(&json!({
+		"appId": "app_123",
+		"action": "vote_1",
+		"signal": "yes",
+		"bridgeUrl": BRIDGE_URL,
+	}))))
+	.await
+	.unwrap();
+
+	let connect_url = call(&session, "connectUrl")
+		.await
+		.unwrap()
+		.as_string()
+		.unwrap();
+	let key: [u8; 32] = ConnectInfo::from_url(&Url::parse(&connect_url).unwrap())
+		.unwrap()
+		.key_bytes()
+		.unwrap()
+		.try_into()
+		.unwrap();
+
+	let status = to_json(&call(&session, "pollForStatus").await.unwrap());
+	assert_eq!(status["status"], "waiting_for_connection");
+
+	let proof = Proof {
+		proof: "0x1".to_string(),
+		merkle_root: "0x2".to_string(),
+		nullifier_hash: "0x3".to_string(),
+		verification_level: VerificationLevel::Orb,
+	};
+	let response = serde_json::to_vec(&BridgeProof::try_from(proof.clone()).unwrap()).unwrap();
+	push_response(&json!({ "status": "retrieved", "response": null }).to_string());
+	push_response(
+		&json!({
+			"status": "completed",
+			"response": crypto::encrypt(&key, &[9; 12], &response).unwrap(),
+		})
+		.to_string(),
+	);
+
+	let status = to_json(&call(&session, "pollForStatus").await.unwrap());
+	assert_eq!(status["status"], "awaiting_confirmation");
+
+	let status = to_json(&call(&session, "pollForStatus").await.unwrap());
+	assert_eq!(status["status"], "confirmed");
+	assert_eq!(status["proof"], json!(proof));
+
+	let response = br#"{"error_code":"verification_rejected"}"#;
+	push_response(
+		&json!({
+			"status": "completed",
+			"response": crypto::encrypt(&key, &[9; 12], response).unwrap(),
+		})
+		.to_string(),
+	);
+
+	let status = to_json(&call(&session, "pollForStatus").await.unwrap());
+	assert_eq!(status["status"], "failed");
+	assert_eq!(status["errorCode"], "verification_rejected");
+}
+
+#[wasm_bindgen_test]
+async fn test_typed_errors() {
+	stub();
+
+	let error = JsFuture::from(create_session(options(&json!({ "action": "vote_1" }))))
+		.await
+		.unwrap_err();
+	assert_eq!(error_name(&error), "ConfigurationError");
+
+	let error = JsFuture::from(create_session(options(&json!({
+		"appId": "not an app id",
+		"action": "vote_1",
+		"bridgeUrl": BRIDGE_URL,
+	}))))
+	.await
+	.unwrap_err();
+	assert_eq!(error_name(&error), "ConfigurationError");
+
+	stub_bridge("not json");
+	let error = JsFuture::from(create_session(options(&json!({
+		"appId": "app_123",
+		"action": "vote_1",
+		"bridgeUrl": BRIDGE_URL,
+	}))))
+	.await
+	.unwrap_err();
+	assert_eq!(error_name(&error), "TransportError");
+}